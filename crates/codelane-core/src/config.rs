@@ -168,6 +168,53 @@ pub enum FileSortOrder {
     None,
 }
 
+/// File category tier for smart sorting
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FileTier {
+    /// Tier 0: Project configuration (package.json, Cargo.toml, CI configs)
+    ProjectConfig = 0,
+    /// Tier 1: Type definitions and interfaces
+    TypeDefinitions = 1,
+    /// Tier 2: Implementation files (core logic)
+    #[default]
+    Implementation = 2,
+    /// Tier 3: Test files
+    Tests = 3,
+    /// Tier 4: Generated and lock files
+    Generated = 4,
+    /// Tier 5: Documentation
+    Documentation = 5,
+}
+
+/// A file category for smart sorting, from lane configuration or a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCategoryRule {
+    /// Label shown for the category in review (e.g., "Migrations")
+    pub label: String,
+
+    /// Regular expressions matched against paths relative to the repository
+    pub patterns: Vec<String>,
+
+    /// Tier the category's files sort and pair with
+    #[serde(default)]
+    pub tier: FileTier,
+}
+
+/// Customization of the smart file sort order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileSortConfig {
+    /// Categories checked in order before the built-in patterns
+    pub categories: Vec<FileCategoryRule>,
+
+    /// Category labels in review order, overriding the tier order.
+    /// Categories that are not listed follow in tier order.
+    pub order: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ThemeConfig {
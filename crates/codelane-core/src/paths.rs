@@ -54,6 +54,53 @@ pub fn worktree_path(project_name: &str, branch: &str) -> crate::Result<PathBuf>
     Ok(data_dir()?.join("worktrees").join(project_name).join(safe_branch))
 }
 
+/// Returns the terminal recordings directory for the current environment.
+///
+/// Asciicast (`.cast`) files recorded from lane terminals are stored here by default.
+pub fn recordings_dir() -> crate::Result<PathBuf> {
+    let dir = data_dir()?.join("recordings");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Returns the search index directory for the current environment.
+///
+/// Persistent trigram indexes for lane working directories are stored here.
+pub fn search_index_dir() -> crate::Result<PathBuf> {
+    let dir = data_dir()?.join("search-index");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Returns the trash directory for the current environment.
+///
+/// Files deleted through Codelane are moved here so they can be restored.
+pub fn trash_dir() -> crate::Result<PathBuf> {
+    let dir = data_dir()?.join("trash");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Returns the diagnostics baseline directory for the current environment.
+///
+/// Temporary worktrees of a lane's base commit are checked out here while
+/// language servers capture baseline diagnostics.
+pub fn baselines_dir() -> crate::Result<PathBuf> {
+    let dir = data_dir()?.join("baselines");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Returns the plugins directory for the current environment.
+///
+/// Each plugin is a subdirectory with a `plugin.toml` manifest and the WASM
+/// component it names.
+pub fn plugins_dir() -> crate::Result<PathBuf> {
+    let dir = data_dir()?.join("plugins");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
 /// Returns the shared hook events directory (not environment-specific).
 ///
 /// Hook scripts write JSON event files here when agents need input.
@@ -125,6 +172,41 @@ mod tests {
         assert!(dir.exists());
     }
 
+    #[test]
+    fn test_recordings_dir() {
+        let dir = recordings_dir().unwrap();
+        assert!(dir.to_string_lossy().ends_with("recordings"));
+        assert!(dir.exists());
+    }
+
+    #[test]
+    fn test_search_index_dir() {
+        let dir = search_index_dir().unwrap();
+        assert!(dir.to_string_lossy().ends_with("search-index"));
+        assert!(dir.exists());
+    }
+
+    #[test]
+    fn test_trash_dir() {
+        let dir = trash_dir().unwrap();
+        assert!(dir.to_string_lossy().ends_with("trash"));
+        assert!(dir.exists());
+    }
+
+    #[test]
+    fn test_baselines_dir() {
+        let dir = baselines_dir().unwrap();
+        assert!(dir.to_string_lossy().ends_with("baselines"));
+        assert!(dir.exists());
+    }
+
+    #[test]
+    fn test_plugins_dir() {
+        let dir = plugins_dir().unwrap();
+        assert!(dir.to_string_lossy().ends_with("plugins"));
+        assert!(dir.exists());
+    }
+
     #[test]
     fn test_worktree_path_sanitizes_branch() {
         let path = worktree_path("my-project", "feature/login").unwrap();
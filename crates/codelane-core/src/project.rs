@@ -1,6 +1,12 @@
 //! Project abstraction for detecting project types and configurations
+//!
+//! A directory can hold several kinds of project at once (a Rust crate with
+//! a `package.json` for its web frontend), and a repository can hold many
+//! projects (Cargo, npm/pnpm, Go and uv workspaces, or just nested
+//! manifests). [`Project::discover`] returns all of them as a tree.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 
 /// Detected project type
@@ -17,31 +23,64 @@ pub enum ProjectType {
     Unknown,
 }
 
+/// Files marking a project root, by project type in detection order.
+/// Markers may use `*` and `?` wildcards.
+const MARKERS: &[(ProjectType, &[&str])] = &[
+    (ProjectType::Rust, &["Cargo.toml"]),
+    (ProjectType::Node, &["package.json"]),
+    (
+        ProjectType::Python,
+        &["pyproject.toml", "setup.py", "requirements.txt"],
+    ),
+    (ProjectType::Go, &["go.mod", "go.work"]),
+    (
+        ProjectType::Java,
+        &["pom.xml", "build.gradle", "build.gradle.kts"],
+    ),
+    (ProjectType::CSharp, &["*.csproj", "*.sln"]),
+    (ProjectType::Ruby, &["Gemfile"]),
+    (ProjectType::Php, &["composer.json"]),
+];
+
+/// Directories never searched for sub-projects
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "dist",
+    "build",
+    "out",
+    "venv",
+    "__pycache__",
+];
+
+/// How deep to look for sub-projects that no workspace manifest declares
+const MAX_SCAN_DEPTH: usize = 4;
+
 impl ProjectType {
     /// Detect project type from directory contents
+    ///
+    /// When markers of several types are present, the first in detection
+    /// order wins; see [`ProjectType::detect_all`].
     pub fn detect(path: &Path) -> Self {
-        if path.join("Cargo.toml").exists() {
-            ProjectType::Rust
-        } else if path.join("package.json").exists() {
-            ProjectType::Node
-        } else if path.join("pyproject.toml").exists()
-            || path.join("setup.py").exists()
-            || path.join("requirements.txt").exists()
-        {
-            ProjectType::Python
-        } else if path.join("go.mod").exists() {
-            ProjectType::Go
-        } else if path.join("pom.xml").exists() || path.join("build.gradle").exists() {
-            ProjectType::Java
-        } else if path.join("*.csproj").exists() || path.join("*.sln").exists() {
-            ProjectType::CSharp
-        } else if path.join("Gemfile").exists() {
-            ProjectType::Ruby
-        } else if path.join("composer.json").exists() {
-            ProjectType::Php
-        } else {
-            ProjectType::Unknown
-        }
+        Self::detect_all(path)
+            .first()
+            .copied()
+            .unwrap_or(ProjectType::Unknown)
+    }
+
+    /// Detect every project type whose markers are in the directory
+    pub fn detect_all(path: &Path) -> Vec<Self> {
+        let names = file_names(path);
+        MARKERS
+            .iter()
+            .filter(|(_, markers)| {
+                markers
+                    .iter()
+                    .any(|marker| names.iter().any(|name| wildcard_match(marker, name)))
+            })
+            .map(|(project_type, _)| *project_type)
+            .collect()
     }
 
     /// Get recommended LSP servers for this project type
@@ -89,22 +128,105 @@ pub struct Project {
 
     /// Git repository root (if different from project root)
     pub git_root: Option<PathBuf>,
+
+    /// Every detected project type, `project_type` first
+    #[serde(default)]
+    pub project_types: Vec<ProjectType>,
+
+    /// Recommended LSP servers for all of the project's types
+    #[serde(default)]
+    pub lsp_servers: Vec<String>,
+
+    /// Sub-projects, such as workspace members, nested under the closest
+    /// enclosing project
+    #[serde(default)]
+    pub children: Vec<Project>,
 }
 
 impl Project {
-    /// Discover project from a path
+    /// Discover the project containing a path, with all its sub-projects
+    ///
+    /// The root is the closest enclosing directory with a project marker,
+    /// or the workspace that declares it as a member.
     pub fn discover(path: &Path) -> Option<Self> {
         let root = Self::find_root(path)?;
-        let project_type = ProjectType::detect(&root);
-        let name = Self::detect_name(&root, project_type);
-        let git_root = Self::find_git_root(&root);
+        let root = Self::find_workspace_root(root);
+        Some(Self::scan(&root))
+    }
+
+    /// Build the project tree rooted exactly at `root`
+    pub fn scan(root: &Path) -> Self {
+        let (members, excluded) = workspace_members(root);
+        let mut dirs: BTreeSet<PathBuf> = members.into_iter().collect();
+        scan_dirs(root, 1, &mut dirs);
+        dirs.retain(|dir| dir != root && !excluded.iter().any(|e| dir.starts_with(e)));
 
-        Some(Self {
+        let dirs: Vec<PathBuf> = dirs.into_iter().collect();
+        let mut project = Self::at(root.to_path_buf());
+        project.children = Self::assemble(&dirs);
+        project
+    }
+
+    /// LSP servers recommended anywhere in the tree, without duplicates
+    pub fn all_lsp_servers(&self) -> Vec<String> {
+        let mut servers = self.lsp_servers.clone();
+        for child in &self.children {
+            for server in child.all_lsp_servers() {
+                if !servers.contains(&server) {
+                    servers.push(server);
+                }
+            }
+        }
+        servers
+    }
+
+    /// Project in `root`, without sub-projects
+    fn at(root: PathBuf) -> Self {
+        let project_types = ProjectType::detect_all(&root);
+        let project_type = project_types
+            .first()
+            .copied()
+            .unwrap_or(ProjectType::Unknown);
+        let mut lsp_servers: Vec<String> = Vec::new();
+        for server in project_types
+            .iter()
+            .flat_map(|t| t.recommended_lsp_servers())
+        {
+            if !lsp_servers.iter().any(|s| s == server) {
+                lsp_servers.push(server.to_string());
+            }
+        }
+
+        Self {
+            name: Self::detect_name(&root, project_type),
+            git_root: Self::find_git_root(&root),
             root,
             project_type,
-            name,
-            git_root,
-        })
+            project_types,
+            lsp_servers,
+            children: Vec::new(),
+        }
+    }
+
+    /// Nest sorted project directories under their closest ancestor
+    ///
+    /// Paths sort component by component, so each directory's descendants
+    /// directly follow it.
+    fn assemble(dirs: &[PathBuf]) -> Vec<Self> {
+        let mut projects = Vec::new();
+        let mut i = 0;
+        while i < dirs.len() {
+            let dir = &dirs[i];
+            let end = dirs[i + 1..]
+                .iter()
+                .position(|d| !d.starts_with(dir))
+                .map_or(dirs.len(), |offset| i + 1 + offset);
+            let mut project = Self::at(dir.clone());
+            project.children = Self::assemble(&dirs[i + 1..end]);
+            projects.push(project);
+            i = end;
+        }
+        projects
     }
 
     /// Find the project root by walking up from the given path
@@ -117,19 +239,8 @@ impl Project {
 
         loop {
             // Check for common project markers
-            let markers = [
-                "Cargo.toml",
-                "package.json",
-                "pyproject.toml",
-                "go.mod",
-                "pom.xml",
-                ".git",
-            ];
-
-            for marker in markers {
-                if current.join(marker).exists() {
-                    return Some(current);
-                }
+            if !ProjectType::detect_all(&current).is_empty() || current.join(".git").exists() {
+                return Some(current);
             }
 
             // Move up
@@ -143,26 +254,64 @@ impl Project {
         None
     }
 
+    /// Walk up from a project root to the outermost workspace, within the
+    /// same git repository, that declares it as a member
+    fn find_workspace_root(root: PathBuf) -> PathBuf {
+        let git_root = Self::find_git_root(&root);
+        let mut workspace = root.clone();
+        let mut current = root.as_path();
+
+        while let Some(parent) = current.parent() {
+            if git_root.as_ref().is_some_and(|g| !parent.starts_with(g)) {
+                break;
+            }
+            let (members, _) = workspace_members(parent);
+            if members.contains(&workspace) {
+                workspace = parent.to_path_buf();
+            }
+            current = parent;
+        }
+        workspace
+    }
+
     /// Detect project name from configuration files
     fn detect_name(root: &Path, project_type: ProjectType) -> String {
         match project_type {
             ProjectType::Rust => {
-                if let Ok(content) = std::fs::read_to_string(root.join("Cargo.toml")) {
-                    if let Ok(cargo) = content.parse::<toml::Table>() {
-                        if let Some(package) = cargo.get("package").and_then(|p| p.as_table()) {
-                            if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
-                                return name.to_string();
-                            }
+                if let Some(cargo) = read_toml(&root.join("Cargo.toml")) {
+                    if let Some(package) = cargo.get("package").and_then(|p| p.as_table()) {
+                        if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
+                            return name.to_string();
                         }
                     }
                 }
             }
             ProjectType::Node => {
-                if let Ok(content) = std::fs::read_to_string(root.join("package.json")) {
-                    if let Ok(pkg) = serde_json::from_str::<serde_json::Value>(&content) {
-                        if let Some(name) = pkg.get("name").and_then(|n| n.as_str()) {
-                            return name.to_string();
-                        }
+                if let Some(pkg) = read_json(&root.join("package.json")) {
+                    if let Some(name) = pkg.get("name").and_then(|n| n.as_str()) {
+                        return name.to_string();
+                    }
+                }
+            }
+            ProjectType::Python => {
+                if let Some(pyproject) = read_toml(&root.join("pyproject.toml")) {
+                    let name = pyproject
+                        .get("project")
+                        .or_else(|| pyproject.get("tool").and_then(|t| t.get("poetry")))
+                        .and_then(|p| p.get("name"))
+                        .and_then(|n| n.as_str());
+                    if let Some(name) = name {
+                        return name.to_string();
+                    }
+                }
+            }
+            ProjectType::Go => {
+                if let Ok(content) = std::fs::read_to_string(root.join("go.mod")) {
+                    let module = content
+                        .lines()
+                        .find_map(|line| line.trim().strip_prefix("module "));
+                    if let Some(module) = module {
+                        return module.trim().trim_matches('"').to_string();
                     }
                 }
             }
@@ -195,3 +344,388 @@ impl Project {
         None
     }
 }
+
+/// Match a file name against a pattern with `*` and `?` wildcards
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, n));
+        } else if let Some((star, tried)) = backtrack {
+            p = star;
+            n = tried + 1;
+            backtrack = Some((star, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Subdirectories worth searching for projects
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !IGNORED_DIRS.contains(&name.as_ref())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Collect directories below `dir` that contain project markers
+fn scan_dirs(dir: &Path, depth: usize, found: &mut BTreeSet<PathBuf>) {
+    if depth > MAX_SCAN_DEPTH {
+        return;
+    }
+    for sub in subdirs(dir) {
+        if !ProjectType::detect_all(&sub).is_empty() {
+            found.insert(sub.clone());
+        }
+        scan_dirs(&sub, depth + 1, found);
+    }
+}
+
+/// Directories matching a workspace member pattern such as `crates/*` or
+/// `packages/**`. Patterns reaching outside `root` match nothing.
+fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    fn expand(dir: &Path, components: &[&str], out: &mut Vec<PathBuf>) {
+        let Some((first, rest)) = components.split_first() else {
+            out.push(dir.to_path_buf());
+            return;
+        };
+        match *first {
+            "" | "." => expand(dir, rest, out),
+            "**" => {
+                expand(dir, rest, out);
+                for sub in subdirs(dir) {
+                    expand(&sub, components, out);
+                }
+            }
+            component if component.contains(['*', '?']) => {
+                for sub in subdirs(dir) {
+                    let name = sub.file_name().unwrap_or_default().to_string_lossy();
+                    if wildcard_match(component, &name) {
+                        expand(&sub, rest, out);
+                    }
+                }
+            }
+            component => {
+                let next = dir.join(component);
+                if next.is_dir() {
+                    expand(&next, rest, out);
+                }
+            }
+        }
+    }
+
+    let components: Vec<&str> = pattern.split(['/', '\\']).collect();
+    if components.contains(&"..") {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    expand(root, &components, &mut out);
+    out
+}
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    std::fs::read_to_string(path).ok()?.parse().ok()
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn toml_strings(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Member patterns of a `pnpm-workspace.yaml` `packages` list
+fn pnpm_packages(content: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed.starts_with("packages:");
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix('-').filter(|_| in_packages) {
+            let item = item.split(" #").next().unwrap_or_default().trim();
+            packages.push(item.trim_matches(['"', '\'']).to_string());
+        }
+    }
+    packages
+}
+
+/// Directories of a `go.work` file's `use` directives
+fn go_work_uses(content: &str) -> Vec<String> {
+    let mut uses = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if !line.is_empty() {
+                uses.push(line.trim_matches('"').to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix("use") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+            } else if !rest.is_empty() {
+                uses.push(rest.trim_matches('"').to_string());
+            }
+        }
+    }
+    uses
+}
+
+/// Member and excluded directories declared by the workspace manifests in
+/// `root`: Cargo, npm/yarn, pnpm, Go and uv workspaces
+fn workspace_members(root: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+
+    if let Some(cargo) = read_toml(&root.join("Cargo.toml")) {
+        let workspace = cargo.get("workspace");
+        include.extend(toml_strings(workspace.and_then(|w| w.get("members"))));
+        exclude.extend(toml_strings(workspace.and_then(|w| w.get("exclude"))));
+    }
+    if let Some(pkg) = read_json(&root.join("package.json")) {
+        let workspaces = pkg.get("workspaces");
+        // Either a list of patterns or yarn's `{ "packages": [...] }`
+        let patterns = workspaces
+            .and_then(|w| w.as_array())
+            .or_else(|| workspaces.and_then(|w| w.get("packages")?.as_array()));
+        for pattern in patterns.into_iter().flatten().filter_map(|p| p.as_str()) {
+            match pattern.strip_prefix('!') {
+                Some(excluded) => exclude.push(excluded.to_string()),
+                None => include.push(pattern.to_string()),
+            }
+        }
+    }
+    if let Ok(content) = std::fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        for pattern in pnpm_packages(&content) {
+            match pattern.strip_prefix('!') {
+                Some(excluded) => exclude.push(excluded.to_string()),
+                None => include.push(pattern),
+            }
+        }
+    }
+    if let Ok(content) = std::fs::read_to_string(root.join("go.work")) {
+        include.extend(go_work_uses(&content));
+    }
+    if let Some(pyproject) = read_toml(&root.join("pyproject.toml")) {
+        let workspace = pyproject
+            .get("tool")
+            .and_then(|t| t.get("uv"))
+            .and_then(|uv| uv.get("workspace"));
+        include.extend(toml_strings(workspace.and_then(|w| w.get("members"))));
+        exclude.extend(toml_strings(workspace.and_then(|w| w.get("exclude"))));
+    }
+
+    let expand_all = |patterns: Vec<String>| -> Vec<PathBuf> {
+        patterns
+            .iter()
+            .flat_map(|pattern| expand_pattern(root, pattern))
+            .filter(|dir| dir != root)
+            .collect()
+    };
+    (expand_all(include), expand_all(exclude))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn child<'a>(project: &'a Project, name: &str) -> &'a Project {
+        project
+            .children
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("no child project {}", name))
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.csproj", "App.csproj"));
+        assert!(!wildcard_match("*.csproj", "App.csproj.user"));
+        assert!(wildcard_match("app-?", "app-1"));
+        assert!(wildcard_match("*-*", "web-app"));
+        assert!(wildcard_match("Cargo.toml", "Cargo.toml"));
+        assert!(!wildcard_match("Cargo.toml", "cargo.toml"));
+    }
+
+    #[test]
+    fn test_detect_extension_markers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(temp_dir.path(), "Api.csproj", "<Project />");
+        assert_eq!(ProjectType::detect(temp_dir.path()), ProjectType::CSharp);
+    }
+
+    #[test]
+    fn test_detect_all_types() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(temp_dir.path(), "package.json", "{}");
+        write(temp_dir.path(), "Cargo.toml", "[package]\nname = \"app\"");
+        assert_eq!(
+            ProjectType::detect_all(temp_dir.path()),
+            vec![ProjectType::Rust, ProjectType::Node]
+        );
+        assert_eq!(ProjectType::detect(temp_dir.path()), ProjectType::Rust);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(ProjectType::detect(empty.path()), ProjectType::Unknown);
+    }
+
+    #[test]
+    fn test_discover_cargo_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]",
+        );
+        write(root, "crates/core/Cargo.toml", "[package]\nname = \"core\"");
+        write(root, "crates/cli/Cargo.toml", "[package]\nname = \"cli\"");
+        write(root, "crates/old/Cargo.toml", "[package]\nname = \"old\"");
+        write(
+            root,
+            "crates/cli/web/package.json",
+            r#"{"name": "cli-web"}"#,
+        );
+        write(root, "target/debug/build/Cargo.toml", "");
+
+        // Discovering from inside a member finds the whole workspace
+        let project = Project::discover(&root.join("crates/core")).unwrap();
+        assert_eq!(project.root, root);
+        assert_eq!(project.project_type, ProjectType::Rust);
+        assert_eq!(project.children.len(), 2);
+
+        let cli = child(&project, "cli");
+        assert_eq!(cli.children.len(), 1);
+        assert_eq!(cli.children[0].name, "cli-web");
+        assert_eq!(cli.children[0].project_type, ProjectType::Node);
+        assert_eq!(
+            project.all_lsp_servers(),
+            vec!["rust-analyzer", "typescript-language-server"]
+        );
+    }
+
+    #[test]
+    fn test_discover_node_workspaces() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"name": "monorepo", "workspaces": ["packages/*", "!packages/skip"]}"#,
+        );
+        write(root, "packages/ui/package.json", r#"{"name": "@acme/ui"}"#);
+        write(root, "packages/skip/package.json", r#"{"name": "skip"}"#);
+        write(root, "node_modules/dep/package.json", r#"{"name": "dep"}"#);
+
+        let project = Project::scan(root);
+        assert_eq!(project.name, "monorepo");
+        let names: Vec<&str> = project.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["@acme/ui"]);
+    }
+
+    #[test]
+    fn test_discover_pnpm_and_deep_members() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        write(root, "package.json", "{}");
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'apps/**/web'\n  - \"libs/*\"  # shared\ncatalog:\n  - ignored\n",
+        );
+        write(root, "apps/a/b/c/d/web/package.json", r#"{"name": "deep"}"#);
+        write(root, "libs/util/package.json", r#"{"name": "util"}"#);
+
+        let project = Project::scan(root);
+        let mut names: Vec<&str> = project.children.iter().map(|c| c.name.as_str()).collect();
+        names.sort();
+        // `deep` is below the scan depth and only found through the workspace
+        assert_eq!(names, vec!["deep", "util"]);
+    }
+
+    #[test]
+    fn test_discover_go_and_python() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        write(root, "go.work", "go 1.22\n\nuse (\n\t./svc // service\n)\n");
+        write(root, "svc/go.mod", "module example.com/svc\n\ngo 1.22\n");
+        write(
+            root,
+            "tools/lint/pyproject.toml",
+            "[project]\nname = \"lint-tools\"",
+        );
+
+        let project = Project::scan(root);
+        assert_eq!(project.project_type, ProjectType::Go);
+        assert_eq!(
+            child(&project, "example.com/svc").project_type,
+            ProjectType::Go
+        );
+        let tools = child(&project, "lint-tools");
+        assert_eq!(tools.project_type, ProjectType::Python);
+        assert_eq!(tools.lsp_servers, vec!["pyright", "ruff-lsp"]);
+    }
+
+    #[test]
+    fn test_go_work_uses() {
+        let content = "go 1.22\nuse ./single\nuse (\n  ./a\n  \"./b\"\n)\n";
+        assert_eq!(go_work_uses(content), vec!["./single", "./a", "./b"]);
+    }
+}
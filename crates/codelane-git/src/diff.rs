@@ -96,3 +96,342 @@ pub struct DiffResult {
     /// Total lines deleted
     pub deletions: u32,
 }
+
+impl DiffResult {
+    /// Parse unified diff output, as produced by `git diff`
+    ///
+    /// Lines that are not part of a file header or hunk are ignored.
+    pub fn parse(diff: &str) -> Self {
+        let mut result = DiffResult::default();
+        // Line numbers of the next old and new lines in the current hunk
+        let mut old_line = 0;
+        let mut new_line = 0;
+
+        for line in diff.lines() {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                let (old_path, new_path) = split_git_paths(paths);
+                result.files.push(FileDiff {
+                    path: new_path,
+                    old_path: Some(old_path),
+                    change_type: FileChangeType::Modified,
+                    hunks: Vec::new(),
+                    is_binary: false,
+                });
+                continue;
+            }
+            let Some(file) = result.files.last_mut() else {
+                continue;
+            };
+
+            if let Some(header) = line.strip_prefix("@@ ") {
+                if let Some(hunk) = parse_hunk_header(header) {
+                    old_line = hunk.old_start;
+                    new_line = hunk.new_start;
+                    file.hunks.push(hunk);
+                }
+                continue;
+            }
+
+            if let Some(hunk) = file.hunks.last_mut() {
+                let change_type = match line.as_bytes().first() {
+                    Some(b'+') => ChangeType::Added,
+                    Some(b'-') => ChangeType::Removed,
+                    // An empty context line may have lost its leading space
+                    Some(b' ') | None => ChangeType::Context,
+                    // "\ No newline at end of file"
+                    _ => continue,
+                };
+                let content = line.get(1..).unwrap_or_default();
+                let (old, new) = match change_type {
+                    ChangeType::Added => (None, Some(new_line)),
+                    ChangeType::Removed => (Some(old_line), None),
+                    _ => (Some(old_line), Some(new_line)),
+                };
+                if old.is_some() {
+                    old_line += 1;
+                }
+                if new.is_some() {
+                    new_line += 1;
+                }
+                match change_type {
+                    ChangeType::Added => result.additions += 1,
+                    ChangeType::Removed => result.deletions += 1,
+                    _ => {}
+                }
+                hunk.lines.push(DiffLine {
+                    content: content.to_string(),
+                    old_line: old,
+                    new_line: new,
+                    change_type,
+                });
+                continue;
+            }
+
+            if line.starts_with("new file mode") {
+                file.change_type = FileChangeType::Added;
+            } else if line.starts_with("deleted file mode") {
+                file.change_type = FileChangeType::Deleted;
+            } else if let Some(path) = line.strip_prefix("rename from ") {
+                file.change_type = FileChangeType::Renamed;
+                file.old_path = Some(PathBuf::from(unquote_path(path)));
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                file.path = PathBuf::from(unquote_path(path));
+            } else if let Some(path) = line.strip_prefix("copy from ") {
+                file.change_type = FileChangeType::Copied;
+                file.old_path = Some(PathBuf::from(unquote_path(path)));
+            } else if let Some(path) = line.strip_prefix("copy to ") {
+                file.path = PathBuf::from(unquote_path(path));
+            } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                file.is_binary = true;
+            } else if let Some(path) = line.strip_prefix("--- ") {
+                if let Some(path) = unquote_path(path).strip_prefix("a/") {
+                    file.old_path = Some(PathBuf::from(path));
+                }
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                if let Some(path) = unquote_path(path).strip_prefix("b/") {
+                    file.path = PathBuf::from(path);
+                }
+            }
+        }
+
+        for file in &mut result.files {
+            if file.change_type == FileChangeType::Deleted {
+                file.path = file.old_path.take().unwrap_or_else(|| file.path.clone());
+            } else if file.old_path.as_ref() == Some(&file.path) {
+                file.old_path = None;
+            }
+        }
+        result
+    }
+}
+
+/// Split the `a/<old> b/<new>` paths of a `diff --git` line
+///
+/// Either path may be quoted by git; see [`unquote_path`].
+fn split_git_paths(paths: &str) -> (PathBuf, PathBuf) {
+    let strip = |path: &str, prefix: &str| PathBuf::from(path.strip_prefix(prefix).unwrap_or(path));
+    if let Some((old, rest)) = take_quoted(paths) {
+        return (
+            strip(&old, "a/"),
+            strip(&unquote_path(rest.trim_start()), "b/"),
+        );
+    }
+    if let Some(start) = paths.strip_suffix('"').and_then(|p| p.rfind(" \"")) {
+        return (
+            strip(&paths[..start], "a/"),
+            strip(&unquote_path(&paths[start + 1..]), "b/"),
+        );
+    }
+
+    let paths = paths.strip_prefix("a/").unwrap_or(paths);
+    // Without renames both paths are the same, so split in the middle
+    let (old, new) = match paths.len().checked_sub(3).map(|n| n / 2) {
+        Some(mid) if paths.get(mid..mid + 3) == Some(" b/") => (&paths[..mid], &paths[mid + 3..]),
+        _ => paths.split_once(" b/").unwrap_or((paths, paths)),
+    };
+    (PathBuf::from(old), PathBuf::from(new))
+}
+
+/// Undo git's quoting of a path
+///
+/// Unless `core.quotePath` is off, git prints paths with control characters,
+/// quotes, backslashes or non-ASCII bytes in double quotes, with C-style
+/// escapes and octal escapes for each byte (`"a/\303\251.rs"`). Other
+/// paths are returned as they are.
+pub fn unquote_path(path: &str) -> String {
+    match take_quoted(path) {
+        Some((unquoted, "")) => unquoted,
+        _ => path.to_string(),
+    }
+}
+
+/// Unquote the quoted string `text` starts with, returning it and the text
+/// after the closing quote
+fn take_quoted(text: &str) -> Option<(String, &str)> {
+    let bytes = text.as_bytes();
+    if bytes.first() != Some(&b'"') {
+        return None;
+    }
+    let mut unquoted = Vec::new();
+    let mut i = 1;
+    loop {
+        match *bytes.get(i)? {
+            b'"' => break,
+            b'\\' => {
+                let escaped = *bytes.get(i + 1)?;
+                i += 2;
+                unquoted.push(match escaped {
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b't' => b'\t',
+                    b'n' => b'\n',
+                    b'v' => 0x0b,
+                    b'f' => 0x0c,
+                    b'r' => b'\r',
+                    b'0'..=b'7' => {
+                        let digits = text.get(i - 1..i + 2)?;
+                        i += 2;
+                        u8::from_str_radix(digits, 8).ok()?
+                    }
+                    other => other,
+                });
+            }
+            byte => {
+                unquoted.push(byte);
+                i += 1;
+            }
+        }
+    }
+    let unquoted = String::from_utf8_lossy(&unquoted).into_owned();
+    Some((unquoted, &text[i + 1..]))
+}
+
+/// Parse the ranges of a hunk header after its leading `@@ `
+fn parse_hunk_header(header: &str) -> Option<DiffHunk> {
+    let (ranges, _) = header.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let range = |range: &str| -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, lines)) => Some((start.parse().ok()?, lines.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(new.strip_prefix('+')?)?;
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+        explanation: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,5 @@ mod tests
+ use std::fs;
+-let a = 1;
++let a = 2;
++let b = a.unwrap();
+
+ fn main() {}
+diff --git a/old name.txt b/new name.txt
+similarity index 100%
+rename from old name.txt
+rename to new name.txt
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+--- a/gone.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn gone() {}
+\\ No newline at end of file
+diff --git a/logo.png b/logo.png
+new file mode 100644
+Binary files /dev/null and b/logo.png differ
+";
+
+    #[test]
+    fn test_parse_hunks() {
+        let diff = DiffResult::parse(DIFF);
+        assert_eq!(diff.files.len(), 4);
+        assert_eq!((diff.additions, diff.deletions), (2, 2));
+
+        let file = &diff.files[0];
+        assert_eq!(file.path, PathBuf::from("src/lib.rs"));
+        assert_eq!(file.old_path, None);
+        assert_eq!(file.change_type, FileChangeType::Modified);
+        let hunk = &file.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 4));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 5));
+
+        let lines: Vec<_> = hunk
+            .lines
+            .iter()
+            .map(|l| (l.change_type, l.old_line, l.new_line, l.content.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (ChangeType::Context, Some(1), Some(1), "use std::fs;"),
+                (ChangeType::Removed, Some(2), None, "let a = 1;"),
+                (ChangeType::Added, None, Some(2), "let a = 2;"),
+                (ChangeType::Added, None, Some(3), "let b = a.unwrap();"),
+                (ChangeType::Context, Some(3), Some(4), ""),
+                (ChangeType::Context, Some(4), Some(5), "fn main() {}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_file_changes() {
+        let diff = DiffResult::parse(DIFF);
+
+        let renamed = &diff.files[1];
+        assert_eq!(renamed.change_type, FileChangeType::Renamed);
+        assert_eq!(renamed.path, PathBuf::from("new name.txt"));
+        assert_eq!(renamed.old_path, Some(PathBuf::from("old name.txt")));
+        assert!(renamed.hunks.is_empty());
+
+        let deleted = &diff.files[2];
+        assert_eq!(deleted.change_type, FileChangeType::Deleted);
+        assert_eq!(deleted.path, PathBuf::from("gone.rs"));
+        assert_eq!(deleted.hunks[0].lines.len(), 1);
+
+        let binary = &diff.files[3];
+        assert_eq!(binary.change_type, FileChangeType::Added);
+        assert!(binary.is_binary);
+    }
+
+    #[test]
+    fn test_split_git_paths() {
+        assert_eq!(
+            split_git_paths("a/a b/c b/a b/c"),
+            (PathBuf::from("a b/c"), PathBuf::from("a b/c"))
+        );
+        assert_eq!(
+            split_git_paths("a/src/x.rs b/lib/y.rs"),
+            (PathBuf::from("src/x.rs"), PathBuf::from("lib/y.rs"))
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_paths() {
+        let diff = DiffResult::parse(
+            "\
+diff --git \"a/caf\\303\\251.rs\" \"b/caf\\303\\251.rs\"
+index 1111111..2222222 100644
+--- \"a/caf\\303\\251.rs\"
++++ \"b/caf\\303\\251.rs\"
+@@ -1 +1 @@
+-a
++b
+diff --git a/plain.rs \"b/tab\\there.rs\"
+similarity index 100%
+rename from plain.rs
+rename to \"tab\\there.rs\"
+",
+        );
+        assert_eq!(diff.files.len(), 2);
+        assert_eq!(diff.files[0].path, PathBuf::from("café.rs"));
+        assert_eq!(diff.files[0].old_path, None);
+        assert_eq!(diff.files[1].path, PathBuf::from("tab\there.rs"));
+        assert_eq!(diff.files[1].old_path, Some(PathBuf::from("plain.rs")));
+
+        assert_eq!(
+            split_git_paths("\"a/say \\\"hi\\\"\" \"b/say \\\"hi\\\"\""),
+            (PathBuf::from("say \"hi\""), PathBuf::from("say \"hi\""))
+        );
+        assert_eq!(unquote_path("\"a\\\\b\""), "a\\b");
+        assert_eq!(unquote_path("not quoted"), "not quoted");
+    }
+}
@@ -1,3 +1,465 @@
 //! LSP client implementation
+//!
+//! An [`LspClient`] runs one language server process and speaks JSON-RPC with
+//! it over stdio. Requests are matched to responses by id, notifications from
+//! the server are handed to a callback, and requests from the server (such as
+//! `workspace/configuration`) are answered with empty defaults.
 
-// TODO: Implement LSP client using tower-lsp
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use lsp_types::notification::{Exit, Initialized, Notification};
+use lsp_types::request::{Initialize, Request, Shutdown};
+use lsp_types::{
+    ClientCapabilities, ClientInfo, CompletionClientCapabilities, CompletionItemCapability,
+    DocumentFormattingClientCapabilities, DocumentSymbolClientCapabilities, GotoCapability,
+    HoverClientCapabilities, InitializeParams, InitializeResult, InitializedParams, MarkupKind,
+    PublishDiagnosticsClientCapabilities, ServerCapabilities, TextDocumentClientCapabilities,
+    TextDocumentSyncClientCapabilities, Url, WindowClientCapabilities, WorkspaceClientCapabilities,
+    WorkspaceFolder,
+};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{mpsc, oneshot, watch};
+
+use crate::manager::ServerConfig;
+use crate::transport::{read_message, write_message};
+use crate::{Error, Result};
+
+/// How long to wait for a response before giving up
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a server gets to exit after `shutdown` before it is killed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A notification sent by a language server
+#[derive(Debug, Clone)]
+pub struct ServerNotification {
+    pub method: String,
+    pub params: Value,
+}
+
+/// Callback receiving server notifications
+pub type NotificationHandler = Arc<dyn Fn(ServerNotification) + Send + Sync>;
+
+type PendingRequests = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value>>>>>;
+
+/// A running language server process
+pub struct LspClient {
+    name: String,
+    pid: Option<u32>,
+    outgoing: mpsc::UnboundedSender<Value>,
+    pending: PendingRequests,
+    next_id: AtomicI64,
+    capabilities: OnceLock<ServerCapabilities>,
+    exit: watch::Receiver<Option<ExitStatus>>,
+    /// Dropping this (with the client) kills the process
+    kill: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl LspClient {
+    /// Spawn the server process in `root`. Must be called within a Tokio runtime.
+    pub fn spawn(
+        config: &ServerConfig,
+        root: &Path,
+        on_notification: NotificationHandler,
+    ) -> Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(config.env.iter().map(|(k, v)| (k, v)))
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Error::ServerNotFound(config.command.clone()),
+                _ => Error::StartFailed(format!("{}: {}", config.name, e)),
+            })?;
+
+        let pid = child.id();
+        let (Some(stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(Error::StartFailed(format!(
+                "{}: stdio not captured",
+                config.name
+            )));
+        };
+
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+        let pending = PendingRequests::default();
+        let (exit_tx, exit) = watch::channel(None);
+        let (kill_tx, kill_rx) = oneshot::channel();
+
+        tokio::spawn(write_loop(stdin, outgoing_rx));
+        tokio::spawn(read_loop(
+            config.name.clone(),
+            stdout,
+            outgoing.clone(),
+            pending.clone(),
+            on_notification,
+        ));
+        let name = config.name.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!("[{}] {}", name, line);
+            }
+        });
+        tokio::spawn(wait_for_exit(child, kill_rx, exit_tx));
+
+        Ok(Self {
+            name: config.name.clone(),
+            pid,
+            outgoing,
+            pending,
+            next_id: AtomicI64::new(1),
+            capabilities: OnceLock::new(),
+            exit,
+            kill: Mutex::new(Some(kill_tx)),
+        })
+    }
+
+    /// Server name from its configuration
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Process ID of the server
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Capabilities reported by `initialize`
+    pub fn capabilities(&self) -> Option<&ServerCapabilities> {
+        self.capabilities.get()
+    }
+
+    /// Whether the server process has exited
+    pub fn has_exited(&self) -> bool {
+        self.exit.borrow().is_some()
+    }
+
+    /// Perform the `initialize` / `initialized` handshake
+    pub async fn initialize(
+        &self,
+        root: &Path,
+        initialization_options: Option<Value>,
+    ) -> Result<InitializeResult> {
+        let root_uri = Url::from_directory_path(root)
+            .map_err(|_| Error::Other(format!("Invalid workspace path: {}", root.display())))?;
+        let folder_name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.display().to_string());
+
+        #[allow(deprecated)]
+        let params = InitializeParams {
+            process_id: Some(std::process::id()),
+            root_uri: Some(root_uri.clone()),
+            initialization_options,
+            capabilities: client_capabilities(),
+            workspace_folders: Some(vec![WorkspaceFolder {
+                uri: root_uri,
+                name: folder_name,
+            }]),
+            client_info: Some(ClientInfo {
+                name: "codelane".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+            ..Default::default()
+        };
+
+        let result = self.request::<Initialize>(params).await?;
+        self.notify::<Initialized>(InitializedParams {})?;
+        let _ = self.capabilities.set(result.capabilities.clone());
+        Ok(result)
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request<R: Request>(&self, params: R::Params) -> Result<R::Result> {
+        let params = serde_json::to_value(params)
+            .map_err(|e| Error::Protocol(format!("Failed to encode {}: {}", R::METHOD, e)))?;
+        let result = self.request_value(R::METHOD, params).await?;
+        serde_json::from_value(result)
+            .map_err(|e| Error::Protocol(format!("Invalid {} response: {}", R::METHOD, e)))
+    }
+
+    async fn request_value(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending_requests().insert(id, tx);
+
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        if self.outgoing.send(message).is_err() {
+            self.pending_requests().remove(&id);
+            return Err(self.not_running());
+        }
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(Error::Protocol(format!(
+                "{} exited before responding to {}",
+                self.name, method
+            ))),
+            Err(_) => {
+                self.pending_requests().remove(&id);
+                let _ = self.outgoing.send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "$/cancelRequest",
+                    "params": {"id": id},
+                }));
+                Err(Error::Protocol(format!(
+                    "{} timed out: {}",
+                    self.name, method
+                )))
+            }
+        }
+    }
+
+    /// Send a notification
+    pub fn notify<N: Notification>(&self, params: N::Params) -> Result<()> {
+        let params = serde_json::to_value(params)
+            .map_err(|e| Error::Protocol(format!("Failed to encode {}: {}", N::METHOD, e)))?;
+        self.outgoing
+            .send(json!({"jsonrpc": "2.0", "method": N::METHOD, "params": params}))
+            .map_err(|_| self.not_running())
+    }
+
+    /// Ask the server to shut down, killing it if it does not exit in time
+    pub async fn shutdown(&self) {
+        if self.has_exited() {
+            return;
+        }
+        let polite = async {
+            self.request::<Shutdown>(()).await?;
+            self.notify::<Exit>(())
+        };
+        if matches!(
+            tokio::time::timeout(SHUTDOWN_TIMEOUT, polite).await,
+            Ok(Ok(()))
+        ) && tokio::time::timeout(SHUTDOWN_TIMEOUT, self.wait())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+
+        tracing::warn!(
+            "Language server {} did not shut down; killing it",
+            self.name
+        );
+        self.kill();
+        self.wait().await;
+    }
+
+    /// Kill the server process
+    pub fn kill(&self) {
+        let kill = self.kill.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(kill) = kill {
+            let _ = kill.send(());
+        }
+    }
+
+    /// Wait for the server process to exit
+    pub async fn wait(&self) -> Option<ExitStatus> {
+        let mut exit = self.exit.clone();
+        let status = exit.wait_for(Option::is_some).await.ok()?;
+        *status
+    }
+
+    fn pending_requests(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<i64, oneshot::Sender<Result<Value>>>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn not_running(&self) -> Error {
+        Error::Protocol(format!("{} is not running", self.name))
+    }
+}
+
+/// Capabilities advertised to every server
+fn client_capabilities() -> ClientCapabilities {
+    ClientCapabilities {
+        workspace: Some(WorkspaceClientCapabilities {
+            configuration: Some(true),
+            workspace_folders: Some(true),
+            ..Default::default()
+        }),
+        text_document: Some(TextDocumentClientCapabilities {
+            synchronization: Some(TextDocumentSyncClientCapabilities {
+                did_save: Some(true),
+                ..Default::default()
+            }),
+            completion: Some(CompletionClientCapabilities {
+                completion_item: Some(CompletionItemCapability {
+                    snippet_support: Some(true),
+                    documentation_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
+                    ..Default::default()
+                }),
+                context_support: Some(true),
+                ..Default::default()
+            }),
+            hover: Some(HoverClientCapabilities {
+                content_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
+                ..Default::default()
+            }),
+            definition: Some(GotoCapability {
+                link_support: Some(true),
+                ..Default::default()
+            }),
+            document_symbol: Some(DocumentSymbolClientCapabilities {
+                hierarchical_document_symbol_support: Some(true),
+                ..Default::default()
+            }),
+            formatting: Some(DocumentFormattingClientCapabilities::default()),
+            publish_diagnostics: Some(PublishDiagnosticsClientCapabilities {
+                related_information: Some(true),
+                version_support: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        window: Some(WindowClientCapabilities {
+            work_done_progress: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Result for a request sent by the server
+fn default_response(method: &str, params: Option<&Value>) -> Value {
+    match method {
+        // One (empty) setting per requested item
+        "workspace/configuration" => {
+            let items = params
+                .and_then(|p| p.get("items"))
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            Value::Array(vec![Value::Null; items])
+        }
+        _ => Value::Null,
+    }
+}
+
+async fn write_loop(mut stdin: ChildStdin, mut outgoing: mpsc::UnboundedReceiver<Value>) {
+    while let Some(message) = outgoing.recv().await {
+        if let Err(e) = write_message(&mut stdin, &message).await {
+            tracing::debug!("Failed to write to language server: {}", e);
+            break;
+        }
+    }
+}
+
+async fn read_loop(
+    name: String,
+    stdout: ChildStdout,
+    outgoing: mpsc::UnboundedSender<Value>,
+    pending: PendingRequests,
+    on_notification: NotificationHandler,
+) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!("Language server {} sent an invalid message: {}", name, e);
+                break;
+            }
+        };
+
+        let method = message.get("method").and_then(Value::as_str);
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => {
+                let result = default_response(method, message.get("params"));
+                let _ = outgoing.send(json!({"jsonrpc": "2.0", "id": id, "result": result}));
+            }
+            (Some(method), None) => on_notification(ServerNotification {
+                method: method.to_string(),
+                params: message.get("params").cloned().unwrap_or(Value::Null),
+            }),
+            (None, Some(id)) => {
+                let sender = id.as_i64().and_then(|id| {
+                    pending
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .remove(&id)
+                });
+                let Some(sender) = sender else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(Error::Response {
+                        code: error.get("code").and_then(Value::as_i64).unwrap_or(0),
+                        message: error
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .unwrap_or("Unknown error")
+                            .to_string(),
+                    }),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+            (None, None) => {}
+        }
+    }
+
+    // Requests still waiting will never be answered
+    pending.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+async fn wait_for_exit(
+    mut child: Child,
+    kill: oneshot::Receiver<()>,
+    exit: watch::Sender<Option<ExitStatus>>,
+) {
+    let status = tokio::select! {
+        status = child.wait() => status,
+        // Sent by `kill`, or dropped with the client
+        _ = kill => {
+            let _ = child.kill().await;
+            child.wait().await
+        }
+    };
+    exit.send_replace(Some(status.unwrap_or_default()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_response() {
+        let params = json!({"items": [{"section": "rust-analyzer"}, {"section": "files"}]});
+        assert_eq!(
+            default_response("workspace/configuration", Some(&params)),
+            json!([null, null])
+        );
+        assert_eq!(default_response("workspace/configuration", None), json!([]));
+        assert_eq!(
+            default_response("client/registerCapability", None),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_client_capabilities() {
+        let capabilities = serde_json::to_value(client_capabilities()).unwrap();
+        assert_eq!(capabilities["workspace"]["configuration"], true);
+        assert_eq!(
+            capabilities["textDocument"]["synchronization"]["didSave"],
+            true
+        );
+    }
+}
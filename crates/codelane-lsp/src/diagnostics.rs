@@ -1,10 +1,18 @@
 //! Diagnostic handling
+//!
+//! Language servers push diagnostics per file with
+//! `textDocument/publishDiagnostics`, each notification replacing what that
+//! server previously reported for the file. [`DiagnosticStore`] keeps the
+//! latest set per lane, server and file, converted to [`Diagnostic`]s.
 
+use lsp_types::{NumberOrString, PublishDiagnosticsParams};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 
 /// Diagnostic severity level
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum DiagnosticSeverity {
     Error,
     Warning,
@@ -12,8 +20,19 @@ pub enum DiagnosticSeverity {
     Hint,
 }
 
+impl From<lsp_types::DiagnosticSeverity> for DiagnosticSeverity {
+    fn from(severity: lsp_types::DiagnosticSeverity) -> Self {
+        match severity {
+            lsp_types::DiagnosticSeverity::WARNING => DiagnosticSeverity::Warning,
+            lsp_types::DiagnosticSeverity::INFORMATION => DiagnosticSeverity::Information,
+            lsp_types::DiagnosticSeverity::HINT => DiagnosticSeverity::Hint,
+            _ => DiagnosticSeverity::Error,
+        }
+    }
+}
+
 /// A diagnostic message
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Diagnostic {
     /// File path
     pub path: PathBuf,
@@ -42,3 +61,366 @@ pub struct Diagnostic {
     /// Error code
     pub code: Option<String>,
 }
+
+impl Diagnostic {
+    /// Convert an LSP diagnostic reported by `server` for `path`.
+    ///
+    /// Positions become 1-indexed; columns stay in the UTF-16 code units LSP
+    /// uses. A missing severity is treated as an error and a missing source
+    /// falls back to the server name.
+    pub fn from_lsp(path: &Path, server: &str, diagnostic: &lsp_types::Diagnostic) -> Self {
+        let range = diagnostic.range;
+        Self {
+            path: path.to_path_buf(),
+            start_line: range.start.line + 1,
+            start_column: range.start.character + 1,
+            end_line: range.end.line + 1,
+            end_column: range.end.character + 1,
+            severity: diagnostic
+                .severity
+                .map_or(DiagnosticSeverity::Error, Into::into),
+            message: diagnostic.message.clone(),
+            source: Some(
+                diagnostic
+                    .source
+                    .clone()
+                    .unwrap_or_else(|| server.to_string()),
+            ),
+            code: diagnostic.code.as_ref().map(|code| match code {
+                NumberOrString::Number(n) => n.to_string(),
+                NumberOrString::String(s) => s.clone(),
+            }),
+        }
+    }
+}
+
+/// Number of diagnostics by severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub information: usize,
+    pub hints: usize,
+}
+
+impl DiagnosticCounts {
+    pub fn add(&mut self, severity: DiagnosticSeverity) {
+        match severity {
+            DiagnosticSeverity::Error => self.errors += 1,
+            DiagnosticSeverity::Warning => self.warnings += 1,
+            DiagnosticSeverity::Information => self.information += 1,
+            DiagnosticSeverity::Hint => self.hints += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.errors + self.warnings + self.information + self.hints
+    }
+}
+
+impl<'a> FromIterator<&'a Diagnostic> for DiagnosticCounts {
+    fn from_iter<I: IntoIterator<Item = &'a Diagnostic>>(diagnostics: I) -> Self {
+        let mut counts = Self::default();
+        for diagnostic in diagnostics {
+            counts.add(diagnostic.severity);
+        }
+        counts
+    }
+}
+
+/// Diagnostics for one file, from every server of the lane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDiagnostics {
+    pub path: PathBuf,
+    pub counts: DiagnosticCounts,
+    /// Sorted by position
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Problem list of a lane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaneDiagnostics {
+    pub lane_id: String,
+    pub counts: DiagnosticCounts,
+    /// Files with at least one diagnostic, sorted by path
+    pub files: Vec<FileDiagnostics>,
+}
+
+/// Diagnostics by server, then file
+type ServerDiagnostics = HashMap<String, HashMap<PathBuf, Vec<Diagnostic>>>;
+
+/// Latest diagnostics of every lane
+#[derive(Default)]
+pub struct DiagnosticStore {
+    lanes: Mutex<HashMap<String, ServerDiagnostics>>,
+}
+
+impl DiagnosticStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lanes(&self) -> MutexGuard<'_, HashMap<String, ServerDiagnostics>> {
+        self.lanes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace what `server` reported for a file. Returns the file's path, or
+    /// `None` if the URI is not a local file.
+    pub fn publish(
+        &self,
+        lane_id: &str,
+        server: &str,
+        params: &PublishDiagnosticsParams,
+    ) -> Option<PathBuf> {
+        let path = params.uri.to_file_path().ok()?;
+        let diagnostics: Vec<Diagnostic> = params
+            .diagnostics
+            .iter()
+            .map(|d| Diagnostic::from_lsp(&path, server, d))
+            .collect();
+
+        let mut lanes = self.lanes();
+        let files = lanes
+            .entry(lane_id.to_string())
+            .or_default()
+            .entry(server.to_string())
+            .or_default();
+        if diagnostics.is_empty() {
+            files.remove(&path);
+        } else {
+            files.insert(path.clone(), diagnostics);
+        }
+        Some(path)
+    }
+
+    /// Forget a server's diagnostics, e.g. when it stops. Returns the files
+    /// that had any.
+    pub fn clear_server(&self, lane_id: &str, server: &str) -> Vec<PathBuf> {
+        self.lanes()
+            .get_mut(lane_id)
+            .and_then(|servers| servers.remove(server))
+            .map(|files| files.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget a lane's diagnostics
+    pub fn clear_lane(&self, lane_id: &str) {
+        self.lanes().remove(lane_id);
+    }
+
+    /// Diagnostics for one file, from all of the lane's servers
+    pub fn file(&self, lane_id: &str, path: &Path) -> FileDiagnostics {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .lanes()
+            .get(lane_id)
+            .map(|servers| {
+                servers
+                    .values()
+                    .filter_map(|files| files.get(path))
+                    .flatten()
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        sort_diagnostics(&mut diagnostics);
+        FileDiagnostics {
+            path: path.to_path_buf(),
+            counts: diagnostics.iter().collect(),
+            diagnostics,
+        }
+    }
+
+    /// Problem list of a lane
+    pub fn lane(&self, lane_id: &str) -> LaneDiagnostics {
+        let mut by_path: BTreeMap<PathBuf, Vec<Diagnostic>> = BTreeMap::new();
+        if let Some(servers) = self.lanes().get(lane_id) {
+            for (path, diagnostics) in servers.values().flatten() {
+                by_path
+                    .entry(path.clone())
+                    .or_default()
+                    .extend(diagnostics.iter().cloned());
+            }
+        }
+
+        let files: Vec<FileDiagnostics> = by_path
+            .into_iter()
+            .map(|(path, mut diagnostics)| {
+                sort_diagnostics(&mut diagnostics);
+                FileDiagnostics {
+                    path,
+                    counts: diagnostics.iter().collect(),
+                    diagnostics,
+                }
+            })
+            .collect();
+        LaneDiagnostics {
+            lane_id: lane_id.to_string(),
+            counts: files.iter().flat_map(|f| &f.diagnostics).collect(),
+            files,
+        }
+    }
+
+    /// Counts for a lane without building the problem list
+    pub fn counts(&self, lane_id: &str) -> DiagnosticCounts {
+        self.lanes()
+            .get(lane_id)
+            .map(|servers| {
+                servers
+                    .values()
+                    .flat_map(|f| f.values())
+                    .flatten()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn sort_diagnostics(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by(|a, b| {
+        (a.start_line, a.start_column, a.severity).cmp(&(b.start_line, b.start_column, b.severity))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range, Url};
+
+    fn lsp_diagnostic(
+        line: u32,
+        severity: Option<lsp_types::DiagnosticSeverity>,
+    ) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic {
+            range: Range::new(Position::new(line, 4), Position::new(line, 9)),
+            severity,
+            message: format!("problem on line {}", line),
+            ..Default::default()
+        }
+    }
+
+    fn publish(path: &str, diagnostics: Vec<lsp_types::Diagnostic>) -> PublishDiagnosticsParams {
+        PublishDiagnosticsParams {
+            uri: Url::from_file_path(path).unwrap(),
+            diagnostics,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_from_lsp() {
+        let mut lsp = lsp_diagnostic(0, Some(lsp_types::DiagnosticSeverity::WARNING));
+        lsp.code = Some(NumberOrString::String("E0308".to_string()));
+        let diagnostic = Diagnostic::from_lsp(Path::new("/repo/a.rs"), "rust-analyzer", &lsp);
+
+        assert_eq!((diagnostic.start_line, diagnostic.start_column), (1, 5));
+        assert_eq!((diagnostic.end_line, diagnostic.end_column), (1, 10));
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostic.source.as_deref(), Some("rust-analyzer"));
+        assert_eq!(diagnostic.code.as_deref(), Some("E0308"));
+
+        lsp.severity = None;
+        lsp.source = Some("rustc".to_string());
+        lsp.code = Some(NumberOrString::Number(42));
+        let diagnostic = Diagnostic::from_lsp(Path::new("/repo/a.rs"), "rust-analyzer", &lsp);
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Error);
+        assert_eq!(diagnostic.source.as_deref(), Some("rustc"));
+        assert_eq!(diagnostic.code.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn test_publish_replaces_per_server() {
+        let store = DiagnosticStore::new();
+        let error = Some(lsp_types::DiagnosticSeverity::ERROR);
+        let hint = Some(lsp_types::DiagnosticSeverity::HINT);
+
+        store.publish(
+            "lane",
+            "a",
+            &publish(
+                "/repo/x.py",
+                vec![lsp_diagnostic(3, error), lsp_diagnostic(1, hint)],
+            ),
+        );
+        store.publish(
+            "lane",
+            "b",
+            &publish("/repo/x.py", vec![lsp_diagnostic(2, error)]),
+        );
+        store.publish(
+            "lane",
+            "a",
+            &publish("/repo/y.py", vec![lsp_diagnostic(0, error)]),
+        );
+
+        let file = store.file("lane", Path::new("/repo/x.py"));
+        let lines: Vec<u32> = file.diagnostics.iter().map(|d| d.start_line).collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert_eq!(
+            file.counts,
+            DiagnosticCounts {
+                errors: 2,
+                hints: 1,
+                ..Default::default()
+            }
+        );
+
+        // A new publish from "a" replaces only what "a" reported
+        store.publish("lane", "a", &publish("/repo/x.py", vec![]));
+        let file = store.file("lane", Path::new("/repo/x.py"));
+        assert_eq!(file.diagnostics.len(), 1);
+        assert_eq!(file.diagnostics[0].source.as_deref(), Some("b"));
+
+        let lane = store.lane("lane");
+        let paths: Vec<&Path> = lane.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![Path::new("/repo/x.py"), Path::new("/repo/y.py")]
+        );
+        assert_eq!(lane.counts.errors, 2);
+        assert_eq!(store.counts("lane"), lane.counts);
+    }
+
+    #[test]
+    fn test_clear() {
+        let store = DiagnosticStore::new();
+        let error = Some(lsp_types::DiagnosticSeverity::ERROR);
+        store.publish(
+            "lane",
+            "a",
+            &publish("/repo/x.py", vec![lsp_diagnostic(0, error)]),
+        );
+        store.publish(
+            "lane",
+            "b",
+            &publish("/repo/y.py", vec![lsp_diagnostic(0, error)]),
+        );
+        store.publish(
+            "other",
+            "a",
+            &publish("/repo/x.py", vec![lsp_diagnostic(0, error)]),
+        );
+
+        assert_eq!(
+            store.clear_server("lane", "a"),
+            vec![PathBuf::from("/repo/x.py")]
+        );
+        assert_eq!(store.counts("lane").total(), 1);
+        assert_eq!(store.counts("other").total(), 1);
+
+        store.clear_lane("lane");
+        assert!(store.lane("lane").files.is_empty());
+        assert_eq!(store.counts("other").total(), 1);
+    }
+
+    #[test]
+    fn test_non_file_uri_ignored() {
+        let store = DiagnosticStore::new();
+        let params = PublishDiagnosticsParams {
+            uri: Url::parse("untitled:Untitled-1").unwrap(),
+            diagnostics: vec![lsp_diagnostic(0, None)],
+            version: None,
+        };
+        assert!(store.publish("lane", "a", &params).is_none());
+        assert_eq!(store.counts("lane").total(), 0);
+    }
+}
@@ -0,0 +1,111 @@
+//! Text edits returned by language servers
+//!
+//! Servers describe formatting results and some completions as [`TextEdit`]s
+//! against the document they were given. Positions count columns in UTF-16
+//! code units, so they are mapped through the text to byte offsets before the
+//! edits are spliced in.
+
+use lsp_types::{Position, TextEdit};
+
+/// Byte offset of `position` in `text`. Positions past the end of a line or
+/// of the text are clamped to it.
+pub fn offset_at(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return text.len(),
+        }
+    }
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |newline| line_start + newline);
+
+    let mut units = 0;
+    for (index, ch) in text[line_start..line_end].char_indices() {
+        if units >= position.character {
+            return line_start + index;
+        }
+        units += ch.len_utf16() as u32;
+    }
+    line_end
+}
+
+/// Apply edits computed against `text`
+///
+/// Edits at the same position are inserted in the order given. Overlapping
+/// edits are invalid; the part of a later edit's range that overlaps an
+/// earlier one is ignored.
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> String {
+    let mut spans: Vec<(usize, usize, &str)> = edits
+        .iter()
+        .map(|edit| {
+            let start = offset_at(text, edit.range.start);
+            let end = offset_at(text, edit.range.end).max(start);
+            (start, end, edit.new_text.as_str())
+        })
+        .collect();
+    spans.sort_by_key(|&(start, end, _)| (start, end));
+
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, end, new_text) in spans {
+        let start = start.max(copied);
+        result.push_str(&text[copied..start]);
+        result.push_str(new_text);
+        copied = end.max(start);
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Range;
+
+    fn edit(start: (u32, u32), end: (u32, u32), new_text: &str) -> TextEdit {
+        TextEdit::new(
+            Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1)),
+            new_text.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_offset_at_counts_utf16() {
+        let text = "a😀b\nnext";
+        // The emoji is two UTF-16 code units and four bytes
+        assert_eq!(offset_at(text, Position::new(0, 1)), 1);
+        assert_eq!(offset_at(text, Position::new(0, 3)), 5);
+        assert_eq!(offset_at(text, Position::new(1, 2)), 9);
+    }
+
+    #[test]
+    fn test_offset_at_clamps() {
+        let text = "ab\r\ncd";
+        assert_eq!(offset_at(text, Position::new(0, 2)), 2);
+        assert_eq!(offset_at(text, Position::new(1, 99)), text.len());
+        assert_eq!(offset_at(text, Position::new(9, 0)), text.len());
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let text = "fn main(){\nlet x=1;\n}\n";
+        let edits = vec![
+            edit((1, 0), (1, 0), "    "),
+            edit((0, 9), (0, 9), " "),
+            edit((1, 5), (1, 6), " = "),
+        ];
+        assert_eq!(
+            apply_text_edits(text, &edits),
+            "fn main() {\n    let x = 1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_text_edits_keeps_insertion_order() {
+        let edits = vec![edit((0, 1), (0, 1), "x"), edit((0, 1), (0, 1), "y")];
+        assert_eq!(apply_text_edits("ab", &edits), "axyb");
+        assert_eq!(apply_text_edits("ab", &[]), "ab");
+    }
+}
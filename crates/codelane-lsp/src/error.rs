@@ -13,6 +13,9 @@ pub enum Error {
     #[error("LSP protocol error: {0}")]
     Protocol(String),
 
+    #[error("Language server error {code}: {message}")]
+    Response { code: i64, message: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
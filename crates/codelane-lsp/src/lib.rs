@@ -4,11 +4,20 @@
 
 pub mod client;
 pub mod diagnostics;
+pub mod edits;
 pub mod manager;
 
 mod error;
+mod transport;
 
+pub use client::{LspClient, NotificationHandler, ServerNotification};
+pub use diagnostics::{
+    Diagnostic, DiagnosticCounts, DiagnosticSeverity, DiagnosticStore, FileDiagnostics,
+    LaneDiagnostics,
+};
+pub use edits::apply_text_edits;
 pub use error::{Error, Result};
+pub use manager::{LspEvent, LspManager, RestartPolicy, ServerConfig, ServerState, ServerStatus};
 
 /// Re-export lsp-types for convenience
 pub use lsp_types;
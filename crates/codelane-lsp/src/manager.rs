@@ -1,3 +1,1035 @@
 //! Language server lifecycle management
+//!
+//! [`LspManager`] runs the language servers configured for each lane in the
+//! lane's working directory. Each server is supervised by a task that performs
+//! the initialize handshake, replays open documents after a restart, and
+//! restarts crashed servers with exponential backoff until
+//! [`RestartPolicy::max_restarts`] is exceeded. Stopping a lane shuts its
+//! servers down gracefully.
+//!
+//! Diagnostics pushed by the servers are collected into a per-lane problem
+//! list. Status changes, diagnostics changes and other server notifications
+//! are published as [`LspEvent`]s.
 
-// TODO: Implement server manager
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use codelane_core::project::ProjectType;
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification,
+    PublishDiagnostics,
+};
+use lsp_types::request::{Completion, Formatting};
+use lsp_types::{
+    CompletionContext, CompletionList, CompletionParams, CompletionResponse, CompletionTriggerKind,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentFormattingParams, FormattingOptions, OneOf, Position, ServerCapabilities,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, Url, VersionedTextDocumentIdentifier,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use crate::client::{LspClient, NotificationHandler};
+use crate::diagnostics::{DiagnosticCounts, DiagnosticStore, FileDiagnostics, LaneDiagnostics};
+use crate::edits::apply_text_edits;
+use crate::{Error, Result};
+
+/// Capacity of the event channel; slow subscribers miss older events
+const EVENT_CAPACITY: usize = 1024;
+
+/// Built-in servers: name, command, arguments and the project type whose
+/// file extensions they handle
+const KNOWN_SERVERS: &[(&str, &str, &[&str], ProjectType)] = &[
+    ("rust-analyzer", "rust-analyzer", &[], ProjectType::Rust),
+    (
+        "typescript-language-server",
+        "typescript-language-server",
+        &["--stdio"],
+        ProjectType::Node,
+    ),
+    (
+        "pyright",
+        "pyright-langserver",
+        &["--stdio"],
+        ProjectType::Python,
+    ),
+    ("ruff-lsp", "ruff-lsp", &[], ProjectType::Python),
+    ("gopls", "gopls", &[], ProjectType::Go),
+    ("jdtls", "jdtls", &[], ProjectType::Java),
+    ("omnisharp", "omnisharp", &["-lsp"], ProjectType::CSharp),
+    ("solargraph", "solargraph", &["stdio"], ProjectType::Ruby),
+    (
+        "intelephense",
+        "intelephense",
+        &["--stdio"],
+        ProjectType::Php,
+    ),
+];
+
+/// How to launch a language server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Name used in lane configuration (e.g., "rust-analyzer")
+    pub name: String,
+
+    /// Executable to run
+    pub command: String,
+
+    /// Command arguments
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Extra environment variables
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+
+    /// File extensions the server handles, without the dot
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// Sent as `initializationOptions`
+    #[serde(default)]
+    pub initialization_options: Option<Value>,
+}
+
+impl ServerConfig {
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            extensions: Vec::new(),
+            initialization_options: None,
+        }
+    }
+
+    pub fn with_args<S: Into<String>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_extensions<S: Into<String>>(
+        mut self,
+        extensions: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Built-in configuration for a server named by
+    /// [`ProjectType::recommended_lsp_servers`]
+    pub fn known(name: &str) -> Option<Self> {
+        KNOWN_SERVERS.iter().find(|(known, ..)| *known == name).map(
+            |(name, command, args, project_type)| {
+                Self::new(*name, *command)
+                    .with_args(args.iter().copied())
+                    .with_extensions(project_type.extensions())
+            },
+        )
+    }
+
+    /// Whether the server handles this file
+    pub fn handles(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+}
+
+/// LSP language identifier for a file, based on its extension
+pub fn language_id(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "py" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        other => other,
+    }
+    .to_string()
+}
+
+/// When and how often crashed servers are restarted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Consecutive restarts before giving up
+    pub max_restarts: u32,
+
+    /// Delay before the first restart; doubled for each consecutive crash
+    pub initial_backoff: Duration,
+
+    /// Upper bound for the restart delay
+    pub max_backoff: Duration,
+
+    /// A server running this long is considered healthy again
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restart number `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Lifecycle state of a language server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerState {
+    Starting,
+    Running,
+    Restarting,
+    Failed,
+    Stopped,
+}
+
+/// Status of one language server in a lane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub lane_id: String,
+    pub name: String,
+    pub state: ServerState,
+    /// Restarts after crashes since the lane started
+    pub restarts: u32,
+    pub pid: Option<u32>,
+    /// Why the server last crashed or failed to start
+    pub error: Option<String>,
+}
+
+/// Published by [`LspManager::subscribe`]
+#[derive(Debug, Clone)]
+pub enum LspEvent {
+    /// A server changed state
+    Status(ServerStatus),
+
+    /// Diagnostics for a file changed; read them with
+    /// [`LspManager::file_diagnostics`]
+    Diagnostics { lane_id: String, path: PathBuf },
+
+    /// A server sent a notification (e.g., `window/logMessage`)
+    Notification {
+        lane_id: String,
+        server: String,
+        method: String,
+        params: Value,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct OpenDocument {
+    language_id: String,
+    version: i32,
+    text: String,
+}
+
+struct ServerInner {
+    state: ServerState,
+    restarts: u32,
+    error: Option<String>,
+    client: Option<Arc<LspClient>>,
+    documents: HashMap<Url, OpenDocument>,
+}
+
+/// One supervised server in a lane
+struct ServerHandle {
+    lane_id: String,
+    config: ServerConfig,
+    root: PathBuf,
+    events: broadcast::Sender<LspEvent>,
+    diagnostics: Arc<DiagnosticStore>,
+    inner: Mutex<ServerInner>,
+    stop: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ServerHandle {
+    fn new(
+        lane_id: &str,
+        config: ServerConfig,
+        root: &Path,
+        events: broadcast::Sender<LspEvent>,
+        diagnostics: Arc<DiagnosticStore>,
+    ) -> Self {
+        Self {
+            lane_id: lane_id.to_string(),
+            config,
+            root: root.to_path_buf(),
+            events,
+            diagnostics,
+            inner: Mutex::new(ServerInner {
+                state: ServerState::Starting,
+                restarts: 0,
+                error: None,
+                client: None,
+                documents: HashMap::new(),
+            }),
+            stop: watch::Sender::new(false),
+            task: Mutex::new(None),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ServerInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn status(&self) -> ServerStatus {
+        let inner = self.lock();
+        self.status_of(&inner)
+    }
+
+    fn status_of(&self, inner: &ServerInner) -> ServerStatus {
+        ServerStatus {
+            lane_id: self.lane_id.clone(),
+            name: self.config.name.clone(),
+            state: inner.state,
+            restarts: inner.restarts,
+            pid: inner.client.as_ref().and_then(|c| c.pid()),
+            error: inner.error.clone(),
+        }
+    }
+
+    /// Apply a change and publish the resulting status
+    fn update(&self, change: impl FnOnce(&mut ServerInner)) {
+        let status = {
+            let mut inner = self.lock();
+            change(&mut inner);
+            self.status_of(&inner)
+        };
+        let _ = self.events.send(LspEvent::Status(status));
+    }
+
+    fn client(&self) -> Option<Arc<LspClient>> {
+        self.lock().client.clone()
+    }
+
+    fn start(self: &Arc<Self>, policy: RestartPolicy) {
+        let task = tokio::spawn(self.clone().supervise(policy));
+        *self.task.lock().unwrap_or_else(|e| e.into_inner()) = Some(task);
+    }
+
+    async fn stop(&self) {
+        self.stop.send_replace(true);
+        let task = self.task.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+
+    async fn launch(&self) -> Result<Arc<LspClient>> {
+        let events = self.events.clone();
+        let diagnostics = self.diagnostics.clone();
+        let lane_id = self.lane_id.clone();
+        let server = self.config.name.clone();
+        let on_notification: NotificationHandler = Arc::new(move |notification| {
+            if notification.method == PublishDiagnostics::METHOD {
+                let path = serde_json::from_value(notification.params)
+                    .ok()
+                    .and_then(|params| diagnostics.publish(&lane_id, &server, &params));
+                if let Some(path) = path {
+                    let _ = events.send(LspEvent::Diagnostics {
+                        lane_id: lane_id.clone(),
+                        path,
+                    });
+                }
+                return;
+            }
+            let _ = events.send(LspEvent::Notification {
+                lane_id: lane_id.clone(),
+                server: server.clone(),
+                method: notification.method,
+                params: notification.params,
+            });
+        });
+
+        let client = Arc::new(LspClient::spawn(&self.config, &self.root, on_notification)?);
+        client
+            .initialize(&self.root, self.config.initialization_options.clone())
+            .await?;
+        Ok(client)
+    }
+
+    /// Start the server and keep it running until stopped
+    async fn supervise(self: Arc<Self>, policy: RestartPolicy) {
+        let mut stop = self.stop.subscribe();
+        let mut attempt = 0;
+
+        loop {
+            let launched = tokio::select! {
+                launched = self.launch() => launched,
+                _ = stopped(&mut stop) => break,
+            };
+
+            match launched {
+                Ok(client) => {
+                    let started = Instant::now();
+                    self.update(|inner| {
+                        // Documents opened while the server was down
+                        for (uri, document) in &inner.documents {
+                            let _ =
+                                client.notify::<DidOpenTextDocument>(open_params(uri, document));
+                        }
+                        inner.client = Some(client.clone());
+                        inner.state = ServerState::Running;
+                    });
+
+                    tokio::select! {
+                        status = client.wait() => {
+                            let reason = match status.and_then(|s| s.code()) {
+                                Some(code) => format!("Exited with code {}", code),
+                                None => "Exited unexpectedly".to_string(),
+                            };
+                            tracing::warn!(
+                                "Language server {} for lane {} crashed: {}",
+                                self.config.name,
+                                self.lane_id,
+                                reason
+                            );
+                            self.lock().error = Some(reason);
+                            self.clear_diagnostics();
+                        }
+                        _ = stopped(&mut stop) => {
+                            self.lock().client = None;
+                            client.shutdown().await;
+                            break;
+                        }
+                    }
+                    if started.elapsed() >= policy.reset_after {
+                        attempt = 0;
+                    }
+                }
+                Err(Error::ServerNotFound(command)) => {
+                    self.update(|inner| {
+                        inner.state = ServerState::Failed;
+                        inner.error = Some(format!("Command not found: {}", command));
+                    });
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Language server {} for lane {} failed to start: {}",
+                        self.config.name,
+                        self.lane_id,
+                        e
+                    );
+                    self.lock().error = Some(e.to_string());
+                }
+            }
+
+            attempt += 1;
+            if attempt > policy.max_restarts {
+                self.update(|inner| {
+                    inner.client = None;
+                    inner.state = ServerState::Failed;
+                });
+                return;
+            }
+            self.update(|inner| {
+                inner.client = None;
+                inner.restarts += 1;
+                inner.state = ServerState::Restarting;
+            });
+
+            tokio::select! {
+                _ = tokio::time::sleep(policy.backoff(attempt)) => {}
+                _ = stopped(&mut stop) => break,
+            }
+        }
+
+        self.clear_diagnostics();
+        self.update(|inner| {
+            inner.client = None;
+            inner.state = ServerState::Stopped;
+        });
+    }
+
+    /// Drop what the server reported; it is stale once the server is gone
+    fn clear_diagnostics(&self) {
+        for path in self
+            .diagnostics
+            .clear_server(&self.lane_id, &self.config.name)
+        {
+            let _ = self.events.send(LspEvent::Diagnostics {
+                lane_id: self.lane_id.clone(),
+                path,
+            });
+        }
+    }
+
+    fn open_document(&self, uri: Url, text: String) {
+        let mut inner = self.lock();
+        if let Some(document) = inner.documents.get_mut(&uri) {
+            document.version += 1;
+            document.text = text;
+            let params = change_params(&uri, document);
+            if let Some(client) = &inner.client {
+                let _ = client.notify::<DidChangeTextDocument>(params);
+            }
+            return;
+        }
+
+        let document = OpenDocument {
+            language_id: language_id(Path::new(uri.path())),
+            version: 1,
+            text,
+        };
+        if let Some(client) = &inner.client {
+            let _ = client.notify::<DidOpenTextDocument>(open_params(&uri, &document));
+        }
+        inner.documents.insert(uri, document);
+    }
+
+    fn change_document(&self, uri: Url, text: String) {
+        let mut inner = self.lock();
+        let Some(document) = inner.documents.get_mut(&uri) else {
+            drop(inner);
+            return self.open_document(uri, text);
+        };
+        document.version += 1;
+        document.text = text;
+        let params = change_params(&uri, document);
+        if let Some(client) = &inner.client {
+            let _ = client.notify::<DidChangeTextDocument>(params);
+        }
+    }
+
+    fn close_document(&self, uri: &Url) {
+        let mut inner = self.lock();
+        if inner.documents.remove(uri).is_none() {
+            return;
+        }
+        if let Some(client) = &inner.client {
+            let _ = client.notify::<DidCloseTextDocument>(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+            });
+        }
+    }
+}
+
+/// Resolves on the next status or diagnostics event for a lane
+async fn next_lane_event(events: &mut broadcast::Receiver<LspEvent>, lane_id: &str) {
+    loop {
+        match events.recv().await {
+            Ok(LspEvent::Status(status)) if status.lane_id == lane_id => return,
+            Ok(LspEvent::Diagnostics {
+                lane_id: changed, ..
+            }) if changed == lane_id => return,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+/// Resolves once the handle's stop flag is set
+async fn stopped(stop: &mut watch::Receiver<bool>) {
+    let _ = stop.wait_for(|stop| *stop).await;
+}
+
+fn open_params(uri: &Url, document: &OpenDocument) -> DidOpenTextDocumentParams {
+    DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: document.language_id.clone(),
+            version: document.version,
+            text: document.text.clone(),
+        },
+    }
+}
+
+fn change_params(uri: &Url, document: &OpenDocument) -> DidChangeTextDocumentParams {
+    DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: uri.clone(),
+            version: document.version,
+        },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: document.text.clone(),
+        }],
+    }
+}
+
+/// Convert an absolute file path to a `file://` URI
+pub fn file_uri(path: &Path) -> Result<Url> {
+    Url::from_file_path(path)
+        .map_err(|_| Error::Other(format!("Invalid file path: {}", path.display())))
+}
+
+/// Runs language servers for lanes
+pub struct LspManager {
+    policy: RestartPolicy,
+    /// Server configurations overriding the built-in ones
+    configs: Mutex<HashMap<String, ServerConfig>>,
+    lanes: Mutex<HashMap<String, Vec<Arc<ServerHandle>>>>,
+    events: broadcast::Sender<LspEvent>,
+    diagnostics: Arc<DiagnosticStore>,
+}
+
+impl LspManager {
+    pub fn new() -> Self {
+        Self::with_policy(RestartPolicy::default())
+    }
+
+    pub fn with_policy(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            configs: Mutex::new(HashMap::new()),
+            lanes: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            diagnostics: Arc::new(DiagnosticStore::new()),
+        }
+    }
+
+    /// Add or replace a server configuration
+    pub fn register_server(&self, config: ServerConfig) {
+        self.configs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(config.name.clone(), config);
+    }
+
+    /// Configuration for a server name, registered or built-in
+    pub fn server_config(&self, name: &str) -> Option<ServerConfig> {
+        let configs = self.configs.lock().unwrap_or_else(|e| e.into_inner());
+        configs
+            .get(name)
+            .cloned()
+            .or_else(|| ServerConfig::known(name))
+    }
+
+    /// Receive status changes and server notifications
+    pub fn subscribe(&self) -> broadcast::Receiver<LspEvent> {
+        self.events.subscribe()
+    }
+
+    fn lanes(&self) -> MutexGuard<'_, HashMap<String, Vec<Arc<ServerHandle>>>> {
+        self.lanes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start servers for a lane in `root`. Servers already running for the
+    /// lane are kept. Must be called within a Tokio runtime.
+    pub fn start_lane(
+        &self,
+        lane_id: &str,
+        root: &Path,
+        servers: &[String],
+    ) -> Result<Vec<ServerStatus>> {
+        let configs = servers
+            .iter()
+            .map(|name| {
+                self.server_config(name)
+                    .ok_or_else(|| Error::ServerNotFound(name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut lanes = self.lanes();
+        let handles = lanes.entry(lane_id.to_string()).or_default();
+        for config in configs {
+            if handles.iter().any(|h| h.config.name == config.name) {
+                continue;
+            }
+            tracing::info!(
+                "Starting language server {} for lane {} in {}",
+                config.name,
+                lane_id,
+                root.display()
+            );
+            let handle = Arc::new(ServerHandle::new(
+                lane_id,
+                config,
+                root,
+                self.events.clone(),
+                self.diagnostics.clone(),
+            ));
+            handle.start(self.policy.clone());
+            handles.push(handle);
+        }
+        Ok(handles.iter().map(|h| h.status()).collect())
+    }
+
+    /// Shut down a lane's servers. Returns false if the lane had none.
+    pub async fn stop_lane(&self, lane_id: &str) -> bool {
+        let Some(handles) = self.lanes().remove(lane_id) else {
+            return false;
+        };
+        futures::future::join_all(handles.iter().map(|h| h.stop())).await;
+        self.diagnostics.clear_lane(lane_id);
+        true
+    }
+
+    /// Shut down every server
+    pub async fn stop_all(&self) {
+        let lanes: Vec<_> = self
+            .lanes()
+            .drain()
+            .flat_map(|(_, handles)| handles)
+            .collect();
+        futures::future::join_all(lanes.iter().map(|h| h.stop())).await;
+        for handle in &lanes {
+            self.diagnostics.clear_lane(&handle.lane_id);
+        }
+    }
+
+    /// Status of a lane's servers
+    pub fn status(&self, lane_id: &str) -> Vec<ServerStatus> {
+        self.lanes()
+            .get(lane_id)
+            .map(|handles| handles.iter().map(|h| h.status()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Wait until the lane's servers have started and stopped publishing
+    /// diagnostics for `quiet`. Returns false if that did not happen within
+    /// `timeout`.
+    pub async fn wait_for_diagnostics(
+        &self,
+        lane_id: &str,
+        quiet: Duration,
+        timeout: Duration,
+    ) -> bool {
+        let mut events = self.subscribe();
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let until = deadline.min(tokio::time::Instant::now() + quiet);
+            let event = tokio::time::timeout_at(until, next_lane_event(&mut events, lane_id));
+            if event.await.is_ok() {
+                continue;
+            }
+            let starting = self
+                .status(lane_id)
+                .iter()
+                .any(|s| matches!(s.state, ServerState::Starting | ServerState::Restarting));
+            if !starting {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+        }
+    }
+
+    /// Problem list of a lane, from all of its servers
+    pub fn diagnostics(&self, lane_id: &str) -> LaneDiagnostics {
+        self.diagnostics.lane(lane_id)
+    }
+
+    /// Diagnostics for one file of a lane
+    pub fn file_diagnostics(&self, lane_id: &str, path: &Path) -> FileDiagnostics {
+        self.diagnostics.file(lane_id, path)
+    }
+
+    /// Diagnostic counts of a lane by severity
+    pub fn diagnostic_counts(&self, lane_id: &str) -> DiagnosticCounts {
+        self.diagnostics.counts(lane_id)
+    }
+
+    fn servers_for(&self, lane_id: &str, path: &Path) -> Vec<Arc<ServerHandle>> {
+        self.lanes()
+            .get(lane_id)
+            .map(|handles| {
+                handles
+                    .iter()
+                    .filter(|h| h.config.handles(path))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Running clients of all of the lane's servers
+    pub fn lane_clients(&self, lane_id: &str) -> Vec<Arc<LspClient>> {
+        self.lanes()
+            .get(lane_id)
+            .map(|handles| handles.iter().filter_map(|h| h.client()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Running clients of the lane's servers that handle `path`
+    pub fn clients_for(&self, lane_id: &str, path: &Path) -> Vec<Arc<LspClient>> {
+        self.servers_for(lane_id, path)
+            .iter()
+            .filter_map(|h| h.client())
+            .collect()
+    }
+
+    /// First running server for `path` whose capabilities pass `supported`
+    pub fn client_supporting(
+        &self,
+        lane_id: &str,
+        path: &Path,
+        supported: impl Fn(&ServerCapabilities) -> bool,
+    ) -> Option<Arc<LspClient>> {
+        self.clients_for(lane_id, path)
+            .into_iter()
+            .find(|client| client.capabilities().is_some_and(&supported))
+    }
+
+    /// Open a document in the servers that handle it. Opening an already
+    /// open document replaces its contents.
+    pub fn open_document(&self, lane_id: &str, path: &Path, text: String) -> Result<()> {
+        let servers = self.servers_for(lane_id, path);
+        if !servers.is_empty() {
+            let uri = file_uri(path)?;
+            for server in servers {
+                server.open_document(uri.clone(), text.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the contents of an open document
+    pub fn change_document(&self, lane_id: &str, path: &Path, text: String) -> Result<()> {
+        let servers = self.servers_for(lane_id, path);
+        if !servers.is_empty() {
+            let uri = file_uri(path)?;
+            for server in servers {
+                server.change_document(uri.clone(), text.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a document is open in any of the lane's servers
+    pub fn is_document_open(&self, lane_id: &str, path: &Path) -> bool {
+        let Ok(uri) = file_uri(path) else {
+            return false;
+        };
+        self.servers_for(lane_id, path)
+            .iter()
+            .any(|server| server.lock().documents.contains_key(&uri))
+    }
+
+    /// Close a document
+    pub fn close_document(&self, lane_id: &str, path: &Path) -> Result<()> {
+        let servers = self.servers_for(lane_id, path);
+        if !servers.is_empty() {
+            let uri = file_uri(path)?;
+            for server in servers {
+                server.close_document(&uri);
+            }
+        }
+        Ok(())
+    }
+
+    /// Request completions at `position` in an open document from the first
+    /// of the lane's servers that provides them
+    ///
+    /// Returns `None` when no running server completes the file.
+    pub async fn completion(
+        &self,
+        lane_id: &str,
+        path: &Path,
+        position: Position,
+        trigger_character: Option<String>,
+    ) -> Result<Option<CompletionList>> {
+        let Some(client) =
+            self.client_supporting(lane_id, path, |c| c.completion_provider.is_some())
+        else {
+            return Ok(None);
+        };
+        let trigger_kind = if trigger_character.is_some() {
+            CompletionTriggerKind::TRIGGER_CHARACTER
+        } else {
+            CompletionTriggerKind::INVOKED
+        };
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_uri(path)?,
+                },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: Some(CompletionContext {
+                trigger_kind,
+                trigger_character,
+            }),
+        };
+        let list = match client.request::<Completion>(params).await? {
+            Some(CompletionResponse::List(list)) => list,
+            Some(CompletionResponse::Array(items)) => CompletionList {
+                is_incomplete: false,
+                items,
+            },
+            None => CompletionList::default(),
+        };
+        Ok(Some(list))
+    }
+
+    /// Format `text` as the new contents of `path` with the first of the
+    /// lane's servers that formats the file
+    ///
+    /// The servers' copy of the document is replaced with `text` first;
+    /// documents that were not open are closed again afterwards. Returns
+    /// `None` when no running server formats the file.
+    pub async fn format_document(
+        &self,
+        lane_id: &str,
+        path: &Path,
+        text: String,
+        options: FormattingOptions,
+    ) -> Result<Option<String>> {
+        let Some(client) = self.client_supporting(lane_id, path, |c| {
+            !matches!(
+                c.document_formatting_provider,
+                None | Some(OneOf::Left(false))
+            )
+        }) else {
+            return Ok(None);
+        };
+        let uri = file_uri(path)?;
+        let was_open = self.is_document_open(lane_id, path);
+        self.change_document(lane_id, path, text.clone())?;
+
+        let edits = client
+            .request::<Formatting>(DocumentFormattingParams {
+                text_document: TextDocumentIdentifier { uri },
+                options,
+                work_done_progress_params: Default::default(),
+            })
+            .await;
+        if !was_open {
+            self.close_document(lane_id, path)?;
+        }
+        Ok(Some(match edits? {
+            Some(edits) => apply_text_edits(&text, &edits),
+            None => text,
+        }))
+    }
+}
+
+impl Default for LspManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_servers_cover_recommendations() {
+        for project_type in [
+            ProjectType::Rust,
+            ProjectType::Node,
+            ProjectType::Python,
+            ProjectType::Go,
+            ProjectType::Java,
+            ProjectType::CSharp,
+            ProjectType::Ruby,
+            ProjectType::Php,
+        ] {
+            for name in project_type.recommended_lsp_servers() {
+                let config = ServerConfig::known(name).expect(name);
+                assert_eq!(config.extensions, project_type.extensions());
+            }
+        }
+        assert!(ServerConfig::known("no-such-server").is_none());
+    }
+
+    #[test]
+    fn test_known_server_commands() {
+        let tsserver = ServerConfig::known("typescript-language-server").unwrap();
+        assert_eq!(tsserver.command, "typescript-language-server");
+        assert_eq!(tsserver.args, vec!["--stdio"]);
+
+        let pyright = ServerConfig::known("pyright").unwrap();
+        assert_eq!(pyright.command, "pyright-langserver");
+    }
+
+    #[test]
+    fn test_server_config_handles() {
+        let config = ServerConfig::known("rust-analyzer").unwrap();
+        assert!(config.handles(Path::new("/repo/src/main.rs")));
+        assert!(config.handles(Path::new("/repo/src/MAIN.RS")));
+        assert!(!config.handles(Path::new("/repo/Cargo.toml")));
+        assert!(!config.handles(Path::new("/repo/Makefile")));
+    }
+
+    #[test]
+    fn test_language_id() {
+        assert_eq!(language_id(Path::new("a.rs")), "rust");
+        assert_eq!(language_id(Path::new("a.tsx")), "typescriptreact");
+        assert_eq!(language_id(Path::new("a.mjs")), "javascript");
+        assert_eq!(language_id(Path::new("a.pyi")), "python");
+        assert_eq!(language_id(Path::new("a.toml")), "toml");
+        assert_eq!(language_id(Path::new("Makefile")), "");
+    }
+
+    #[test]
+    fn test_restart_backoff() {
+        let policy = RestartPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_server_status_serialization() {
+        let status = ServerStatus {
+            lane_id: "lane-1".to_string(),
+            name: "rust-analyzer".to_string(),
+            state: ServerState::Restarting,
+            restarts: 2,
+            pid: None,
+            error: Some("Exited with code 101".to_string()),
+        };
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["state"], "restarting");
+        assert_eq!(json["restarts"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_start_lane_rejects_unknown_server() {
+        let manager = LspManager::new();
+        let err = manager
+            .start_lane("lane-1", Path::new("/tmp"), &["no-such-server".to_string()])
+            .unwrap_err();
+        assert!(matches!(err, Error::ServerNotFound(name) if name == "no-such-server"));
+        assert!(manager.status("lane-1").is_empty());
+    }
+}
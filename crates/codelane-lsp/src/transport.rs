@@ -0,0 +1,126 @@
+//! JSON-RPC message framing over stdio
+//!
+//! Each message is a JSON body preceded by a `Content-Length` header and a
+//! blank line, as described in the LSP base protocol.
+
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{Error, Result};
+
+/// Largest message body accepted from a server
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// Read the next message, or `None` at end of stream
+pub(crate) async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return if content_length.is_none() {
+                Ok(None)
+            } else {
+                Err(Error::Protocol(
+                    "Unexpected end of stream in headers".to_string(),
+                ))
+            };
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let length = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|e| Error::Protocol(format!("Invalid Content-Length: {}", e)))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let length = content_length
+        .ok_or_else(|| Error::Protocol("Missing Content-Length header".to_string()))?;
+    if length > MAX_MESSAGE_LEN {
+        return Err(Error::Protocol(format!(
+            "Message too large: {} bytes",
+            length
+        )));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| Error::Protocol(format!("Invalid JSON message: {}", e)))
+}
+
+/// Write one message and flush it
+pub(crate) async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    message: &Value,
+) -> Result<()> {
+    let body = serde_json::to_vec(message)
+        .map_err(|e| Error::Protocol(format!("Failed to encode message: {}", e)))?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(
+            &mut buffer,
+            &json!({"jsonrpc": "2.0", "id": 1, "result": "héllo"}),
+        )
+        .await
+        .unwrap();
+        write_message(&mut buffer, &json!({"jsonrpc": "2.0", "method": "exit"}))
+            .await
+            .unwrap();
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        let first = read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(first["result"], "héllo");
+        let second = read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(second["method"], "exit");
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers() {
+        let body = r#"{"id":2}"#;
+        let raw = format!(
+            "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut reader = BufReader::new(raw.as_bytes());
+        let message = read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(message["id"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_messages() {
+        let mut reader = BufReader::new("Content-Type: x\r\n\r\n{}".as_bytes());
+        assert!(read_message(&mut reader).await.is_err());
+
+        let mut reader = BufReader::new("Content-Length: 10\r\n\r\n{}".as_bytes());
+        assert!(read_message(&mut reader).await.is_err());
+
+        let mut reader = BufReader::new("Content-Length: 2\r\n".as_bytes());
+        assert!(read_message(&mut reader).await.is_err());
+    }
+}
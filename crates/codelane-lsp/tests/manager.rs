@@ -0,0 +1,391 @@
+//! Language server manager tests against the fake server in `tests/support`
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use codelane_lsp::lsp_types::request::Request;
+use codelane_lsp::{
+    DiagnosticSeverity, Error, LspClient, LspEvent, LspManager, RestartPolicy, ServerConfig,
+    ServerState, ServerStatus,
+};
+use serde_json::Value;
+
+const FAKE_SERVER: &str = env!("CARGO_BIN_EXE_fake-lsp-server");
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+enum FakeState {}
+
+impl Request for FakeState {
+    type Params = ();
+    type Result = Value;
+    const METHOD: &'static str = "fake/state";
+}
+
+enum FakeCrash {}
+
+impl codelane_lsp::lsp_types::notification::Notification for FakeCrash {
+    type Params = ();
+    const METHOD: &'static str = "fake/crash";
+}
+
+fn fake_config() -> ServerConfig {
+    ServerConfig::new("fake", FAKE_SERVER).with_extensions(["txt"])
+}
+
+fn fast_policy(max_restarts: u32) -> RestartPolicy {
+    RestartPolicy {
+        max_restarts,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        ..Default::default()
+    }
+}
+
+fn manager(policy: RestartPolicy, config: ServerConfig) -> LspManager {
+    let manager = LspManager::with_policy(policy);
+    manager.register_server(config);
+    manager
+}
+
+/// Poll the lane's first server until `done` accepts its status
+async fn wait_for_status(
+    manager: &LspManager,
+    lane_id: &str,
+    done: impl Fn(&ServerStatus) -> bool,
+) -> ServerStatus {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Some(status) = manager.status(lane_id).into_iter().next() {
+                if done(&status) {
+                    return status;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("timed out waiting for server status")
+}
+
+async fn running_client(manager: &LspManager, lane_id: &str, path: &Path) -> Arc<LspClient> {
+    wait_for_status(manager, lane_id, |s| s.state == ServerState::Running).await;
+    manager
+        .clients_for(lane_id, path)
+        .pop()
+        .expect("running client")
+}
+
+#[tokio::test]
+async fn test_handshake_and_document_sync() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(0), fake_config());
+    let mut events = manager.subscribe();
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+
+    let path = root.path().join("notes.txt");
+    let client = running_client(&manager, "lane-1", &path).await;
+    assert_eq!(
+        client
+            .capabilities()
+            .and_then(|c| c.text_document_sync.clone()),
+        Some(codelane_lsp::lsp_types::TextDocumentSyncCapability::Kind(
+            codelane_lsp::lsp_types::TextDocumentSyncKind::FULL
+        ))
+    );
+
+    manager
+        .open_document("lane-1", &path, "hello\n".to_string())
+        .unwrap();
+    manager
+        .change_document("lane-1", &path, "ERROR here\n".to_string())
+        .unwrap();
+    // Not handled by the fake server
+    manager
+        .open_document("lane-1", &root.path().join("main.rs"), String::new())
+        .unwrap();
+
+    let state = client.request::<FakeState>(()).await.unwrap();
+    assert_eq!(state["configurationAnswered"], true);
+    let documents = state["documents"].as_array().unwrap();
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0]["version"], 2);
+    assert_eq!(documents[0]["text"], "ERROR here\n");
+
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Ok(LspEvent::Diagnostics { path: changed, .. }) = events.recv().await {
+                if changed == path && manager.diagnostic_counts("lane-1").errors == 1 {
+                    break;
+                }
+            }
+        }
+    })
+    .await
+    .unwrap();
+    let problems = manager.diagnostics("lane-1");
+    assert_eq!(problems.files.len(), 1);
+    let diagnostic = &problems.files[0].diagnostics[0];
+    assert_eq!(diagnostic.path, path);
+    assert_eq!((diagnostic.start_line, diagnostic.end_column), (1, 11));
+    assert_eq!(diagnostic.severity, DiagnosticSeverity::Error);
+    assert_eq!(diagnostic.source.as_deref(), Some("fake"));
+
+    manager.close_document("lane-1", &path).unwrap();
+    let state = client.request::<FakeState>(()).await.unwrap();
+    assert!(state["documents"].as_array().unwrap().is_empty());
+
+    manager.stop_all().await;
+}
+
+#[tokio::test]
+async fn test_restart_after_crash_reopens_documents() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(3), fake_config());
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+
+    let path = root.path().join("notes.txt");
+    let client = running_client(&manager, "lane-1", &path).await;
+    manager
+        .open_document("lane-1", &path, "draft".to_string())
+        .unwrap();
+    let first_pid = client.pid();
+
+    client.notify::<FakeCrash>(()).unwrap();
+    let status = wait_for_status(&manager, "lane-1", |s| {
+        s.state == ServerState::Running && s.restarts == 1
+    })
+    .await;
+    assert_eq!(status.error.as_deref(), Some("Exited with code 3"));
+    assert_ne!(status.pid, first_pid);
+
+    let client = manager.clients_for("lane-1", &path).pop().unwrap();
+    let state = client.request::<FakeState>(()).await.unwrap();
+    let documents = state["documents"].as_array().unwrap();
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0]["text"], "draft");
+
+    manager.stop_all().await;
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_restarts() {
+    let root = tempfile::tempdir().unwrap();
+    let config = fake_config().with_env("FAKE_LSP_EXIT_IMMEDIATELY", "1");
+    let manager = manager(fast_policy(2), config);
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+
+    let status = wait_for_status(&manager, "lane-1", |s| s.state == ServerState::Failed).await;
+    assert_eq!(status.restarts, 2);
+    assert!(status.error.is_some());
+    assert!(manager
+        .clients_for("lane-1", &root.path().join("notes.txt"))
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_missing_command_fails_without_retry() {
+    let root = tempfile::tempdir().unwrap();
+    let config =
+        ServerConfig::new("fake", "codelane-no-such-language-server").with_extensions(["txt"]);
+    let manager = manager(fast_policy(5), config);
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+
+    let status = wait_for_status(&manager, "lane-1", |s| s.state == ServerState::Failed).await;
+    assert_eq!(status.restarts, 0);
+    assert!(status
+        .error
+        .unwrap()
+        .contains("codelane-no-such-language-server"));
+}
+
+#[tokio::test]
+async fn test_stop_lane_shuts_down_servers() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(3), fake_config());
+    let mut events = manager.subscribe();
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    let client = running_client(&manager, "lane-1", &root.path().join("a.txt")).await;
+
+    assert!(manager.stop_lane("lane-1").await);
+    assert!(!manager.stop_lane("lane-1").await);
+    assert!(manager.status("lane-1").is_empty());
+
+    // Graceful shutdown, not a crash followed by a restart
+    let status = client.wait().await.unwrap();
+    assert!(status.success());
+    let mut last_state = None;
+    while let Ok(event) = events.try_recv() {
+        if let LspEvent::Status(status) = event {
+            assert_eq!(status.restarts, 0);
+            last_state = Some(status.state);
+        }
+    }
+    assert_eq!(last_state, Some(ServerState::Stopped));
+}
+
+#[tokio::test]
+async fn test_start_lane_is_idempotent() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(0), fake_config());
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    let statuses = manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    assert_eq!(statuses.len(), 1);
+
+    let err = manager
+        .start_lane(
+            "lane-1",
+            root.path(),
+            &["fake".to_string(), "nope".to_string()],
+        )
+        .unwrap_err();
+    assert!(matches!(err, Error::ServerNotFound(name) if name == "nope"));
+
+    manager.stop_all().await;
+}
+
+#[tokio::test]
+async fn test_diagnostics_cleared_when_lane_stops() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(0), fake_config());
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    let path = root.path().join("notes.txt");
+    let client = running_client(&manager, "lane-1", &path).await;
+
+    manager
+        .open_document("lane-1", &path, "ok\nERROR\nERROR\n".to_string())
+        .unwrap();
+    // The server publishes before answering requests that follow the open
+    client.request::<FakeState>(()).await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while manager.diagnostic_counts("lane-1").errors != 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        manager.file_diagnostics("lane-1", &path).diagnostics.len(),
+        2
+    );
+
+    manager.stop_lane("lane-1").await;
+    assert_eq!(manager.diagnostic_counts("lane-1").total(), 0);
+    assert!(manager.diagnostics("lane-1").files.is_empty());
+}
+
+#[tokio::test]
+async fn test_wait_for_diagnostics() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(0), fake_config());
+    let path = root.path().join("notes.txt");
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    // Documents opened before the server is up are sent once it starts
+    manager
+        .open_document("lane-1", &path, "ERROR\n".to_string())
+        .unwrap();
+    assert!(manager.is_document_open("lane-1", &path));
+    assert!(!manager.is_document_open("lane-1", &root.path().join("other.txt")));
+
+    let settled = manager
+        .wait_for_diagnostics("lane-1", Duration::from_millis(200), TIMEOUT)
+        .await;
+    assert!(settled);
+    assert_eq!(manager.diagnostic_counts("lane-1").errors, 1);
+
+    manager.stop_all().await;
+}
+
+#[tokio::test]
+async fn test_completion() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(0), fake_config());
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    let path = root.path().join("notes.txt");
+    running_client(&manager, "lane-1", &path).await;
+    manager
+        .open_document("lane-1", &path, "beta alpha.beta".to_string())
+        .unwrap();
+
+    let position = codelane_lsp::lsp_types::Position::new(0, 11);
+    let list = manager
+        .completion("lane-1", &path, position, Some(".".to_string()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!list.is_incomplete);
+    let labels: Vec<&str> = list.items.iter().map(|i| i.label.as_str()).collect();
+    assert_eq!(labels, vec!["alpha", "beta"]);
+    assert_eq!(list.items[0].detail.as_deref(), Some("."));
+
+    // No server handles the file
+    let other = root.path().join("main.rs");
+    assert!(manager
+        .completion("lane-1", &other, position, None)
+        .await
+        .unwrap()
+        .is_none());
+
+    manager.stop_all().await;
+}
+
+#[tokio::test]
+async fn test_format_document() {
+    let root = tempfile::tempdir().unwrap();
+    let manager = manager(fast_policy(0), fake_config());
+    manager
+        .start_lane("lane-1", root.path(), &["fake".to_string()])
+        .unwrap();
+    let path = root.path().join("notes.txt");
+    running_client(&manager, "lane-1", &path).await;
+    let options = codelane_lsp::lsp_types::FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    };
+
+    let formatted = manager
+        .format_document(
+            "lane-1",
+            &path,
+            "one  \ntwo\t\n".to_string(),
+            options.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(formatted.as_deref(), Some("one\ntwo\n"));
+    // Documents that were not open are closed again
+    assert!(!manager.is_document_open("lane-1", &path));
+
+    manager
+        .open_document("lane-1", &path, "old".to_string())
+        .unwrap();
+    let formatted = manager
+        .format_document("lane-1", &path, "new ".to_string(), options)
+        .await
+        .unwrap();
+    assert_eq!(formatted.as_deref(), Some("new"));
+    assert!(manager.is_document_open("lane-1", &path));
+
+    manager.stop_all().await;
+}
@@ -0,0 +1,201 @@
+//! Minimal language server for the integration tests.
+//!
+//! Speaks just enough LSP over stdio to exercise the manager: the initialize
+//! handshake, document sync, diagnostics for lines containing "ERROR",
+//! completion of the words in a document, formatting that strips trailing
+//! whitespace, and a few test-only messages:
+//!
+//! - `fake/state` request: open documents, whether the server's
+//!   `workspace/configuration` request was answered, and the process id
+//! - `fake/crash` notification: exit immediately with code 3
+//!
+//! Setting `FAKE_LSP_EXIT_IMMEDIATELY` makes the server exit before reading
+//! anything, simulating a server that cannot start.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let mut body = vec![0; length?];
+    reader.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn write_message(message: Value) {
+    let body = message.to_string();
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    stdout.flush().unwrap();
+}
+
+fn publish_diagnostics(uri: &Value, text: &str) {
+    let diagnostics: Vec<Value> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains("ERROR"))
+        .map(|(number, line)| {
+            json!({
+                "range": {
+                    "start": {"line": number, "character": 0},
+                    "end": {"line": number, "character": line.len()},
+                },
+                "severity": 1,
+                "source": "fake",
+                "message": "found ERROR",
+            })
+        })
+        .collect();
+    write_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    }));
+}
+
+/// Edits removing trailing whitespace from each line
+fn format_edits(text: &str) -> Vec<Value> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| line.len() != line.trim_end().len())
+        .map(|(number, line)| {
+            json!({
+                "range": {
+                    "start": {"line": number, "character": line.trim_end().len()},
+                    "end": {"line": number, "character": line.len()},
+                },
+                "newText": "",
+            })
+        })
+        .collect()
+}
+
+fn main() {
+    if std::env::var_os("FAKE_LSP_EXIT_IMMEDIATELY").is_some() {
+        std::process::exit(1);
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut documents: BTreeMap<String, (i64, String)> = BTreeMap::new();
+    let mut configuration_answered = false;
+    let mut shutdown = false;
+
+    while let Some(message) = read_message(&mut stdin) {
+        let method = message["method"].as_str().unwrap_or_default();
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+
+        match (method, id) {
+            // Response to our workspace/configuration request
+            ("", Some(_)) => configuration_answered = true,
+            ("initialize", Some(id)) => write_message(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "completionProvider": {"triggerCharacters": ["."]},
+                        "documentFormattingProvider": true,
+                    },
+                    "serverInfo": {"name": "fake-lsp-server"},
+                },
+            })),
+            ("initialized", None) => write_message(json!({
+                "jsonrpc": "2.0",
+                "id": "config-1",
+                "method": "workspace/configuration",
+                "params": {"items": [{"section": "fake"}]},
+            })),
+            ("textDocument/didOpen", None) => {
+                let document = &params["textDocument"];
+                let text = document["text"].as_str().unwrap_or_default().to_string();
+                publish_diagnostics(&document["uri"], &text);
+                documents.insert(
+                    document["uri"].as_str().unwrap_or_default().to_string(),
+                    (document["version"].as_i64().unwrap_or_default(), text),
+                );
+            }
+            ("textDocument/didChange", None) => {
+                let document = &params["textDocument"];
+                let text = params["contentChanges"][0]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                publish_diagnostics(&document["uri"], &text);
+                documents.insert(
+                    document["uri"].as_str().unwrap_or_default().to_string(),
+                    (document["version"].as_i64().unwrap_or_default(), text),
+                );
+            }
+            ("textDocument/didClose", None) => {
+                documents.remove(params["textDocument"]["uri"].as_str().unwrap_or_default());
+            }
+            ("textDocument/completion", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = documents.get(uri).map(|(_, text)| text.as_str());
+                let mut words: Vec<&str> = text
+                    .unwrap_or_default()
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                words.sort_unstable();
+                words.dedup();
+                let trigger = &params["context"]["triggerCharacter"];
+                let items: Vec<Value> = words
+                    .iter()
+                    .map(|word| json!({"label": word, "detail": trigger}))
+                    .collect();
+                write_message(json!({"jsonrpc": "2.0", "id": id, "result": items}));
+            }
+            ("textDocument/formatting", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let edits = documents.get(uri).map(|(_, text)| format_edits(text));
+                write_message(json!({"jsonrpc": "2.0", "id": id, "result": edits}));
+            }
+            ("fake/state", Some(id)) => {
+                let documents: Vec<Value> = documents
+                    .iter()
+                    .map(|(uri, (version, text))| json!({"uri": uri, "version": version, "text": text}))
+                    .collect();
+                write_message(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "documents": documents,
+                        "configurationAnswered": configuration_answered,
+                        "pid": std::process::id(),
+                    },
+                }));
+            }
+            ("fake/crash", None) => std::process::exit(3),
+            ("shutdown", Some(id)) => {
+                shutdown = true;
+                write_message(json!({"jsonrpc": "2.0", "id": id, "result": null}));
+            }
+            ("exit", None) => std::process::exit(if shutdown { 0 } else { 1 }),
+            (_, Some(id)) => write_message(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": -32601, "message": format!("Method not found: {}", method)},
+            })),
+            (_, None) => {}
+        }
+    }
+}
@@ -1,3 +1,12 @@
 //! Plugin API definitions
+//!
+//! Bindings generated from `wit/plugin.wit`, the interface between the host
+//! and plugin components.
 
-// TODO: Define WIT interface bindings
+wasmtime::component::bindgen!({
+    path: "wit",
+    world: "plugin",
+});
+
+pub use codelane::plugin::host::{self, Host};
+pub use codelane::plugin::types::{self, HttpRequest, HttpResponse, LogLevel};
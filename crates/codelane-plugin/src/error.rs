@@ -1,5 +1,7 @@
 //! Plugin error types
 
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,9 +12,23 @@ pub enum Error {
     #[error("Plugin load failed: {0}")]
     LoadFailed(String),
 
+    #[error("Invalid plugin manifest {}:{}:{}: {}", .path.display(), .line, .column, .message)]
+    Manifest {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
     #[error("Plugin execution error: {0}")]
     Execution(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Plugin exceeded its limits: {0}")]
+    ResourceLimit(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -1,3 +1,1156 @@
 //! WASM plugin host
+//!
+//! Plugins live in subdirectories of the plugins directory (see
+//! `codelane_core::paths::plugins_dir`), each with a `plugin.toml` manifest
+//! and the WASM component its `entry` names. Every plugin runs in its own
+//! store: host calls are checked against the manifest's permissions, and
+//! each call into a plugin is bounded by fuel, a wall-clock deadline and a
+//! memory limit so a buggy plugin fails instead of hanging the app.
+//!
+//! In developer mode the host watches the plugins directory and reloads a
+//! plugin when its files change. A reload that fails keeps the previous
+//! version running, with its commands, until the plugin is fixed.
 
-// TODO: Implement Wasmtime-based plugin host
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Component as PathComponent, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use codelane_core::config::FileCategoryRule;
+use codelane_git::diff::FileDiff;
+use serde::Serialize;
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+use crate::api::{self, HttpRequest, HttpResponse, LogLevel, Plugin, Registration};
+use crate::manifest::{PluginCapabilities, PluginManifest, PluginPermissions};
+use crate::review::{self, Finding, ReviewReport, ReviewRule};
+use crate::sandbox::Sandbox;
+use crate::watcher::PluginWatcher;
+use crate::{Error, Result};
+
+/// Name of the manifest file in a plugin directory
+pub const MANIFEST_FILE: &str = "plugin.toml";
+
+/// How often the epoch advances; the granularity of call deadlines
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Largest HTTP response body handed to a plugin
+const MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Resources a single call into a plugin may use
+#[derive(Debug, Clone)]
+pub struct PluginLimits {
+    /// Fuel per call, roughly the number of WASM instructions
+    pub fuel: u64,
+
+    /// Wall-clock time per call, including host calls such as HTTP requests
+    pub timeout: Duration,
+
+    /// Maximum size of a plugin's linear memory in bytes
+    pub max_memory: usize,
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 1_000_000_000,
+            timeout: Duration::from_secs(5),
+            max_memory: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Whether a plugin can be called
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginStatus {
+    Active,
+    Failed,
+}
+
+/// A loaded plugin as shown in the plugin list
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub path: PathBuf,
+    pub status: PluginStatus,
+    /// Why the plugin failed to load or stopped working
+    pub error: Option<String>,
+    pub capabilities: PluginCapabilities,
+    pub permissions: PluginPermissions,
+}
+
+/// A command registered by a plugin, for the command palette
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginCommand {
+    pub plugin: String,
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// A plugin directory whose manifest could not be loaded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginLoadError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Outcome of reloading a plugin directory
+#[derive(Debug)]
+pub enum PluginReload {
+    /// The plugin was loaded and activated
+    Loaded(Box<PluginInfo>),
+
+    /// The plugin's manifest is gone and the plugin was unloaded
+    Removed(PathBuf),
+
+    /// The plugin failed to load; a running version is kept
+    Failed(PathBuf, Error),
+}
+
+/// Per-plugin data available to host calls
+pub struct PluginState {
+    name: String,
+    sandbox: Sandbox,
+    limits: StoreLimits,
+    http: ureq::Agent,
+}
+
+impl PluginState {
+    fn new(manifest: &PluginManifest, limits: &PluginLimits) -> Self {
+        Self {
+            name: manifest.plugin.name.clone(),
+            sandbox: Sandbox::new(manifest.permissions.clone()),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory)
+                .build(),
+            // Redirects could leave the network allow-list
+            http: ureq::AgentBuilder::new()
+                .redirects(0)
+                .timeout(limits.timeout)
+                .build(),
+        }
+    }
+}
+
+impl api::types::Host for PluginState {}
+
+impl api::Host for PluginState {
+    fn log(&mut self, level: LogLevel, message: String) {
+        let plugin = self.name.as_str();
+        match level {
+            LogLevel::Trace => tracing::trace!(plugin, "{}", message),
+            LogLevel::Debug => tracing::debug!(plugin, "{}", message),
+            LogLevel::Info => tracing::info!(plugin, "{}", message),
+            LogLevel::Warn => tracing::warn!(plugin, "{}", message),
+            LogLevel::Error => tracing::error!(plugin, "{}", message),
+        }
+    }
+
+    fn read_file(&mut self, path: String) -> std::result::Result<Vec<u8>, String> {
+        let path = self.sandbox.readable(&path).map_err(|e| e.to_string())?;
+        std::fs::read(path).map_err(|e| e.to_string())
+    }
+
+    fn write_file(&mut self, path: String, contents: Vec<u8>) -> std::result::Result<(), String> {
+        let path = self.sandbox.writable(&path).map_err(|e| e.to_string())?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    fn list_dir(&mut self, path: String) -> std::result::Result<Vec<String>, String> {
+        let path = self.sandbox.readable(&path).map_err(|e| e.to_string())?;
+        let mut names = std::fs::read_dir(path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    fn fetch(&mut self, request: HttpRequest) -> std::result::Result<HttpResponse, String> {
+        self.sandbox
+            .check_url(&request.url)
+            .map_err(|e| e.to_string())?;
+
+        let mut call = self.http.request(&request.method, &request.url);
+        for (name, value) in &request.headers {
+            call = call.set(name, value);
+        }
+        let result = match &request.body {
+            Some(body) => call.send_bytes(body),
+            None => call.call(),
+        };
+        let response = match result {
+            Ok(response) => response,
+            // Error statuses are still responses to the plugin
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_RESPONSE_SIZE)
+            .read_to_end(&mut body)
+            .map_err(|e| e.to_string())?;
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Advances the engine's epoch so calls past their deadline are interrupted
+struct EpochTicker {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl EpochTicker {
+    fn start(engine: Engine) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("plugin-epoch".to_string())
+            .spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    std::thread::sleep(EPOCH_TICK);
+                    engine.increment_epoch();
+                }
+            })
+            .ok();
+        Self { stop, thread }
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Instance {
+    store: Store<PluginState>,
+    bindings: Plugin,
+}
+
+struct LoadedPlugin {
+    manifest: PluginManifest,
+    dir: PathBuf,
+    instance: Option<Instance>,
+    error: Option<String>,
+    commands: Vec<PluginCommand>,
+    review_rules: Vec<ReviewRule>,
+}
+
+impl LoadedPlugin {
+    fn info(&self) -> PluginInfo {
+        let meta = &self.manifest.plugin;
+        PluginInfo {
+            name: meta.name.clone(),
+            version: meta.version.clone(),
+            description: meta.description.clone(),
+            path: self.dir.clone(),
+            status: if self.instance.is_some() {
+                PluginStatus::Active
+            } else {
+                PluginStatus::Failed
+            },
+            error: self.error.clone(),
+            capabilities: self.manifest.capabilities.clone(),
+            permissions: self.manifest.permissions.clone(),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.instance.is_some()
+    }
+
+    /// Keep what the plugin registered that its manifest declares
+    fn register(&mut self, registration: Registration) {
+        let name = &self.manifest.plugin.name;
+        let capabilities = &self.manifest.capabilities;
+        let undeclared = |kind: &str, id: &str| {
+            tracing::warn!(
+                "Plugin {} registered {} {} that its manifest does not declare",
+                name,
+                kind,
+                id
+            );
+        };
+
+        self.commands = registration
+            .commands
+            .into_iter()
+            .filter(|command| {
+                let declared = capabilities.commands.contains(&command.id);
+                if !declared {
+                    undeclared("command", &command.id);
+                }
+                declared
+            })
+            .map(|command| PluginCommand {
+                plugin: name.clone(),
+                id: command.id,
+                title: command.title,
+                description: command.description,
+            })
+            .collect();
+        self.review_rules = registration
+            .review_rules
+            .into_iter()
+            .filter(|rule| {
+                let declared = capabilities.review_rules.contains(&rule.id);
+                if !declared {
+                    undeclared("review rule", &rule.id);
+                }
+                declared
+            })
+            .map(|rule| ReviewRule {
+                plugin: name.clone(),
+                id: rule.id,
+                description: rule.description,
+            })
+            .collect();
+    }
+}
+
+/// Loads plugins and runs calls into them
+pub struct PluginHost {
+    engine: Engine,
+    linker: Linker<PluginState>,
+    plugins_dir: PathBuf,
+    limits: PluginLimits,
+    plugins: BTreeMap<String, LoadedPlugin>,
+    /// Manifest errors of plugin directories without a loaded plugin
+    load_errors: BTreeMap<PathBuf, String>,
+    /// Watches the plugins directory in developer mode
+    watcher: Option<PluginWatcher>,
+    _ticker: EpochTicker,
+}
+
+impl PluginHost {
+    /// Create a host for the plugins in `plugins_dir` with default limits
+    pub fn new(plugins_dir: PathBuf) -> Result<Self> {
+        Self::with_limits(plugins_dir, PluginLimits::default())
+    }
+
+    pub fn with_limits(plugins_dir: PathBuf, limits: PluginLimits) -> Result<Self> {
+        let mut config = Config::new();
+        config
+            .wasm_component_model(true)
+            .consume_fuel(true)
+            .epoch_interruption(true);
+        let engine = Engine::new(&config).map_err(|e| Error::Other(e.to_string()))?;
+
+        let mut linker = Linker::new(&engine);
+        Plugin::add_to_linker(&mut linker, |state: &mut PluginState| state)
+            .map_err(|e| Error::Other(e.to_string()))?;
+
+        Ok(Self {
+            _ticker: EpochTicker::start(engine.clone()),
+            engine,
+            linker,
+            plugins_dir,
+            limits,
+            plugins: BTreeMap::new(),
+            load_errors: BTreeMap::new(),
+            watcher: None,
+        })
+    }
+
+    pub fn plugins_dir(&self) -> &Path {
+        &self.plugins_dir
+    }
+
+    /// Plugin directories in the plugins directory, sorted by path
+    pub fn discover(&self) -> Result<Vec<PathBuf>> {
+        if !self.plugins_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut dirs = std::fs::read_dir(&self.plugins_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join(MANIFEST_FILE).is_file())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    /// Load every discovered plugin
+    ///
+    /// A plugin that fails to load is listed as failed if its manifest
+    /// could be read, and in [`errors`](Self::errors) otherwise.
+    pub fn load_all(&mut self) -> Result<Vec<PluginInfo>> {
+        for dir in self.discover()? {
+            if let Err(e) = self.load(&dir) {
+                tracing::warn!("Failed to load plugin {}: {}", dir.display(), e);
+            }
+        }
+        Ok(self.plugins())
+    }
+
+    /// Load the plugin in `dir` and activate it
+    ///
+    /// Replaces a loaded plugin with the same name or directory. Once the
+    /// manifest has been read, a plugin whose component fails to load or
+    /// activate is kept in the list as failed, with the error.
+    pub fn load(&mut self, dir: &Path) -> Result<PluginInfo> {
+        let manifest = match PluginManifest::load(&dir.join(MANIFEST_FILE)) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                self.load_errors.insert(dir.to_path_buf(), e.to_string());
+                return Err(e);
+            }
+        };
+        let loaded = self.instantiate(dir, &manifest);
+        self.insert(dir, manifest, loaded)
+    }
+
+    /// Reload the plugin in `dir` after its files changed
+    ///
+    /// If the new version fails to load, an active plugin keeps running
+    /// with the commands and review rules it registered, and the failure
+    /// is shown as its error.
+    pub fn reload(&mut self, dir: &Path) -> PluginReload {
+        if !dir.join(MANIFEST_FILE).is_file() {
+            self.load_errors.remove(dir);
+            self.plugins.retain(|_, plugin| plugin.dir != dir);
+            return PluginReload::Removed(dir.to_path_buf());
+        }
+
+        let running = self
+            .plugins
+            .iter()
+            .find(|(_, plugin)| plugin.dir == dir && plugin.is_active())
+            .map(|(name, _)| name.clone());
+        let Some(running) = running else {
+            return match self.load(dir) {
+                Ok(info) => PluginReload::Loaded(Box::new(info)),
+                Err(e) => PluginReload::Failed(dir.to_path_buf(), e),
+            };
+        };
+
+        let result = PluginManifest::load(&dir.join(MANIFEST_FILE)).and_then(|manifest| {
+            let loaded = self.instantiate(dir, &manifest)?;
+            Ok((manifest, loaded))
+        });
+        match result {
+            Ok((manifest, loaded)) => match self.insert(dir, manifest, Ok(loaded)) {
+                Ok(info) => PluginReload::Loaded(Box::new(info)),
+                Err(e) => PluginReload::Failed(dir.to_path_buf(), e),
+            },
+            Err(e) => {
+                tracing::warn!("Failed to reload plugin {}: {}", running, e);
+                if let Some(plugin) = self.plugins.get_mut(&running) {
+                    plugin.error = Some(format!(
+                        "Reload failed, running the previous version: {}",
+                        e
+                    ));
+                }
+                PluginReload::Failed(dir.to_path_buf(), e)
+            }
+        }
+    }
+
+    /// Watch the plugins directory and reload plugins when they change
+    pub fn set_developer_mode(&mut self, enabled: bool) -> Result<()> {
+        if enabled && self.watcher.is_none() {
+            self.watcher = Some(PluginWatcher::new(&self.plugins_dir)?);
+        } else if !enabled {
+            self.watcher = None;
+        }
+        Ok(())
+    }
+
+    pub fn developer_mode(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Reload the plugins whose files changed, in developer mode
+    ///
+    /// A plugin is reloaded once its files have been unchanged for a
+    /// moment, so a build writing several files reloads it once. Call this
+    /// periodically while developer mode is on.
+    pub fn reload_changed(&mut self) -> Vec<PluginReload> {
+        let dirs = match &mut self.watcher {
+            Some(watcher) => watcher.settled(),
+            None => return Vec::new(),
+        };
+        dirs.iter().map(|dir| self.reload(dir)).collect()
+    }
+
+    /// Plugin directories whose manifest could not be loaded
+    pub fn errors(&self) -> Vec<PluginLoadError> {
+        self.load_errors
+            .iter()
+            .map(|(path, message)| PluginLoadError {
+                path: path.clone(),
+                message: message.clone(),
+            })
+            .collect()
+    }
+
+    /// Add a plugin, replacing one with the same name or directory
+    fn insert(
+        &mut self,
+        dir: &Path,
+        manifest: PluginManifest,
+        loaded: Result<(Instance, Registration)>,
+    ) -> Result<PluginInfo> {
+        // The plugin may have been renamed since it was loaded
+        self.plugins.retain(|_, plugin| plugin.dir != dir);
+        self.load_errors.remove(dir);
+        let name = manifest.plugin.name.clone();
+
+        let (instance, registration, error) = match loaded {
+            Ok((instance, registration)) => (Some(instance), Some(registration), None),
+            Err(e) => (None, None, Some(e)),
+        };
+        let mut plugin = LoadedPlugin {
+            manifest,
+            dir: dir.to_path_buf(),
+            instance,
+            error: error.as_ref().map(Error::to_string),
+            commands: Vec::new(),
+            review_rules: Vec::new(),
+        };
+        if let Some(registration) = registration {
+            plugin.register(registration);
+        }
+        let info = plugin.info();
+        self.plugins.insert(name, plugin);
+        match error {
+            Some(e) => Err(e),
+            None => Ok(info),
+        }
+    }
+
+    /// Unload a plugin by name
+    pub fn unload(&mut self, name: &str) -> Result<()> {
+        self.plugins
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| Error::NotFound(name.to_string()))
+    }
+
+    /// Loaded plugins, sorted by name
+    pub fn plugins(&self) -> Vec<PluginInfo> {
+        self.plugins.values().map(LoadedPlugin::info).collect()
+    }
+
+    pub fn plugin(&self, name: &str) -> Option<PluginInfo> {
+        self.plugins.get(name).map(LoadedPlugin::info)
+    }
+
+    /// Commands of the active plugins
+    pub fn commands(&self) -> Vec<PluginCommand> {
+        self.plugins
+            .values()
+            .filter(|plugin| plugin.is_active())
+            .flat_map(|plugin| plugin.commands.iter().cloned())
+            .collect()
+    }
+
+    /// File categories declared by the active plugins, for review sorting
+    pub fn file_categories(&self) -> Vec<FileCategoryRule> {
+        self.plugins
+            .values()
+            .filter(|plugin| plugin.is_active())
+            .flat_map(|plugin| plugin.manifest.capabilities.file_categories.iter().cloned())
+            .collect()
+    }
+
+    /// Run a plugin's command with file access confined to `workspace`
+    pub fn run_command(&mut self, plugin: &str, id: &str, workspace: Option<&Path>) -> Result<()> {
+        let registered = self
+            .plugins
+            .get(plugin)
+            .is_some_and(|loaded| loaded.commands.iter().any(|command| command.id == id));
+        if !registered {
+            return Err(Error::NotFound(format!("{}:{}", plugin, id)));
+        }
+        self.call(plugin, workspace, |bindings, store| {
+            bindings.call_run_command(store, id)
+        })?
+        .map_err(|e| Error::Execution(format!("{}:{} failed: {}", plugin, id, e)))
+    }
+
+    /// Review rules of the active plugins
+    pub fn review_rules(&self) -> Vec<ReviewRule> {
+        self.plugins
+            .values()
+            .filter(|plugin| plugin.is_active())
+            .flat_map(|plugin| plugin.review_rules.iter().cloned())
+            .collect()
+    }
+
+    /// Run every review rule against the changes in `workspace`
+    ///
+    /// A rule that fails is listed in the report's errors; the findings of
+    /// the other rules are still reported.
+    pub fn review(&mut self, workspace: &Path, files: &[FileDiff]) -> ReviewReport {
+        let diff = review::wit_diff(files);
+        let mut report = ReviewReport::default();
+        for rule in self.review_rules() {
+            let result = self.call(&rule.plugin, Some(workspace), |bindings, store| {
+                bindings.call_review(store, &rule.id, &diff)
+            });
+            match result {
+                Ok(Ok(findings)) => report.findings.extend(
+                    findings
+                        .into_iter()
+                        .map(|finding| Finding::from_wit(&rule.plugin, &rule.id, finding)),
+                ),
+                Ok(Err(e)) => report
+                    .errors
+                    .push(format!("{}:{} failed: {}", rule.plugin, rule.id, e)),
+                Err(e) => report.errors.push(e.to_string()),
+            }
+        }
+        report.sort();
+        report
+    }
+
+    /// Call into an active plugin under the host's limits
+    ///
+    /// `workspace` is the root that the plugin's file access is confined
+    /// to for this call. A plugin that traps, runs out of fuel or misses
+    /// its deadline is marked as failed and can't be called again until it
+    /// is reloaded.
+    pub fn call<R>(
+        &mut self,
+        name: &str,
+        workspace: Option<&Path>,
+        f: impl FnOnce(&Plugin, &mut Store<PluginState>) -> wasmtime::Result<R>,
+    ) -> Result<R> {
+        let plugin = self
+            .plugins
+            .get_mut(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))?;
+        let instance = plugin.instance.as_mut().ok_or_else(|| {
+            Error::Execution(format!(
+                "{} is not active: {}",
+                name,
+                plugin.error.as_deref().unwrap_or("not loaded")
+            ))
+        })?;
+
+        instance
+            .store
+            .data_mut()
+            .sandbox
+            .set_workspace(workspace.map(Path::to_path_buf));
+        let result = refuel(&mut instance.store, &self.limits)
+            .and_then(|()| f(&instance.bindings, &mut instance.store))
+            .map_err(|e| call_error(name, e));
+        instance.store.data_mut().sandbox.set_workspace(None);
+
+        if let Err(e) = &result {
+            // A trapped instance can't be entered again
+            tracing::warn!("Plugin {} failed: {}", name, e);
+            plugin.instance = None;
+            plugin.error = Some(e.to_string());
+        }
+        result
+    }
+
+    fn instantiate(
+        &self,
+        dir: &Path,
+        manifest: &PluginManifest,
+    ) -> Result<(Instance, Registration)> {
+        let entry = entry_path(dir, &manifest.plugin.entry)?;
+        let component = Component::from_file(&self.engine, &entry)
+            .map_err(|e| Error::LoadFailed(format!("{}: {:#}", entry.display(), e)))?;
+
+        let name = manifest.plugin.name.as_str();
+        let mut store = Store::new(&self.engine, PluginState::new(manifest, &self.limits));
+        store.limiter(|state| &mut state.limits);
+        refuel(&mut store, &self.limits).map_err(|e| call_error(name, e))?;
+        let bindings = Plugin::instantiate(&mut store, &component, &self.linker)
+            .map_err(|e| call_error(name, e))?;
+
+        refuel(&mut store, &self.limits).map_err(|e| call_error(name, e))?;
+        let registration = bindings
+            .call_activate(&mut store)
+            .map_err(|e| call_error(name, e))?
+            .map_err(|e| Error::LoadFailed(format!("{} failed to activate: {}", name, e)))?;
+
+        Ok((Instance { store, bindings }, registration))
+    }
+}
+
+/// Reset a store's fuel and deadline before a call
+fn refuel(store: &mut Store<PluginState>, limits: &PluginLimits) -> wasmtime::Result<()> {
+    store.set_fuel(limits.fuel)?;
+    let ticks = limits.timeout.as_millis() / EPOCH_TICK.as_millis();
+    store.set_epoch_deadline(ticks.max(1) as u64);
+    Ok(())
+}
+
+fn call_error(name: &str, error: wasmtime::Error) -> Error {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => Error::ResourceLimit(format!("{} ran out of fuel", name)),
+        Some(Trap::Interrupt) => Error::ResourceLimit(format!("{} timed out", name)),
+        _ => Error::Execution(format!("{}: {:#}", name, error)),
+    }
+}
+
+/// Path of a plugin's component, which must be inside its directory
+fn entry_path(dir: &Path, entry: &str) -> Result<PathBuf> {
+    let relative = Path::new(entry);
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, PathComponent::Normal(_) | PathComponent::CurDir));
+    if entry.is_empty() || !inside {
+        return Err(Error::LoadFailed(format!(
+            "entry must be a path inside the plugin directory: {}",
+            entry
+        )));
+    }
+    let path = dir.join(relative);
+    if !path.is_file() {
+        return Err(Error::LoadFailed(format!(
+            "entry not found: {}",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn write_plugin(root: &Path, dir: &str, name: &str, entry: &str) -> PathBuf {
+        let dir = root.join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(MANIFEST_FILE),
+            format!(
+                "[plugin]\nname = \"{}\"\nversion = \"0.1.0\"\nentry = \"{}\"\n",
+                name, entry
+            ),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_discover_plugins() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write_plugin(temp_dir.path(), "b-plugin", "b", "plugin.wasm");
+        write_plugin(temp_dir.path(), "a-plugin", "a", "plugin.wasm");
+        std::fs::create_dir(temp_dir.path().join("not-a-plugin")).unwrap();
+
+        let host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+        let dirs = host.discover().unwrap();
+        assert_eq!(
+            dirs,
+            vec![
+                temp_dir.path().join("a-plugin"),
+                temp_dir.path().join("b-plugin")
+            ]
+        );
+
+        let missing = PluginHost::new(temp_dir.path().join("missing")).unwrap();
+        assert!(missing.discover().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_entry_must_stay_in_plugin_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("plugin.wasm"), b"").unwrap();
+
+        assert!(entry_path(temp_dir.path(), "plugin.wasm").is_ok());
+        assert!(entry_path(temp_dir.path(), "../plugin.wasm").is_err());
+        assert!(entry_path(temp_dir.path(), "/tmp/plugin.wasm").is_err());
+        assert!(entry_path(temp_dir.path(), "").is_err());
+        assert!(entry_path(temp_dir.path(), "missing.wasm").is_err());
+    }
+
+    #[test]
+    fn test_invalid_component_is_listed_as_failed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = write_plugin(temp_dir.path(), "broken", "broken", "plugin.wasm");
+        std::fs::write(dir.join("plugin.wasm"), b"not wasm").unwrap();
+
+        let mut host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+        let plugins = host.load_all().unwrap();
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0].name, "broken");
+        assert_eq!(plugins[0].status, PluginStatus::Failed);
+        assert!(plugins[0].error.is_some());
+
+        let err = host.call("broken", None, |_, _| Ok(())).unwrap_err();
+        assert!(matches!(err, Error::Execution(_)));
+        assert!(matches!(
+            host.call("missing", None, |_, _| Ok(())),
+            Err(Error::NotFound(_))
+        ));
+
+        host.unload("broken").unwrap();
+        assert!(host.plugins().is_empty());
+    }
+
+    /// A component implementing the plugin world
+    ///
+    /// It registers the commands `hello`, `fail` and `sneaky` and the review
+    /// rule `no-unwrap`. `fail` returns an error; `no-unwrap` reports a
+    /// warning on line 3 of the first file of the diff. `activate` replaces
+    /// the body of the activate export.
+    fn component(activate: Option<&str>) -> String {
+        let store = |values: &[(u32, u32)]| {
+            values
+                .iter()
+                .map(|(addr, value)| {
+                    format!("(i32.store (i32.const {}) (i32.const {}))", addr, value)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let registration = store(&[
+            // Commands: id, title, description (none)
+            (256, 100),
+            (260, 5),
+            (264, 110),
+            (268, 9),
+            (284, 210),
+            (288, 4),
+            (292, 210),
+            (296, 4),
+            (312, 120),
+            (316, 6),
+            (320, 120),
+            (324, 6),
+            // Review rules: id, description
+            (352, 130),
+            (356, 9),
+            (360, 140),
+            (364, 15),
+            // ok(registration)
+            (404, 256),
+            (408, 3),
+            (412, 352),
+            (416, 1),
+        ]) + "\n(i32.const 400)";
+        let activate = activate.map_or(registration, str::to_string);
+
+        format!(
+            r#"
+            (component
+              (core module $m
+                (memory (export "memory") 1)
+                (global $heap (mut i32) (i32.const 4096))
+                (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+                  (local $ptr i32)
+                  (local.set $ptr
+                    (i32.and
+                      (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+                      (i32.sub (i32.const 0) (local.get 2))))
+                  (global.set $heap (i32.add (local.get $ptr) (local.get 3)))
+                  (local.get $ptr))
+                (data (i32.const 100) "hello")
+                (data (i32.const 110) "Say hello")
+                (data (i32.const 120) "sneaky")
+                (data (i32.const 130) "no-unwrap")
+                (data (i32.const 140) "No new unwrap()")
+                (data (i32.const 180) "new unwrap()")
+                (data (i32.const 200) "boom")
+                (data (i32.const 210) "fail")
+                (func (export "activate") (result i32)
+                  {activate})
+                (func (export "run-command") (param i32 i32) (result i32)
+                  (if (i32.eq (local.get 1) (i32.const 4))
+                    (then
+                      (i32.store8 (i32.const 440) (i32.const 1))
+                      (i32.store (i32.const 444) (i32.const 200))
+                      (i32.store (i32.const 448) (i32.const 4)))
+                    (else (i32.store8 (i32.const 440) (i32.const 0))))
+                  (i32.const 440))
+                (func (export "review") (param i32 i32 i32 i32) (result i32)
+                  (i32.store (i32.const 480) (i32.load (local.get 2)))
+                  (i32.store (i32.const 484) (i32.load offset=4 (local.get 2)))
+                  (i32.store (i32.const 488) (i32.const 3))
+                  (i32.store8 (i32.const 492) (i32.const 1))
+                  (i32.store (i32.const 496) (i32.const 180))
+                  (i32.store (i32.const 500) (i32.const 12))
+                  (i32.store (i32.const 524) (i32.const 480))
+                  (i32.store (i32.const 528)
+                    (select (i32.const 1) (i32.const 0) (local.get 3)))
+                  (i32.const 520)))
+              (core instance $i (instantiate $m))
+
+              ;; Types used by exports must be exported themselves
+              (type $command-def (record
+                (field "id" string)
+                (field "title" string)
+                (field "description" (option string))))
+              (export $command "command" (type $command-def))
+              (type $review-rule-def (record
+                (field "id" string)
+                (field "description" string)))
+              (export $review-rule "review-rule" (type $review-rule-def))
+              (type $registration-def (record
+                (field "commands" (list $command))
+                (field "review-rules" (list $review-rule))))
+              (export $registration "registration" (type $registration-def))
+              (type $change-kind-def (enum "added" "deleted" "modified" "renamed" "copied"))
+              (export $change-kind "change-kind" (type $change-kind-def))
+              (type $line-kind-def (enum "context" "added" "removed"))
+              (export $line-kind "line-kind" (type $line-kind-def))
+              (type $diff-line-def (record
+                (field "kind" $line-kind)
+                (field "old-line" (option u32))
+                (field "new-line" (option u32))
+                (field "content" string)))
+              (export $diff-line "diff-line" (type $diff-line-def))
+              (type $diff-hunk-def (record
+                (field "old-start" u32)
+                (field "old-lines" u32)
+                (field "new-start" u32)
+                (field "new-lines" u32)
+                (field "lines" (list $diff-line))))
+              (export $diff-hunk "diff-hunk" (type $diff-hunk-def))
+              (type $file-diff-def (record
+                (field "path" string)
+                (field "old-path" (option string))
+                (field "change" $change-kind)
+                (field "binary" bool)
+                (field "hunks" (list $diff-hunk))))
+              (export $file-diff "file-diff" (type $file-diff-def))
+              (type $severity-def (enum "info" "warning" "error"))
+              (export $severity "severity" (type $severity-def))
+              (type $finding-def (record
+                (field "file" string)
+                (field "line" u32)
+                (field "severity" $severity)
+                (field "message" string)))
+              (export $finding "finding" (type $finding-def))
+
+              (func (export "activate") (result (result $registration (error string)))
+                (canon lift (core func $i "activate") (memory $i "memory")))
+              (func (export "run-command") (param "id" string) (result (result (error string)))
+                (canon lift (core func $i "run-command")
+                  (memory $i "memory") (realloc (func $i "cabi_realloc"))))
+              (func (export "review")
+                (param "rule" string) (param "diff" (list $file-diff))
+                (result (result (list $finding) (error string)))
+                (canon lift (core func $i "review")
+                  (memory $i "memory") (realloc (func $i "cabi_realloc")))))
+            "#
+        )
+    }
+
+    fn install(root: &Path, activate: Option<&str>) -> PathBuf {
+        let dir = write_plugin(root, "house-rules", "house-rules", "plugin.wat");
+        let mut manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap();
+        manifest.push_str(
+            "\n[capabilities]\ncommands = [\"hello\", \"fail\"]\nreview_rules = [\"no-unwrap\"]\n\
+             \n[[capabilities.file_categories]]\nlabel = \"Protobuf\"\n\
+             patterns = ['^gen/proto/']\ntier = \"generated\"\n",
+        );
+        std::fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        std::fs::write(dir.join("plugin.wat"), component(activate)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_activation_runs_under_limits() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = install(temp_dir.path(), Some("(loop $spin (br $spin)) unreachable"));
+
+        let limits = PluginLimits {
+            fuel: 1_000_000,
+            ..Default::default()
+        };
+        let mut host = PluginHost::with_limits(temp_dir.path().to_path_buf(), limits).unwrap();
+        let err = host.load(&dir).unwrap_err();
+        assert!(matches!(err, Error::ResourceLimit(_)), "{}", err);
+        assert_eq!(host.plugins()[0].status, PluginStatus::Failed);
+        assert!(host.commands().is_empty());
+    }
+
+    #[test]
+    fn test_plugin_commands() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = install(temp_dir.path(), None);
+        let mut host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+        host.load(&dir).unwrap();
+
+        // `sneaky` is not declared in the manifest
+        let commands = host.commands();
+        let ids: Vec<_> = commands.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["hello", "fail"]);
+        assert_eq!(commands[0].title, "Say hello");
+        assert_eq!(commands[0].plugin, "house-rules");
+        assert_eq!(host.file_categories()[0].label, "Protobuf");
+
+        host.run_command("house-rules", "hello", None).unwrap();
+        let err = host.run_command("house-rules", "fail", None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Plugin execution error: house-rules:fail failed: boom"
+        );
+        assert!(matches!(
+            host.run_command("house-rules", "sneaky", None),
+            Err(Error::NotFound(_))
+        ));
+        // A command returning an error leaves the plugin usable
+        assert_eq!(host.plugins()[0].status, PluginStatus::Active);
+    }
+
+    #[test]
+    fn test_plugin_review_rules() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = install(temp_dir.path(), None);
+        let mut host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+        host.load(&dir).unwrap();
+        assert_eq!(host.review_rules()[0].id, "no-unwrap");
+
+        let diff = codelane_git::diff::DiffResult::parse(
+            "diff --git a/src/lib.rs b/src/lib.rs\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -1,2 +1,3 @@\n \
+             fn run() {\n\
+             +    load().unwrap();\n \
+             }\n",
+        );
+        let report = host.review(temp_dir.path(), &diff.files);
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(
+            report.findings,
+            vec![Finding {
+                plugin: "house-rules".to_string(),
+                rule: "no-unwrap".to_string(),
+                file: PathBuf::from("src/lib.rs"),
+                line: 3,
+                severity: review::Severity::Warning,
+                message: "new unwrap()".to_string(),
+            }]
+        );
+
+        assert!(host.review(temp_dir.path(), &[]).findings.is_empty());
+    }
+
+    fn command_ids(host: &PluginHost) -> Vec<String> {
+        host.commands().into_iter().map(|c| c.id).collect()
+    }
+
+    fn drop_fail_command(dir: &Path) {
+        let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap();
+        let manifest = manifest.replace("[\"hello\", \"fail\"]", "[\"hello\"]");
+        std::fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+    }
+
+    #[test]
+    fn test_reload_keeps_running_version_on_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = install(temp_dir.path(), None);
+        let mut host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+        host.load(&dir).unwrap();
+
+        drop_fail_command(&dir);
+        assert!(matches!(host.reload(&dir), PluginReload::Loaded(_)));
+        assert_eq!(command_ids(&host), vec!["hello"]);
+
+        let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), "[plugin]\nname = \"house-rules\n").unwrap();
+        match host.reload(&dir) {
+            PluginReload::Failed(path, Error::Manifest { line, .. }) => {
+                assert_eq!(path, dir);
+                assert_eq!(line, 2);
+            }
+            other => panic!("unexpected reload: {:?}", other),
+        }
+        let info = host.plugin("house-rules").unwrap();
+        assert_eq!(info.status, PluginStatus::Active);
+        assert!(info.error.unwrap().starts_with("Reload failed"));
+        assert_eq!(command_ids(&host), vec!["hello"]);
+        host.run_command("house-rules", "hello", None).unwrap();
+
+        std::fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        assert!(matches!(host.reload(&dir), PluginReload::Loaded(_)));
+        assert!(host.plugin("house-rules").unwrap().error.is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(host.reload(&dir), PluginReload::Removed(_)));
+        assert!(host.plugins().is_empty());
+    }
+
+    #[test]
+    fn test_manifest_errors_are_listed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = write_plugin(temp_dir.path(), "linter", "my linter", "plugin.wasm");
+        let mut host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+
+        assert!(host.load_all().unwrap().is_empty());
+        let errors = host.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, dir);
+        assert!(
+            errors[0].message.contains("plugin.toml:2:1: name must be"),
+            "{}",
+            errors[0].message
+        );
+
+        write_plugin(temp_dir.path(), "linter", "linter", "plugin.wasm");
+        assert!(matches!(host.reload(&dir), PluginReload::Failed(..)));
+        assert!(host.errors().is_empty());
+        assert_eq!(host.plugins()[0].status, PluginStatus::Failed);
+    }
+
+    #[test]
+    fn test_developer_mode_reloads_changed_plugins() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = install(temp_dir.path(), None);
+        let mut host = PluginHost::new(temp_dir.path().to_path_buf()).unwrap();
+        host.load(&dir).unwrap();
+        assert!(host.reload_changed().is_empty());
+
+        host.set_developer_mode(true).unwrap();
+        assert!(host.developer_mode());
+        drop_fail_command(&dir);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut reloads = Vec::new();
+        while reloads.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            reloads = host.reload_changed();
+        }
+        assert_eq!(reloads.len(), 1, "{:?}", reloads);
+        assert!(matches!(&reloads[0], PluginReload::Loaded(info) if info.path == dir));
+        assert_eq!(command_ids(&host), vec!["hello"]);
+
+        host.set_developer_mode(false).unwrap();
+        assert!(!host.developer_mode());
+    }
+}
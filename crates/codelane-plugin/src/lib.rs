@@ -5,7 +5,10 @@
 pub mod api;
 pub mod host;
 pub mod manifest;
+pub mod review;
+pub mod sandbox;
 
 mod error;
+mod watcher;
 
 pub use error::{Error, Result};
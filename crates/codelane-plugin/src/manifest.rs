@@ -1,7 +1,8 @@
 //! Plugin manifest parsing
 
+use codelane_core::config::FileCategoryRule;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 /// Plugin manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +45,14 @@ pub struct PluginCapabilities {
     #[serde(default)]
     pub commands: Vec<String>,
 
+    /// Review rules this plugin provides
+    #[serde(default)]
+    pub review_rules: Vec<String>,
+
+    /// File categories for sorting files in review
+    #[serde(default)]
+    pub file_categories: Vec<FileCategoryRule>,
+
     /// Themes this plugin provides
     #[serde(default)]
     pub themes: Vec<String>,
@@ -78,10 +87,173 @@ pub struct PluginPermissions {
 
 impl PluginManifest {
     /// Load manifest from a plugin.toml file
+    ///
+    /// Syntax and validation errors point at the offending line.
     pub fn load(path: &PathBuf) -> crate::Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let manifest: Self = toml::from_str(&content)
-            .map_err(|e| crate::Error::LoadFailed(e.to_string()))?;
+        let error = |offset: usize, message: String| {
+            let (line, column) = line_column(&content, offset);
+            crate::Error::Manifest {
+                path: path.clone(),
+                line,
+                column,
+                message,
+            }
+        };
+
+        let manifest: Self = toml::from_str(&content).map_err(|e| {
+            let offset = e.span().map_or(0, |span| span.start);
+            error(offset, e.message().to_string())
+        })?;
+        manifest
+            .validate()
+            .map_err(|(key, message)| error(key_offset(&content, "plugin", key), message))?;
         Ok(manifest)
     }
+
+    /// Check the values serde can't; returns the `[plugin]` key at fault
+    fn validate(&self) -> Result<(), (&'static str, String)> {
+        let meta = &self.plugin;
+        let valid_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+        if meta.name.is_empty() || !meta.name.chars().all(valid_name) {
+            return Err((
+                "name",
+                format!(
+                    "name must be letters, digits, '-', '_' or '.', not {:?}",
+                    meta.name
+                ),
+            ));
+        }
+        if meta.version.is_empty() {
+            return Err(("version", "version must not be empty".to_string()));
+        }
+        let entry = Path::new(&meta.entry);
+        let inside = entry
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if meta.entry.is_empty() || !inside {
+            return Err((
+                "entry",
+                format!(
+                    "entry must be a path inside the plugin directory, not {:?}",
+                    meta.entry
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Byte offset of `key` in `[table]`, or of the table header if the key
+/// is missing
+fn key_offset(content: &str, table: &str, key: &str) -> usize {
+    let header = format!("[{}]", table);
+    let mut in_table = false;
+    let mut header_offset = 0;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.starts_with('[') {
+            in_table = trimmed.trim_end().starts_with(&header);
+            if in_table {
+                header_offset = offset + indent;
+            }
+        } else if in_table {
+            let is_key = trimmed
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='));
+            if is_key {
+                return offset + indent;
+            }
+        }
+        offset += line.len();
+    }
+    header_offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn load(content: &str) -> crate::Result<PluginManifest> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("plugin.toml");
+        std::fs::write(&path, content).unwrap();
+        PluginManifest::load(&path)
+    }
+
+    fn position(error: Error) -> (usize, usize, String) {
+        match error {
+            Error::Manifest {
+                line,
+                column,
+                message,
+                ..
+            } => (line, column, message),
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_manifest() {
+        let manifest = load(
+            "[plugin]\nname = \"house-rules\"\nversion = \"0.1.0\"\nentry = \"plugin.wasm\"\n\n\
+             [capabilities]\ncommands = [\"hello\"]\n\n\
+             [permissions]\nread_workspace = true\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.plugin.name, "house-rules");
+        assert_eq!(manifest.capabilities.commands, vec!["hello"]);
+        assert!(manifest.permissions.read_workspace);
+        assert!(!manifest.permissions.write_workspace);
+    }
+
+    #[test]
+    fn test_syntax_error_position() {
+        let err = load("[plugin]\nname = \"a\"\nversion = 0.1.0\"\n").unwrap_err();
+        assert_eq!(position(err).0, 3);
+
+        // Type errors point at the value
+        let err = load(
+            "[plugin]\nname = \"a\"\nversion = \"1\"\nentry = \"x\"\n\n\
+             [permissions]\nnetwork = \"api.github.com\"\n",
+        )
+        .unwrap_err();
+        let (line, column, _) = position(err);
+        assert_eq!((line, column), (7, 11));
+    }
+
+    #[test]
+    fn test_validation_error_position() {
+        let err = load(
+            "# A plugin\n[plugin]\nname = \"house rules\"\nversion = \"0.1.0\"\nentry = \"x.wasm\"\n",
+        )
+        .unwrap_err();
+        let (line, column, message) = position(err);
+        assert_eq!((line, column), (3, 1));
+        assert!(message.starts_with("name must be"));
+
+        let err =
+            load("[plugin]\nname = \"a\"\nversion = \"1\"\n  entry = \"../x.wasm\"\n").unwrap_err();
+        let (line, column, _) = position(err);
+        assert_eq!((line, column), (4, 3));
+    }
+
+    #[test]
+    fn test_manifest_error_display() {
+        let err = load("[plugin]\nname = \"\"\nversion = \"1\"\nentry = \"x\"\n").unwrap_err();
+        let text = err.to_string();
+        assert!(text.starts_with("Invalid plugin manifest /"), "{}", text);
+        assert!(text.contains("plugin.toml:2:1: name must be"), "{}", text);
+    }
 }
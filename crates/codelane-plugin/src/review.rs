@@ -0,0 +1,195 @@
+//! Plugin review rules
+//!
+//! Plugins register review rules when they are activated. A rule receives
+//! the structured diff of the changes under review and returns findings,
+//! which the review panel shows next to its own comments.
+
+use std::path::PathBuf;
+
+use codelane_git::diff::{ChangeType, DiffHunk, DiffLine, FileChangeType, FileDiff};
+use serde::Serialize;
+
+use crate::api::types as wit;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A review rule registered by a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReviewRule {
+    pub plugin: String,
+    pub id: String,
+    pub description: String,
+}
+
+/// A problem a review rule found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub plugin: String,
+    pub rule: String,
+
+    /// File path, relative to the workspace
+    pub file: PathBuf,
+
+    /// Line in the new file (1-indexed)
+    pub line: u32,
+
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Findings of every review rule
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReviewReport {
+    /// Findings sorted by file and line
+    pub findings: Vec<Finding>,
+
+    /// Rules that failed to run
+    pub errors: Vec<String>,
+}
+
+impl Finding {
+    pub(crate) fn from_wit(plugin: &str, rule: &str, finding: wit::Finding) -> Self {
+        Self {
+            plugin: plugin.to_string(),
+            rule: rule.to_string(),
+            file: PathBuf::from(finding.file),
+            line: finding.line,
+            severity: match finding.severity {
+                wit::Severity::Info => Severity::Info,
+                wit::Severity::Warning => Severity::Warning,
+                wit::Severity::Error => Severity::Error,
+            },
+            message: finding.message,
+        }
+    }
+}
+
+impl ReviewReport {
+    /// Sort findings by file and line, most severe first
+    pub(crate) fn sort(&mut self) {
+        self.findings.sort_by(|a, b| {
+            (&a.file, a.line, b.severity, &a.plugin, &a.rule)
+                .cmp(&(&b.file, b.line, a.severity, &b.plugin, &b.rule))
+        });
+    }
+}
+
+/// Convert a diff for passing to plugins
+pub(crate) fn wit_diff(files: &[FileDiff]) -> Vec<wit::FileDiff> {
+    files.iter().map(wit_file).collect()
+}
+
+fn wit_file(file: &FileDiff) -> wit::FileDiff {
+    wit::FileDiff {
+        path: file.path.to_string_lossy().to_string(),
+        old_path: file
+            .old_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string()),
+        change: match file.change_type {
+            FileChangeType::Added => wit::ChangeKind::Added,
+            FileChangeType::Deleted => wit::ChangeKind::Deleted,
+            FileChangeType::Modified => wit::ChangeKind::Modified,
+            FileChangeType::Renamed => wit::ChangeKind::Renamed,
+            FileChangeType::Copied => wit::ChangeKind::Copied,
+        },
+        binary: file.is_binary,
+        hunks: file.hunks.iter().map(wit_hunk).collect(),
+    }
+}
+
+fn wit_hunk(hunk: &DiffHunk) -> wit::DiffHunk {
+    wit::DiffHunk {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+        lines: hunk.lines.iter().map(wit_line).collect(),
+    }
+}
+
+fn wit_line(line: &DiffLine) -> wit::DiffLine {
+    wit::DiffLine {
+        kind: match line.change_type {
+            ChangeType::Added | ChangeType::Modified => wit::LineKind::Added,
+            ChangeType::Removed => wit::LineKind::Removed,
+            ChangeType::Context => wit::LineKind::Context,
+        },
+        old_line: line.old_line,
+        new_line: line.new_line,
+        content: line.content.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codelane_git::diff::DiffResult;
+
+    #[test]
+    fn test_wit_diff() {
+        let diff = DiffResult::parse(
+            "diff --git a/src/main.rs b/src/main.rs\n\
+             --- a/src/main.rs\n\
+             +++ b/src/main.rs\n\
+             @@ -1,2 +1,2 @@\n \
+             fn main() {\n\
+             -    run();\n\
+             +    run().unwrap();\n",
+        );
+
+        let files = wit_diff(&diff.files);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/main.rs");
+        assert!(matches!(files[0].change, wit::ChangeKind::Modified));
+        let lines = &files[0].hunks[0].lines;
+        assert_eq!(lines.len(), 3);
+        assert!(matches!(lines[2].kind, wit::LineKind::Added));
+        assert_eq!(lines[2].new_line, Some(2));
+        assert_eq!(lines[2].content, "    run().unwrap();");
+    }
+
+    #[test]
+    fn test_report_sorting() {
+        let finding = |file: &str, line, severity| Finding {
+            plugin: "house-rules".to_string(),
+            rule: "no-unwrap".to_string(),
+            file: PathBuf::from(file),
+            line,
+            severity,
+            message: String::new(),
+        };
+        let mut report = ReviewReport {
+            findings: vec![
+                finding("src/b.rs", 1, Severity::Info),
+                finding("src/a.rs", 9, Severity::Info),
+                finding("src/a.rs", 9, Severity::Error),
+                finding("src/a.rs", 2, Severity::Warning),
+            ],
+            errors: vec![],
+        };
+        report.sort();
+
+        let order: Vec<_> = report
+            .findings
+            .iter()
+            .map(|f| (f.file.to_str().unwrap(), f.line, f.severity))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("src/a.rs", 2, Severity::Warning),
+                ("src/a.rs", 9, Severity::Error),
+                ("src/a.rs", 9, Severity::Info),
+                ("src/b.rs", 1, Severity::Info),
+            ]
+        );
+    }
+}
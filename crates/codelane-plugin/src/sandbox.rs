@@ -0,0 +1,240 @@
+//! Permission checks for host calls
+//!
+//! A [`Sandbox`] holds what a plugin's manifest grants it and the workspace
+//! it is currently called for. Workspace paths are resolved against the
+//! workspace root and may not leave it, including through symlinks.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::manifest::PluginPermissions;
+use crate::{Error, Result};
+
+/// What a plugin may access
+#[derive(Debug, Clone, Default)]
+pub struct Sandbox {
+    permissions: PluginPermissions,
+    workspace: Option<PathBuf>,
+}
+
+impl Sandbox {
+    pub fn new(permissions: PluginPermissions) -> Self {
+        Self {
+            permissions,
+            workspace: None,
+        }
+    }
+
+    /// Set the workspace that host calls operate on
+    pub fn set_workspace(&mut self, workspace: Option<PathBuf>) {
+        self.workspace = workspace;
+    }
+
+    pub fn workspace(&self) -> Option<&Path> {
+        self.workspace.as_deref()
+    }
+
+    /// Resolve a workspace path the plugin wants to read
+    pub fn readable(&self, path: &str) -> Result<PathBuf> {
+        if !self.permissions.read_workspace {
+            return Err(Error::PermissionDenied(
+                "reading the workspace needs the read_workspace permission".to_string(),
+            ));
+        }
+        self.resolve(path)
+    }
+
+    /// Resolve a workspace path the plugin wants to write
+    pub fn writable(&self, path: &str) -> Result<PathBuf> {
+        if !self.permissions.write_workspace {
+            return Err(Error::PermissionDenied(
+                "writing the workspace needs the write_workspace permission".to_string(),
+            ));
+        }
+        self.resolve(path)
+    }
+
+    /// Check that a URL's host is in the network allow-list
+    ///
+    /// Entries match a host exactly, or all of its subdomains when written
+    /// as `*.example.com`.
+    pub fn check_url(&self, url: &str) -> Result<()> {
+        let host = url_host(url)
+            .ok_or_else(|| Error::PermissionDenied(format!("unsupported URL: {}", url)))?;
+        let allowed = self.permissions.network.iter().any(|entry| {
+            let entry = entry.to_ascii_lowercase();
+            match entry.strip_prefix("*.") {
+                Some(domain) => host.ends_with(&format!(".{}", domain)),
+                None => host == entry,
+            }
+        });
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied(format!(
+                "{} is not in the network allow-list",
+                host
+            )))
+        }
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let root = self
+            .workspace
+            .as_deref()
+            .ok_or_else(|| Error::PermissionDenied("no workspace is open".to_string()))?;
+        let escapes = || Error::PermissionDenied(format!("{} is outside the workspace", path));
+
+        let relative = Path::new(path);
+        let mut resolved = root.to_path_buf();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => resolved.push(part),
+                Component::CurDir => {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(escapes())
+                }
+            }
+        }
+
+        // Symlinks inside the workspace may point out of it; check where each
+        // one on the way really leads. A dangling link is rejected, since
+        // writing through it would create its target wherever that is.
+        let root = root.canonicalize()?;
+        let mut current = root.clone();
+        for component in relative.components() {
+            let Component::Normal(part) = component else {
+                continue;
+            };
+            current.push(part);
+            let metadata = match current.symlink_metadata() {
+                Ok(metadata) => metadata,
+                // Nothing below a missing path can be a link
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e.into()),
+            };
+            if metadata.file_type().is_symlink() {
+                current = current.canonicalize().map_err(|_| escapes())?;
+                if !current.starts_with(&root) {
+                    return Err(escapes());
+                }
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+/// Lowercase host of an `http` or `https` URL
+fn url_host(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = match host_port.strip_prefix('[') {
+        // IPv6 literal
+        Some(bracketed) => bracketed.split(']').next()?,
+        None => host_port.split(':').next()?,
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_ascii_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(read: bool, write: bool, network: &[&str]) -> Sandbox {
+        Sandbox::new(PluginPermissions {
+            network: network.iter().map(|h| h.to_string()).collect(),
+            read_workspace: read,
+            write_workspace: write,
+        })
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://api.github.com/repos").as_deref(),
+            Some("api.github.com")
+        );
+        assert_eq!(
+            url_host("http://user:pw@Example.com:8080?q").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_host("http://[::1]:3000/").as_deref(), Some("::1"));
+        assert_eq!(url_host("file:///etc/passwd"), None);
+        assert_eq!(url_host("example.com"), None);
+    }
+
+    #[test]
+    fn test_network_allow_list() {
+        let sandbox = sandbox(false, false, &["api.github.com", "*.gitlab.com"]);
+        assert!(sandbox.check_url("https://api.github.com/user").is_ok());
+        assert!(sandbox.check_url("https://ci.gitlab.com").is_ok());
+        assert!(sandbox.check_url("https://gitlab.com").is_err());
+        assert!(sandbox.check_url("https://github.com").is_err());
+        // The allowed name as userinfo does not count
+        assert!(sandbox
+            .check_url("https://api.github.com@evil.example/")
+            .is_err());
+    }
+
+    #[test]
+    fn test_workspace_permissions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut read_only = sandbox(true, false, &[]);
+        assert!(matches!(
+            read_only.readable("src/lib.rs"),
+            Err(Error::PermissionDenied(_))
+        ));
+
+        read_only.set_workspace(Some(temp_dir.path().to_path_buf()));
+        assert_eq!(
+            read_only.readable("./src/lib.rs").unwrap(),
+            temp_dir.path().join("src/lib.rs")
+        );
+        assert!(matches!(
+            read_only.writable("src/lib.rs"),
+            Err(Error::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_paths_cannot_leave_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut sandbox = sandbox(true, true, &[]);
+        sandbox.set_workspace(Some(temp_dir.path().to_path_buf()));
+
+        assert!(sandbox.readable("../secret").is_err());
+        assert!(sandbox.readable("/etc/passwd").is_err());
+        assert!(sandbox.writable("a/../../b").is_err());
+
+        #[cfg(unix)]
+        {
+            let outside = tempfile::tempdir().unwrap();
+            std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("link")).unwrap();
+            assert!(sandbox.readable("link/file").is_err());
+
+            // A dangling link would create its target outside the workspace
+            std::os::unix::fs::symlink(
+                outside.path().join("new-file"),
+                temp_dir.path().join("out"),
+            )
+            .unwrap();
+            assert!(sandbox.writable("out").is_err());
+
+            // Links that stay inside the workspace are fine
+            std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+            std::os::unix::fs::symlink(temp_dir.path().join("src"), temp_dir.path().join("alias"))
+                .unwrap();
+            assert_eq!(
+                sandbox.writable("alias/lib.rs").unwrap(),
+                temp_dir.path().join("alias/lib.rs")
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Plugin directory watching for developer mode
+//!
+//! Changes are grouped by plugin directory and reported once the directory
+//! has been quiet for [`SETTLE_TIME`], so a build writing several files
+//! causes a single reload.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{Error, Result};
+
+/// How long a plugin directory must be unchanged before it is reloaded
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+pub(crate) struct PluginWatcher {
+    /// The plugins directory, as given and as the OS reports it
+    roots: Vec<PathBuf>,
+    plugins_dir: PathBuf,
+    _watcher: RecommendedWatcher,
+    /// Events, stamped with when they were received from the OS
+    events: Receiver<(Instant, notify::Result<Event>)>,
+    /// Changed plugin directories and when they last changed
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl PluginWatcher {
+    pub(crate) fn new(plugins_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(plugins_dir)?;
+        let (tx, events) = mpsc::channel();
+        let watch_error = |e: notify::Error| {
+            Error::Other(format!("Failed to watch {}: {}", plugins_dir.display(), e))
+        };
+        // Stamp events as they arrive rather than when they are drained, so a
+        // change isn't held back by how often `settled` is called
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send((Instant::now(), event));
+        })
+        .map_err(watch_error)?;
+        watcher
+            .watch(plugins_dir, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+
+        let mut roots = vec![plugins_dir.to_path_buf()];
+        roots.extend(plugins_dir.canonicalize().ok());
+        Ok(Self {
+            roots,
+            plugins_dir: plugins_dir.to_path_buf(),
+            _watcher: watcher,
+            events,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Plugin directories that changed and have been quiet since
+    pub(crate) fn settled(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        while let Ok((received, event)) = self.events.try_recv() {
+            match event {
+                Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                    for path in &event.paths {
+                        if let Some(dir) = self.plugin_dir(path) {
+                            self.pending.insert(dir, received);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Plugin watch error: {}", e),
+            }
+        }
+
+        let mut settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| now.duration_since(**changed) >= SETTLE_TIME)
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        settled.sort();
+        for dir in &settled {
+            self.pending.remove(dir);
+        }
+        settled
+    }
+
+    /// The plugin directory a changed path belongs to
+    fn plugin_dir(&self, path: &Path) -> Option<PathBuf> {
+        let relative = self
+            .roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())?;
+        match relative.components().next()? {
+            Component::Normal(name) => Some(self.plugins_dir.join(name)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let watcher = PluginWatcher::new(temp_dir.path()).unwrap();
+
+        assert_eq!(
+            watcher.plugin_dir(&temp_dir.path().join("linter/target/plugin.wasm")),
+            Some(temp_dir.path().join("linter"))
+        );
+        assert_eq!(
+            watcher.plugin_dir(&temp_dir.path().join("linter")),
+            Some(temp_dir.path().join("linter"))
+        );
+        assert_eq!(watcher.plugin_dir(temp_dir.path()), None);
+        assert_eq!(watcher.plugin_dir(Path::new("/elsewhere/linter")), None);
+    }
+
+    #[test]
+    fn test_changes_settle_before_reporting() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut watcher = PluginWatcher::new(temp_dir.path()).unwrap();
+        let dir = temp_dir.path().join("linter");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("plugin.toml"), "").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut settled = Vec::new();
+        while settled.is_empty() && Instant::now() < deadline {
+            settled = watcher.settled();
+            // Nothing is reported while the change is still fresh
+            if !watcher.pending.is_empty() {
+                assert!(settled.is_empty());
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(settled, vec![dir]);
+        assert!(watcher.settled().is_empty());
+    }
+
+    #[test]
+    fn test_changes_are_stamped_when_received() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut watcher = PluginWatcher::new(temp_dir.path()).unwrap();
+        let dir = temp_dir.path().join("linter");
+        std::fs::create_dir(&dir).unwrap();
+
+        // A change that settled before the first poll is reported right away
+        std::thread::sleep(SETTLE_TIME * 3);
+        assert_eq!(watcher.settled(), vec![dir]);
+    }
+}
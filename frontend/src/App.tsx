@@ -6,6 +6,7 @@ import { MainLayout } from './components/layout';
 import { CreateLaneDialog } from './components/lanes';
 import { SettingsDialog } from './components/SettingsDialog';
 import { AboutDialog } from './components/AboutDialog';
+import { CommandPalette } from './components/CommandPalette';
 import { OnboardingWizard, type WizardData } from './components/onboarding';
 import { listLanes, deleteLane } from './lib/lane-api';
 import { getActiveLaneId, setActiveLaneId } from './lib/storage';
@@ -18,12 +19,14 @@ import { tabManager } from './services/TabManager';
 import { resourceManager } from './services/ResourceManager';
 import { agentNotificationService } from './services/AgentNotificationService';
 import { hookService } from './services/HookService';
+import { CommonShortcuts, useShortcut } from './services/KeyboardShortcutManager';
 import codelaneLogoWhite from './assets/codelane-logo-white.png';
 
 function App() {
   const [dialogOpen, setDialogOpen] = createSignal(false);
   const [settingsOpen, setSettingsOpen] = createSignal(false);
   const [aboutOpen, setAboutOpen] = createSignal(false);
+  const [paletteOpen, setPaletteOpen] = createSignal(false);
   const [onboardingOpen, setOnboardingOpen] = createSignal(false);
   const [lanes, setLanes] = createSignal<Lane[]>([]);
   const [activeLaneId, setActiveLaneIdSignal] = createSignal<string | null>(null);
@@ -37,6 +40,15 @@ function App() {
   // Track terminal IDs for process monitoring
   const [terminalIds, setTerminalIds] = createSignal<Map<string, string>>(new Map());
 
+  // Open the command palette from anywhere
+  useShortcut(CommonShortcuts.COMMAND_PALETTE(() => setPaletteOpen(true)));
+
+  // Working directory of the active lane, the scope of plugin commands
+  const activeWorkingDir = createMemo(() => {
+    const lane = lanes().find(l => l.id === activeLaneId());
+    return lane ? lane.worktreePath || lane.workingDir : undefined;
+  });
+
   // Disable right-click context menu in production
   onMount(() => {
     if (!import.meta.env.DEV) {
@@ -423,6 +435,13 @@ function App() {
         onOpenChange={setAboutOpen}
       />
 
+      {/* Command Palette */}
+      <CommandPalette
+        open={paletteOpen()}
+        onOpenChange={setPaletteOpen}
+        workingDir={activeWorkingDir()}
+      />
+
       {/* Onboarding Wizard */}
       <OnboardingWizard
         open={onboardingOpen()}
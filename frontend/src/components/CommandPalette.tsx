@@ -0,0 +1,109 @@
+// Command Palette - Lists and runs the commands registered by plugins
+
+import { createSignal, createEffect, createMemo, on, For, Show } from 'solid-js';
+import { Dialog } from './ui';
+import { listPluginCommands, runPluginCommand } from '../lib/plugin-api';
+import type { PluginCommand } from '../types/plugin';
+
+interface CommandPaletteProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  workingDir?: string; // Active lane's working directory, the commands' file scope
+}
+
+export function CommandPalette(props: CommandPaletteProps) {
+  const [commands, setCommands] = createSignal<PluginCommand[]>([]);
+  const [query, setQuery] = createSignal('');
+  const [error, setError] = createSignal<string | null>(null);
+  const [running, setRunning] = createSignal(false);
+
+  // Reload the commands each time the palette opens, plugins may have changed
+  createEffect(on(() => props.open, async (open) => {
+    if (!open) return;
+    setQuery('');
+    setError(null);
+    try {
+      setCommands(await listPluginCommands());
+    } catch (err) {
+      setCommands([]);
+      setError(String(err));
+    }
+  }));
+
+  const filteredCommands = createMemo(() => {
+    const q = query().trim().toLowerCase();
+    if (!q) return commands();
+    return commands().filter(command =>
+      command.title.toLowerCase().includes(q) || command.plugin.toLowerCase().includes(q)
+    );
+  });
+
+  const handleRun = async (command: PluginCommand) => {
+    setRunning(true);
+    setError(null);
+    try {
+      await runPluginCommand(command.plugin, command.id, props.workingDir);
+      props.onOpenChange(false);
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const handleKeyDown = (e: KeyboardEvent) => {
+    if (e.key === 'Enter') {
+      const first = filteredCommands()[0];
+      if (first && !running()) {
+        e.preventDefault();
+        void handleRun(first);
+      }
+    }
+  };
+
+  return (
+    <Dialog open={props.open} onOpenChange={props.onOpenChange} title="Command Palette">
+      <input
+        type="text"
+        value={query()}
+        onInput={(e) => setQuery(e.currentTarget.value)}
+        onKeyDown={handleKeyDown}
+        placeholder="Type a command..."
+        aria-label="Filter commands"
+        autofocus
+        class="w-full px-3 py-2 bg-zed-bg-surface border border-zed-border-default rounded-md text-zed-text-primary placeholder:text-zed-text-tertiary focus:outline-none focus:ring-2 focus:ring-zed-accent-blue focus:border-transparent"
+      />
+
+      <Show when={error()}>
+        <p class="mt-3 text-sm text-red-400">{error()}</p>
+      </Show>
+
+      <ul class="mt-3 max-h-80 overflow-y-auto" role="listbox">
+        <For
+          each={filteredCommands()}
+          fallback={<li class="px-3 py-2 text-sm text-zed-text-tertiary">No commands</li>}
+        >
+          {(command) => (
+            <li>
+              <button
+                type="button"
+                role="option"
+                disabled={running()}
+                onClick={() => handleRun(command)}
+                class="w-full text-left px-3 py-2 rounded-md hover:bg-zed-bg-hover disabled:opacity-50 transition-colors"
+              >
+                <div class="text-sm text-zed-text-primary">{command.title}</div>
+                <div class="text-xs text-zed-text-tertiary">
+                  {command.plugin}
+                  <Show when={command.description}>
+                    {' · '}{command.description}
+                  </Show>
+                </div>
+              </button>
+            </li>
+          )}
+        </For>
+      </ul>
+    </Dialog>
+  );
+}
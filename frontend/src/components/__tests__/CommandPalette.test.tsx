@@ -0,0 +1,85 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { render, screen, fireEvent, waitFor } from '@solidjs/testing-library';
+
+const mockInvoke = vi.fn();
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: (...args: unknown[]) => mockInvoke(...args),
+}));
+
+// Mock Kobalte Dialog to render children directly
+vi.mock('@kobalte/core/dialog', () => {
+  const Dialog = (props: any) => {
+    if (!props.open) return null;
+    return props.children;
+  };
+  Dialog.Portal = (props: any) => props.children;
+  Dialog.Overlay = (props: any) => <div {...props} />;
+  Dialog.Content = (props: any) => <div {...props} />;
+  Dialog.CloseButton = (props: any) => <button {...props} data-testid="close-button" />;
+  Dialog.Title = (props: any) => <h2 {...props} />;
+  Dialog.Description = (props: any) => <p {...props} />;
+  return { Dialog };
+});
+
+import { CommandPalette } from '../CommandPalette';
+
+const commands = [
+  { plugin: 'formatter', id: 'format-all', title: 'Format All Files', description: 'Format every file' },
+  { plugin: 'todos', id: 'list', title: 'List TODOs' },
+];
+
+describe('CommandPalette', () => {
+  beforeEach(() => {
+    mockInvoke.mockReset();
+    mockInvoke.mockImplementation(async (command: string) =>
+      command === 'plugin_commands' ? commands : undefined
+    );
+  });
+
+  it('lists plugin commands when opened', async () => {
+    render(() => <CommandPalette open={true} onOpenChange={vi.fn()} />);
+
+    expect(await screen.findByText('Format All Files')).toBeInTheDocument();
+    expect(screen.getByText('List TODOs')).toBeInTheDocument();
+    expect(mockInvoke).toHaveBeenCalledWith('plugin_commands');
+  });
+
+  it('filters commands by title', async () => {
+    render(() => <CommandPalette open={true} onOpenChange={vi.fn()} />);
+    await screen.findByText('Format All Files');
+
+    fireEvent.input(screen.getByLabelText('Filter commands'), { target: { value: 'todo' } });
+
+    expect(screen.queryByText('Format All Files')).not.toBeInTheDocument();
+    expect(screen.getByText('List TODOs')).toBeInTheDocument();
+  });
+
+  it('runs the selected command in the working directory and closes', async () => {
+    const onOpenChange = vi.fn();
+    render(() => <CommandPalette open={true} onOpenChange={onOpenChange} workingDir="/test/dir" />);
+
+    fireEvent.click(await screen.findByText('List TODOs'));
+
+    await waitFor(() => expect(onOpenChange).toHaveBeenCalledWith(false));
+    expect(mockInvoke).toHaveBeenCalledWith('plugin_run_command', {
+      plugin: 'todos',
+      id: 'list',
+      workingDir: '/test/dir',
+    });
+  });
+
+  it('shows command failures and stays open', async () => {
+    mockInvoke.mockImplementation(async (command: string) => {
+      if (command === 'plugin_commands') return commands;
+      throw 'Plugin todos trapped';
+    });
+    const onOpenChange = vi.fn();
+    render(() => <CommandPalette open={true} onOpenChange={onOpenChange} />);
+
+    fireEvent.click(await screen.findByText('List TODOs'));
+
+    expect(await screen.findByText('Plugin todos trapped')).toBeInTheDocument();
+    expect(onOpenChange).not.toHaveBeenCalled();
+  });
+});
@@ -1,5 +1,5 @@
 // DiffViewer using @git-diff-view with Shiki highlighting
-import { createSignal, createEffect, createMemo, Show, onMount, onCleanup } from 'solid-js';
+import { createSignal, createEffect, createMemo, For, Show, onMount, onCleanup } from 'solid-js';
 import { DiffView, DiffModeEnum } from '@git-diff-view/solid';
 import { DiffFile } from '@git-diff-view/core';
 import { invoke } from '@tauri-apps/api/core';
@@ -8,6 +8,7 @@ import { getFileAtRevision } from '../../lib/git-api';
 import { detectLanguage, getShikiLanguage } from './types';
 import { editorSettingsManager } from '../../services/EditorSettingsManager';
 import type { DiffViewMode } from './diff/types';
+import type { PluginFinding, ReviewDiagnostic } from '../../services/api/types';
 
 // Import styles
 import '@git-diff-view/solid/styles/diff-view.css';
@@ -19,8 +20,28 @@ interface DiffViewerProps {
   workingDir?: string;
   embedded?: boolean; // If true, don't add overflow-auto (parent handles scrolling)
   viewMode?: 'unified' | 'split'; // External view mode (overrides internal state)
+  diagnostics?: ReviewDiagnostic[]; // Shown below the new-file lines they start on
+  findings?: PluginFinding[]; // Plugin review findings, shown below their new-file lines
 }
 
+interface ExtendLine {
+  diagnostics: ReviewDiagnostic[];
+  findings: PluginFinding[];
+}
+
+const diagnosticColor: Record<ReviewDiagnostic['severity'], string> = {
+  Error: 'text-red-400',
+  Warning: 'text-yellow-400',
+  Information: 'text-blue-400',
+  Hint: 'text-zed-text-tertiary',
+};
+
+const findingColor: Record<PluginFinding['severity'], string> = {
+  error: 'text-red-400',
+  warning: 'text-yellow-400',
+  info: 'text-blue-400',
+};
+
 export function DiffViewer(props: DiffViewerProps) {
   const [highlighterReady, setHighlighterReady] = createSignal(false);
   const [viewMode, setViewMode] = createSignal<DiffViewMode>(editorSettingsManager.getDiffViewDefaultMode());
@@ -32,6 +53,21 @@ export function DiffViewer(props: DiffViewerProps) {
   // Reactive effective view mode: use external prop if provided, otherwise internal state
   const effectiveViewMode = createMemo(() => props.viewMode ?? viewMode());
 
+  // Diagnostics and plugin findings grouped by the new-file line they start on,
+  // rendered as extend lines
+  const extendData = createMemo(() => {
+    const newFile: Record<string, { data: ExtendLine }> = {};
+    const lineData = (line: number) =>
+      (newFile[String(line)] ??= { data: { diagnostics: [], findings: [] } }).data;
+    for (const diagnostic of props.diagnostics ?? []) {
+      lineData(diagnostic.start_line).diagnostics.push(diagnostic);
+    }
+    for (const finding of props.findings ?? []) {
+      lineData(finding.line).findings.push(finding);
+    }
+    return { newFile };
+  });
+
   // Initialize highlighter on mount
   onMount(async () => {
     await initDiffHighlighter();
@@ -176,6 +212,33 @@ export function DiffViewer(props: DiffViewerProps) {
               diffViewHighlight={true}
               diffViewTheme="dark"
               diffViewFontSize={14}
+              extendData={extendData()}
+              renderExtendLine={({ data }: { data: ExtendLine }) => (
+                <div class="px-3 py-1 space-y-0.5 text-xs font-mono bg-zed-bg-panel border-y border-zed-border-subtle">
+                  <For each={data.diagnostics}>
+                    {(diagnostic) => (
+                      <div class={diagnosticColor[diagnostic.severity]}>
+                        {diagnostic.severity}: {diagnostic.message}
+                        <Show when={diagnostic.source}>
+                          <span class="ml-1 text-zed-text-tertiary">
+                            ({diagnostic.source}{diagnostic.code ? ` ${diagnostic.code}` : ''})
+                          </span>
+                        </Show>
+                      </div>
+                    )}
+                  </For>
+                  <For each={data.findings}>
+                    {(finding) => (
+                      <div class={findingColor[finding.severity]}>
+                        {finding.severity}: {finding.message}
+                        <span class="ml-1 text-zed-text-tertiary">
+                          ({finding.plugin} {finding.rule})
+                        </span>
+                      </div>
+                    )}
+                  </For>
+                </div>
+              )}
             />
           )}
         </Show>
@@ -174,8 +174,9 @@ export function EditorPanel(props: EditorPanelProps) {
       editorStateManager.clearExternalChangeFlag(props.laneId, pending.id);
     } else if (result === 'overwrite') {
       // Save local changes to disk, overwriting external changes
-      await editorStateManager.saveFile(props.laneId, pending.id);
+      // (clear the flag first, so the save is not rejected as a conflict)
       editorStateManager.clearExternalChangeFlag(props.laneId, pending.id);
+      await editorStateManager.saveFile(props.laneId, pending.id);
     }
   };
 
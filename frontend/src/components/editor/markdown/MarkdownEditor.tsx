@@ -7,7 +7,7 @@ import type { OpenFile } from '../types';
 import { FloatingToolbar } from './FloatingToolbar';
 import { editorStateManager } from '../../../services/EditorStateManager';
 import { editorSettingsManager } from '../../../services/EditorSettingsManager';
-import { useShikiHighlighter, createTipTapEditor, useMarkdownSave, type TipTapEditorInstance } from './hooks';
+import { useShikiHighlighter, createTipTapEditor, useMarkdownSave, WRITE_CONFLICT_ERROR, type TipTapEditorInstance } from './hooks';
 import { useExternalContentSync } from '../hooks';
 import './markdown-editor.css';
 
@@ -193,6 +193,7 @@ export function MarkdownEditor(props: MarkdownEditorProps) {
     laneId: props.laneId,
     fileId: props.file.id,
     filePath: props.file.path,
+    getExpectedHash: () => props.file.contentHash,
     getContent: () => {
       if (mode() === 'source') {
         return sourceContent();
@@ -368,7 +369,9 @@ export function MarkdownEditor(props: MarkdownEditorProps) {
             <span class="text-zed-text-tertiary">Saving...</span>
           </Show>
           <Show when={saveManager.saveError()}>
-            <span class="text-zed-accent-red" title={saveManager.saveError()!}>Save failed</span>
+            <span class="text-zed-accent-red" title={saveManager.saveError()!}>
+              {saveManager.saveError()!.startsWith(WRITE_CONFLICT_ERROR) ? 'Changed on disk, not saved' : 'Save failed'}
+            </span>
           </Show>
         </div>
       </div>
@@ -0,0 +1,101 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+const mockInvoke = vi.fn();
+const mockUpdateFileContent = vi.fn();
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: (...args: unknown[]) => mockInvoke(...args),
+}));
+
+vi.mock('../../../../../services/EditorStateManager', () => ({
+  editorStateManager: {
+    setFileModified: vi.fn(),
+    updateFileContent: (...args: unknown[]) => mockUpdateFileContent(...args),
+  },
+}));
+
+vi.mock('../useTipTapEditor', () => ({
+  normalizeForComparison: (content: string) => content.trim(),
+}));
+
+import { useMarkdownSave } from '../useMarkdownSave';
+
+function createSave(content: string, onSaveComplete = vi.fn(), expectedHash?: string) {
+  const save = useMarkdownSave({
+    laneId: 'lane-1',
+    fileId: 'file-1',
+    filePath: '/lane/README.md',
+    getContent: () => content,
+    getExpectedHash: () => expectedHash,
+    onSaveComplete,
+  });
+  save.setOriginalContent('# Original');
+  save.updateModifiedState(content);
+  return save;
+}
+
+beforeEach(() => {
+  mockInvoke.mockReset();
+  mockUpdateFileContent.mockReset();
+});
+
+describe('useMarkdownSave', () => {
+  it('saves through the lane so format on save applies', async () => {
+    mockInvoke.mockResolvedValue({ hash: 'abc', size: 8 });
+    const save = createSave('# Edited');
+
+    await save.save();
+
+    expect(mockInvoke).toHaveBeenCalledWith('write_file', {
+      path: '/lane/README.md',
+      contents: '# Edited',
+      options: null,
+      laneId: 'lane-1',
+    });
+    expect(save.isModified()).toBe(false);
+  });
+
+  it('saves only if the file still has the hash it was loaded with', async () => {
+    mockInvoke.mockResolvedValue({ hash: 'def', size: 8 });
+    const save = createSave('# Edited', vi.fn(), 'abc');
+
+    await save.save();
+
+    expect(mockInvoke).toHaveBeenCalledWith('write_file', expect.objectContaining({
+      options: { expected_hash: 'abc' },
+    }));
+    expect(mockUpdateFileContent).toHaveBeenCalledWith('lane-1', 'file-1', '# Edited', 'def');
+  });
+
+  it('reports a write conflict', async () => {
+    mockInvoke.mockRejectedValue('Write conflict: /lane/README.md changed on disk since it was read');
+    const save = createSave('# Edited', vi.fn(), 'abc');
+
+    await save.save();
+
+    expect(save.saveError()).toBe('Write conflict: /lane/README.md changed on disk since it was read');
+    expect(save.isModified()).toBe(true);
+    expect(mockUpdateFileContent).not.toHaveBeenCalled();
+  });
+
+  it('reports the formatted contents as saved', async () => {
+    mockInvoke.mockResolvedValue({ hash: 'abc', size: 10, formatted: '# Edited\n\n' });
+    const onSaveComplete = vi.fn();
+    const save = createSave('# Edited', onSaveComplete);
+
+    await save.save();
+
+    expect(onSaveComplete).toHaveBeenCalledWith('# Edited\n\n');
+    expect(mockUpdateFileContent).toHaveBeenCalledWith('lane-1', 'file-1', '# Edited\n\n', 'abc');
+  });
+
+  it('keeps the file modified when the save fails', async () => {
+    mockInvoke.mockRejectedValue(new Error('disk full'));
+    const save = createSave('# Edited');
+
+    await save.save();
+
+    expect(save.saveError()).toBe('disk full');
+    expect(save.isModified()).toBe(true);
+  });
+});
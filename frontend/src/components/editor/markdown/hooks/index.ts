@@ -2,4 +2,4 @@
 
 export { useShikiHighlighter } from './useShikiHighlighter';
 export { createTipTapEditor, normalizeForComparison, type TipTapEditorInstance } from './useTipTapEditor';
-export { useMarkdownSave, type UseMarkdownSaveResult } from './useMarkdownSave';
+export { useMarkdownSave, WRITE_CONFLICT_ERROR, type UseMarkdownSaveResult } from './useMarkdownSave';
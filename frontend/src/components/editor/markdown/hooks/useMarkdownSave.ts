@@ -11,10 +11,21 @@ export interface UseMarkdownSaveOptions {
   fileId?: string;
   filePath: string;
   getContent: () => string;
+  getExpectedHash?: () => string | undefined; // Hash of the file as loaded; the save fails if it changed on disk
   onSaveComplete?: (content: string) => void;
   onModifiedChange?: (isModified: boolean) => void;
 }
 
+/** Prefix of the `write_file` error when the file changed on disk */
+export const WRITE_CONFLICT_ERROR = 'Write conflict';
+
+/** Result of the `write_file` command */
+interface SavedFile {
+  hash: string;
+  /** Contents as written, if the lane's format on save changed them */
+  formatted?: string;
+}
+
 export interface UseMarkdownSaveResult {
   isSaving: () => boolean;
   saveError: () => string | null;
@@ -59,12 +70,17 @@ export function useMarkdownSave(options: UseMarkdownSaveOptions): UseMarkdownSav
     setSaveError(null);
 
     try {
-      const contentToSave = options.getContent();
+      const contents = options.getContent();
 
-      await invoke('write_file', {
+      // The lane's format on save may change the contents that are written
+      const expectedHash = options.getExpectedHash?.();
+      const saved = await invoke<SavedFile>('write_file', {
         path: options.filePath,
-        contents: contentToSave,
+        contents,
+        options: expectedHash ? { expected_hash: expectedHash } : null,
+        laneId: options.laneId ?? null,
       });
+      const contentToSave = saved.formatted ?? contents;
 
       // Update the original content to the saved content
       originalNormalizedContent = normalizeForComparison(contentToSave);
@@ -73,13 +89,14 @@ export function useMarkdownSave(options: UseMarkdownSaveOptions): UseMarkdownSav
 
       // Notify EditorStateManager
       if (options.laneId && options.fileId) {
-        editorStateManager.updateFileContent(options.laneId, options.fileId, contentToSave);
+        editorStateManager.updateFileContent(options.laneId, options.fileId, contentToSave, saved.hash);
       }
 
       options.onSaveComplete?.(contentToSave);
     } catch (err) {
       console.error('Failed to save file:', err);
-      setSaveError(err instanceof Error ? err.message : 'Failed to save file');
+      // Commands reject with the error string
+      setSaveError(err instanceof Error ? err.message : String(err));
     } finally {
       setIsSaving(false);
     }
@@ -18,6 +18,7 @@ export interface OpenFile {
   forceSourceMode?: number; // For markdown files: timestamp to force source view (e.g., when opened from search)
   // External change tracking
   lastKnownModifiedTime?: number; // Unix timestamp when file was last loaded/saved
+  contentHash?: string; // Hash of the file on disk when last loaded/saved, saves are rejected if it changed
   hasExternalChanges?: boolean; // Flag set when external modification detected
   // Diff view mode
   diffContent?: string; // If set, show diff instead of regular content
@@ -37,7 +37,8 @@ export function useFileWatcher(options: UseFileWatcherOptions): void {
     // Set up file watcher for the new directory
     fileWatchService
       .watchDirectory(currentWorkingDir, (event: FileWatchEvent) => {
-        const parentDir = getParentPath(event.path);
+        // On overflow, changes were dropped; refresh from the root
+        const parentDir = event.kind === 'overflow' ? currentWorkingDir : getParentPath(event.path);
 
         // Only refresh if the parent is expanded or is the root
         if (parentDir === currentWorkingDir || expandedDirs().has(parentDir)) {
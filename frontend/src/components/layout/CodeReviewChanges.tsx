@@ -124,6 +124,7 @@ export function CodeReviewChanges(props: CodeReviewChangesProps) {
           files: commitChanges,
           sortOrder: sortOrder(),
           workingDir: props.workingDir,
+          laneId: props.laneId,
         });
         setSortedFiles(sorted);
       } catch (error) {
@@ -147,6 +148,7 @@ export function CodeReviewChanges(props: CodeReviewChangesProps) {
         files,
         sortOrder: order,
         workingDir: props.workingDir,
+        laneId: props.laneId,
       });
       setSortedFiles(sorted);
     } catch (error) {
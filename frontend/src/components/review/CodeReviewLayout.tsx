@@ -412,6 +412,8 @@ export function CodeReviewLayout(props: CodeReviewLayoutProps) {
               workingDir={props.workingDir}
               sortedFiles={reviewState().sortedFiles}
               fileDiffs={reviewState().fileDiffs}
+              changedLineDiagnostics={reviewState().changedLineDiagnostics}
+              pluginFindings={reviewState().pluginFindings}
               perFileFeedback={reviewState().perFileFeedback}
               visibleFilePath={reviewState().visibleFilePath}
               scrollToPath={reviewState().scrollToPath}
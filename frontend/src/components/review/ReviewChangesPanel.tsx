@@ -8,12 +8,15 @@
 import { createMemo } from 'solid-js';
 import { ReviewFileScrollView } from './ReviewFileScrollView';
 import type { FileChangeStats } from '../../types/git';
+import type { PluginFinding, ReviewDiagnostic } from '../../services/api/types';
 
 interface ReviewChangesPanelProps {
   laneId: string;
   workingDir: string;
   sortedFiles: FileChangeStats[];
   fileDiffs: Map<string, string>;
+  changedLineDiagnostics: Map<string, ReviewDiagnostic[]>;
+  pluginFindings: Map<string, PluginFinding[]>;
   perFileFeedback: Map<string, string>;
   visibleFilePath: string | null;
   scrollToPath: string | null;
@@ -32,6 +35,8 @@ export function ReviewChangesPanel(props: ReviewChangesPanelProps) {
         workingDir={props.workingDir}
         sortedFiles={props.sortedFiles}
         fileDiffs={props.fileDiffs}
+        changedLineDiagnostics={props.changedLineDiagnostics}
+        pluginFindings={props.pluginFindings}
         perFileFeedback={props.perFileFeedback}
         visibleFilePath={props.visibleFilePath}
         onVisibleFileChange={props.onVisibleFileChange}
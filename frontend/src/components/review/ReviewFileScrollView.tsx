@@ -18,12 +18,15 @@ import { MarkdownRenderer } from '../../lib/markdown/MarkdownRenderer';
 import { debounce } from '../../utils/debounce';
 import { useLazyDiff } from '../../hooks/useLazyDiff';
 import type { FileChangeStats } from '../../types/git';
+import type { PluginFinding, ReviewDiagnostic } from '../../services/api/types';
 
 interface ReviewFileScrollViewProps {
   laneId: string;
   workingDir: string;
   sortedFiles: FileChangeStats[];
   fileDiffs: Map<string, string>;
+  changedLineDiagnostics: Map<string, ReviewDiagnostic[]>; // Introduced on changed lines, shown inline
+  pluginFindings: Map<string, PluginFinding[]>; // From plugin review rules, shown inline
   perFileFeedback: Map<string, string>;
   visibleFilePath: string | null;
   onVisibleFileChange: (path: string) => void;
@@ -379,6 +382,8 @@ export function ReviewFileScrollView(props: ReviewFileScrollViewProps) {
                         workingDir={props.workingDir}
                         embedded={true}
                         viewMode={diffViewMode()}
+                        diagnostics={props.changedLineDiagnostics.get(file.path)}
+                        findings={props.pluginFindings.get(file.path)}
                       />
                     </div>
                   </Show>
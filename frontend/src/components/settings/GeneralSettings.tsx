@@ -2,12 +2,14 @@
 
 import { createSignal, onMount } from 'solid-js';
 import { editorSettingsManager, type MarkdownDefaultMode, type DiffViewDefaultMode } from '../../services/EditorSettingsManager';
+import { getPluginDeveloperMode, setPluginDeveloperMode } from '../../lib/plugin-api';
 
 export type FileSortOrder = 'alphabetical' | 'smart' | 'smart-dependencies' | 'change-size' | 'none';
 
 export function GeneralSettings() {
   const settings = editorSettingsManager.getSettings();
   const [fileSortOrder, setFileSortOrder] = createSignal<FileSortOrder>('smart');
+  const [developerMode, setDeveloperMode] = createSignal(false);
 
   // Load settings from localStorage
   onMount(() => {
@@ -17,6 +19,15 @@ export function GeneralSettings() {
     }
   });
 
+  // Developer mode lives in the plugin host, not localStorage
+  onMount(async () => {
+    try {
+      setDeveloperMode(await getPluginDeveloperMode());
+    } catch (err) {
+      console.warn('Failed to get plugin developer mode:', err);
+    }
+  });
+
   const handleMarkdownModeChange = (mode: MarkdownDefaultMode) => {
     editorSettingsManager.setMarkdownDefaultMode(mode);
   };
@@ -32,6 +43,15 @@ export function GeneralSettings() {
     window.dispatchEvent(new CustomEvent('fileSortOrderChanged', { detail: order }));
   };
 
+  const handleDeveloperModeChange = async (enabled: boolean) => {
+    try {
+      await setPluginDeveloperMode(enabled);
+      setDeveloperMode(enabled);
+    } catch (err) {
+      console.error('Failed to set plugin developer mode:', err);
+    }
+  };
+
   return (
     <div>
       <h2 class="text-xl font-semibold text-zed-text-primary mb-2">General Settings</h2>
@@ -172,6 +192,45 @@ export function GeneralSettings() {
             </div>
           </div>
         </div>
+
+        {/* Plugin Settings */}
+        <div>
+          <h3 class="text-sm font-medium text-zed-text-primary mb-4">Plugins</h3>
+
+          {/* Developer Mode */}
+          <div class="p-4 rounded-lg bg-zed-bg-surface border border-zed-border-default">
+            <div class="flex items-center justify-between">
+              <div>
+                <p class="text-sm font-medium text-zed-text-primary">Developer Mode</p>
+                <p class="text-xs text-zed-text-tertiary mt-1">
+                  Reload plugins automatically when their files change
+                </p>
+              </div>
+              <div class="flex items-center gap-1 p-1 bg-zed-bg-panel rounded-md border border-zed-border-default">
+                <button
+                  class={`px-3 py-1.5 text-sm rounded transition-colors ${
+                    !developerMode()
+                      ? 'bg-zed-accent-blue text-white'
+                      : 'text-zed-text-secondary hover:text-zed-text-primary hover:bg-zed-bg-hover'
+                  }`}
+                  onClick={() => handleDeveloperModeChange(false)}
+                >
+                  Off
+                </button>
+                <button
+                  class={`px-3 py-1.5 text-sm rounded transition-colors ${
+                    developerMode()
+                      ? 'bg-zed-accent-blue text-white'
+                      : 'text-zed-text-secondary hover:text-zed-text-primary hover:bg-zed-bg-hover'
+                  }`}
+                  onClick={() => handleDeveloperModeChange(true)}
+                >
+                  On
+                </button>
+              </div>
+            </div>
+          </div>
+        </div>
       </div>
     </div>
   );
@@ -0,0 +1,39 @@
+/**
+ * Plugin API - Wrapper around Tauri commands for WASM plugins
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { PluginCommand } from '../types/plugin';
+
+/**
+ * List the commands registered by the active plugins
+ */
+export async function listPluginCommands(): Promise<PluginCommand[]> {
+  return invoke<PluginCommand[]>('plugin_commands');
+}
+
+/**
+ * Run a plugin command, confining its file access to workingDir
+ */
+export async function runPluginCommand(
+  plugin: string,
+  id: string,
+  workingDir?: string
+): Promise<void> {
+  return invoke<void>('plugin_run_command', { plugin, id, workingDir: workingDir ?? null });
+}
+
+/**
+ * Whether plugin developer mode is on
+ */
+export async function getPluginDeveloperMode(): Promise<boolean> {
+  return invoke<boolean>('plugin_developer_mode');
+}
+
+/**
+ * Turn plugin developer mode on or off; while on, plugins are reloaded
+ * when their files change
+ */
+export async function setPluginDeveloperMode(enabled: boolean): Promise<void> {
+  return invoke<void>('plugin_set_developer_mode', { enabled });
+}
@@ -13,6 +13,12 @@ interface FileStats {
   size: number;
 }
 
+/** Result of the `read_text_file` command */
+interface TextFileContents {
+  contents: string;
+  version: { hash: string };
+}
+
 // Lane state structure
 interface LaneState {
   openFiles: Record<string, OpenFile>;
@@ -414,8 +420,8 @@ class EditorStateManager {
 
     try {
       // Load content and get file stats in parallel
-      const [content, stats] = await Promise.all([
-        invoke<string>('read_file', { path }),
+      const [file, stats] = await Promise.all([
+        invoke<TextFileContents>('read_text_file', { path }),
         invoke<FileStats>('get_file_stats', { path }).catch(() => null),
       ]);
 
@@ -423,9 +429,10 @@ class EditorStateManager {
       if (this.store.lanes[laneId]?.openFiles[fileId]) {
         this.batchUpdate(() => {
           this.updateFile(laneId, fileId, {
-            content,
+            content: file.contents,
             isLoading: false,
             lastKnownModifiedTime: stats?.modified ?? undefined,
+            contentHash: file.version.hash,
             hasExternalChanges: false,
           });
         });
@@ -626,11 +633,12 @@ class EditorStateManager {
   }
 
   // Clear external change flag (user chose to keep local changes)
+  // The next save then overwrites the file instead of being rejected as a conflict
   clearExternalChangeFlag(laneId: string, fileId: string): void {
     const lane = this.store.lanes[laneId];
     if (!lane?.openFiles[fileId]) return;
 
-    this.updateFile(laneId, fileId, { hasExternalChanges: false });
+    this.updateFile(laneId, fileId, { hasExternalChanges: false, contentHash: undefined });
   }
 
   // Reload file from disk (user chose to reload)
@@ -642,17 +650,18 @@ class EditorStateManager {
     if (!file) return;
 
     try {
-      const [content, stats] = await Promise.all([
-        invoke<string>('read_file', { path: file.path }),
+      const [disk, stats] = await Promise.all([
+        invoke<TextFileContents>('read_text_file', { path: file.path }),
         invoke<FileStats>('get_file_stats', { path: file.path }).catch(() => null),
       ]);
 
       this.batchUpdate(() => {
         this.updateFile(laneId, fileId, {
-          content,
+          content: disk.contents,
           isModified: false,
           hasExternalChanges: false,
           lastKnownModifiedTime: stats?.modified ?? undefined,
+          contentHash: disk.version.hash,
         });
       });
     } catch (err) {
@@ -780,7 +789,7 @@ class EditorStateManager {
   }
 
   // Update file content (after save)
-  async updateFileContent(laneId: string, fileId: string, content: string): Promise<void> {
+  async updateFileContent(laneId: string, fileId: string, content: string, contentHash?: string): Promise<void> {
     const lane = this.store.lanes[laneId];
     if (!lane?.openFiles[fileId]) return;
 
@@ -802,6 +811,7 @@ class EditorStateManager {
         isModified: false,
         hasExternalChanges: false,
         lastKnownModifiedTime,
+        contentHash,
       });
     });
   }
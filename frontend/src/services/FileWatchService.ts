@@ -6,7 +6,15 @@ import { listen } from '@tauri-apps/api/event';
 export interface FileWatchEvent {
   watch_id: string;
   path: string;
-  kind: 'create' | 'modify' | 'delete' | 'rename';
+  // 'overflow' means changes were dropped; `path` is the watched path to rescan
+  kind: 'create' | 'modify' | 'delete' | 'rename' | 'overflow';
+}
+
+// Debounced changes for one watch, emitted by the backend as 'file-watch-batch'
+export interface FileWatchBatch {
+  watch_id: string;
+  events: FileWatchEvent[];
+  overflow: boolean;
 }
 
 type FileChangeCallback = (event: FileWatchEvent) => void;
@@ -26,12 +34,26 @@ class FileWatchService {
   async init(): Promise<void> {
     if (this.initialized) return;
 
-    this.unlisten = await listen<FileWatchEvent>('file-watch-event', (event) => {
-      this.handleEvent(event.payload);
+    this.unlisten = await listen<FileWatchBatch>('file-watch-batch', (event) => {
+      this.handleBatch(event.payload);
     });
     this.initialized = true;
   }
 
+  private handleBatch(batch: FileWatchBatch): void {
+    if (batch.overflow) {
+      const path = this.watchIdToPath.get(batch.watch_id);
+      if (path) {
+        this.handleEvent({ watch_id: batch.watch_id, path, kind: 'overflow' });
+      }
+      return;
+    }
+
+    for (const event of batch.events) {
+      this.handleEvent(event);
+    }
+  }
+
   private handleEvent(event: FileWatchEvent): void {
     // O(1) lookup using watchId -> path map
     const path = this.watchIdToPath.get(event.watch_id);
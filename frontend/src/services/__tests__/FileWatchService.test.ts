@@ -1,5 +1,5 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
-import type { FileWatchEvent } from '../FileWatchService';
+import type { FileWatchBatch, FileWatchEvent } from '../FileWatchService';
 
 // Mock Tauri APIs
 const mockInvoke = vi.fn();
@@ -14,7 +14,7 @@ vi.mock('@tauri-apps/api/event', () => ({
 }));
 
 let fileWatchService: typeof import('../FileWatchService')['fileWatchService'];
-let capturedEventHandler: ((event: { payload: FileWatchEvent }) => void) | null = null;
+let capturedEventHandler: ((event: { payload: FileWatchBatch }) => void) | null = null;
 
 beforeEach(async () => {
   mockInvoke.mockReset();
@@ -22,7 +22,7 @@ beforeEach(async () => {
   capturedEventHandler = null;
 
   // Mock listen to capture the event handler
-  mockListen.mockImplementation(async (eventName: string, handler: (event: { payload: FileWatchEvent }) => void) => {
+  mockListen.mockImplementation(async (eventName: string, handler: (event: { payload: FileWatchBatch }) => void) => {
     capturedEventHandler = handler;
     return () => {
       capturedEventHandler = null;
@@ -34,12 +34,16 @@ beforeEach(async () => {
   fileWatchService = mod.fileWatchService;
 });
 
-function simulateFileEvent(event: FileWatchEvent): void {
+function simulateBatch(batch: FileWatchBatch): void {
   if (capturedEventHandler) {
-    capturedEventHandler({ payload: event });
+    capturedEventHandler({ payload: batch });
   }
 }
 
+function simulateFileEvent(event: FileWatchEvent): void {
+  simulateBatch({ watch_id: event.watch_id, events: [event], overflow: false });
+}
+
 describe('FileWatchService', () => {
   describe('watchDirectory', () => {
     it('calls invoke to start watching', async () => {
@@ -115,6 +119,44 @@ describe('FileWatchService', () => {
       expect(watchCalls).toHaveLength(1);
     });
 
+    it('dispatches every event in a batch', async () => {
+      mockInvoke.mockResolvedValue('watch-id-1');
+      const callback = vi.fn();
+
+      await fileWatchService.watchDirectory('/path/to/dir', callback);
+
+      simulateBatch({
+        watch_id: 'watch-id-1',
+        events: [
+          { watch_id: 'watch-id-1', path: '/path/to/dir/a.ts', kind: 'create' },
+          { watch_id: 'watch-id-1', path: '/path/to/dir/b.ts', kind: 'delete' },
+        ],
+        overflow: false,
+      });
+
+      expect(callback).toHaveBeenCalledTimes(2);
+      expect(callback).toHaveBeenLastCalledWith({
+        watch_id: 'watch-id-1',
+        path: '/path/to/dir/b.ts',
+        kind: 'delete',
+      });
+    });
+
+    it('reports overflow as a rescan of the watched path', async () => {
+      mockInvoke.mockResolvedValue('watch-id-1');
+      const callback = vi.fn();
+
+      await fileWatchService.watchDirectory('/path/to/dir', callback);
+
+      simulateBatch({ watch_id: 'watch-id-1', events: [], overflow: true });
+
+      expect(callback).toHaveBeenCalledWith({
+        watch_id: 'watch-id-1',
+        path: '/path/to/dir',
+        kind: 'overflow',
+      });
+    });
+
     it('does not dispatch events for unknown watch ids', async () => {
       mockInvoke.mockResolvedValue('watch-id-1');
       const callback = vi.fn();
@@ -9,11 +9,16 @@ import type {
   ReviewGenerationParams,
   FileReviewParams,
   SortFilesParams,
+  DiagnosticsDeltaParams,
+  DiagnosticsDelta,
+  PluginReviewParams,
+  PluginReviewReport,
   TestToolParams,
   AIReviewResult,
   FileChangeStats,
 } from './types';
 import type { AITool } from '../AIReviewService';
+import { ReviewAPIError } from './ReviewAPIError';
 
 /**
  * Mock Review API
@@ -104,6 +109,56 @@ Model: ${params.model || 'default'}`,
     });
   }
 
+  async getDiagnosticsDelta(params: DiagnosticsDeltaParams): Promise<DiagnosticsDelta> {
+    await this.wait();
+
+    const introduced = {
+      path: '/mock/src/lib.rs',
+      start_line: 3,
+      start_column: 9,
+      end_line: 3,
+      end_column: 14,
+      severity: 'Error' as const,
+      message: 'Mock error: cannot find value `x` in this scope',
+      source: 'rustc',
+      code: 'E0425',
+    };
+    return {
+      lane_id: params.laneId,
+      base_commit: '0000000000000000000000000000000000000000',
+      introduced: { errors: 1, warnings: 0, information: 0, hints: 0 },
+      fixed: { errors: 0, warnings: 0, information: 0, hints: 0 },
+      unchanged: 0,
+      complete: true,
+      files: [
+        {
+          path: 'src/lib.rs',
+          introduced: [introduced],
+          fixed: [],
+          on_changed_lines: [introduced],
+        },
+      ],
+    };
+  }
+
+  async reviewWithPlugins(_params: PluginReviewParams): Promise<PluginReviewReport> {
+    await this.wait();
+
+    return {
+      findings: [
+        {
+          plugin: 'house-rules',
+          rule: 'no-unwrap',
+          file: 'src/lib.rs',
+          line: 3,
+          severity: 'warning',
+          message: 'Mock finding: new unwrap()',
+        },
+      ],
+      errors: [],
+    };
+  }
+
   async testTool(params: TestToolParams): Promise<boolean> {
     await this.wait();
 
@@ -146,6 +201,14 @@ export class FailingMockReviewAPI implements IReviewAPI {
     return params.files;
   }
 
+  async getDiagnosticsDelta(_params: DiagnosticsDeltaParams): Promise<DiagnosticsDelta> {
+    throw new ReviewAPIError('Mock error: No language servers configured', undefined, 'DIAGNOSTICS_DELTA_FAILED');
+  }
+
+  async reviewWithPlugins(_params: PluginReviewParams): Promise<PluginReviewReport> {
+    throw new ReviewAPIError('Mock error: Plugins are unavailable', undefined, 'PLUGIN_REVIEW_FAILED');
+  }
+
   async testTool(_params: TestToolParams): Promise<boolean> {
     return false;
   }
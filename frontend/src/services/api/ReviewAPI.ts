@@ -10,6 +10,10 @@ import type {
   ReviewGenerationParams,
   FileReviewParams,
   SortFilesParams,
+  DiagnosticsDeltaParams,
+  DiagnosticsDelta,
+  PluginReviewParams,
+  PluginReviewReport,
   TestToolParams,
   AIReviewResult,
   FileChangeStats,
@@ -39,6 +43,16 @@ export interface IReviewAPI {
    */
   sortFiles(params: SortFilesParams): Promise<FileChangeStats[]>;
 
+  /**
+   * Get the language server diagnostics a lane's changes introduced or fixed
+   */
+  getDiagnosticsDelta(params: DiagnosticsDeltaParams): Promise<DiagnosticsDelta>;
+
+  /**
+   * Run the installed plugins' review rules against uncommitted changes
+   */
+  reviewWithPlugins(params: PluginReviewParams): Promise<PluginReviewReport>;
+
   /**
    * Test if an AI tool is available
    */
@@ -128,12 +142,35 @@ export class ReviewAPI implements IReviewAPI {
         files: params.files,
         sortOrder: params.sortOrder,
         workingDir: params.workingDir,
+        laneId: params.laneId ?? null,
       });
     } catch (err) {
       throw ReviewAPIError.from(err, 'Failed to sort files', 'SORT_FILES_FAILED');
     }
   }
 
+  async getDiagnosticsDelta(params: DiagnosticsDeltaParams): Promise<DiagnosticsDelta> {
+    try {
+      return await invoke<DiagnosticsDelta>('lsp_diagnostics_delta', {
+        laneId: params.laneId,
+        base: params.base ?? null,
+        refreshBaseline: params.refreshBaseline ?? null,
+      });
+    } catch (err) {
+      throw ReviewAPIError.from(err, 'Failed to compute diagnostics delta', 'DIAGNOSTICS_DELTA_FAILED');
+    }
+  }
+
+  async reviewWithPlugins(params: PluginReviewParams): Promise<PluginReviewReport> {
+    try {
+      return await invoke<PluginReviewReport>('plugin_review', {
+        workingDir: params.workingDir,
+      });
+    } catch (err) {
+      throw ReviewAPIError.from(err, 'Failed to run plugin review rules', 'PLUGIN_REVIEW_FAILED');
+    }
+  }
+
   async testTool(params: TestToolParams): Promise<boolean> {
     try {
       return await invoke<boolean>('ai_test_tool', {
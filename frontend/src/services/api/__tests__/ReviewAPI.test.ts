@@ -4,10 +4,17 @@
  * Demonstrates how to use MockReviewAPI for testing.
  */
 
-import { describe, it, expect } from 'vitest';
+import { describe, it, expect, vi, beforeEach } from 'vitest';
 import { MockReviewAPI, FailingMockReviewAPI } from '../MockReviewAPI';
+import { ReviewAPI } from '../ReviewAPI';
 import type { ReviewGenerationParams, FileReviewParams, SortFilesParams } from '../types';
 
+const mockInvoke = vi.fn();
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: (...args: unknown[]) => mockInvoke(...args),
+}));
+
 describe('MockReviewAPI', () => {
   const mockAPI = new MockReviewAPI();
 
@@ -77,6 +84,23 @@ describe('MockReviewAPI', () => {
     expect(result).toContain('gemini');
   });
 
+  it('should return diagnostics introduced on changed lines', async () => {
+    const delta = await mockAPI.getDiagnosticsDelta({ laneId: 'lane-1' });
+
+    expect(delta.lane_id).toBe('lane-1');
+    expect(delta.introduced.errors).toBe(1);
+    expect(delta.files[0].path).toBe('src/lib.rs');
+    expect(delta.files[0].on_changed_lines[0].start_line).toBe(3);
+  });
+
+  it('should return plugin review findings', async () => {
+    const report = await mockAPI.reviewWithPlugins({ workingDir: '/test/dir' });
+
+    expect(report.errors).toEqual([]);
+    expect(report.findings[0].file).toBe('src/lib.rs');
+    expect(report.findings[0].rule).toBe('no-unwrap');
+  });
+
   it('should allow configuring delay', async () => {
     mockAPI.setDelay(10);
 
@@ -137,6 +161,18 @@ describe('FailingMockReviewAPI', () => {
     expect(result).toEqual(files);
   });
 
+  it('should throw for getDiagnosticsDelta', async () => {
+    await expect(failingAPI.getDiagnosticsDelta({ laneId: 'lane-1' })).rejects.toThrow(
+      'No language servers configured'
+    );
+  });
+
+  it('should throw for reviewWithPlugins', async () => {
+    await expect(failingAPI.reviewWithPlugins({ workingDir: '/test/dir' })).rejects.toThrow(
+      'Plugins are unavailable'
+    );
+  });
+
   it('should return false for testTool', async () => {
     const result = await failingAPI.testTool({ tool: 'claude' });
     expect(result).toBe(false);
@@ -147,3 +183,62 @@ describe('FailingMockReviewAPI', () => {
     expect(result).toEqual([]);
   });
 });
+
+describe('ReviewAPI', () => {
+  const api = new ReviewAPI();
+
+  beforeEach(() => {
+    mockInvoke.mockReset();
+  });
+
+  it('should sort files with the lane categories', async () => {
+    const files = [{ path: 'a.ts', status: 'modified' as const, additions: 1, deletions: 0 }];
+    mockInvoke.mockResolvedValue(files);
+
+    await api.sortFiles({ files, sortOrder: 'smart', workingDir: '/test/dir', laneId: 'lane-1' });
+
+    expect(mockInvoke).toHaveBeenCalledWith('git_sort_files', {
+      files,
+      sortOrder: 'smart',
+      workingDir: '/test/dir',
+      laneId: 'lane-1',
+    });
+  });
+
+  it('should request the diagnostics delta for a lane', async () => {
+    mockInvoke.mockResolvedValue({ lane_id: 'lane-1', files: [] });
+
+    await api.getDiagnosticsDelta({ laneId: 'lane-1' });
+
+    expect(mockInvoke).toHaveBeenCalledWith('lsp_diagnostics_delta', {
+      laneId: 'lane-1',
+      base: null,
+      refreshBaseline: null,
+    });
+  });
+
+  it('should wrap diagnostics delta failures', async () => {
+    mockInvoke.mockRejectedValue('Lane not found: lane-1');
+
+    await expect(api.getDiagnosticsDelta({ laneId: 'lane-1' })).rejects.toMatchObject({
+      code: 'DIAGNOSTICS_DELTA_FAILED',
+    });
+  });
+
+  it('should run plugin review rules for a working directory', async () => {
+    mockInvoke.mockResolvedValue({ findings: [], errors: [] });
+
+    const report = await api.reviewWithPlugins({ workingDir: '/test/dir' });
+
+    expect(mockInvoke).toHaveBeenCalledWith('plugin_review', { workingDir: '/test/dir' });
+    expect(report.findings).toEqual([]);
+  });
+
+  it('should wrap plugin review failures', async () => {
+    mockInvoke.mockRejectedValue('Plugins are unavailable: no engine');
+
+    await expect(api.reviewWithPlugins({ workingDir: '/test/dir' })).rejects.toMatchObject({
+      code: 'PLUGIN_REVIEW_FAILED',
+    });
+  });
+});
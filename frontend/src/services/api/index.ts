@@ -17,6 +17,15 @@ export type {
   ReviewGenerationParams,
   FileReviewParams,
   SortFilesParams,
+  DiagnosticsDeltaParams,
+  DiagnosticsDelta,
+  FileDiagnosticsDelta,
+  ReviewDiagnostic,
+  DiagnosticSeverity,
+  PluginReviewParams,
+  PluginReviewReport,
+  PluginFinding,
+  PluginFindingSeverity,
   TestToolParams,
   GetAvailableToolsResult,
   AIReviewResult,
@@ -30,6 +30,75 @@ export interface SortFilesParams {
   files: FileChangeStats[];
   sortOrder: string;
   workingDir: string;
+  laneId?: string; // Lane whose configured file categories apply
+}
+
+export interface DiagnosticsDeltaParams {
+  laneId: string;
+  base?: string | null; // Branch or revision (default: the default branch)
+  refreshBaseline?: boolean;
+}
+
+export type DiagnosticSeverity = 'Error' | 'Warning' | 'Information' | 'Hint';
+
+/** A language server diagnostic; lines and columns are 1-indexed */
+export interface ReviewDiagnostic {
+  path: string;
+  start_line: number;
+  start_column: number;
+  end_line: number;
+  end_column: number;
+  severity: DiagnosticSeverity;
+  message: string;
+  source: string | null;
+  code: string | null;
+}
+
+export interface DiagnosticCounts {
+  errors: number;
+  warnings: number;
+  information: number;
+  hints: number;
+}
+
+export interface FileDiagnosticsDelta {
+  path: string; // Relative to the lane's working directory
+  introduced: ReviewDiagnostic[];
+  fixed: ReviewDiagnostic[];
+  on_changed_lines: ReviewDiagnostic[]; // Introduced on added or modified lines
+}
+
+/** Diagnostics introduced or fixed by a lane's changes */
+export interface DiagnosticsDelta {
+  lane_id: string;
+  base_commit: string;
+  introduced: DiagnosticCounts;
+  fixed: DiagnosticCounts;
+  unchanged: number;
+  complete: boolean; // False if the servers were still busy
+  files: FileDiagnosticsDelta[];
+}
+
+export interface PluginReviewParams {
+  workingDir: string;
+}
+
+export type PluginFindingSeverity = 'info' | 'warning' | 'error';
+
+/** A problem a plugin review rule found; lines are 1-indexed in the new file */
+export interface PluginFinding {
+  plugin: string;
+  rule: string;
+  file: string; // Relative to the working directory
+  line: number;
+  severity: PluginFindingSeverity;
+  message: string;
+}
+
+/** Findings of every plugin review rule */
+export interface PluginReviewReport {
+  findings: PluginFinding[]; // Sorted by file and line
+  errors: string[]; // Rules that failed to run
 }
 
 export interface TestToolParams {
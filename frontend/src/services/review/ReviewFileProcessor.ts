@@ -5,6 +5,8 @@
  * - Fetching changed files with stats
  * - Fetching file diffs
  * - Sorting files
+ * - Fetching diagnostics introduced on changed lines
+ * - Running plugin review rules
  *
  * Pure async functions with no state management.
  */
@@ -12,6 +14,7 @@
 import { getChangesWithStats, getGitDiff } from '../../lib/git-api';
 import { reviewAPI } from '../api/provider';
 import type { FileChangeStats } from '../../types/git';
+import type { PluginFinding, ReviewDiagnostic } from '../api/types';
 
 export class ReviewFileProcessor {
   /**
@@ -72,13 +75,15 @@ export class ReviewFileProcessor {
    */
   async sortFiles(
     files: FileChangeStats[],
-    workingDir: string
+    workingDir: string,
+    laneId?: string
   ): Promise<FileChangeStats[]> {
     try {
       const sortedFiles = await reviewAPI.sortFiles({
         files,
         sortOrder: 'smart',
         workingDir,
+        laneId,
       });
       return sortedFiles;
     } catch {
@@ -86,6 +91,49 @@ export class ReviewFileProcessor {
       return files;
     }
   }
+
+  /**
+   * Fetch the diagnostics a lane's changes introduced on added or modified
+   * lines, by file path
+   * Returns an empty map if the lane has no language servers configured
+   */
+  async fetchChangedLineDiagnostics(laneId: string): Promise<Map<string, ReviewDiagnostic[]>> {
+    const diagnostics = new Map<string, ReviewDiagnostic[]>();
+    try {
+      const delta = await reviewAPI.getDiagnosticsDelta({ laneId });
+      for (const file of delta.files) {
+        if (file.on_changed_lines.length > 0) {
+          diagnostics.set(file.path, file.on_changed_lines);
+        }
+      }
+    } catch (err) {
+      console.warn('[Review] Diagnostics delta unavailable:', err);
+    }
+    return diagnostics;
+  }
+
+  /**
+   * Run the installed plugins' review rules against the working directory's
+   * uncommitted changes, returning their findings by file path
+   * Returns an empty map if plugins are unavailable
+   */
+  async fetchPluginFindings(workingDir: string): Promise<Map<string, PluginFinding[]>> {
+    const findings = new Map<string, PluginFinding[]>();
+    try {
+      const report = await reviewAPI.reviewWithPlugins({ workingDir });
+      for (const error of report.errors) {
+        console.warn('[Review] Plugin review rule failed:', error);
+      }
+      for (const finding of report.findings) {
+        const fileFindings = findings.get(finding.file) ?? [];
+        fileFindings.push(finding);
+        findings.set(finding.file, fileFindings);
+      }
+    } catch (err) {
+      console.warn('[Review] Plugin review unavailable:', err);
+    }
+    return findings;
+  }
 }
 
 // Export singleton instance
@@ -8,6 +8,10 @@
  * 4. Generate AI summary
  * 5. Generate per-file feedback
  *
+ * Diagnostics the changes introduced are fetched alongside and attached to
+ * the diff once the language servers have settled, as are the findings of
+ * plugin review rules.
+ *
  * Uses ReviewStateManager for state updates.
  * Uses ReviewFileProcessor for file operations.
  * Uses AIReviewService for AI generation.
@@ -225,6 +229,8 @@ export class ReviewOrchestrator {
           perFileFeedback: new Map(),
           sortedFiles: [],
           fileDiffs: new Map(),
+          changedLineDiagnostics: new Map(),
+          pluginFindings: new Map(),
           generatedAt: Date.now(),
           changesetChecksum: '', // Empty checksum for no changes
           progress: {
@@ -287,7 +293,7 @@ export class ReviewOrchestrator {
         },
       }));
 
-      const sortedFiles = await reviewFileProcessor.sortFiles(changesWithStats, workingDir);
+      const sortedFiles = await reviewFileProcessor.sortFiles(changesWithStats, workingDir, laneId);
 
       // Check if aborted
       if (controller.signal.aborted) {
@@ -349,13 +355,16 @@ export class ReviewOrchestrator {
       const changesetChecksum = computeChangesetChecksum(changesWithStats);
 
       // 5. Update state with summary (show layout immediately)
+      const generatedAt = Date.now();
       reviewStateManager.setState(laneId, prev => ({
         ...prev,
         status: 'ready' as ReviewPhase,
         reviewMarkdown,
         sortedFiles,
         fileDiffs,
-        generatedAt: Date.now(),
+        changedLineDiagnostics: new Map(),
+        pluginFindings: new Map(),
+        generatedAt,
         changesetChecksum,
         progress: {
           phase: 'ready',
@@ -366,6 +375,28 @@ export class ReviewOrchestrator {
         scrollToPath: null,
       }));
 
+      // Attach introduced diagnostics to the diff (non-blocking; capturing the
+      // baseline can take a while on first use)
+      void reviewFileProcessor.fetchChangedLineDiagnostics(laneId).then(diagnostics => {
+        if (controller.signal.aborted) {
+          return;
+        }
+        // Skip if the review was regenerated meanwhile
+        reviewStateManager.setState(laneId, prev =>
+          prev.generatedAt === generatedAt ? { ...prev, changedLineDiagnostics: diagnostics } : prev
+        );
+      });
+
+      // Attach plugin review findings to the diff (non-blocking)
+      void reviewFileProcessor.fetchPluginFindings(workingDir).then(pluginFindings => {
+        if (controller.signal.aborted) {
+          return;
+        }
+        reviewStateManager.setState(laneId, prev =>
+          prev.generatedAt === generatedAt ? { ...prev, pluginFindings } : prev
+        );
+      });
+
       // 6. Generate per-file feedback in parallel (non-blocking)
       const filePrompt = settings.filePrompt || aiReviewService.getDefaultFilePrompt();
       const concurrency = settings.concurrency || 4; // Get configurable concurrency limit
@@ -8,6 +8,7 @@
 
 import { createSignal, createRoot, type Accessor } from 'solid-js';
 import type { FileChangeStats } from '../../types/git';
+import type { PluginFinding, ReviewDiagnostic } from '../api/types';
 
 export type ReviewPhase =
   | 'idle'
@@ -32,6 +33,8 @@ export interface CodeReviewState {
   perFileFeedback: Map<string, string>;
   sortedFiles: FileChangeStats[];
   fileDiffs: Map<string, string>;
+  changedLineDiagnostics: Map<string, ReviewDiagnostic[]>; // Introduced on changed lines, by file path
+  pluginFindings: Map<string, PluginFinding[]>; // From plugin review rules, by file path
   error: string | null;
   generatedAt: number | null;
   visibleFilePath: string | null;
@@ -48,6 +51,8 @@ function createDefaultState(): CodeReviewState {
     sortedFiles: [],
     scrollToPath: null,
     fileDiffs: new Map(),
+    changedLineDiagnostics: new Map(),
+    pluginFindings: new Map(),
     error: null,
     generatedAt: null,
     visibleFilePath: null,
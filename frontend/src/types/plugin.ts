@@ -0,0 +1,9 @@
+/**
+ * A command registered by a plugin, shown in the command palette
+ */
+export interface PluginCommand {
+  plugin: string;       // Name of the plugin that registered the command
+  id: string;           // Command id, unique within the plugin
+  title: string;
+  description?: string;
+}
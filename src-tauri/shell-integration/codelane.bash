@@ -0,0 +1,74 @@
+# Codelane shell integration for bash
+#
+# Loaded via `bash --init-file`. Emulates a login shell by sourcing the usual
+# profile files, then reports prompts, commands and the working directory to
+# Codelane using OSC 133 / OSC 633 / OSC 7 escape sequences.
+
+if [ -r /etc/profile ]; then . /etc/profile; fi
+if [ -r "$HOME/.bash_profile" ]; then
+    . "$HOME/.bash_profile"
+elif [ -r "$HOME/.bash_login" ]; then
+    . "$HOME/.bash_login"
+elif [ -r "$HOME/.profile" ]; then
+    . "$HOME/.profile"
+fi
+
+if [ -n "$__codelane_integration_loaded" ]; then return; fi
+__codelane_integration_loaded=1
+
+__codelane_ready=0
+__codelane_running=0
+__codelane_prompt_end=$'\[\e]133;B\a\]'
+
+__codelane_escape() {
+    local s="$1"
+    s="${s//\\/\\\\}"
+    s="${s//;/\\x3b}"
+    s="${s//$'\n'/\\x0a}"
+    printf '%s' "$s"
+}
+
+__codelane_preexec() {
+    [ "$__codelane_ready" = 1 ] || return
+    [ -n "$COMP_LINE" ] && return
+    [ "$1" = "__codelane_precmd" ] && return
+    __codelane_ready=0
+    __codelane_running=1
+
+    local cmd
+    cmd="$(HISTTIMEFORMAT= builtin history 1 2>/dev/null)"
+    cmd="${cmd#"${cmd%%[![:space:]]*}"}"
+    cmd="${cmd#*[[:digit:]]}"
+    cmd="${cmd#"${cmd%%[![:digit:]]*}"}"
+    cmd="${cmd#"${cmd%%[![:space:]]*}"}"
+    [ -n "$cmd" ] || cmd="$1"
+
+    printf '\e]633;E;%s\a' "$(__codelane_escape "$cmd")"
+    printf '\e]133;C\a'
+}
+
+__codelane_precmd() {
+    local status=$?
+    # Keep the rest of PROMPT_COMMAND from looking like a user command
+    __codelane_ready=0
+    if [ "$__codelane_running" = 1 ]; then
+        printf '\e]133;D;%s\a' "$status"
+    fi
+    __codelane_running=0
+    printf '\e]7;file://%s%s\a' "$HOSTNAME" "$PWD"
+    printf '\e]133;A\a'
+}
+
+__codelane_prompt_ready() {
+    case "$PS1" in
+        *"$__codelane_prompt_end") ;;
+        *) PS1="$PS1$__codelane_prompt_end" ;;
+    esac
+    __codelane_ready=1
+}
+
+# Strip trailing separators from an existing PROMPT_COMMAND before wrapping it
+__codelane_user_prompt_command="${PROMPT_COMMAND%"${PROMPT_COMMAND##*[![:space:];]}"}"
+
+trap '__codelane_preexec "$BASH_COMMAND"' DEBUG
+PROMPT_COMMAND="__codelane_precmd${__codelane_user_prompt_command:+;$__codelane_user_prompt_command};__codelane_prompt_ready"
@@ -0,0 +1,34 @@
+# Codelane shell integration for fish
+#
+# Loaded via `fish --init-command`. Reports prompts, commands and the working
+# directory to Codelane using OSC 133 / OSC 633 / OSC 7 escape sequences.
+
+if set -q __codelane_integration_loaded
+    exit
+end
+set -g __codelane_integration_loaded 1
+set -g __codelane_running 0
+set -g __codelane_status 0
+
+function __codelane_escape
+    string replace -a '\\' '\\\\' -- $argv[1] | string replace -a ';' '\\x3b' | string join '\\x0a'
+end
+
+function __codelane_preexec --on-event fish_preexec
+    set -g __codelane_running 1
+    printf '\e]633;E;%s\a' (__codelane_escape "$argv")
+    printf '\e]133;C\a'
+end
+
+function __codelane_postexec --on-event fish_postexec
+    set -g __codelane_status $status
+end
+
+function __codelane_prompt --on-event fish_prompt
+    if test "$__codelane_running" = 1
+        printf '\e]133;D;%s\a' $__codelane_status
+    end
+    set -g __codelane_running 0
+    printf '\e]7;file://%s%s\a' (hostname) "$PWD"
+    printf '\e]133;A\a'
+end
@@ -6,6 +6,12 @@
 use serde::Serialize;
 use std::process::{Command, Stdio};
 use std::io::Write;
+use tauri::State;
+
+use crate::process_registry::{ProcessKind, ProcessRegistry, Registration};
+
+/// Lane an AI tool invocation runs for, so its process shows up in the registry
+type LaneTracking<'a> = Option<(&'a ProcessRegistry, &'a str)>;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct AIReviewResult {
@@ -15,13 +21,18 @@ pub struct AIReviewResult {
 }
 
 /// Generate a code changes summary with feedback using the configured AI tool
+///
+/// When `lane_id` is given, the tool process is registered with the lane while
+/// it runs (see [`crate::process_registry`]).
 #[tauri::command]
 pub async fn ai_generate_review(
+    registry: State<'_, ProcessRegistry>,
     tool: String,
     diff_content: String,
     prompt: String,
     working_dir: String,
     model: Option<String>,
+    lane_id: Option<String>,
 ) -> Result<AIReviewResult, String> {
     // Build the full prompt
     let full_prompt = format!(
@@ -29,12 +40,14 @@ pub async fn ai_generate_review(
         prompt, diff_content
     );
 
+    let lane: LaneTracking<'_> = lane_id.as_deref().map(|id| (registry.inner(), id));
+
     // Execute based on tool type
     let result = match tool.as_str() {
-        "claude" => execute_claude(&full_prompt, &working_dir, model.as_deref()),
-        "aider" => execute_aider(&full_prompt, &working_dir, model.as_deref()),
-        "opencode" => execute_opencode(&full_prompt, &working_dir, model.as_deref()),
-        "gemini" => execute_gemini(&full_prompt, &working_dir, model.as_deref()),
+        "claude" => execute_claude(&full_prompt, &working_dir, model.as_deref(), lane),
+        "aider" => execute_aider(&full_prompt, &working_dir, model.as_deref(), lane),
+        "opencode" => execute_opencode(&full_prompt, &working_dir, model.as_deref(), lane),
+        "gemini" => execute_gemini(&full_prompt, &working_dir, model.as_deref(), lane),
         _ => return Err(format!("Unsupported AI tool: {}", tool)),
     };
 
@@ -63,8 +76,22 @@ fn command_exists(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Register a spawned tool process with its lane until the guard is dropped
+fn track_tool<'a>(
+    lane: LaneTracking<'a>,
+    child: &std::process::Child,
+    tool: &str,
+) -> Option<Registration<'a>> {
+    lane.map(|(registry, lane_id)| registry.track(lane_id, child.id(), ProcessKind::AiTool, tool))
+}
+
 /// Execute Claude Code CLI
-fn execute_claude(prompt: &str, working_dir: &str, model: Option<&str>) -> Result<String, String> {
+fn execute_claude(
+    prompt: &str,
+    working_dir: &str,
+    model: Option<&str>,
+    lane: LaneTracking<'_>,
+) -> Result<String, String> {
     eprintln!("[AI] Executing Claude with model: {:?}, prompt length: {}", model, prompt.len());
 
     if !command_exists("claude") {
@@ -92,6 +119,7 @@ fn execute_claude(prompt: &str, working_dir: &str, model: Option<&str>) -> Resul
     let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to spawn claude: {}", e))?;
+    let _registration = track_tool(lane, &child, "claude");
 
     // Write prompt to stdin
     if let Some(mut stdin) = child.stdin.take() {
@@ -121,7 +149,12 @@ fn execute_claude(prompt: &str, working_dir: &str, model: Option<&str>) -> Resul
 }
 
 /// Execute Aider CLI
-fn execute_aider(prompt: &str, working_dir: &str, model: Option<&str>) -> Result<String, String> {
+fn execute_aider(
+    prompt: &str,
+    working_dir: &str,
+    model: Option<&str>,
+    lane: LaneTracking<'_>,
+) -> Result<String, String> {
     eprintln!("[AI] Executing Aider with model: {:?}, prompt length: {}", model, prompt.len());
 
     if !command_exists("aider") {
@@ -143,9 +176,14 @@ fn execute_aider(prompt: &str, working_dir: &str, model: Option<&str>) -> Result
         command.arg("--model").arg(model_name);
     }
 
-    let output = command
-        .output()
+    let child = command
+        .spawn()
         .map_err(|e| format!("Failed to execute aider: {}", e))?;
+    let _registration = track_tool(lane, &child, "aider");
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for output: {}", e))?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -160,7 +198,12 @@ fn execute_aider(prompt: &str, working_dir: &str, model: Option<&str>) -> Result
 }
 
 /// Execute OpenCode CLI
-fn execute_opencode(prompt: &str, working_dir: &str, model: Option<&str>) -> Result<String, String> {
+fn execute_opencode(
+    prompt: &str,
+    working_dir: &str,
+    model: Option<&str>,
+    lane: LaneTracking<'_>,
+) -> Result<String, String> {
     eprintln!("[AI] Executing OpenCode with model: {:?}, prompt length: {}", model, prompt.len());
 
     if !command_exists("opencode") {
@@ -182,6 +225,7 @@ fn execute_opencode(prompt: &str, working_dir: &str, model: Option<&str>) -> Res
     let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to spawn opencode: {}", e))?;
+    let _registration = track_tool(lane, &child, "opencode");
 
     if let Some(mut stdin) = child.stdin.take() {
         stdin
@@ -207,7 +251,12 @@ fn execute_opencode(prompt: &str, working_dir: &str, model: Option<&str>) -> Res
 }
 
 /// Execute Gemini CLI
-fn execute_gemini(prompt: &str, working_dir: &str, model: Option<&str>) -> Result<String, String> {
+fn execute_gemini(
+    prompt: &str,
+    working_dir: &str,
+    model: Option<&str>,
+    lane: LaneTracking<'_>,
+) -> Result<String, String> {
     eprintln!("[AI] Executing Gemini with model: {:?}, prompt length: {}", model, prompt.len());
 
     if !command_exists("gemini") {
@@ -230,6 +279,7 @@ fn execute_gemini(prompt: &str, working_dir: &str, model: Option<&str>) -> Resul
     let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to spawn gemini: {}", e))?;
+    let _registration = track_tool(lane, &child, "gemini");
 
     if let Some(mut stdin) = child.stdin.take() {
         stdin
@@ -0,0 +1,909 @@
+//! Diagnostics Delta
+//!
+//! Answers "did the lane's changes make things worse?" by comparing the
+//! lane's current language server diagnostics with a baseline captured at the
+//! commit the lane started from (the merge base with the default branch, or an
+//! explicit revision).
+//!
+//! The baseline is captured by checking the base commit out into a temporary
+//! worktree and running the lane's language servers there. Diagnostics are
+//! matched across the two versions by severity, message, source and code,
+//! using the diff to follow lines that moved; what is left over was
+//! introduced or fixed by the lane. Introduced diagnostics on added or
+//! modified lines are reported separately so the review diff can show them
+//! inline.
+
+use codelane_lsp::{Diagnostic, DiagnosticCounts, LaneDiagnostics, LspManager};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::State;
+
+use crate::git::run_git;
+use crate::lane::LaneState;
+use crate::lsp::{lane_servers, LspState};
+
+/// Suffix of the pseudo-lane whose servers analyze the baseline worktree
+const BASELINE_LANE_SUFFIX: &str = "#baseline";
+
+/// Diagnostics are considered settled after this long without changes
+const QUIET_PERIOD: Duration = Duration::from_secs(2);
+
+/// Longest time to wait for servers to finish analyzing
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Most changed files opened in the servers; servers that only analyze open
+/// documents report nothing for the rest
+const MAX_OPENED_FILES: usize = 200;
+
+/// Diagnostics a lane introduced or fixed in one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiagnosticsDelta {
+    /// Path relative to the lane's working directory
+    pub path: String,
+    /// Diagnostics present now but not at the base
+    pub introduced: Vec<Diagnostic>,
+    /// Diagnostics present at the base but not now (with current paths)
+    pub fixed: Vec<Diagnostic>,
+    /// Introduced diagnostics on lines the lane added or modified
+    pub on_changed_lines: Vec<Diagnostic>,
+}
+
+/// Diagnostics introduced or fixed by a lane's changes
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsDelta {
+    pub lane_id: String,
+    /// Commit the baseline was captured at
+    pub base_commit: String,
+    pub introduced: DiagnosticCounts,
+    pub fixed: DiagnosticCounts,
+    /// Diagnostics present in both versions
+    pub unchanged: usize,
+    /// False if the servers were still busy when a snapshot was taken
+    pub complete: bool,
+    /// Files with introduced or fixed diagnostics, sorted by path
+    pub files: Vec<FileDiagnosticsDelta>,
+}
+
+/// Diagnostics captured at a lane's base commit
+struct Baseline {
+    commit: String,
+    /// Files opened in the servers while capturing
+    opened: HashSet<String>,
+    /// Diagnostics by path relative to the working directory
+    diagnostics: HashMap<String, Vec<Diagnostic>>,
+    complete: bool,
+}
+
+/// Baselines by lane, reused until the base commit changes
+pub struct DiagnosticsBaselineState {
+    baselines: Mutex<HashMap<String, Baseline>>,
+}
+
+impl DiagnosticsBaselineState {
+    pub fn new() -> Self {
+        Self {
+            baselines: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for DiagnosticsBaselineState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `--unified=0` hunk header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hunk {
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+}
+
+/// How a file differs from the base commit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FileChange {
+    /// New or untracked file
+    added: bool,
+    deleted: bool,
+    hunks: Vec<Hunk>,
+}
+
+impl FileChange {
+    fn added() -> Self {
+        Self {
+            added: true,
+            ..Default::default()
+        }
+    }
+
+    /// Where a base line ended up, or `None` if it was removed or modified
+    fn old_to_new(&self, line: u32) -> Option<u32> {
+        if self.added || self.deleted {
+            return None;
+        }
+        let mut offset: i64 = 0;
+        for hunk in &self.hunks {
+            if hunk.old_lines == 0 {
+                // Pure insertion after line `old_start`
+                if hunk.old_start >= line {
+                    break;
+                }
+            } else if line < hunk.old_start {
+                break;
+            } else if line < hunk.old_start + hunk.old_lines {
+                return None;
+            }
+            offset += i64::from(hunk.new_lines) - i64::from(hunk.old_lines);
+        }
+        u32::try_from(i64::from(line) + offset).ok()
+    }
+
+    /// Whether a current line was added or modified
+    fn is_changed_line(&self, line: u32) -> bool {
+        self.added
+            || self.hunks.iter().any(|h| {
+                h.new_lines > 0 && (h.new_start..h.new_start + h.new_lines).contains(&line)
+            })
+    }
+}
+
+/// Parse a hunk header (`-a,b +c,d @@ ...`)
+fn parse_hunk(header: &str) -> Option<Hunk> {
+    let mut parts = header.split_whitespace();
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let range = |side: &str| -> Option<(u32, u32)> {
+        match side.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((side.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old)?;
+    let (new_start, new_lines) = range(new)?;
+    Some(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+    })
+}
+
+/// Parse a `--unified=0 --no-renames` diff into changes by path
+fn parse_diff(diff: &str) -> HashMap<String, FileChange> {
+    let mut changes: HashMap<String, FileChange> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut in_header = false;
+    let mut old_path: Option<&str> = None;
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            current = None;
+            in_header = true;
+            old_path = None;
+        } else if let Some(path) = line.strip_prefix("--- ").filter(|_| in_header) {
+            old_path = path.strip_prefix("a/");
+        } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| in_header) {
+            let new_path = path.strip_prefix("b/");
+            let Some(path) = new_path.or(old_path) else {
+                continue;
+            };
+            changes.insert(
+                path.to_string(),
+                FileChange {
+                    added: old_path.is_none(),
+                    deleted: new_path.is_none(),
+                    hunks: Vec::new(),
+                },
+            );
+            current = Some(path.to_string());
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            in_header = false;
+            let change = current.as_ref().and_then(|path| changes.get_mut(path));
+            if let (Some(change), Some(hunk)) = (change, parse_hunk(header)) {
+                change.hunks.push(hunk);
+            }
+        }
+    }
+
+    changes
+}
+
+/// Files changed in `working_dir` since `commit`, including uncommitted and
+/// untracked files, keyed by path relative to `working_dir`
+fn changed_files(working_dir: &Path, commit: &str) -> Result<HashMap<String, FileChange>, String> {
+    // Explicit prefixes, since `diff.noprefix` and `diff.mnemonicPrefix`
+    // change the `--- a/` and `+++ b/` lines this parses
+    let diff = run_git(
+        working_dir,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--unified=0",
+            "--no-renames",
+            "--no-color",
+            "--no-ext-diff",
+            "--relative",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            commit,
+            "--",
+        ],
+    )
+    .map_err(|e| format!("Failed to diff against {}: {}", commit, e))?;
+    let mut changes = parse_diff(&diff);
+
+    let untracked = run_git(
+        working_dir,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+        ],
+    )?;
+    for path in untracked.lines().filter(|p| !p.is_empty()) {
+        changes.insert(path.to_string(), FileChange::added());
+    }
+    Ok(changes)
+}
+
+/// The commit to compare against: the merge base of `HEAD` and `base`, or
+/// `base` itself when the histories are unrelated
+fn resolve_base_commit(working_dir: &Path, base: &str) -> Result<String, String> {
+    run_git(working_dir, &["merge-base", "HEAD", base])
+        .or_else(|_| {
+            run_git(
+                working_dir,
+                &["rev-parse", "--verify", &format!("{}^{{commit}}", base)],
+            )
+        })
+        .map(|commit| commit.trim().to_string())
+        .map_err(|e| format!("Failed to resolve base {}: {}", base, e))
+}
+
+/// Path of `path` relative to `root`, with `/` separators
+fn relative_path(path: &Path, roots: &[PathBuf]) -> Option<String> {
+    roots
+        .iter()
+        .find_map(|root| path.strip_prefix(root).ok())
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Key diagnostics by path relative to `root`, rewriting their paths to the
+/// same file under `working_dir`. Diagnostics outside `root` are dropped.
+fn snapshot(
+    diagnostics: LaneDiagnostics,
+    root: &Path,
+    working_dir: &Path,
+) -> HashMap<String, Vec<Diagnostic>> {
+    let mut roots = vec![root.to_path_buf()];
+    if let Ok(canonical) = root.canonicalize() {
+        roots.push(canonical);
+    }
+
+    let mut files = HashMap::new();
+    for file in diagnostics.files {
+        let Some(relative) = relative_path(&file.path, &roots) else {
+            continue;
+        };
+        let path = working_dir.join(&relative);
+        let diagnostics = file
+            .diagnostics
+            .into_iter()
+            .map(|d| Diagnostic {
+                path: path.clone(),
+                ..d
+            })
+            .collect();
+        files.insert(relative, diagnostics);
+    }
+    files
+}
+
+/// Open files in the lane's servers so that servers which only analyze open
+/// documents report on them; documents that are already open are updated to
+/// their contents on disk. Returns the files that were considered.
+fn open_files<'a>(
+    lsp: &LspManager,
+    lane_id: &str,
+    root: &Path,
+    paths: impl Iterator<Item = &'a String>,
+) -> HashSet<String> {
+    let mut opened = HashSet::new();
+    for relative in paths.take(MAX_OPENED_FILES) {
+        opened.insert(relative.clone());
+        let path = root.join(relative);
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let result = if lsp.is_document_open(lane_id, &path) {
+            lsp.change_document(lane_id, &path, text)
+        } else {
+            lsp.open_document(lane_id, &path, text)
+        };
+        if let Err(e) = result {
+            tracing::debug!("Failed to open {} for diagnostics: {}", path.display(), e);
+        }
+    }
+    opened
+}
+
+/// Take a lane's cached baseline out of `cache` if it can be reused: it was
+/// captured at `commit` and covers every changed file that existed there.
+/// A baseline that cannot be reused is dropped.
+fn take_cached_baseline(
+    cache: &mut HashMap<String, Baseline>,
+    lane_id: &str,
+    commit: &str,
+    changes: &HashMap<String, FileChange>,
+    refresh: bool,
+) -> Option<Baseline> {
+    let baseline = cache.remove(lane_id)?;
+    let reusable = !refresh
+        && baseline.commit == commit
+        && changes
+            .iter()
+            .all(|(path, c)| c.added || baseline.opened.contains(path));
+    reusable.then_some(baseline)
+}
+
+/// Check `commit` out into a temporary worktree and collect the diagnostics
+/// the lane's servers report there
+async fn capture_baseline(
+    lsp: &LspManager,
+    lane_id: &str,
+    working_dir: &Path,
+    commit: &str,
+    servers: &[String],
+    changes: &HashMap<String, FileChange>,
+) -> Result<Baseline, String> {
+    let prefix = run_git(working_dir, &["rev-parse", "--show-prefix"])?;
+    let worktree = codelane_core::paths::baselines_dir()
+        .map_err(|e| format!("Failed to create baseline directory: {}", e))?
+        .join(uuid::Uuid::new_v4().simple().to_string());
+    let worktree_str = worktree.to_string_lossy().to_string();
+    run_git(
+        working_dir,
+        &["worktree", "add", "--detach", &worktree_str, commit],
+    )
+    .map_err(|e| format!("Failed to check out {}: {}", commit, e))?;
+    let root = worktree.join(prefix.trim());
+    let baseline_lane = format!("{}{}", lane_id, BASELINE_LANE_SUFFIX);
+
+    tracing::info!(
+        "Capturing diagnostics baseline for lane {} at {}",
+        lane_id,
+        commit
+    );
+    let captured = async {
+        lsp.start_lane(&baseline_lane, &root, servers)
+            .map_err(|e| format!("Failed to start language servers: {}", e))?;
+        let existing = changes.iter().filter(|(_, c)| !c.added).map(|(p, _)| p);
+        let opened = open_files(lsp, &baseline_lane, &root, existing);
+        let complete = lsp
+            .wait_for_diagnostics(&baseline_lane, QUIET_PERIOD, SETTLE_TIMEOUT)
+            .await;
+        Ok::<_, String>(Baseline {
+            commit: commit.to_string(),
+            opened,
+            diagnostics: snapshot(lsp.diagnostics(&baseline_lane), &root, working_dir),
+            complete,
+        })
+    }
+    .await;
+
+    lsp.stop_lane(&baseline_lane).await;
+    if let Err(e) = run_git(
+        working_dir,
+        &["worktree", "remove", "--force", &worktree_str],
+    ) {
+        tracing::warn!("Failed to remove baseline worktree {}: {}", worktree_str, e);
+        let _ = fs::remove_dir_all(&worktree);
+        let _ = run_git(working_dir, &["worktree", "prune"]);
+    }
+    captured
+}
+
+/// Match one file's diagnostics across versions
+///
+/// A base diagnostic matches a current one with the same severity, message,
+/// source and code on the line the diff moved it to. Diagnostics left over
+/// are then matched by content alone, which covers code that moved within an
+/// edited region.
+fn file_delta(
+    path: &str,
+    base: &[Diagnostic],
+    current: &[Diagnostic],
+    change: Option<&FileChange>,
+) -> (FileDiagnosticsDelta, usize) {
+    let key = |d: &Diagnostic| {
+        (
+            d.severity,
+            d.message.clone(),
+            d.source.clone(),
+            d.code.clone(),
+        )
+    };
+    let mut base_matched = vec![false; base.len()];
+    let mut current_matched = vec![false; current.len()];
+
+    for (i, b) in base.iter().enumerate() {
+        let line = match change {
+            Some(change) => change.old_to_new(b.start_line),
+            None => Some(b.start_line),
+        };
+        let Some(line) = line else {
+            continue;
+        };
+        let found = current
+            .iter()
+            .enumerate()
+            .position(|(j, c)| !current_matched[j] && c.start_line == line && key(c) == key(b));
+        if let Some(j) = found {
+            base_matched[i] = true;
+            current_matched[j] = true;
+        }
+    }
+    for (i, b) in base.iter().enumerate() {
+        if base_matched[i] {
+            continue;
+        }
+        let found = current
+            .iter()
+            .enumerate()
+            .position(|(j, c)| !current_matched[j] && key(c) == key(b));
+        if let Some(j) = found {
+            base_matched[i] = true;
+            current_matched[j] = true;
+        }
+    }
+
+    let introduced: Vec<Diagnostic> = current
+        .iter()
+        .zip(&current_matched)
+        .filter(|(_, matched)| !**matched)
+        .map(|(d, _)| d.clone())
+        .collect();
+    let fixed: Vec<Diagnostic> = base
+        .iter()
+        .zip(&base_matched)
+        .filter(|(_, matched)| !**matched)
+        .map(|(d, _)| d.clone())
+        .collect();
+    let on_changed_lines = introduced
+        .iter()
+        .filter(|d| change.is_some_and(|c| c.is_changed_line(d.start_line)))
+        .cloned()
+        .collect();
+    let unchanged = current.len() - introduced.len();
+
+    let delta = FileDiagnosticsDelta {
+        path: path.to_string(),
+        introduced,
+        fixed,
+        on_changed_lines,
+    };
+    (delta, unchanged)
+}
+
+/// Compare baseline and current diagnostics of every file
+fn compute_delta(
+    base: &HashMap<String, Vec<Diagnostic>>,
+    current: &HashMap<String, Vec<Diagnostic>>,
+    changes: &HashMap<String, FileChange>,
+) -> (Vec<FileDiagnosticsDelta>, usize) {
+    let paths: BTreeSet<&String> = base.keys().chain(current.keys()).collect();
+    let mut files = Vec::new();
+    let mut unchanged = 0;
+    for path in paths {
+        let (delta, same) = file_delta(
+            path,
+            base.get(path).map_or(&[], Vec::as_slice),
+            current.get(path).map_or(&[], Vec::as_slice),
+            changes.get(path),
+        );
+        unchanged += same;
+        if !delta.introduced.is_empty() || !delta.fixed.is_empty() {
+            files.push(delta);
+        }
+    }
+    (files, unchanged)
+}
+
+/// Compute the diagnostics a lane's changes introduced or fixed
+///
+/// Starts the lane's language servers if needed. The baseline is captured on
+/// first use (which can take a while for large projects) and reused until the
+/// base commit or the set of changed files changes.
+///
+/// # Arguments
+/// * `lane_id` - Lane to compare
+/// * `base` - Branch or revision to compare against (default: the
+///   repository's default branch); its merge base with `HEAD` is used
+/// * `refresh_baseline` - Recapture the baseline even if one is cached
+///
+/// # Returns
+/// Introduced and fixed diagnostics by file, with those on changed lines
+/// listed separately for the review diff
+#[tauri::command]
+pub async fn lsp_diagnostics_delta(
+    lane_id: String,
+    base: Option<String>,
+    refresh_baseline: Option<bool>,
+    lanes: State<'_, LaneState>,
+    lsp: State<'_, LspState>,
+    baselines: State<'_, DiagnosticsBaselineState>,
+) -> Result<DiagnosticsDelta, String> {
+    let lane = {
+        let lanes = lanes.lanes.lock().unwrap();
+        lanes
+            .get(&lane_id)
+            .cloned()
+            .ok_or_else(|| format!("Lane not found: {}", lane_id))?
+    };
+    let working_dir = PathBuf::from(&lane.working_dir);
+    let servers = lane_servers(&lane.config.lsp_servers, &working_dir);
+    if servers.is_empty() {
+        return Err(format!(
+            "No language servers configured for lane {}",
+            lane_id
+        ));
+    }
+
+    let base = match base {
+        Some(base) => base,
+        None => crate::git::git_default_branch(lane.working_dir.clone()).await?,
+    };
+    let commit = resolve_base_commit(&working_dir, &base)?;
+    let changes = changed_files(&working_dir, &commit)?;
+    let manager = &lsp.manager;
+
+    let cached = take_cached_baseline(
+        &mut baselines.baselines.lock().unwrap(),
+        &lane_id,
+        &commit,
+        &changes,
+        refresh_baseline.unwrap_or(false),
+    );
+    let baseline = match cached {
+        Some(baseline) => baseline,
+        None => {
+            capture_baseline(manager, &lane_id, &working_dir, &commit, &servers, &changes).await?
+        }
+    };
+
+    manager
+        .start_lane(&lane_id, &working_dir, &servers)
+        .map_err(|e| format!("Failed to start language servers: {}", e))?;
+    let current_files = changes.iter().filter(|(_, c)| !c.deleted).map(|(p, _)| p);
+    open_files(manager, &lane_id, &working_dir, current_files);
+    let settled = manager
+        .wait_for_diagnostics(&lane_id, QUIET_PERIOD, SETTLE_TIMEOUT)
+        .await;
+    let current = snapshot(manager.diagnostics(&lane_id), &working_dir, &working_dir);
+
+    let (files, unchanged) = compute_delta(&baseline.diagnostics, &current, &changes);
+    let delta = DiagnosticsDelta {
+        lane_id: lane_id.clone(),
+        base_commit: commit,
+        introduced: files.iter().flat_map(|f| &f.introduced).collect(),
+        fixed: files.iter().flat_map(|f| &f.fixed).collect(),
+        unchanged,
+        complete: settled && baseline.complete,
+        files,
+    };
+
+    baselines
+        .baselines
+        .lock()
+        .unwrap()
+        .insert(lane_id, baseline);
+    Ok(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codelane_lsp::DiagnosticSeverity;
+    use std::process::Command;
+
+    fn diagnostic(line: u32, message: &str) -> Diagnostic {
+        Diagnostic {
+            path: PathBuf::from("/lane/src/lib.rs"),
+            start_line: line,
+            start_column: 1,
+            end_line: line,
+            end_column: 5,
+            severity: DiagnosticSeverity::Error,
+            message: message.to_string(),
+            source: Some("rustc".to_string()),
+            code: None,
+        }
+    }
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<(u32, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.start_line, d.message.as_str()))
+            .collect()
+    }
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -2,0 +3,2 @@ fn main() {
++    let a = 1;
++    let b = 2;
+@@ -10 +12 @@ fn other() {
+--- old line that looks like a header
++++ new line that looks like a header
+@@ -20,3 +21,0 @@ fn gone() {
+-x
+-y
+-z
+diff --git a/new.rs b/new.rs
+new file mode 100644
+--- /dev/null
++++ b/new.rs
+@@ -0,0 +1,2 @@
++fn new() {}
++
+diff --git a/old.rs b/old.rs
+deleted file mode 100644
+--- a/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn old() {}
+diff --git a/image.png b/image.png
+Binary files a/image.png and b/image.png differ
+";
+
+    #[test]
+    fn test_parse_diff() {
+        let changes = parse_diff(DIFF);
+        assert_eq!(changes.len(), 3);
+
+        let lib = &changes["src/lib.rs"];
+        assert!(!lib.added && !lib.deleted);
+        assert_eq!(
+            lib.hunks,
+            vec![
+                Hunk {
+                    old_start: 2,
+                    old_lines: 0,
+                    new_start: 3,
+                    new_lines: 2
+                },
+                Hunk {
+                    old_start: 10,
+                    old_lines: 1,
+                    new_start: 12,
+                    new_lines: 1
+                },
+                Hunk {
+                    old_start: 20,
+                    old_lines: 3,
+                    new_start: 21,
+                    new_lines: 0
+                },
+            ]
+        );
+        assert!(changes["new.rs"].added);
+        assert!(changes["old.rs"].deleted);
+    }
+
+    #[test]
+    fn test_old_to_new() {
+        let lib = &parse_diff(DIFF)["src/lib.rs"];
+        assert_eq!(lib.old_to_new(1), Some(1));
+        assert_eq!(lib.old_to_new(2), Some(2));
+        assert_eq!(lib.old_to_new(3), Some(5));
+        assert_eq!(lib.old_to_new(9), Some(11));
+        assert_eq!(lib.old_to_new(10), None);
+        assert_eq!(lib.old_to_new(11), Some(13));
+        assert_eq!(lib.old_to_new(20), None);
+        assert_eq!(lib.old_to_new(22), None);
+        assert_eq!(lib.old_to_new(23), Some(22));
+
+        assert_eq!(FileChange::added().old_to_new(1), None);
+    }
+
+    #[test]
+    fn test_is_changed_line() {
+        let lib = &parse_diff(DIFF)["src/lib.rs"];
+        let changed: Vec<u32> = (1..=25).filter(|l| lib.is_changed_line(*l)).collect();
+        assert_eq!(changed, vec![3, 4, 12]);
+        assert!(FileChange::added().is_changed_line(1));
+    }
+
+    #[test]
+    fn test_file_delta_follows_moved_lines() {
+        let change = &parse_diff(DIFF)["src/lib.rs"];
+        let base = vec![
+            diagnostic(5, "unused variable"),
+            diagnostic(10, "type mismatch"),
+            diagnostic(21, "missing semicolon"),
+        ];
+        let current = vec![
+            // Moved down by the insertion
+            diagnostic(7, "unused variable"),
+            // Still there after the line was edited
+            diagnostic(12, "type mismatch"),
+            // New, on an added line
+            diagnostic(4, "cannot find value"),
+            // New, on an untouched line
+            diagnostic(30, "cannot find value"),
+        ];
+
+        let (delta, unchanged) = file_delta("src/lib.rs", &base, &current, Some(change));
+        assert_eq!(unchanged, 2);
+        assert_eq!(
+            messages(&delta.introduced),
+            vec![(4, "cannot find value"), (30, "cannot find value")]
+        );
+        assert_eq!(messages(&delta.fixed), vec![(21, "missing semicolon")]);
+        assert_eq!(
+            messages(&delta.on_changed_lines),
+            vec![(4, "cannot find value")]
+        );
+    }
+
+    #[test]
+    fn test_file_delta_duplicates_and_severity() {
+        let base = vec![diagnostic(1, "dup"), diagnostic(2, "dup")];
+        let mut warning = diagnostic(3, "dup");
+        warning.severity = DiagnosticSeverity::Warning;
+        let current = vec![
+            diagnostic(1, "dup"),
+            diagnostic(2, "dup"),
+            diagnostic(5, "dup"),
+            warning,
+        ];
+
+        // Unchanged file: identical lines match, extras are introduced
+        let (delta, unchanged) = file_delta("a.rs", &base, &current, None);
+        assert_eq!(unchanged, 2);
+        assert_eq!(messages(&delta.introduced), vec![(5, "dup"), (3, "dup")]);
+        assert!(delta.fixed.is_empty());
+        assert!(delta.on_changed_lines.is_empty());
+    }
+
+    #[test]
+    fn test_take_cached_baseline() {
+        let baseline = |commit: &str| Baseline {
+            commit: commit.to_string(),
+            opened: HashSet::from(["src/lib.rs".to_string()]),
+            diagnostics: HashMap::new(),
+            complete: true,
+        };
+        let changes = parse_diff(DIFF);
+        let lib_only: HashMap<String, FileChange> = changes
+            .iter()
+            .filter(|(path, c)| *path == "src/lib.rs" || c.added)
+            .map(|(path, c)| (path.clone(), c.clone()))
+            .collect();
+        let take = |cached: Baseline,
+                    commit: &str,
+                    changes: &HashMap<String, FileChange>,
+                    refresh: bool| {
+            let mut cache = HashMap::from([("lane".to_string(), cached)]);
+            let taken = take_cached_baseline(&mut cache, "lane", commit, changes, refresh);
+            assert!(cache.is_empty());
+            taken.is_some()
+        };
+
+        // Reused while the base commit and changed files are covered
+        assert!(take(baseline("abc"), "abc", &lib_only, false));
+        assert!(!take(baseline("abc"), "abc", &lib_only, true));
+        assert!(!take(baseline("abc"), "def", &lib_only, false));
+        // old.rs changed since, and was not opened when capturing
+        assert!(!take(baseline("abc"), "abc", &changes, false));
+        assert!(
+            take_cached_baseline(&mut HashMap::new(), "lane", "abc", &lib_only, false).is_none()
+        );
+    }
+
+    #[test]
+    fn test_compute_delta() {
+        let changes = parse_diff(DIFF);
+        let base = HashMap::from([
+            ("old.rs".to_string(), vec![diagnostic(1, "old error")]),
+            ("clean.rs".to_string(), vec![diagnostic(3, "still here")]),
+        ]);
+        let current = HashMap::from([
+            ("new.rs".to_string(), vec![diagnostic(1, "new error")]),
+            ("clean.rs".to_string(), vec![diagnostic(3, "still here")]),
+        ]);
+
+        let (files, unchanged) = compute_delta(&base, &current, &changes);
+        assert_eq!(unchanged, 1);
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["new.rs", "old.rs"]);
+        assert_eq!(files[0].on_changed_lines.len(), 1);
+        assert_eq!(files[1].fixed.len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_relative_paths() {
+        let diagnostics = LaneDiagnostics {
+            lane_id: "lane#baseline".to_string(),
+            counts: DiagnosticCounts::default(),
+            files: vec![
+                codelane_lsp::FileDiagnostics {
+                    path: PathBuf::from("/baseline/src/lib.rs"),
+                    counts: DiagnosticCounts::default(),
+                    diagnostics: vec![diagnostic(1, "error")],
+                },
+                codelane_lsp::FileDiagnostics {
+                    path: PathBuf::from("/elsewhere/lib.rs"),
+                    counts: DiagnosticCounts::default(),
+                    diagnostics: vec![diagnostic(1, "error")],
+                },
+            ],
+        };
+
+        let files = snapshot(diagnostics, Path::new("/baseline"), Path::new("/lane"));
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files["src/lib.rs"][0].path,
+            PathBuf::from("/lane/src/lib.rs")
+        );
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .expect("Failed to run git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_changed_files_against_base() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init", "-q", "-b", "main"]);
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "user.name", "Test"]);
+        fs::write(dir.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        fs::write(dir.join("b.txt"), "gone\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-q", "-m", "base"]);
+
+        git(dir, &["checkout", "-q", "-b", "lane"]);
+        fs::write(dir.join("a.txt"), "zero\none\ntwo\nthree\n").unwrap();
+        git(dir, &["commit", "-q", "-am", "lane work"]);
+        fs::remove_file(dir.join("b.txt")).unwrap();
+        fs::write(dir.join("c.txt"), "untracked\n").unwrap();
+
+        let commit = resolve_base_commit(dir, "main").unwrap();
+        let changes = changed_files(dir, &commit).unwrap();
+        assert_eq!(changes["a.txt"].old_to_new(1), Some(2));
+        assert!(changes["b.txt"].deleted);
+        assert!(changes["c.txt"].added);
+        assert_eq!(changes.len(), 3);
+
+        // Diff prefix settings must not change which lines are found
+        for setting in ["diff.noprefix", "diff.mnemonicPrefix"] {
+            git(dir, &["config", setting, "true"]);
+            let changes = changed_files(dir, &commit).unwrap();
+            assert_eq!(changes["a.txt"].old_to_new(1), Some(2), "with {}", setting);
+            assert!(changes["b.txt"].deleted, "with {}", setting);
+            git(dir, &["config", "--unset", setting]);
+        }
+
+        assert!(resolve_base_commit(dir, "no-such-branch").is_err());
+    }
+}
@@ -0,0 +1,675 @@
+//! Fuzzy File Finder
+//!
+//! Backend for quick-open. The files below a root are listed once with the
+//! `ignore` walker (same rules as project search), cached, and kept current
+//! from `watch_path` events. Queries are ranked by a fuzzy subsequence scorer
+//! that favours matches in the file name, at word boundaries and in runs, with
+//! a boost for recently opened files.
+
+use ignore::WalkState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::time::Instant;
+use tauri::State;
+
+use crate::fs::canonical_event_path;
+use crate::search::build_walker;
+
+/// Default number of results returned by `find_files`
+const DEFAULT_LIMIT: u32 = 50;
+
+/// Number of roots whose file lists are kept in memory
+const MAX_CACHED_ROOTS: usize = 8;
+
+/// Number of recently opened files that receive a ranking boost
+const MAX_RECENT_FILES: usize = 50;
+
+/// Boost for the most recently opened file, decreasing with age
+const RECENT_BONUS: i64 = 120;
+
+// Scoring weights
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 10;
+const BONUS_CAMEL: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 12;
+const BONUS_FILE_NAME: i64 = 6;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP: i64 = 1;
+
+/// A file matching a quick-open query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMatch {
+    /// Absolute path
+    pub path: String,
+    /// Path relative to the searched root
+    pub relative_path: String,
+    pub name: String,
+    pub score: i64,
+    /// Character indices in `relative_path` that matched the query
+    pub positions: Vec<u32>,
+}
+
+/// Cached file list of one root
+#[derive(Debug)]
+struct FileList {
+    /// Canonical root directory
+    root: PathBuf,
+    /// Paths relative to `root`
+    files: BTreeSet<String>,
+    /// Rebuild on next use (e.g. after a `.gitignore` changed)
+    stale: bool,
+    last_used: Instant,
+}
+
+impl FileList {
+    fn build(root: PathBuf) -> Self {
+        let files = walk_files(&root, &root).into_iter().collect();
+        Self {
+            root,
+            files,
+            stale: false,
+            last_used: Instant::now(),
+        }
+    }
+
+    fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        if relative.as_os_str().is_empty() {
+            return None;
+        }
+        Some(relative.to_string_lossy().into_owned())
+    }
+
+    /// Apply a watch event for a canonical path below the root
+    fn apply(&mut self, path: &Path, change: ListChange) {
+        if path.file_name().is_some_and(|name| name == ".gitignore") {
+            self.stale = true;
+        }
+
+        match change {
+            ListChange::Unchanged => {}
+            ListChange::Added(files) => self.files.extend(files),
+            ListChange::Removed(relative) => {
+                // Drop the file or everything below the directory
+                self.files.remove(&relative);
+                let prefix = format!("{}{}", relative, std::path::MAIN_SEPARATOR);
+                let nested: Vec<String> = self
+                    .files
+                    .range(prefix.clone()..)
+                    .take_while(|f| f.starts_with(&prefix))
+                    .cloned()
+                    .collect();
+                for file in nested {
+                    self.files.remove(&file);
+                }
+            }
+        }
+    }
+}
+
+/// What a watch event changes in a file list
+///
+/// Worked out from the disk before the list is locked, since adding a
+/// directory walks it.
+enum ListChange {
+    /// Already listed, or nothing to add
+    Unchanged,
+    /// Created or moved in: everything the walker would list at the path
+    Added(Vec<String>),
+    /// Deleted or moved away: the path relative to the root
+    Removed(String),
+}
+
+impl ListChange {
+    /// Inspect `path` below `root`; `listed` is whether the list has it
+    fn inspect(root: &Path, path: &Path, relative: String, listed: bool) -> Self {
+        if path.is_dir() {
+            ListChange::Added(walk_files(root, path))
+        } else if path.is_file() {
+            if listed {
+                ListChange::Unchanged
+            } else {
+                ListChange::Added(walk_files(root, path))
+            }
+        } else {
+            ListChange::Removed(relative)
+        }
+    }
+}
+
+/// List the files at or below `path` that a walk of `root` would include
+///
+/// The walk starts at `root` but only descends into the directories leading
+/// to `path`, so every ignore rule above it applies; this also answers whether
+/// a single new file or directory is ignored.
+fn walk_files(root: &Path, path: &Path) -> Vec<String> {
+    let target = path.to_path_buf();
+
+    let mut builder = build_walker(&root.to_string_lossy(), None, None);
+    builder.filter_entry(move |entry| {
+        entry.file_name() != ".git"
+            && (target.starts_with(entry.path()) || entry.path().starts_with(&target))
+    });
+    let walker = builder
+        .threads(
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+        )
+        .build_parallel();
+
+    let (tx, rx) = mpsc::channel::<String>();
+    walker.run(|| {
+        let tx = tx.clone();
+        Box::new(move |entry| {
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return WalkState::Continue;
+            }
+            if let Ok(relative) = entry.path().strip_prefix(root) {
+                if tx.send(relative.to_string_lossy().into_owned()).is_err() {
+                    return WalkState::Quit;
+                }
+            }
+            WalkState::Continue
+        })
+    });
+    drop(tx);
+    rx.into_iter().collect()
+}
+
+/// State for cached file lists and recently opened files
+pub struct FileFinderState {
+    lists: Mutex<HashMap<PathBuf, Arc<RwLock<FileList>>>>,
+    /// Canonical paths, most recent first
+    recent: Mutex<VecDeque<PathBuf>>,
+}
+
+impl FileFinderState {
+    pub fn new() -> Self {
+        Self {
+            lists: Mutex::new(HashMap::new()),
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// The cached list for a root, if any
+    fn cached(&self, root: &Path) -> Result<Option<Arc<RwLock<FileList>>>, String> {
+        let lists = self
+            .lists
+            .lock()
+            .map_err(|e| format!("Failed to lock file finder state: {}", e))?;
+        Ok(lists.get(root).cloned())
+    }
+
+    fn insert(&self, list: FileList) -> Result<Arc<RwLock<FileList>>, String> {
+        let mut lists = self
+            .lists
+            .lock()
+            .map_err(|e| format!("Failed to lock file finder state: {}", e))?;
+
+        // Evict the least recently used root
+        if lists.len() >= MAX_CACHED_ROOTS && !lists.contains_key(&list.root) {
+            let oldest = lists
+                .iter()
+                .min_by_key(|(_, l)| l.read().map(|l| l.last_used).ok())
+                .map(|(root, _)| root.clone());
+            if let Some(oldest) = oldest {
+                lists.remove(&oldest);
+            }
+        }
+
+        let root = list.root.clone();
+        let list = Arc::new(RwLock::new(list));
+        lists.insert(root, list.clone());
+        Ok(list)
+    }
+
+    /// Update cached file lists from watch events
+    ///
+    /// Walks run with no lock held; each list is locked only to apply the
+    /// result, so quick open is not blocked while the watcher walks.
+    pub fn apply_changes(&self, paths: &[PathBuf]) {
+        let lists: Vec<Arc<RwLock<FileList>>> = match self.lists.lock() {
+            Ok(lists) => lists.values().cloned().collect(),
+            Err(_) => return,
+        };
+        if lists.is_empty() {
+            return;
+        }
+        for path in paths.iter().filter_map(|p| canonical_event_path(p)) {
+            // Changes inside .git never affect the list
+            if path.components().any(|c| c.as_os_str() == ".git") {
+                continue;
+            }
+            for list in &lists {
+                let (root, relative, listed) = {
+                    let list = list.read().unwrap_or_else(|e| e.into_inner());
+                    let Some(relative) = list.relative(&path) else {
+                        continue;
+                    };
+                    let listed = list.files.contains(&relative);
+                    (list.root.clone(), relative, listed)
+                };
+                let change = ListChange::inspect(&root, &path, relative, listed);
+                list.write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .apply(&path, change);
+            }
+        }
+    }
+
+    /// Drop cached lists that may contain paths under `root`, after changes
+    /// too numerous to apply one by one
+    pub fn invalidate(&self, root: &Path) {
+        if let Ok(mut lists) = self.lists.lock() {
+            lists.retain(|list_root, _| {
+                !list_root.starts_with(root) && !root.starts_with(list_root)
+            });
+        }
+    }
+
+    fn record_opened(&self, path: PathBuf) -> Result<(), String> {
+        let mut recent = self
+            .recent
+            .lock()
+            .map_err(|e| format!("Failed to lock file finder state: {}", e))?;
+        recent.retain(|p| p != &path);
+        recent.push_front(path);
+        recent.truncate(MAX_RECENT_FILES);
+        Ok(())
+    }
+
+    /// Recency boost by path relative to `root`
+    fn recent_boosts(&self, root: &Path) -> HashMap<String, i64> {
+        let Ok(recent) = self.recent.lock() else {
+            return HashMap::new();
+        };
+        recent
+            .iter()
+            .enumerate()
+            .filter_map(|(rank, path)| {
+                let relative = path.strip_prefix(root).ok()?;
+                let boost =
+                    RECENT_BONUS * (MAX_RECENT_FILES - rank) as i64 / MAX_RECENT_FILES as i64;
+                Some((relative.to_string_lossy().into_owned(), boost))
+            })
+            .collect()
+    }
+}
+
+impl Default for FileFinderState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fuzzy-match `query` against a path
+///
+/// Returns the score and the matched character positions, or `None` if the
+/// query is not a subsequence of the path. Matching is case-insensitive unless
+/// the query contains an uppercase letter (smart case).
+fn fuzzy_match(query: &[char], candidate: &[char]) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let case_sensitive = query.iter().any(|c| c.is_uppercase());
+    let name_start = candidate
+        .iter()
+        .rposition(|&c| c == '/' || c == '\\')
+        .map_or(0, |i| i + 1);
+
+    // Prefer a match inside the file name, then anywhere in the path
+    let in_name = match_positions(query, candidate, name_start, case_sensitive);
+    let anywhere = match_positions(query, candidate, 0, case_sensitive)?;
+
+    let best = [in_name, Some(anywhere)]
+        .into_iter()
+        .flatten()
+        .map(|positions| {
+            (
+                score_positions(candidate, &positions, name_start),
+                positions,
+            )
+        })
+        .max_by_key(|(score, _)| *score)?;
+
+    // Shorter paths win ties
+    Some((best.0 - candidate.len() as i64 / 8, best.1))
+}
+
+fn chars_eq(a: char, b: char, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        a == b
+    } else {
+        a == b || a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+/// Positions of the shortest match of `query` in `candidate[from..]`
+///
+/// A forward pass finds where the first match ends, a backward pass from
+/// there finds the latest start, and the final forward pass from that start
+/// picks the positions.
+fn match_positions(
+    query: &[char],
+    candidate: &[char],
+    from: usize,
+    case_sensitive: bool,
+) -> Option<Vec<usize>> {
+    let mut qi = 0;
+    let mut end = None;
+    for (i, &c) in candidate.iter().enumerate().skip(from) {
+        if chars_eq(query[qi], c, case_sensitive) {
+            qi += 1;
+            if qi == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut qi = query.len();
+    let mut start = end;
+    for i in (from..=end).rev() {
+        if chars_eq(query[qi - 1], candidate[i], case_sensitive) {
+            qi -= 1;
+            if qi == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut qi = 0;
+    for (i, &c) in candidate.iter().enumerate().take(end + 1).skip(start) {
+        if qi < query.len() && chars_eq(query[qi], c, case_sensitive) {
+            positions.push(i);
+            qi += 1;
+        }
+    }
+    Some(positions)
+}
+
+fn score_positions(candidate: &[char], positions: &[usize], name_start: usize) -> i64 {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+
+    for &pos in positions {
+        score += SCORE_MATCH;
+
+        let before = pos.checked_sub(1).map(|i| candidate[i]);
+        match before {
+            None | Some('/' | '\\' | '_' | '-' | '.' | ' ') => score += BONUS_BOUNDARY,
+            Some(b) if b.is_lowercase() && candidate[pos].is_uppercase() => score += BONUS_CAMEL,
+            _ => {}
+        }
+        if pos >= name_start {
+            score += BONUS_FILE_NAME;
+        }
+
+        match previous {
+            Some(p) if pos == p + 1 => score += BONUS_CONSECUTIVE,
+            Some(p) => score -= PENALTY_GAP_START + PENALTY_GAP * (pos - p - 2) as i64,
+            None => {}
+        }
+        previous = Some(pos);
+    }
+
+    score
+}
+
+/// Rank the files of a list against a query
+fn rank(
+    list: &FileList,
+    query: &str,
+    recent: &HashMap<String, i64>,
+    limit: usize,
+) -> Vec<FileMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut matches: Vec<FileMatch> = list
+        .files
+        .iter()
+        .filter_map(|relative| {
+            let chars: Vec<char> = relative.chars().collect();
+            let (score, positions) = fuzzy_match(&query, &chars)?;
+            let boost = recent.get(relative).copied().unwrap_or(0);
+            // An empty query lists recently opened files only
+            if query.is_empty() && boost == 0 {
+                return None;
+            }
+            Some(FileMatch {
+                path: list.root.join(relative).to_string_lossy().into_owned(),
+                relative_path: relative.clone(),
+                name: Path::new(relative)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                score: score + boost,
+                positions: positions.into_iter().map(|p| p as u32).collect(),
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// Find files by fuzzy path matching
+///
+/// The first call for a root walks it (respecting .gitignore) and caches the
+/// file list; later calls reuse the list, which watch events keep current.
+///
+/// # Arguments
+/// * `root_path` - Directory to search, typically a lane's working directory
+/// * `query` - Fuzzy query; uppercase letters make it case sensitive. Empty
+///   returns recently opened files
+/// * `limit` - Maximum number of results (default: 50)
+///
+/// # Returns
+/// Matching files, best first
+#[tauri::command]
+pub async fn find_files(
+    state: State<'_, FileFinderState>,
+    root_path: String,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<FileMatch>, String> {
+    let root = Path::new(&root_path)
+        .canonicalize()
+        .map_err(|e| format!("Invalid path {}: {}", root_path, e))?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root_path));
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let recent = state.recent_boosts(&root);
+
+    let list = match state.cached(&root)? {
+        Some(list) if !list.read().is_ok_and(|l| l.stale) => list,
+        _ => {
+            tracing::info!("Listing files for quick open in {:?}", root);
+            let build_root = root.clone();
+            let list = tokio::task::spawn_blocking(move || FileList::build(build_root))
+                .await
+                .map_err(|e| format!("Failed to list files: {}", e))?;
+            state.insert(list)?
+        }
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let mut list = list.write().unwrap_or_else(|e| e.into_inner());
+        list.last_used = Instant::now();
+        rank(&list, &query, &recent, limit)
+    })
+    .await
+    .map_err(|e| format!("Failed to rank files: {}", e))
+}
+
+/// Record that a file was opened, boosting it in `find_files` results
+///
+/// # Arguments
+/// * `path` - Absolute path of the opened file
+#[tauri::command]
+pub async fn record_file_opened(
+    state: State<'_, FileFinderState>,
+    path: String,
+) -> Result<(), String> {
+    let path = Path::new(&path)
+        .canonicalize()
+        .map_err(|e| format!("Invalid path {}: {}", path, e))?;
+    state.record_opened(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn score(query: &str, candidate: &str) -> Option<i64> {
+        let query: Vec<char> = query.chars().collect();
+        let candidate: Vec<char> = candidate.chars().collect();
+        fuzzy_match(&query, &candidate).map(|(score, _)| score)
+    }
+
+    fn positions(query: &str, candidate: &str) -> Vec<usize> {
+        let query: Vec<char> = query.chars().collect();
+        let candidate: Vec<char> = candidate.chars().collect();
+        fuzzy_match(&query, &candidate).unwrap().1
+    }
+
+    #[test]
+    fn test_fuzzy_match_requires_subsequence() {
+        assert!(score("mnrs", "src/main.rs").is_some());
+        assert!(score("srm", "src/main.rs").is_some());
+        assert!(score("xyz", "src/main.rs").is_none());
+        assert!(score("sr/", "src/main.rs").is_some());
+    }
+
+    #[test]
+    fn test_smart_case() {
+        assert!(score("main", "src/Main.rs").is_some());
+        assert!(score("Main", "src/main.rs").is_none());
+        assert!(score("Main", "src/Main.rs").is_some());
+    }
+
+    #[test]
+    fn test_file_name_preferred_over_directories() {
+        // "lib" in the file name beats "lib" spread across directories
+        assert!(score("lib", "src/lib.rs") > score("lib", "lua/inner/bin/x.rs"));
+        assert!(score("search", "src/search.rs") > score("search", "search/src/other.rs"));
+        assert_eq!(positions("main", "main/src/main.rs"), vec![9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn test_boundaries_and_runs_rank_higher() {
+        assert!(score("fs", "src/file_search.rs") > score("fs", "src/offsets.rs"));
+        assert!(score("ss", "src/SearchState.ts") > score("ss", "src/session.ts"));
+        assert!(score("abc", "abc.rs") > score("abc", "a_b_c.rs"));
+    }
+
+    #[test]
+    fn test_shortest_window_is_used() {
+        // The match ending at the first complete occurrence starts as late as possible
+        assert_eq!(positions("ab", "a_xab"), vec![3, 4]);
+    }
+
+    fn tree() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        // .gitignore files only apply inside a git repository
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/components")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/components/Button.tsx"), "").unwrap();
+        fs::write(root.join("target/debug/main.d"), "").unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_file_list_respects_gitignore() {
+        let temp = tree();
+        let list = FileList::build(temp.path().canonicalize().unwrap());
+        let files: Vec<&str> = list.files.iter().map(String::as_str).collect();
+        assert_eq!(
+            files,
+            vec![".gitignore", "src/components/Button.tsx", "src/main.rs"]
+        );
+    }
+
+    fn apply_change(list: &mut FileList, path: &Path) {
+        if let Some(relative) = list.relative(path) {
+            let listed = list.files.contains(&relative);
+            let change = ListChange::inspect(&list.root, path, relative, listed);
+            list.apply(path, change);
+        }
+    }
+
+    #[test]
+    fn test_file_list_applies_changes() {
+        let temp = tree();
+        let root = temp.path().canonicalize().unwrap();
+        let mut list = FileList::build(root.clone());
+
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        apply_change(&mut list, &root.join("src/lib.rs"));
+        assert!(list.files.contains("src/lib.rs"));
+
+        // Ignored files stay out of the list
+        fs::write(root.join("target/debug/new.d"), "").unwrap();
+        apply_change(&mut list, &root.join("target/debug/new.d"));
+        assert!(!list.files.iter().any(|f| f.starts_with("target")));
+
+        fs::create_dir_all(root.join("docs/guide")).unwrap();
+        fs::write(root.join("docs/guide/intro.md"), "").unwrap();
+        apply_change(&mut list, &root.join("docs"));
+        assert!(list.files.contains("docs/guide/intro.md"));
+
+        fs::remove_dir_all(root.join("src")).unwrap();
+        apply_change(&mut list, &root.join("src"));
+        let files: Vec<&str> = list.files.iter().map(String::as_str).collect();
+        assert_eq!(files, vec![".gitignore", "docs/guide/intro.md"]);
+    }
+
+    #[test]
+    fn test_rank_with_recency() {
+        let temp = tree();
+        let root = temp.path().canonicalize().unwrap();
+        let list = FileList::build(root.clone());
+        let state = FileFinderState::new();
+
+        let results = rank(&list, "main", &state.recent_boosts(&root), 10);
+        assert_eq!(results[0].relative_path, "src/main.rs");
+        assert_eq!(results[0].name, "main.rs");
+        assert_eq!(results[0].positions, vec![4, 5, 6, 7]);
+
+        // Empty queries list recently opened files
+        assert!(rank(&list, "", &state.recent_boosts(&root), 10).is_empty());
+        state
+            .record_opened(root.join("src/components/Button.tsx"))
+            .unwrap();
+        let results = rank(&list, "", &state.recent_boosts(&root), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].relative_path, "src/components/Button.tsx");
+
+        // A recent file outranks a slightly better match
+        let plain = rank(&list, "sr", &HashMap::new(), 10);
+        let boosted = rank(&list, "sr", &state.recent_boosts(&root), 10);
+        assert_eq!(plain[0].relative_path, "src/main.rs");
+        assert_eq!(boosted[0].relative_path, "src/components/Button.tsx");
+    }
+}
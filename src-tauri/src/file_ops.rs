@@ -0,0 +1,944 @@
+//! File Operations
+//!
+//! Create, rename, move, copy and delete files and directories in a lane's
+//! working tree. Deletes move entries into the Codelane trash
+//! (`~/.codelane/<env>/trash/`) so they can be restored, and every operation is
+//! journaled so the most recent ones can be undone. Undo never destroys data:
+//! undoing a create or copy moves the new entry to the trash.
+//!
+//! # Events
+//!
+//! Each operation emits a `file-watch-batch` event (see [`FileWatchBatch`])
+//! with `watch_id: "file-ops"`, so views listening to file watchers refresh
+//! even when no watcher covers the path.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::file_watch::{FileWatchBatch, FILE_WATCH_BATCH_EVENT};
+use crate::fs::FileWatchEvent;
+
+/// Watch ID used for events emitted by file operations
+pub const FILE_OPS_WATCH_ID: &str = "file-ops";
+
+/// Number of operations kept for undo
+const MAX_JOURNAL_ENTRIES: usize = 50;
+
+/// Metadata file stored next to each trashed entry
+const TRASH_INFO_FILE: &str = "info.json";
+
+/// A changed path and the file watch event kind to report for it
+type PathChange = (PathBuf, &'static str);
+
+/// A completed file operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FileOperation {
+    Create { path: String, is_dir: bool },
+    Move { from: String, to: String },
+    Copy { from: String, to: String },
+    Delete { path: String, trash_id: String },
+}
+
+/// A journaled operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub operation: FileOperation,
+    pub timestamp: u64, // Unix timestamp
+}
+
+/// An entry in the Codelane trash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    pub is_dir: bool,
+    pub deleted_at: u64, // Unix timestamp
+}
+
+/// Managed state for the operation journal
+pub struct FileOpsState {
+    /// Most recent last
+    journal: Mutex<VecDeque<JournalEntry>>,
+    /// Held for the duration of an undo so two undos never pick the same entry
+    undoing: Mutex<()>,
+}
+
+impl FileOpsState {
+    pub fn new() -> Self {
+        Self {
+            journal: Mutex::new(VecDeque::new()),
+            undoing: Mutex::new(()),
+        }
+    }
+
+    fn journal(&self) -> Result<std::sync::MutexGuard<'_, VecDeque<JournalEntry>>, String> {
+        self.journal
+            .lock()
+            .map_err(|e| format!("Failed to lock file operation journal: {}", e))
+    }
+
+    fn record(&self, operation: FileOperation) -> Result<JournalEntry, String> {
+        let entry = JournalEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            operation,
+            timestamp: now_secs(),
+        };
+        let mut journal = self.journal()?;
+        journal.push_back(entry.clone());
+        while journal.len() > MAX_JOURNAL_ENTRIES {
+            journal.pop_front();
+        }
+        Ok(entry)
+    }
+
+    /// Undo the most recent operation
+    ///
+    /// Blocks on file I/O; the journal itself is only locked to pick and drop
+    /// the entry. If undo fails the entry stays in the journal for a retry,
+    /// unless the files no longer allow undoing it, in which case it is dropped
+    /// so older operations can still be undone.
+    fn undo(&self, trash: &Path) -> Result<(JournalEntry, Vec<PathChange>), String> {
+        let _undoing = self
+            .undoing
+            .lock()
+            .map_err(|e| format!("Failed to lock undo: {}", e))?;
+        let entry = self
+            .journal()?
+            .back()
+            .cloned()
+            .ok_or_else(|| "Nothing to undo".to_string())?;
+
+        match undo_operation(&entry.operation, trash) {
+            Ok(changes) => {
+                self.forget(|e| e.id == entry.id)?;
+                Ok((entry, changes))
+            }
+            Err(e) if !can_undo(&entry.operation, trash) => {
+                self.forget(|e| e.id == entry.id)?;
+                Err(format!("{} (the operation can no longer be undone)", e))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Drop journal entries matching `predicate`
+    fn forget(&self, predicate: impl Fn(&JournalEntry) -> bool) -> Result<(), String> {
+        self.journal()?.retain(|entry| !predicate(entry));
+        Ok(())
+    }
+
+    /// Drop the delete that moved a trash entry, once it has been restored
+    fn forget_delete(&self, id: &str) -> Result<(), String> {
+        self.forget(|entry| {
+            matches!(&entry.operation, FileOperation::Delete { trash_id, .. } if trash_id == id)
+        })
+    }
+
+    fn history(&self) -> Result<Vec<JournalEntry>, String> {
+        Ok(self.journal()?.iter().rev().cloned().collect())
+    }
+}
+
+impl Default for FileOpsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn trash_root() -> Result<PathBuf, String> {
+    codelane_core::paths::trash_dir().map_err(|e| format!("Failed to get trash directory: {}", e))
+}
+
+/// Require an absolute path
+fn absolute(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path.display()));
+    }
+    Ok(path)
+}
+
+fn ensure_missing(path: &Path) -> Result<(), String> {
+    if path.symlink_metadata().is_ok() {
+        return Err(format!("Already exists: {}", path.display()));
+    }
+    Ok(())
+}
+
+fn ensure_exists(path: &Path) -> Result<(), String> {
+    if path.symlink_metadata().is_err() {
+        return Err(format!("Does not exist: {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Resolve `.` and `..` components and symlinked ancestors, so paths can be
+/// compared; the final component is kept as is
+fn normalize(path: &Path) -> PathBuf {
+    let mut lexical = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                lexical.pop();
+            }
+            other => lexical.push(other),
+        }
+    }
+
+    // Canonicalize the deepest existing ancestor; the rest may not exist yet
+    let mut names = Vec::new();
+    let mut current = lexical.as_path();
+    while let (Some(parent), Some(name)) = (current.parent(), current.file_name()) {
+        names.push(name);
+        if let Ok(canonical) = parent.canonicalize() {
+            return names
+                .iter()
+                .rev()
+                .fold(canonical, |path, name| path.join(name));
+        }
+        current = parent;
+    }
+    lexical
+}
+
+fn create_parent(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Copy a file, symlink or directory tree
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), String> {
+    let metadata = from
+        .symlink_metadata()
+        .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+
+    if metadata.is_dir() {
+        fs::create_dir(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+        let entries = fs::read_dir(from).map_err(|e| format!("Failed to read directory: {}", e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+            .map_err(|e| format!("Failed to set permissions: {}", e))?;
+        return Ok(());
+    }
+
+    #[cfg(unix)]
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(from).map_err(|e| format!("Failed to read symlink: {}", e))?;
+        return std::os::unix::fs::symlink(target, to)
+            .map_err(|e| format!("Failed to create symlink: {}", e));
+    }
+
+    fs::copy(from, to)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to copy {}: {}", from.display(), e))
+}
+
+fn remove_recursive(path: &Path) -> std::io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Rename, falling back to copy + delete across filesystems
+fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_recursive(from, to) {
+                let _ = remove_recursive(to);
+                return Err(e);
+            }
+            remove_recursive(from).map_err(|e| format!("Failed to remove original: {}", e))
+        }
+        Err(e) => Err(format!(
+            "Failed to move {} to {}: {}",
+            from.display(),
+            to.display(),
+            e
+        )),
+    }
+}
+
+fn create(path: &Path, is_dir: bool, contents: Option<&str>) -> Result<FileOperation, String> {
+    ensure_missing(path)?;
+    create_parent(path)?;
+    if is_dir {
+        fs::create_dir(path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    } else {
+        fs::write(path, contents.unwrap_or_default())
+            .map_err(|e| format!("Failed to create file: {}", e))?;
+    }
+    Ok(FileOperation::Create {
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+    })
+}
+
+fn move_entry(from: &Path, to: &Path) -> Result<FileOperation, String> {
+    ensure_exists(from)?;
+    ensure_missing(to)?;
+    if normalize(to).starts_with(normalize(from)) {
+        return Err(format!("Cannot move {} into itself", from.display()));
+    }
+    create_parent(to)?;
+    move_path(from, to)?;
+    Ok(FileOperation::Move {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+    })
+}
+
+fn copy_entry(from: &Path, to: &Path) -> Result<FileOperation, String> {
+    ensure_exists(from)?;
+    ensure_missing(to)?;
+    if normalize(to).starts_with(normalize(from)) {
+        return Err(format!("Cannot copy {} into itself", from.display()));
+    }
+    create_parent(to)?;
+    if let Err(e) = copy_recursive(from, to) {
+        let _ = remove_recursive(to);
+        return Err(e);
+    }
+    Ok(FileOperation::Copy {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+    })
+}
+
+/// Move an entry into the trash
+fn trash_entry(path: &Path, trash: &Path) -> Result<TrashItem, String> {
+    ensure_exists(path)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Cannot delete {}", path.display()))?;
+
+    let item = TrashItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        original_path: path.to_string_lossy().to_string(),
+        is_dir: path.symlink_metadata().is_ok_and(|m| m.is_dir()),
+        deleted_at: now_secs(),
+    };
+
+    let dir = trash.join(&item.id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create trash entry: {}", e))?;
+    let info = serde_json::to_string_pretty(&item).map_err(|e| e.to_string())?;
+    fs::write(dir.join(TRASH_INFO_FILE), info)
+        .map_err(|e| format!("Failed to write trash entry: {}", e))?;
+
+    if let Err(e) = move_path(path, &dir.join(&item.name)) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+    Ok(item)
+}
+
+fn read_trash_item(dir: &Path) -> Option<TrashItem> {
+    let info = fs::read_to_string(dir.join(TRASH_INFO_FILE)).ok()?;
+    serde_json::from_str(&info).ok()
+}
+
+/// Move a trashed entry back to its original path
+fn restore_entry(id: &str, trash: &Path) -> Result<TrashItem, String> {
+    // IDs are UUIDs; reject anything that could escape the trash directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid trash id: {}", id));
+    }
+    let dir = trash.join(id);
+    let item = read_trash_item(&dir).ok_or_else(|| format!("Trash entry not found: {}", id))?;
+    let original = PathBuf::from(&item.original_path);
+
+    ensure_missing(&original)?;
+    create_parent(&original)?;
+    move_path(&dir.join(&item.name), &original)?;
+    let _ = fs::remove_dir_all(&dir);
+    Ok(item)
+}
+
+fn list_trash(trash: &Path) -> Result<Vec<TrashItem>, String> {
+    let entries = fs::read_dir(trash).map_err(|e| format!("Failed to read trash: {}", e))?;
+    let mut items: Vec<TrashItem> = entries
+        .flatten()
+        .filter_map(|entry| read_trash_item(&entry.path()))
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    Ok(items)
+}
+
+/// Reverse an operation, returning the changed paths and event kinds
+fn undo_operation(operation: &FileOperation, trash: &Path) -> Result<Vec<PathChange>, String> {
+    match operation {
+        FileOperation::Create { path, .. } | FileOperation::Copy { to: path, .. } => {
+            let path = PathBuf::from(path);
+            trash_entry(&path, trash)?;
+            Ok(vec![(path, "delete")])
+        }
+        FileOperation::Move { from, to } => {
+            let (from, to) = (PathBuf::from(from), PathBuf::from(to));
+            move_entry(&to, &from)?;
+            Ok(vec![(to, "rename"), (from, "rename")])
+        }
+        FileOperation::Delete { trash_id, .. } => {
+            let item = restore_entry(trash_id, trash)?;
+            Ok(vec![(PathBuf::from(item.original_path), "create")])
+        }
+    }
+}
+
+/// Whether the entry an undo would move back still exists
+///
+/// When it does, undo can be retried once whatever blocked it is cleared up.
+fn can_undo(operation: &FileOperation, trash: &Path) -> bool {
+    match operation {
+        FileOperation::Create { path, .. }
+        | FileOperation::Copy { to: path, .. }
+        | FileOperation::Move { to: path, .. } => Path::new(path).symlink_metadata().is_ok(),
+        FileOperation::Delete { trash_id, .. } => read_trash_item(&trash.join(trash_id)).is_some(),
+    }
+}
+
+/// Paths and event kinds affected by an operation
+fn changes(operation: &FileOperation) -> Vec<PathChange> {
+    match operation {
+        FileOperation::Create { path, .. } => vec![(PathBuf::from(path), "create")],
+        FileOperation::Move { from, to } => vec![
+            (PathBuf::from(from), "rename"),
+            (PathBuf::from(to), "rename"),
+        ],
+        FileOperation::Copy { to, .. } => vec![(PathBuf::from(to), "create")],
+        FileOperation::Delete { path, .. } => vec![(PathBuf::from(path), "delete")],
+    }
+}
+
+fn emit_changes(app: &AppHandle, changes: &[PathChange]) {
+    let events = changes
+        .iter()
+        .map(|(path, kind)| FileWatchEvent {
+            watch_id: FILE_OPS_WATCH_ID.to_string(),
+            path: path.to_string_lossy().to_string(),
+            kind: kind.to_string(),
+        })
+        .collect();
+    let _ = app.emit(
+        FILE_WATCH_BATCH_EVENT,
+        &FileWatchBatch {
+            watch_id: FILE_OPS_WATCH_ID.to_string(),
+            events,
+            overflow: false,
+        },
+    );
+}
+
+/// Run a blocking operation, journal it and emit its events
+async fn perform(
+    app: &AppHandle,
+    state: &FileOpsState,
+    op: impl FnOnce() -> Result<FileOperation, String> + Send + 'static,
+) -> Result<JournalEntry, String> {
+    let operation = tokio::task::spawn_blocking(op)
+        .await
+        .map_err(|e| format!("File operation failed: {}", e))??;
+    emit_changes(app, &changes(&operation));
+    state.record(operation)
+}
+
+/// Create a new file
+///
+/// # Arguments
+/// * `path` - Absolute path of the file; parent directories are created
+/// * `contents` - Initial contents (default: empty)
+///
+/// # Returns
+/// The journal entry for the operation
+#[tauri::command]
+pub async fn file_create(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    path: String,
+    contents: Option<String>,
+) -> Result<JournalEntry, String> {
+    tracing::info!("Creating file: {}", path);
+    let path = absolute(&path)?;
+    perform(&app, &state, move || {
+        create(&path, false, contents.as_deref())
+    })
+    .await
+}
+
+/// Create a new directory
+///
+/// # Arguments
+/// * `path` - Absolute path of the directory; parent directories are created
+///
+/// # Returns
+/// The journal entry for the operation
+#[tauri::command]
+pub async fn directory_create(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    path: String,
+) -> Result<JournalEntry, String> {
+    tracing::info!("Creating directory: {}", path);
+    let path = absolute(&path)?;
+    perform(&app, &state, move || create(&path, true, None)).await
+}
+
+/// Rename a file or directory within its parent directory
+///
+/// # Arguments
+/// * `path` - Absolute path of the entry
+/// * `new_name` - New file name (no path separators)
+///
+/// # Returns
+/// The journal entry for the operation
+#[tauri::command]
+pub async fn file_rename(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    path: String,
+    new_name: String,
+) -> Result<JournalEntry, String> {
+    tracing::info!("Renaming {} to {}", path, new_name);
+    let from = absolute(&path)?;
+    if new_name.is_empty()
+        || new_name == "."
+        || new_name == ".."
+        || new_name.contains(['/', std::path::MAIN_SEPARATOR])
+    {
+        return Err(format!("Invalid file name: {}", new_name));
+    }
+    let to = from.with_file_name(&new_name);
+    perform(&app, &state, move || move_entry(&from, &to)).await
+}
+
+/// Move a file or directory
+///
+/// # Arguments
+/// * `from` - Absolute path of the entry
+/// * `to` - Absolute destination path (must not exist)
+///
+/// # Returns
+/// The journal entry for the operation
+#[tauri::command]
+pub async fn file_move(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    from: String,
+    to: String,
+) -> Result<JournalEntry, String> {
+    tracing::info!("Moving {} to {}", from, to);
+    let (from, to) = (absolute(&from)?, absolute(&to)?);
+    perform(&app, &state, move || move_entry(&from, &to)).await
+}
+
+/// Copy a file or directory tree
+///
+/// # Arguments
+/// * `from` - Absolute path of the entry
+/// * `to` - Absolute destination path (must not exist)
+///
+/// # Returns
+/// The journal entry for the operation
+#[tauri::command]
+pub async fn file_copy(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    from: String,
+    to: String,
+) -> Result<JournalEntry, String> {
+    tracing::info!("Copying {} to {}", from, to);
+    let (from, to) = (absolute(&from)?, absolute(&to)?);
+    perform(&app, &state, move || copy_entry(&from, &to)).await
+}
+
+/// Delete a file or directory by moving it to the Codelane trash
+///
+/// # Arguments
+/// * `path` - Absolute path of the entry
+///
+/// # Returns
+/// The journal entry for the operation; its `trash_id` can be passed to `trash_restore`
+#[tauri::command]
+pub async fn file_delete(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    path: String,
+) -> Result<JournalEntry, String> {
+    tracing::info!("Deleting (to trash): {}", path);
+    let path = absolute(&path)?;
+    let trash = trash_root()?;
+    perform(&app, &state, move || {
+        let item = trash_entry(&path, &trash)?;
+        Ok(FileOperation::Delete {
+            path: item.original_path,
+            trash_id: item.id,
+        })
+    })
+    .await
+}
+
+/// Undo the most recent file operation
+///
+/// Creates and copies are undone by moving the new entry to the trash, moves
+/// by moving back, and deletes by restoring from the trash. An operation whose
+/// files have changed so it can no longer be undone is dropped from the journal.
+///
+/// # Returns
+/// The journal entry that was undone
+#[tauri::command]
+pub async fn file_ops_undo(app: AppHandle) -> Result<JournalEntry, String> {
+    let trash = trash_root()?;
+    let handle = app.clone();
+    let (entry, changes) =
+        tokio::task::spawn_blocking(move || handle.state::<FileOpsState>().undo(&trash))
+            .await
+            .map_err(|e| format!("Failed to undo: {}", e))??;
+    tracing::info!("Undid file operation: {:?}", entry.operation);
+    emit_changes(&app, &changes);
+    Ok(entry)
+}
+
+/// Get the file operation journal, most recent first
+#[tauri::command]
+pub async fn file_ops_history(state: State<'_, FileOpsState>) -> Result<Vec<JournalEntry>, String> {
+    state.history()
+}
+
+/// List entries in the Codelane trash, most recently deleted first
+#[tauri::command]
+pub async fn trash_list() -> Result<Vec<TrashItem>, String> {
+    let trash = trash_root()?;
+    tokio::task::spawn_blocking(move || list_trash(&trash))
+        .await
+        .map_err(|e| format!("Failed to list trash: {}", e))?
+}
+
+/// Restore a trashed entry to its original path
+///
+/// The delete that trashed it is dropped from the undo journal.
+///
+/// # Arguments
+/// * `id` - Trash entry ID
+///
+/// # Returns
+/// The restored entry
+#[tauri::command]
+pub async fn trash_restore(
+    app: AppHandle,
+    state: State<'_, FileOpsState>,
+    id: String,
+) -> Result<TrashItem, String> {
+    tracing::info!("Restoring from trash: {}", id);
+    let trash = trash_root()?;
+    let item = tokio::task::spawn_blocking(move || restore_entry(&id, &trash))
+        .await
+        .map_err(|e| format!("Failed to restore: {}", e))??;
+    state.forget_delete(&item.id)?;
+    emit_changes(&app, &[(PathBuf::from(&item.original_path), "create")]);
+    Ok(item)
+}
+
+/// Permanently delete entries from the Codelane trash
+///
+/// # Arguments
+/// * `older_than_secs` - Only delete entries trashed at least this long ago
+///   (default: all entries)
+///
+/// # Returns
+/// Number of entries deleted
+#[tauri::command]
+pub async fn trash_empty(older_than_secs: Option<u64>) -> Result<u32, String> {
+    let trash = trash_root()?;
+    let cutoff = now_secs().saturating_sub(older_than_secs.unwrap_or(0));
+    tokio::task::spawn_blocking(move || {
+        let mut removed = 0;
+        for item in list_trash(&trash)? {
+            if item.deleted_at <= cutoff && fs::remove_dir_all(trash.join(&item.id)).is_ok() {
+                removed += 1;
+            }
+        }
+        tracing::info!("Emptied {} trash entries", removed);
+        Ok(removed)
+    })
+    .await
+    .map_err(|e| format!("Failed to empty trash: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct Sandbox {
+        _temp: TempDir,
+        work: PathBuf,
+        trash: PathBuf,
+    }
+
+    fn sandbox() -> Sandbox {
+        let temp = TempDir::new().unwrap();
+        let work = temp.path().join("work");
+        let trash = temp.path().join("trash");
+        fs::create_dir_all(&work).unwrap();
+        fs::create_dir_all(&trash).unwrap();
+        Sandbox {
+            _temp: temp,
+            work,
+            trash,
+        }
+    }
+
+    #[test]
+    fn test_create_and_undo() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let file = sb.work.join("src/new.rs");
+
+        let op = create(&file, false, Some("fn main() {}")).unwrap();
+        state.record(op).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "fn main() {}");
+        assert!(create(&file, false, None)
+            .unwrap_err()
+            .contains("Already exists"));
+
+        let (entry, changes) = state.undo(&sb.trash).unwrap();
+        assert!(matches!(entry.operation, FileOperation::Create { .. }));
+        assert_eq!(changes, vec![(file.clone(), "delete")]);
+        assert!(!file.exists());
+
+        // Undone creates stay recoverable in the trash
+        let items = list_trash(&sb.trash).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "new.rs");
+        assert!(state
+            .undo(&sb.trash)
+            .unwrap_err()
+            .contains("Nothing to undo"));
+    }
+
+    #[test]
+    fn test_move_and_undo() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let dir = sb.work.join("dir");
+        fs::create_dir_all(dir.join("inner")).unwrap();
+        fs::write(dir.join("inner/a.txt"), "a").unwrap();
+
+        let moved = sb.work.join("renamed");
+        state.record(move_entry(&dir, &moved).unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(moved.join("inner/a.txt")).unwrap(), "a");
+        assert!(!dir.exists());
+
+        assert!(move_entry(&moved, &moved.join("inner/x"))
+            .unwrap_err()
+            .contains("into itself"));
+
+        state.undo(&sb.trash).unwrap();
+        assert!(dir.join("inner/a.txt").exists());
+        assert!(!moved.exists());
+    }
+
+    #[test]
+    fn test_copy_directory_and_undo() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let src = sb.work.join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/b.txt"), "b").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("nested/b.txt", src.join("link")).unwrap();
+
+        let copy = sb.work.join("copy");
+        state.record(copy_entry(&src, &copy).unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(copy.join("nested/b.txt")).unwrap(), "b");
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(copy.join("link")).unwrap(),
+            PathBuf::from("nested/b.txt")
+        );
+        assert!(copy_entry(&src, &copy)
+            .unwrap_err()
+            .contains("Already exists"));
+
+        state.undo(&sb.trash).unwrap();
+        assert!(!copy.exists());
+        assert!(src.join("nested/b.txt").exists());
+    }
+
+    #[test]
+    fn test_delete_restore_and_undo() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let file = sb.work.join("notes.md");
+        fs::write(&file, "keep me").unwrap();
+
+        let item = trash_entry(&file, &sb.trash).unwrap();
+        assert!(!file.exists());
+        assert_eq!(item.original_path, file.to_string_lossy());
+        assert_eq!(list_trash(&sb.trash).unwrap(), vec![item.clone()]);
+
+        // Restore refuses to overwrite
+        fs::write(&file, "new").unwrap();
+        assert!(restore_entry(&item.id, &sb.trash)
+            .unwrap_err()
+            .contains("Already exists"));
+        fs::remove_file(&file).unwrap();
+
+        state
+            .record(FileOperation::Delete {
+                path: item.original_path.clone(),
+                trash_id: item.id.clone(),
+            })
+            .unwrap();
+        let (_, changes) = state.undo(&sb.trash).unwrap();
+        assert_eq!(changes, vec![(file.clone(), "create")]);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "keep me");
+        assert!(list_trash(&sb.trash).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_entry() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let from = sb.work.join("a.txt");
+        let to = sb.work.join("b.txt");
+        fs::write(&from, "a").unwrap();
+        state.record(move_entry(&from, &to).unwrap()).unwrap();
+
+        // Something new now occupies the original path
+        fs::write(&from, "other").unwrap();
+        assert!(state.undo(&sb.trash).is_err());
+        assert_eq!(state.history().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_undo_drops_entries_that_cannot_be_undone() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let file = sb.work.join("a.txt");
+        state.record(create(&file, false, None).unwrap()).unwrap();
+        let moved = sb.work.join("b.txt");
+        state.record(move_entry(&file, &moved).unwrap()).unwrap();
+
+        // The moved file is gone, so the move can never be undone
+        fs::remove_file(&moved).unwrap();
+        assert!(state
+            .undo(&sb.trash)
+            .unwrap_err()
+            .contains("can no longer be undone"));
+        assert_eq!(state.history().unwrap().len(), 1);
+
+        // The create is now on top, and its file is gone as well
+        assert!(state.undo(&sb.trash).is_err());
+        assert!(state.history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_forgets_delete() {
+        let sb = sandbox();
+        let state = FileOpsState::new();
+        let file = sb.work.join("notes.md");
+        fs::write(&file, "keep me").unwrap();
+        let item = trash_entry(&file, &sb.trash).unwrap();
+        state
+            .record(FileOperation::Delete {
+                path: item.original_path.clone(),
+                trash_id: item.id.clone(),
+            })
+            .unwrap();
+
+        restore_entry(&item.id, &sb.trash).unwrap();
+        assert!(!can_undo(&state.history().unwrap()[0].operation, &sb.trash));
+        state.forget_delete(&item.id).unwrap();
+        assert!(state.history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_move_into_itself_through_unnormalized_path() {
+        let sb = sandbox();
+        let dir = sb.work.join("dir");
+        fs::create_dir_all(&dir).unwrap();
+
+        let inside = sb.work.join("other/../dir/./inner");
+        assert!(move_entry(&dir, &inside)
+            .unwrap_err()
+            .contains("into itself"));
+        assert!(copy_entry(&dir, &inside)
+            .unwrap_err()
+            .contains("into itself"));
+
+        #[cfg(unix)]
+        {
+            let link = sb.work.join("link");
+            std::os::unix::fs::symlink(&dir, &link).unwrap();
+            assert!(move_entry(&dir, &link.join("inner"))
+                .unwrap_err()
+                .contains("into itself"));
+            // Moving the symlink itself into its target is fine
+            move_entry(&link, &dir.join("link")).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_path_ids() {
+        let sb = sandbox();
+        assert!(restore_entry("../work", &sb.trash)
+            .unwrap_err()
+            .contains("Invalid trash id"));
+    }
+
+    #[test]
+    fn test_journal_is_bounded() {
+        let state = FileOpsState::new();
+        for i in 0..MAX_JOURNAL_ENTRIES + 5 {
+            state
+                .record(FileOperation::Create {
+                    path: format!("/tmp/{}", i),
+                    is_dir: false,
+                })
+                .unwrap();
+        }
+        let history = state.history().unwrap();
+        assert_eq!(history.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(
+            history[0].operation,
+            FileOperation::Create {
+                path: format!("/tmp/{}", MAX_JOURNAL_ENTRIES + 4),
+                is_dir: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_operation_serialization() {
+        let op = FileOperation::Move {
+            from: "/a".to_string(),
+            to: "/b".to_string(),
+        };
+        let json = serde_json::to_string(&op).unwrap();
+        assert_eq!(json, r#"{"type":"move","from":"/a","to":"/b"}"#);
+    }
+}
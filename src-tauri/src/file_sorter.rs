@@ -2,33 +2,22 @@
 //!
 //! Implements heuristic-based file categorization and ordering to improve
 //! code review efficiency by showing files in logical dependency order.
+//!
+//! Lanes and plugins can add categories for layouts the built-in patterns
+//! don't know (Rails, Django migrations, generated code directories) and
+//! reorder categories; see [`Categorizer`].
 
 use regex::Regex;
 use serde::Serialize;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::OnceLock;
 
 use crate::git::FileChangeStats;
 use crate::dependency_graph;
 
-/// File category tier for smart sorting
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
-#[serde(rename_all = "lowercase")]
-pub enum FileTier {
-    /// Tier 0: Project configuration (package.json, Cargo.toml, CI configs)
-    ProjectConfig = 0,
-    /// Tier 1: Type definitions and interfaces
-    TypeDefinitions = 1,
-    /// Tier 2: Implementation files (core logic)
-    Implementation = 2,
-    /// Tier 3: Test files
-    Tests = 3,
-    /// Tier 4: Generated and lock files
-    Generated = 4,
-    /// Tier 5: Documentation
-    Documentation = 5,
-}
+use codelane_core::config::{FileCategoryRule, FileSortConfig};
+pub use codelane_core::config::FileTier;
 
 /// File category with tier and label
 #[derive(Debug, Clone, Serialize)]
@@ -160,6 +149,117 @@ pub fn categorize_file(path: &str) -> FileCategory {
     }
 }
 
+/// Position of a category in review order: its place in the configured
+/// order, its tier, then its configured category
+type SortKey = (usize, FileTier, usize);
+
+/// A configured category with its compiled patterns
+#[derive(Debug)]
+struct CategoryRule {
+    patterns: Vec<Regex>,
+    category: FileCategory,
+}
+
+impl CategoryRule {
+    fn compile(rule: &FileCategoryRule) -> Result<Self, String> {
+        let patterns = rule
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| format!("Invalid pattern for category {}: {}", rule.label, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self {
+            patterns,
+            category: FileCategory {
+                tier: rule.tier,
+                label: rule.label.clone(),
+            },
+        })
+    }
+}
+
+/// Categorizes and sorts files with configured categories
+///
+/// Configured categories are checked before plugin categories, which are
+/// checked before the built-in patterns; both sort ahead of the built-in
+/// files of their tier. The default categorizer only uses the built-in
+/// patterns.
+#[derive(Debug, Default)]
+pub struct Categorizer {
+    rules: Vec<CategoryRule>,
+    order: Vec<String>,
+}
+
+impl Categorizer {
+    /// Compile the categories of a sort configuration
+    ///
+    /// Categories with invalid patterns are skipped here and in
+    /// [`add_plugin_categories`](Self::add_plugin_categories), so that one
+    /// bad rule doesn't disable smart sorting.
+    pub fn new(config: &FileSortConfig) -> Self {
+        let mut categorizer = Self {
+            rules: Vec::new(),
+            order: config.order.clone(),
+        };
+        categorizer.add_categories(&config.categories, "lane");
+        categorizer
+    }
+
+    /// Add the categories declared by plugins, checked after the configured
+    /// ones
+    pub fn add_plugin_categories(&mut self, categories: &[FileCategoryRule]) {
+        self.add_categories(categories, "plugin");
+    }
+
+    fn add_categories(&mut self, categories: &[FileCategoryRule], source: &str) {
+        for rule in categories {
+            match CategoryRule::compile(rule) {
+                Ok(rule) => self.rules.push(rule),
+                Err(e) => tracing::warn!("Skipping {} file category: {}", source, e),
+            }
+        }
+    }
+
+    /// Categorize a file by path
+    pub fn categorize(&self, path: &str) -> FileCategory {
+        self.rules
+            .iter()
+            .find(|rule| rule.patterns.iter().any(|re| re.is_match(path)))
+            .map(|rule| rule.category.clone())
+            .unwrap_or_else(|| categorize_file(path))
+    }
+
+    fn sort_key(&self, category: &FileCategory) -> SortKey {
+        let position = self
+            .order
+            .iter()
+            .position(|label| *label == category.label)
+            .unwrap_or(self.order.len());
+        let rule = self
+            .rules
+            .iter()
+            .position(|rule| rule.category.label == category.label)
+            .unwrap_or(self.rules.len());
+        (position, category.tier, rule)
+    }
+
+    fn categorize_all(
+        &self,
+        files: Vec<FileChangeStats>,
+    ) -> Vec<(FileChangeStats, FileCategory, SortKey)> {
+        files
+            .into_iter()
+            .map(|f| {
+                let category = self.categorize(&f.path);
+                let key = self.sort_key(&category);
+                (f, category, key)
+            })
+            .collect()
+    }
+}
+
 /// Find matching test file for an implementation file
 fn find_matching_test<'a>(impl_path: &str, test_files: &[&'a FileChangeStats]) -> Option<&'a FileChangeStats> {
     // Remove extension from implementation file
@@ -200,72 +300,66 @@ fn find_matching_test<'a>(impl_path: &str, test_files: &[&'a FileChangeStats]) -
 
 /// Sort files using smart heuristic-based ordering
 pub fn sort_files_smart(files: Vec<FileChangeStats>) -> Vec<FileChangeStats> {
-    // Step 1: Categorize all files
-    let mut categorized: Vec<(FileChangeStats, FileCategory)> = files
-        .into_iter()
-        .map(|f| {
-            let category = categorize_file(&f.path);
-            (f, category)
-        })
-        .collect();
-
-    // Step 2: Sort by tier, then by diff size within tier, then alphabetically
-    categorized.sort_by(|a, b| {
-        // Primary: tier
-        match a.1.tier.cmp(&b.1.tier) {
-            Ordering::Equal => {
-                // Secondary: diff size (descending) - show larger changes first
-                let size_a = a.0.additions + a.0.deletions;
-                let size_b = b.0.additions + b.0.deletions;
-                match size_b.cmp(&size_a) {
-                    Ordering::Equal => {
-                        // Tertiary: alphabetical
-                        a.0.path.cmp(&b.0.path)
+    Categorizer::default().sort_smart(files)
+}
+
+impl Categorizer {
+    /// Sort files using smart heuristic-based ordering
+    pub fn sort_smart(&self, files: Vec<FileChangeStats>) -> Vec<FileChangeStats> {
+        // Step 1: Categorize all files
+        let mut categorized = self.categorize_all(files);
+
+        // Step 2: Sort by category, then by diff size within it, then alphabetically
+        categorized.sort_by(|a, b| {
+            // Primary: category position
+            match a.2.cmp(&b.2) {
+                Ordering::Equal => {
+                    // Secondary: diff size (descending) - show larger changes first
+                    let size_a = a.0.additions + a.0.deletions;
+                    let size_b = b.0.additions + b.0.deletions;
+                    match size_b.cmp(&size_a) {
+                        Ordering::Equal => {
+                            // Tertiary: alphabetical
+                            a.0.path.cmp(&b.0.path)
+                        }
+                        other => other,
                     }
-                    other => other,
                 }
+                other => other,
             }
-            other => other,
-        }
-    });
-
-    // Step 3: Pair test files with their implementation files
-    let mut result = Vec::new();
-    let test_files: Vec<&FileChangeStats> = categorized
-        .iter()
-        .filter(|(_, cat)| cat.tier == FileTier::Tests)
-        .map(|(f, _)| f)
-        .collect();
-
-    let mut paired_tests = std::collections::HashSet::new();
-
-    for (file, category) in &categorized {
-        // Skip test files here - they'll be added when paired
-        if category.tier == FileTier::Tests {
-            continue;
-        }
-
-        result.push(file.clone());
+        });
+
+        // Step 3: Pair test files with their implementation files. Tests
+        // that are not paired keep their category's position, so the
+        // configured order applies to test categories too.
+        let mut result = Vec::new();
+        let test_files: Vec<&FileChangeStats> = categorized
+            .iter()
+            .filter(|(_, cat, _)| cat.tier == FileTier::Tests)
+            .map(|(f, _, _)| f)
+            .collect();
+
+        let mut added = HashSet::new();
+
+        for (file, category, _) in &categorized {
+            // Tests already paired with their implementation
+            if !added.insert(file.path.clone()) {
+                continue;
+            }
+            result.push(file.clone());
 
-        // If this is an implementation file, try to find and pair its test
-        if category.tier == FileTier::Implementation {
-            if let Some(test_file) = find_matching_test(&file.path, &test_files) {
-                if !paired_tests.contains(&test_file.path) {
-                    result.push(test_file.clone());
-                    paired_tests.insert(test_file.path.clone());
+            // If this is an implementation file, try to find and pair its test
+            if category.tier == FileTier::Implementation {
+                if let Some(test_file) = find_matching_test(&file.path, &test_files) {
+                    if added.insert(test_file.path.clone()) {
+                        result.push(test_file.clone());
+                    }
                 }
             }
         }
-    }
 
-    // Add any unpaired test files at the end of the test section
-    for (file, category) in &categorized {
-        if category.tier == FileTier::Tests && !paired_tests.contains(&file.path) {
-            result.push(file.clone());
-        }
+        result
     }
-
-    result
 }
 
 /// Sort files alphabetically by path
@@ -291,40 +385,42 @@ pub fn sort_files_smart_dependencies(
     files: Vec<FileChangeStats>,
     file_contents: HashMap<String, String>,
 ) -> Vec<FileChangeStats> {
-    // Step 1: Categorize all files into tiers (same as smart sort)
-    let mut categorized: Vec<(FileChangeStats, FileCategory)> = files
-        .into_iter()
-        .map(|f| {
-            let category = categorize_file(&f.path);
-            (f, category)
-        })
-        .collect();
-
-    // Step 2: Group by tier
-    let mut tiers = HashMap::<FileTier, Vec<FileChangeStats>>::new();
-    for (file, category) in categorized {
-        tiers.entry(category.tier).or_insert_with(Vec::new).push(file);
-    }
-
-    // Step 3: Sort each tier independently
-    let mut result = Vec::new();
-
-    // Process tiers in order
-    for tier_id in [
-        FileTier::ProjectConfig,
-        FileTier::TypeDefinitions,
-        FileTier::Implementation,
-        FileTier::Tests,
-        FileTier::Generated,
-        FileTier::Documentation,
-    ] {
-        if let Some(mut tier_files) = tiers.remove(&tier_id) {
+    Categorizer::default().sort_smart_dependencies(files, &file_contents)
+}
+
+impl Categorizer {
+    /// Sort files with dependency awareness using tree-sitter
+    /// Combines smart tier-based sorting with topological ordering within tiers
+    pub fn sort_smart_dependencies(
+        &self,
+        files: Vec<FileChangeStats>,
+        file_contents: &HashMap<String, String>,
+    ) -> Vec<FileChangeStats> {
+        // Step 1: Categorize all files (same as smart sort) and group them
+        // by category, in review order
+        let mut groups = BTreeMap::<SortKey, Vec<FileChangeStats>>::new();
+        for (file, _, key) in self.categorize_all(files) {
+            groups.entry(key).or_default().push(file);
+        }
+
+        let test_files: Vec<FileChangeStats> = groups
+            .iter()
+            .filter(|((_, tier, _), _)| *tier == FileTier::Tests)
+            .flat_map(|(_, files)| files.iter().cloned())
+            .collect();
+        let test_refs: Vec<&FileChangeStats> = test_files.iter().collect();
+
+        // Step 2: Sort each group independently
+        let mut result = Vec::new();
+        let mut added = HashSet::new();
+
+        for ((_, tier, _), mut group_files) in groups {
             // For Implementation tier, apply dependency sorting
-            if tier_id == FileTier::Implementation && tier_files.len() > 1 {
-                tier_files = dependency_graph::sort_by_dependencies(tier_files, &file_contents);
+            if tier == FileTier::Implementation && group_files.len() > 1 {
+                group_files = dependency_graph::sort_by_dependencies(group_files, file_contents);
             } else {
                 // For other tiers, sort by size then alphabetically
-                tier_files.sort_by(|a, b| {
+                group_files.sort_by(|a, b| {
                     let size_a = a.additions + a.deletions;
                     let size_b = b.additions + b.deletions;
                     match size_b.cmp(&size_a) {
@@ -334,27 +430,27 @@ pub fn sort_files_smart_dependencies(
                 });
             }
 
-            // Add tier files to result
-            for file in &tier_files {
+            // Add group files to result
+            for file in group_files {
+                // Tests already paired with their implementation
+                if !added.insert(file.path.clone()) {
+                    continue;
+                }
                 result.push(file.clone());
 
                 // If this is an implementation file, try to pair its test
-                if tier_id == FileTier::Implementation {
-                    if let Some(test_tier) = tiers.get_mut(&FileTier::Tests) {
-                        let test_refs: Vec<&FileChangeStats> = test_tier.iter().collect();
-                        if let Some(test_file) = find_matching_test(&file.path, &test_refs) {
-                            let test_path = test_file.path.clone();
+                if tier == FileTier::Implementation {
+                    if let Some(test_file) = find_matching_test(&file.path, &test_refs) {
+                        if added.insert(test_file.path.clone()) {
                             result.push(test_file.clone());
-                            // Remove from test tier so we don't add it again
-                            test_tier.retain(|f| f.path != test_path);
                         }
                     }
                 }
             }
         }
-    }
 
-    result
+        result
+    }
 }
 
 #[cfg(test)]
@@ -501,6 +597,189 @@ mod tests {
         assert_eq!(sorted.len(), 0);
     }
 
+    fn categorizer() -> Categorizer {
+        Categorizer::new(&FileSortConfig {
+            categories: vec![
+                FileCategoryRule {
+                    label: "Migrations".to_string(),
+                    patterns: vec![r"^db/migrate/".to_string(), r"/migrations/\d+_".to_string()],
+                    tier: FileTier::Generated,
+                },
+                FileCategoryRule {
+                    label: "Models".to_string(),
+                    patterns: vec![r"^app/models/".to_string()],
+                    tier: FileTier::TypeDefinitions,
+                },
+            ],
+            order: vec![],
+        })
+    }
+
+    #[test]
+    fn test_configured_categories() {
+        let categorizer = categorizer();
+
+        let category = categorizer.categorize("blog/migrations/0002_add_slug.py");
+        assert_eq!(category.tier, FileTier::Generated);
+        assert_eq!(category.label, "Migrations");
+        assert_eq!(categorizer.categorize("app/models/user.rb").label, "Models");
+        // Built-in patterns still apply
+        assert_eq!(
+            categorizer.categorize("Cargo.toml").tier,
+            FileTier::ProjectConfig
+        );
+        assert_eq!(
+            categorizer.categorize("app/controllers/users.rb").label,
+            "Implementation"
+        );
+    }
+
+    #[test]
+    fn test_invalid_category_pattern() {
+        let broken = FileCategoryRule {
+            label: "Broken".to_string(),
+            patterns: vec!["(".to_string()],
+            tier: FileTier::Implementation,
+        };
+        let err = CategoryRule::compile(&broken).unwrap_err();
+        assert!(err.starts_with("Invalid pattern for category Broken"));
+
+        // The bad rule is skipped, the others still apply
+        let categorizer = Categorizer::new(&FileSortConfig {
+            categories: vec![
+                broken,
+                FileCategoryRule {
+                    label: "Models".to_string(),
+                    patterns: vec![r"^app/models/".to_string()],
+                    tier: FileTier::TypeDefinitions,
+                },
+            ],
+            order: vec![],
+        });
+        assert_eq!(categorizer.rules.len(), 1);
+        assert_eq!(categorizer.categorize("app/models/user.rb").label, "Models");
+    }
+
+    #[test]
+    fn test_sort_with_configured_categories() {
+        let files = vec![
+            make_file("Cargo.lock", 50, 0),
+            make_file("db/migrate/001_create_users.rb", 10, 0),
+            make_file("app/models/user.rb", 5, 0),
+            make_file("app/controllers/users.rb", 5, 0),
+            make_file("lib/types/user.rb", 1, 0),
+        ];
+
+        // Configured categories sort ahead of the built-in files of their tier
+        let sorted = categorizer().sort_smart(files.clone());
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "app/models/user.rb",
+                "lib/types/user.rb",
+                "app/controllers/users.rb",
+                "db/migrate/001_create_users.rb",
+                "Cargo.lock",
+            ]
+        );
+
+        let sorted = categorizer().sort_smart_dependencies(files, &HashMap::new());
+        assert_eq!(
+            sorted.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(),
+            paths
+        );
+    }
+
+    #[test]
+    fn test_sort_with_custom_order() {
+        let mut config = FileSortConfig {
+            order: vec!["Migrations".to_string(), "Implementation".to_string()],
+            ..Default::default()
+        };
+        config.categories = vec![FileCategoryRule {
+            label: "Migrations".to_string(),
+            patterns: vec![r"^db/migrate/".to_string()],
+            tier: FileTier::Generated,
+        }];
+        let categorizer = Categorizer::new(&config);
+
+        let files = vec![
+            make_file("package.json", 1, 0),
+            make_file("src/app.ts", 1, 0),
+            make_file("src/app.test.ts", 1, 0),
+            make_file("db/migrate/001_init.rb", 1, 0),
+        ];
+        let sorted = categorizer.sort_smart(files);
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.as_str()).collect();
+        // Listed categories first, the rest in tier order; tests stay paired
+        assert_eq!(
+            paths,
+            vec![
+                "db/migrate/001_init.rb",
+                "src/app.ts",
+                "src/app.test.ts",
+                "package.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_unpaired_tests_in_order() {
+        let files = vec![
+            make_file("README.md", 1, 0),
+            make_file("src/app.ts", 1, 0),
+            make_file("tests/e2e.rs", 1, 0),
+        ];
+
+        // Unpaired tests sort in their tier, ahead of documentation
+        let sorted = sort_files_smart(files.clone());
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/app.ts", "tests/e2e.rs", "README.md"]);
+
+        // and follow the configured order
+        let categorizer = Categorizer::new(&FileSortConfig {
+            order: vec!["Tests".to_string()],
+            ..Default::default()
+        });
+        let sorted = categorizer.sort_smart(files.clone());
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["tests/e2e.rs", "src/app.ts", "README.md"]);
+        let sorted = categorizer.sort_smart_dependencies(files, &HashMap::new());
+        assert_eq!(
+            sorted.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(),
+            paths
+        );
+    }
+
+    #[test]
+    fn test_plugin_categories() {
+        let mut categorizer = categorizer();
+        categorizer.add_plugin_categories(&[
+            FileCategoryRule {
+                label: "Broken".to_string(),
+                patterns: vec!["(".to_string()],
+                tier: FileTier::Implementation,
+            },
+            FileCategoryRule {
+                label: "Protobuf".to_string(),
+                patterns: vec![r"^gen/proto/".to_string(), r"^db/migrate/".to_string()],
+                tier: FileTier::Generated,
+            },
+        ]);
+
+        // Invalid plugin categories are skipped, configured ones win
+        assert_eq!(
+            categorizer.categorize("gen/proto/user.rs").label,
+            "Protobuf"
+        );
+        assert_eq!(
+            categorizer.categorize("db/migrate/001_init.rb").label,
+            "Migrations"
+        );
+        assert_eq!(categorizer.categorize("src/app.ts").label, "Implementation");
+    }
+
     #[test]
     fn test_file_category_serialization() {
         let category = categorize_file("package.json");
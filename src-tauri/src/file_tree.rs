@@ -0,0 +1,454 @@
+//! File Explorer Tree
+//!
+//! Lists a directory tree to a requested depth for the file explorer. The walk
+//! respects .gitignore (optionally listing ignored entries too), and entries
+//! are decorated with their git status; folders carry the most significant
+//! status of anything below them, so the tree can show what a lane changed.
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::git::run_git;
+
+/// Default depth of `list_directory_tree` (direct children only)
+const DEFAULT_DEPTH: u32 = 1;
+
+/// Git status of a file or folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitFileStatus {
+    Ignored,
+    Untracked,
+    Added,
+    Renamed,
+    Deleted,
+    Modified,
+    Conflicted,
+}
+
+impl GitFileStatus {
+    /// Status from a porcelain `XY` code
+    fn from_code(index: char, worktree: char) -> Option<Self> {
+        match (index, worktree) {
+            ('?', '?') => Some(Self::Untracked),
+            ('!', '!') => Some(Self::Ignored),
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => Some(Self::Conflicted),
+            _ => {
+                // The working tree state is what the explorer shows
+                let code = if worktree != ' ' { worktree } else { index };
+                match code {
+                    'M' | 'T' => Some(Self::Modified),
+                    'A' | 'C' => Some(Self::Added),
+                    'D' => Some(Self::Deleted),
+                    'R' => Some(Self::Renamed),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// A file or folder in the explorer tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTreeEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub size: Option<u64>,
+    pub modified: Option<u64>, // Unix timestamp
+    /// Status of a file; for folders, the most significant status below it
+    pub git_status: Option<GitFileStatus>,
+    /// Children of a folder; `None` when beyond the requested depth
+    pub children: Option<Vec<FileTreeEntry>>,
+}
+
+/// Git statuses of the paths below a repository root
+#[derive(Debug, Default)]
+struct StatusMap {
+    /// Status of files, relative to the repository root
+    files: HashMap<String, GitFileStatus>,
+    /// Untracked or ignored folders reported as a whole (`dir/`)
+    folders: HashMap<String, GitFileStatus>,
+    /// Most significant status below each folder
+    aggregate: HashMap<String, GitFileStatus>,
+}
+
+impl StatusMap {
+    /// Parse `git status --porcelain=v1 -z --ignored` output
+    fn parse(output: &str) -> Self {
+        let mut map = Self::default();
+        let mut records = output.split('\0');
+
+        while let Some(record) = records.next() {
+            if record.len() < 4 {
+                continue;
+            }
+            let mut code = record.chars();
+            let index = code.next().unwrap_or(' ');
+            let worktree = code.next().unwrap_or(' ');
+            let path = &record[3..];
+            // Renames and copies are followed by the original path
+            if matches!(index, 'R' | 'C') {
+                records.next();
+            }
+
+            let Some(status) = GitFileStatus::from_code(index, worktree) else {
+                continue;
+            };
+            match path.strip_suffix('/') {
+                Some(folder) => {
+                    map.folders.insert(folder.to_string(), status);
+                    map.add_to_parents(folder, status);
+                }
+                None => {
+                    map.files.insert(path.to_string(), status);
+                    map.add_to_parents(path, status);
+                }
+            }
+        }
+
+        map
+    }
+
+    fn add_to_parents(&mut self, path: &str, status: GitFileStatus) {
+        // Ignored content does not make a folder look changed
+        if status == GitFileStatus::Ignored {
+            return;
+        }
+        let mut current = path;
+        while let Some((parent, _)) = current.rsplit_once('/') {
+            let entry = self.aggregate.entry(parent.to_string()).or_insert(status);
+            *entry = (*entry).max(status);
+            current = parent;
+        }
+        let root = self.aggregate.entry(String::new()).or_insert(status);
+        *root = (*root).max(status);
+    }
+
+    /// Status of an entry given its path relative to the repository root
+    fn status(&self, relative: &str, is_dir: bool) -> Option<GitFileStatus> {
+        // Inside an untracked or ignored folder
+        let mut current = relative;
+        loop {
+            if let Some(&status) = self.folders.get(current) {
+                return Some(status);
+            }
+            match current.rsplit_once('/') {
+                Some((parent, _)) => current = parent,
+                None => break,
+            }
+        }
+
+        if is_dir {
+            self.aggregate.get(relative).copied()
+        } else {
+            self.files.get(relative).copied()
+        }
+    }
+}
+
+/// Repository root and git statuses for the tree at `root`, if it is in a repository
+fn load_statuses(root: &Path, include_ignored: bool) -> Option<(PathBuf, StatusMap)> {
+    let toplevel = run_git(root, &["rev-parse", "--show-toplevel"]).ok()?;
+    let toplevel = PathBuf::from(toplevel.trim()).canonicalize().ok()?;
+
+    let mut args = vec!["status", "--porcelain=v1", "-z"];
+    if include_ignored {
+        args.push("--ignored");
+    }
+    args.extend(["--", "."]);
+    match run_git(root, &args) {
+        Ok(output) => Some((toplevel, StatusMap::parse(&output))),
+        Err(e) => {
+            tracing::warn!("Failed to get git status for {:?}: {}", root, e);
+            None
+        }
+    }
+}
+
+/// A walked entry before it is placed in the tree
+struct WalkedEntry {
+    path: PathBuf,
+    depth: usize,
+    entry: FileTreeEntry,
+}
+
+/// Walk `root` to `depth` levels and assemble the tree
+fn build_tree(
+    root: &Path,
+    depth: u32,
+    include_hidden: bool,
+    include_ignored: bool,
+    statuses: Option<&(PathBuf, StatusMap)>,
+) -> Vec<FileTreeEntry> {
+    let respect_ignore = !include_ignored;
+    let mut walker = WalkBuilder::new(root);
+    walker
+        .max_depth(Some(depth as usize))
+        .hidden(!include_hidden)
+        .ignore(respect_ignore)
+        .git_ignore(respect_ignore)
+        .git_global(respect_ignore)
+        .git_exclude(respect_ignore)
+        .parents(true)
+        .follow_links(false)
+        .filter_entry(|entry| entry.file_name() != ".git");
+
+    let mut walked = Vec::new();
+    for entry in walker.build().flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        let path = entry.path().to_path_buf();
+        let file_type = entry.file_type();
+        let is_dir = file_type.is_some_and(|t| t.is_dir());
+        let metadata = entry.metadata().ok();
+
+        let git_status = statuses.and_then(|(toplevel, map)| {
+            let relative = path.strip_prefix(toplevel).ok()?;
+            map.status(&relative.to_string_lossy(), is_dir)
+        });
+
+        walked.push(WalkedEntry {
+            depth: entry.depth(),
+            entry: FileTreeEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: path.to_string_lossy().to_string(),
+                is_dir,
+                is_symlink: entry.path_is_symlink(),
+                size: metadata.as_ref().filter(|m| m.is_file()).map(|m| m.len()),
+                modified: metadata.as_ref().and_then(|m| {
+                    m.modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs())
+                }),
+                git_status,
+                children: None,
+            },
+            path,
+        });
+    }
+
+    // Attach children to their folders, deepest first
+    walked.sort_by_key(|w| std::cmp::Reverse(w.depth));
+    let mut children: HashMap<PathBuf, Vec<FileTreeEntry>> = HashMap::new();
+    for WalkedEntry {
+        path,
+        depth: entry_depth,
+        mut entry,
+    } in walked
+    {
+        if entry.is_dir && entry_depth < depth as usize {
+            let mut nested = children.remove(&path).unwrap_or_default();
+            sort_entries(&mut nested);
+            entry.children = Some(nested);
+        }
+        if let Some(parent) = path.parent() {
+            children
+                .entry(parent.to_path_buf())
+                .or_default()
+                .push(entry);
+        }
+    }
+
+    let mut entries = children.remove(root).unwrap_or_default();
+    sort_entries(&mut entries);
+    entries
+}
+
+/// Directories first, then alphabetically
+fn sort_entries(entries: &mut [FileTreeEntry]) {
+    entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    });
+}
+
+/// List a directory tree for the file explorer
+///
+/// # Arguments
+/// * `path` - Directory to list
+/// * `depth` - Number of levels to load (default: 1). Folders at the last level
+///   have `children: null` and can be loaded with another call
+/// * `include_hidden` - Whether to include dotfiles (default: false)
+/// * `include_ignored` - Whether to include .gitignored entries, marked
+///   `ignored` (default: false)
+/// * `git_status` - Whether to decorate entries with git status (default: true)
+///
+/// # Returns
+/// The entries of `path`, directories first
+#[tauri::command]
+pub async fn list_directory_tree(
+    path: String,
+    depth: Option<u32>,
+    include_hidden: Option<bool>,
+    include_ignored: Option<bool>,
+    git_status: Option<bool>,
+) -> Result<Vec<FileTreeEntry>, String> {
+    let depth = depth.unwrap_or(DEFAULT_DEPTH).max(1);
+    let include_hidden = include_hidden.unwrap_or(false);
+    let include_ignored = include_ignored.unwrap_or(false);
+    tracing::debug!("Listing directory tree: {} (depth={})", path, depth);
+
+    let root = Path::new(&path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let statuses = if git_status.unwrap_or(true) {
+            load_statuses(&root, include_ignored)
+        } else {
+            None
+        };
+        build_tree(
+            &root,
+            depth,
+            include_hidden,
+            include_ignored,
+            statuses.as_ref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to list directory: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_status_from_code() {
+        use GitFileStatus::*;
+        assert_eq!(GitFileStatus::from_code('?', '?'), Some(Untracked));
+        assert_eq!(GitFileStatus::from_code('!', '!'), Some(Ignored));
+        assert_eq!(GitFileStatus::from_code('M', ' '), Some(Modified));
+        assert_eq!(GitFileStatus::from_code('A', 'M'), Some(Modified));
+        assert_eq!(GitFileStatus::from_code('A', ' '), Some(Added));
+        assert_eq!(GitFileStatus::from_code(' ', 'D'), Some(Deleted));
+        assert_eq!(GitFileStatus::from_code('R', ' '), Some(Renamed));
+        assert_eq!(GitFileStatus::from_code('U', 'U'), Some(Conflicted));
+        assert_eq!(GitFileStatus::from_code('A', 'A'), Some(Conflicted));
+    }
+
+    #[test]
+    fn test_status_map_parse_and_aggregate() {
+        use GitFileStatus::*;
+        let output = [
+            " M src/lib.rs",
+            "A  src/ui/button.rs",
+            "R  src/new.rs",
+            "src/old.rs",
+            "?? scratch/",
+            "!! target/",
+            " D docs/gone.md",
+            "",
+        ]
+        .join("\0");
+        let map = StatusMap::parse(&output);
+
+        assert_eq!(map.status("src/lib.rs", false), Some(Modified));
+        assert_eq!(map.status("src/new.rs", false), Some(Renamed));
+        assert_eq!(map.status("src/old.rs", false), None);
+        assert_eq!(map.status("src/main.rs", false), None);
+        assert_eq!(map.status("scratch/notes/a.txt", false), Some(Untracked));
+        assert_eq!(map.status("target/debug", true), Some(Ignored));
+
+        // Folders report the most significant status below them
+        assert_eq!(map.status("src", true), Some(Modified));
+        assert_eq!(map.status("src/ui", true), Some(Added));
+        assert_eq!(map.status("docs", true), Some(Deleted));
+        assert_eq!(map.status("assets", true), None);
+        assert_eq!(map.status("", true), Some(Modified));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        run_git(dir, args).unwrap();
+    }
+
+    fn find<'a>(entries: &'a [FileTreeEntry], name: &str) -> &'a FileTreeEntry {
+        entries
+            .iter()
+            .find(|e| e.name == name)
+            .unwrap_or_else(|| panic!("missing entry {}", name))
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_tree() {
+        use GitFileStatus::*;
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        git(root, &["init", "-q"]);
+        git(root, &["config", "user.name", "Test"]);
+        git(root, &["config", "user.email", "test@example.com"]);
+
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "one\n").unwrap();
+        fs::write(root.join("src/nested/deep.rs"), "deep\n").unwrap();
+        fs::write(root.join("README.md"), "readme\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "initial"]);
+
+        fs::write(root.join("src/nested/deep.rs"), "changed\n").unwrap();
+        fs::write(root.join("src/new.rs"), "new\n").unwrap();
+        fs::write(root.join("build/out.bin"), "bin\n").unwrap();
+
+        let path = root.to_string_lossy().to_string();
+        let tree = list_directory_tree(path.clone(), Some(2), None, None, None)
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = tree.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["src", "README.md"]);
+
+        let src = find(&tree, "src");
+        assert_eq!(src.git_status, Some(Modified));
+        let children = src.children.as_ref().unwrap();
+        let names: Vec<&str> = children.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["nested", "lib.rs", "new.rs"]);
+        assert_eq!(find(children, "new.rs").git_status, Some(Untracked));
+        assert_eq!(find(children, "lib.rs").git_status, None);
+
+        // Folders at the depth limit are not expanded but still aggregate
+        let nested = find(children, "nested");
+        assert!(nested.children.is_none());
+        assert_eq!(nested.git_status, Some(Modified));
+
+        // Hidden and ignored entries are opt-in
+        let tree = list_directory_tree(path, Some(1), Some(true), Some(true), None)
+            .await
+            .unwrap();
+        assert_eq!(find(&tree, "build").git_status, Some(Ignored));
+        assert!(tree.iter().any(|e| e.name == ".gitignore"));
+        assert!(!tree.iter().any(|e| e.name == ".git"));
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_tree_outside_git() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("a.txt"), "a").unwrap();
+
+        let tree = list_directory_tree(
+            temp.path().to_string_lossy().to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].git_status, None);
+        assert_eq!(tree[0].size, Some(1));
+    }
+}
@@ -0,0 +1,798 @@
+//! File Watching
+//!
+//! Shared, debounced watchers behind `watch_path`. One OS watcher serves each
+//! watched root: subscriptions for the same path, or for paths inside a
+//! recursively watched root, share it. Raw `notify` events are coalesced per
+//! path until the root has been quiet for [`DEBOUNCE`] (or for at most
+//! [`MAX_LATENCY`] during a continuous burst), then delivered to each
+//! subscriber as a single batch, after dropping paths matched by `.gitignore`
+//! and the subscriber's glob filters.
+//!
+//! # Events
+//!
+//! `file-watch-batch` carries a [`FileWatchBatch`]. When a burst touches too
+//! many paths to report one by one, or the OS reports dropped events, the batch
+//! is sent with `overflow: true` and no events; subscribers should rescan.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::Override;
+use ignore::Match;
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::file_finder::FileFinderState;
+use crate::fs::FileWatchEvent;
+use crate::search::build_overrides;
+use crate::search_index::SearchIndexState;
+
+/// Event carrying a [`FileWatchBatch`]
+pub const FILE_WATCH_BATCH_EVENT: &str = "file-watch-batch";
+
+/// Quiet period after the last raw event before a batch is delivered
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Longest a change is held back while events keep arriving
+const MAX_LATENCY: Duration = Duration::from_secs(1);
+
+/// Distinct paths collected for one batch before it is reported as overflow
+const MAX_BATCH_PATHS: usize = 10_000;
+
+/// Options for a watch subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchOptions {
+    /// Drop changes to paths ignored by `.gitignore` or `.git/info/exclude`.
+    /// Defaults to true for recursive watches and false otherwise, so a
+    /// watched ignored file (e.g. `.env`) still reports changes.
+    pub respect_gitignore: Option<bool>,
+    /// Only report paths matching this glob
+    pub include_pattern: Option<String>,
+    /// Never report paths matching this glob
+    pub exclude_pattern: Option<String>,
+}
+
+/// Coalesced changes delivered to one subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileWatchBatch {
+    pub watch_id: String,
+    pub events: Vec<FileWatchEvent>,
+    /// Changes were dropped; the subscriber should rescan the watched path
+    pub overflow: bool,
+}
+
+/// An OS watcher and the subscriptions it serves
+pub struct RootWatcher {
+    watcher: RecommendedWatcher,
+    recursive: bool,
+    subscribers: Arc<Mutex<HashMap<String, Subscriber>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Create,
+    Modify,
+    Delete,
+    Rename,
+}
+
+impl ChangeKind {
+    fn from_event(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(ChangeKind::Create),
+            EventKind::Modify(notify::event::ModifyKind::Name(_)) => Some(ChangeKind::Rename),
+            EventKind::Modify(_) => Some(ChangeKind::Modify),
+            EventKind::Remove(_) => Some(ChangeKind::Delete),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Create => "create",
+            ChangeKind::Modify => "modify",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Rename => "rename",
+        }
+    }
+
+    /// Combine two changes to the same path; `None` if they cancel out
+    fn then(self, next: ChangeKind) -> Option<ChangeKind> {
+        match (self, next) {
+            (ChangeKind::Create, ChangeKind::Modify) => Some(ChangeKind::Create),
+            (ChangeKind::Create, ChangeKind::Delete) => None,
+            // Replaced by a new file, as editors and atomic writers do
+            (ChangeKind::Delete, ChangeKind::Create) => Some(ChangeKind::Modify),
+            (_, next) => Some(next),
+        }
+    }
+}
+
+/// Changes collected for the next batch, merged per path
+#[derive(Debug, Default)]
+struct PendingChanges {
+    changes: BTreeMap<PathBuf, ChangeKind>,
+    overflow: bool,
+}
+
+impl PendingChanges {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty() && !self.overflow
+    }
+
+    fn add_event(&mut self, result: notify::Result<Event>) {
+        match result {
+            Ok(event) if event.need_rescan() => self.set_overflow(),
+            Ok(event) => {
+                let Some(kind) = ChangeKind::from_event(&event.kind) else {
+                    return;
+                };
+                for path in event.paths {
+                    self.add(path, kind);
+                }
+            }
+            Err(e) => tracing::warn!("File watch error: {}", e),
+        }
+    }
+
+    fn add(&mut self, path: PathBuf, kind: ChangeKind) {
+        if self.overflow {
+            return;
+        }
+        let full = self.changes.len() >= MAX_BATCH_PATHS;
+        match self.changes.entry(path) {
+            std::collections::btree_map::Entry::Occupied(mut entry) => {
+                match entry.get().then(kind) {
+                    Some(merged) => *entry.get_mut() = merged,
+                    None => {
+                        entry.remove();
+                    }
+                }
+            }
+            std::collections::btree_map::Entry::Vacant(_) if full => self.set_overflow(),
+            std::collections::btree_map::Entry::Vacant(entry) => {
+                entry.insert(kind);
+            }
+        }
+    }
+
+    fn set_overflow(&mut self) {
+        self.overflow = true;
+        self.changes.clear();
+    }
+}
+
+/// A subscription to changes under `path`
+struct Subscriber {
+    /// Canonical watched path, which raw event paths are matched against
+    path: PathBuf,
+    /// The watched path as the subscriber gave it; reported paths are rebased
+    /// onto it so they compare equal to paths the subscriber already has
+    requested: PathBuf,
+    recursive: bool,
+    respect_gitignore: bool,
+    overrides: Option<Override>,
+}
+
+impl Subscriber {
+    fn new(path: &Path, requested: &Path, recursive: bool, options: &WatchOptions) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            requested: requested.to_path_buf(),
+            recursive,
+            respect_gitignore: options.respect_gitignore.unwrap_or(recursive),
+            overrides: build_overrides(
+                &path.to_string_lossy(),
+                options.include_pattern.as_deref(),
+                options.exclude_pattern.as_deref(),
+            ),
+        }
+    }
+
+    fn accepts(&self, path: &Path, ignore: &mut IgnoreFilter) -> bool {
+        let covered = if self.recursive {
+            path.starts_with(&self.path)
+        } else {
+            path == self.path || path.parent() == Some(self.path.as_path())
+        };
+        if !covered {
+            return false;
+        }
+        if let Some(overrides) = &self.overrides {
+            if overrides.matched(path, path.is_dir()).is_ignore() {
+                return false;
+            }
+        }
+        !self.respect_gitignore || !ignore.is_ignored(path)
+    }
+
+    /// A canonical event path as seen from the requested path
+    fn rebase(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.path) {
+            Ok(relative) if relative.as_os_str().is_empty() => self.requested.clone(),
+            Ok(relative) => self.requested.join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    fn batch(
+        &self,
+        watch_id: &str,
+        pending: &PendingChanges,
+        ignore: &mut IgnoreFilter,
+    ) -> Option<FileWatchBatch> {
+        let events: Vec<FileWatchEvent> = pending
+            .changes
+            .iter()
+            .filter(|(path, _)| self.accepts(path, ignore))
+            .map(|(path, kind)| FileWatchEvent {
+                watch_id: watch_id.to_string(),
+                path: self.rebase(path).to_string_lossy().to_string(),
+                kind: kind.as_str().to_string(),
+            })
+            .collect();
+
+        (pending.overflow || !events.is_empty()).then(|| FileWatchBatch {
+            watch_id: watch_id.to_string(),
+            events,
+            overflow: pending.overflow,
+        })
+    }
+}
+
+/// `.gitignore` rules for the repository containing a watched root, loaded
+/// per directory on demand. Global excludes are not consulted.
+struct IgnoreFilter {
+    repo_root: Option<PathBuf>,
+    /// Rules from `.git/info/exclude`
+    exclude: Option<Gitignore>,
+    /// Parsed `.gitignore` by directory; `None` if the directory has none
+    dirs: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl IgnoreFilter {
+    fn new(root: &Path) -> Self {
+        let repo_root = root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf);
+        let exclude = repo_root
+            .as_deref()
+            .and_then(|repo| load_gitignore(repo, &repo.join(".git/info/exclude")));
+        Self {
+            repo_root,
+            exclude,
+            dirs: HashMap::new(),
+        }
+    }
+
+    /// Drop cached rules if `path` is a `.gitignore` file
+    fn invalidate(&mut self, path: &Path) {
+        if path.file_name().is_some_and(|name| name == ".gitignore") {
+            if let Some(dir) = path.parent() {
+                self.dirs.remove(dir);
+            }
+        }
+    }
+
+    fn is_ignored(&mut self, path: &Path) -> bool {
+        let Some(repo_root) = &self.repo_root else {
+            return false;
+        };
+        let Ok(relative) = path.strip_prefix(repo_root) else {
+            return false;
+        };
+        // Git never applies ignore rules to its own directory
+        if relative.starts_with(".git") {
+            return false;
+        }
+
+        let is_dir = path.is_dir();
+        // Deeper .gitignore files take precedence over shallower ones
+        for dir in path.ancestors().skip(1) {
+            if !dir.starts_with(repo_root) {
+                break;
+            }
+            let gitignore = self
+                .dirs
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| load_gitignore(dir, &dir.join(".gitignore")));
+            if let Some(gitignore) = gitignore {
+                match gitignore.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        self.exclude.as_ref().is_some_and(|exclude| {
+            exclude
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+        })
+    }
+}
+
+fn load_gitignore(dir: &Path, file: &Path) -> Option<Gitignore> {
+    if !file.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    if let Some(e) = builder.add(file) {
+        tracing::warn!("Failed to read {}: {}", file.display(), e);
+    }
+    builder
+        .build()
+        .ok()
+        .filter(|gitignore| !gitignore.is_empty())
+}
+
+/// Subscribe `watch_id` to changes under `path` (canonical), sharing an
+/// existing watcher on the same path or on a recursively watched ancestor.
+/// Events are reported under `requested`, the path as the caller gave it.
+pub fn subscribe(
+    app: &AppHandle,
+    watchers: &mut HashMap<PathBuf, RootWatcher>,
+    watch_id: &str,
+    path: &Path,
+    requested: &Path,
+    recursive: bool,
+    options: &WatchOptions,
+) -> Result<(), String> {
+    let subscriber = Subscriber::new(path, requested, recursive, options);
+
+    let shared_root = watchers
+        .iter()
+        .find(|(root, watcher)| *root == path || (watcher.recursive && path.starts_with(root)))
+        .map(|(root, _)| root.clone());
+
+    if let Some(root) = shared_root {
+        let root_watcher = watchers
+            .get_mut(&root)
+            .ok_or_else(|| "File watcher disappeared".to_string())?;
+        if recursive && !root_watcher.recursive {
+            let _ = root_watcher.watcher.unwatch(&root);
+            root_watcher
+                .watcher
+                .watch(&root, RecursiveMode::Recursive)
+                .map_err(|e| format!("Failed to start watching: {}", e))?;
+            root_watcher.recursive = true;
+        }
+        tracing::debug!(
+            "Sharing watcher on {} for {}",
+            root.display(),
+            path.display()
+        );
+        root_watcher
+            .subscribers
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(watch_id.to_string(), subscriber);
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = RecommendedWatcher::new(tx, Config::default())
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher
+        .watch(path, mode)
+        .map_err(|e| format!("Failed to start watching: {}", e))?;
+
+    let subscribers = Arc::new(Mutex::new(HashMap::from([(
+        watch_id.to_string(),
+        subscriber,
+    )])));
+
+    // Spawn thread to batch events (use Builder to handle spawn failure gracefully)
+    let app = app.clone();
+    let root = path.to_path_buf();
+    let thread_subscribers = subscribers.clone();
+    std::thread::Builder::new()
+        .name(format!("file-watch-{}", &watch_id[..watch_id.len().min(8)]))
+        .spawn(move || run_batches(app, root, rx, thread_subscribers))
+        .map_err(|e| format!("Failed to spawn file watch thread: {}", e))?;
+
+    watchers.insert(
+        path.to_path_buf(),
+        RootWatcher {
+            watcher,
+            recursive,
+            subscribers,
+        },
+    );
+    Ok(())
+}
+
+/// Remove a subscription, dropping its OS watcher when no subscribers remain
+pub fn unsubscribe(watchers: &mut HashMap<PathBuf, RootWatcher>, watch_id: &str) -> bool {
+    let mut emptied = None;
+    let mut found = false;
+    for (root, watcher) in watchers.iter() {
+        let mut subscribers = watcher
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if subscribers.remove(watch_id).is_some() {
+            found = true;
+            if subscribers.is_empty() {
+                emptied = Some(root.clone());
+            }
+            break;
+        }
+    }
+    // Dropping the watcher closes its channel, which ends the batching thread
+    if let Some(root) = emptied {
+        watchers.remove(&root);
+    }
+    found
+}
+
+/// Collect raw events into batches until the watcher is dropped
+fn run_batches(
+    app: AppHandle,
+    root: PathBuf,
+    rx: Receiver<notify::Result<Event>>,
+    subscribers: Arc<Mutex<HashMap<String, Subscriber>>>,
+) {
+    let mut ignore = IgnoreFilter::new(&root);
+    let mut pending = PendingChanges::default();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        let received = match batch_started {
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(started) => {
+                rx.recv_timeout(MAX_LATENCY.saturating_sub(started.elapsed()).min(DEBOUNCE))
+            }
+        };
+        let disconnected = matches!(received, Err(RecvTimeoutError::Disconnected));
+        if let Ok(result) = received {
+            pending.add_event(result);
+            let started = *batch_started.get_or_insert_with(Instant::now);
+            if started.elapsed() < MAX_LATENCY {
+                continue;
+            }
+        }
+
+        if !pending.is_empty() {
+            deliver(&app, &root, &mut ignore, &subscribers, &pending);
+            pending = PendingChanges::default();
+        }
+        batch_started = None;
+        if disconnected {
+            break;
+        }
+    }
+}
+
+fn deliver(
+    app: &AppHandle,
+    root: &Path,
+    ignore: &mut IgnoreFilter,
+    subscribers: &Mutex<HashMap<String, Subscriber>>,
+    pending: &PendingChanges,
+) {
+    let paths: Vec<PathBuf> = pending.changes.keys().cloned().collect();
+    for path in &paths {
+        ignore.invalidate(path);
+    }
+
+    // Keep trigram indexes and quick-open file lists fresh. Indexes check
+    // file freshness on use, so only the file lists need dropping on overflow.
+    if let Some(index_state) = app.try_state::<SearchIndexState>() {
+        index_state.apply_changes(&paths);
+    }
+    if let Some(finder_state) = app.try_state::<FileFinderState>() {
+        if pending.overflow {
+            finder_state.invalidate(root);
+        } else {
+            finder_state.apply_changes(&paths);
+        }
+    }
+
+    let Ok(subscribers) = subscribers.lock() else {
+        return;
+    };
+    for (watch_id, subscriber) in subscribers.iter() {
+        if let Some(batch) = subscriber.batch(watch_id, pending, ignore) {
+            let _ = app.emit(FILE_WATCH_BATCH_EVENT, &batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn pending<P: AsRef<Path>>(changes: &[(P, ChangeKind)]) -> PendingChanges {
+        let mut pending = PendingChanges::default();
+        for (path, kind) in changes {
+            pending.add(path.as_ref().to_path_buf(), *kind);
+        }
+        pending
+    }
+
+    #[test]
+    fn test_pending_changes_coalesce() {
+        let pending = pending(&[
+            ("/repo/new.rs", ChangeKind::Create),
+            ("/repo/new.rs", ChangeKind::Modify),
+            ("/repo/new.rs", ChangeKind::Modify),
+            ("/repo/tmp.rs", ChangeKind::Create),
+            ("/repo/tmp.rs", ChangeKind::Delete),
+            ("/repo/saved.rs", ChangeKind::Delete),
+            ("/repo/saved.rs", ChangeKind::Create),
+            ("/repo/old.rs", ChangeKind::Modify),
+            ("/repo/old.rs", ChangeKind::Delete),
+        ]);
+
+        let changes: Vec<_> = pending
+            .changes
+            .iter()
+            .map(|(path, kind)| (path.to_str().unwrap(), kind.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("/repo/new.rs", "create"),
+                ("/repo/old.rs", "delete"),
+                ("/repo/saved.rs", "modify"),
+            ]
+        );
+        assert!(!pending.overflow);
+    }
+
+    #[test]
+    fn test_pending_changes_overflow() {
+        let mut pending = PendingChanges::default();
+        for i in 0..MAX_BATCH_PATHS {
+            pending.add(PathBuf::from(format!("/repo/{}", i)), ChangeKind::Create);
+        }
+        // Repeated paths never overflow
+        pending.add(PathBuf::from("/repo/0"), ChangeKind::Modify);
+        assert!(!pending.overflow);
+
+        pending.add(PathBuf::from("/repo/one-too-many"), ChangeKind::Create);
+        assert!(pending.overflow);
+        assert!(pending.changes.is_empty());
+        assert!(!pending.is_empty());
+
+        pending.add(PathBuf::from("/repo/later"), ChangeKind::Create);
+        assert!(pending.changes.is_empty());
+    }
+
+    #[test]
+    fn test_pending_changes_rescan_is_overflow() {
+        let mut pending = PendingChanges::default();
+        pending.add(PathBuf::from("/repo/a"), ChangeKind::Create);
+        pending.add_event(Ok(
+            Event::new(EventKind::Other).set_flag(notify::event::Flag::Rescan)
+        ));
+        assert!(pending.overflow);
+    }
+
+    #[test]
+    fn test_change_kind_from_event() {
+        use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
+
+        let kind = |k| ChangeKind::from_event(&k).map(ChangeKind::as_str);
+        assert_eq!(kind(EventKind::Create(CreateKind::File)), Some("create"));
+        assert_eq!(kind(EventKind::Modify(ModifyKind::Any)), Some("modify"));
+        assert_eq!(
+            kind(EventKind::Modify(ModifyKind::Name(RenameMode::To))),
+            Some("rename")
+        );
+        assert_eq!(kind(EventKind::Remove(RemoveKind::File)), Some("delete"));
+        assert_eq!(
+            kind(EventKind::Access(notify::event::AccessKind::Any)),
+            None
+        );
+    }
+
+    fn sandbox_repo() -> (tempfile::TempDir, PathBuf) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(repo.join(".git/info")).unwrap();
+        fs::create_dir_all(repo.join("target/debug")).unwrap();
+        fs::create_dir_all(repo.join("web/dist")).unwrap();
+        fs::write(repo.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(repo.join("web/.gitignore"), "dist/\n!keep.log\n").unwrap();
+        fs::write(repo.join(".git/info/exclude"), "scratch.txt\n").unwrap();
+        (temp_dir, repo)
+    }
+
+    #[test]
+    fn test_ignore_filter() {
+        let (_temp_dir, repo) = sandbox_repo();
+        let mut ignore = IgnoreFilter::new(&repo);
+
+        assert!(ignore.is_ignored(&repo.join("target/debug/build.rs")));
+        assert!(ignore.is_ignored(&repo.join("target")));
+        assert!(ignore.is_ignored(&repo.join("build.log")));
+        assert!(ignore.is_ignored(&repo.join("web/dist/app.js")));
+        assert!(ignore.is_ignored(&repo.join("scratch.txt")));
+        assert!(!ignore.is_ignored(&repo.join("web/keep.log")));
+        assert!(!ignore.is_ignored(&repo.join("src/main.rs")));
+        assert!(!ignore.is_ignored(&repo.join(".git/HEAD")));
+    }
+
+    #[test]
+    fn test_ignore_filter_reloads_changed_gitignore() {
+        let (_temp_dir, repo) = sandbox_repo();
+        let mut ignore = IgnoreFilter::new(&repo);
+        assert!(!ignore.is_ignored(&repo.join("notes.md")));
+
+        fs::write(repo.join(".gitignore"), "*.md\n").unwrap();
+        ignore.invalidate(&repo.join(".gitignore"));
+
+        assert!(ignore.is_ignored(&repo.join("notes.md")));
+        assert!(!ignore.is_ignored(&repo.join("target/debug/build.rs")));
+    }
+
+    #[test]
+    fn test_ignore_filter_outside_git_repo() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+        let mut ignore = IgnoreFilter::new(&root);
+        assert!(!ignore.is_ignored(&root.join("build.log")));
+    }
+
+    #[test]
+    fn test_subscriber_batches() {
+        let (_temp_dir, repo) = sandbox_repo();
+        let mut ignore = IgnoreFilter::new(&repo);
+        let path = |p: &str| repo.join(p).to_string_lossy().to_string();
+        let pending = pending(&[
+            (path("src/main.rs"), ChangeKind::Modify),
+            (path("src/lib.rs"), ChangeKind::Create),
+            (path("src/nested/mod.rs"), ChangeKind::Modify),
+            (path("target/debug/out"), ChangeKind::Create),
+            (path("README.md"), ChangeKind::Modify),
+        ]);
+        let event_paths = |batch: Option<FileWatchBatch>| -> Vec<String> {
+            let batch = batch.expect("batch");
+            assert!(!batch.overflow);
+            batch.events.into_iter().map(|e| e.path).collect()
+        };
+
+        // Recursive watches respect .gitignore by default
+        let all = Subscriber::new(&repo, &repo, true, &WatchOptions::default());
+        assert_eq!(
+            event_paths(all.batch("a", &pending, &mut ignore)),
+            vec![
+                path("README.md"),
+                path("src/lib.rs"),
+                path("src/main.rs"),
+                path("src/nested/mod.rs"),
+            ]
+        );
+
+        let unfiltered = Subscriber::new(
+            &repo,
+            &repo,
+            true,
+            &WatchOptions {
+                respect_gitignore: Some(false),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            unfiltered
+                .batch("b", &pending, &mut ignore)
+                .unwrap()
+                .events
+                .len(),
+            5
+        );
+
+        let src_only = Subscriber::new(
+            &repo.join("src"),
+            &repo.join("src"),
+            false,
+            &WatchOptions::default(),
+        );
+        assert_eq!(
+            event_paths(src_only.batch("c", &pending, &mut ignore)),
+            vec![path("src/lib.rs"), path("src/main.rs")]
+        );
+
+        let globbed = Subscriber::new(
+            &repo,
+            &repo,
+            true,
+            &WatchOptions {
+                include_pattern: Some("*.rs".to_string()),
+                exclude_pattern: Some("**/nested/**".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            event_paths(globbed.batch("d", &pending, &mut ignore)),
+            vec![path("src/lib.rs"), path("src/main.rs")]
+        );
+
+        let docs = Subscriber::new(
+            &repo.join("docs"),
+            &repo.join("docs"),
+            true,
+            &WatchOptions::default(),
+        );
+        assert!(docs.batch("e", &pending, &mut ignore).is_none());
+    }
+
+    #[test]
+    fn test_subscriber_reports_requested_paths() {
+        let mut ignore = IgnoreFilter {
+            repo_root: None,
+            exclude: None,
+            dirs: HashMap::new(),
+        };
+        let pending = pending(&[
+            ("/private/tmp/work".to_string(), ChangeKind::Modify),
+            ("/private/tmp/work/src/a.rs".to_string(), ChangeKind::Create),
+        ]);
+
+        // e.g. a watch on /tmp/work where /tmp links to /private/tmp
+        let subscriber = Subscriber::new(
+            Path::new("/private/tmp/work"),
+            Path::new("/tmp/work"),
+            true,
+            &WatchOptions::default(),
+        );
+        let batch = subscriber.batch("watch-1", &pending, &mut ignore).unwrap();
+        let paths: Vec<String> = batch.events.into_iter().map(|e| e.path).collect();
+        assert_eq!(paths, vec!["/tmp/work", "/tmp/work/src/a.rs"]);
+    }
+
+    #[test]
+    fn test_subscriber_overflow_batch() {
+        let mut pending = PendingChanges::default();
+        pending.set_overflow();
+        let mut ignore = IgnoreFilter {
+            repo_root: None,
+            exclude: None,
+            dirs: HashMap::new(),
+        };
+
+        let subscriber = Subscriber::new(
+            Path::new("/repo"),
+            Path::new("/repo"),
+            true,
+            &WatchOptions::default(),
+        );
+        let batch = subscriber.batch("watch-1", &pending, &mut ignore).unwrap();
+        assert!(batch.overflow);
+        assert!(batch.events.is_empty());
+        assert_eq!(batch.watch_id, "watch-1");
+    }
+
+    #[test]
+    fn test_file_watch_batch_serialization() {
+        let batch = FileWatchBatch {
+            watch_id: "watch-1".to_string(),
+            events: vec![FileWatchEvent {
+                watch_id: "watch-1".to_string(),
+                path: "/repo/a.rs".to_string(),
+                kind: "modify".to_string(),
+            }],
+            overflow: false,
+        };
+        let json = serde_json::to_value(&batch).unwrap();
+        assert_eq!(json["watch_id"], "watch-1");
+        assert_eq!(json["events"][0]["kind"], "modify");
+        assert_eq!(json["overflow"], false);
+    }
+}
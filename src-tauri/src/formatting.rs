@@ -0,0 +1,270 @@
+//! Code Formatting
+//!
+//! Formats files with the lane's formatter: a command-line formatter
+//! configured for the file's extension (e.g. `rustfmt`, `prettier`), or
+//! otherwise the first of the lane's language servers that can format the
+//! file. Lanes with `formatOnSave` enabled run this from `write_file`.
+
+use codelane_core::config::EditorConfig;
+use codelane_lsp::lsp_types::FormattingOptions;
+use codelane_lsp::LspManager;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::State;
+use tokio::io::AsyncWriteExt;
+
+use crate::lane::{Lane, LaneState};
+use crate::lsp::LspState;
+
+/// How long a command-line formatter may run
+const FORMATTER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A command-line formatter that reads the file on stdin and writes the
+/// formatted file to stdout
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterConfig {
+    /// File extensions to format, without the dot
+    pub extensions: Vec<String>,
+
+    /// Program to run, e.g. "prettier"
+    pub command: String,
+
+    /// Arguments; `{path}` is replaced with the path of the file
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl FormatterConfig {
+    fn handles(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e == ext))
+    }
+}
+
+fn formatting_options(tab_size: Option<u32>, insert_spaces: Option<bool>) -> FormattingOptions {
+    let defaults = EditorConfig::default();
+    FormattingOptions {
+        tab_size: tab_size.unwrap_or(defaults.tab_size),
+        insert_spaces: insert_spaces.unwrap_or(defaults.insert_spaces),
+        ..Default::default()
+    }
+}
+
+/// Run a command-line formatter on `text` in the lane's working directory
+async fn run_formatter(
+    formatter: &FormatterConfig,
+    working_dir: &Path,
+    path: &Path,
+    text: &str,
+) -> Result<String, String> {
+    let path = path.to_string_lossy();
+    let args = formatter
+        .args
+        .iter()
+        .map(|arg| arg.replace("{path}", &path));
+    let mut child = tokio::process::Command::new(&formatter.command)
+        .args(args)
+        .current_dir(working_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", formatter.command, e))?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = text.to_string();
+    let writer = tokio::spawn(async move {
+        // The formatter may exit early on a syntax error, closing its stdin
+        let _ = stdin.write_all(input.as_bytes()).await;
+    });
+
+    let output = tokio::time::timeout(FORMATTER_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| format!("{} timed out", formatter.command))?
+        .map_err(|e| format!("Failed to run {}: {}", formatter.command, e))?;
+    let _ = writer.await;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            formatter.command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| format!("{} produced invalid UTF-8", formatter.command))
+}
+
+/// Format `text` as the contents of `path` with the lane's formatter
+///
+/// Returns `None` when the lane has no formatter for the file.
+async fn format_text(
+    lane: &Lane,
+    manager: &LspManager,
+    path: &Path,
+    text: String,
+    options: FormattingOptions,
+) -> Result<Option<String>, String> {
+    if let Some(formatter) = lane.config.formatters.iter().find(|f| f.handles(path)) {
+        let working_dir = Path::new(&lane.working_dir);
+        return run_formatter(formatter, working_dir, path, &text)
+            .await
+            .map(Some);
+    }
+    manager
+        .format_document(&lane.id, path, text, options)
+        .await
+        .map_err(|e| format!("Formatting failed: {}", e))
+}
+
+/// Contents to save for `path`: formatted if the lane formats on save
+///
+/// A failing formatter never blocks the save; the contents are written as
+/// they are and the failure is logged.
+pub(crate) async fn format_on_save(
+    lane: &Lane,
+    manager: &LspManager,
+    path: &Path,
+    contents: String,
+) -> Option<String> {
+    if !lane.config.format_on_save {
+        return None;
+    }
+    match format_text(
+        lane,
+        manager,
+        path,
+        contents,
+        formatting_options(None, None),
+    )
+    .await
+    {
+        Ok(formatted) => formatted,
+        Err(e) => {
+            tracing::warn!("Format on save failed for {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Format a document with the lane's formatter
+///
+/// # Arguments
+/// * `lane_id` - Lane the file belongs to
+/// * `path` - Absolute path of the file
+/// * `text` - Contents to format, usually the editor buffer
+/// * `tab_size` - Indentation width for language servers (default: 4)
+/// * `insert_spaces` - Indent with spaces for language servers (default: true)
+///
+/// # Returns
+/// The formatted text, or `None` if the lane has no formatter for the file
+#[tauri::command]
+pub async fn format_document(
+    lane_id: String,
+    path: String,
+    text: String,
+    tab_size: Option<u32>,
+    insert_spaces: Option<bool>,
+    lanes: State<'_, LaneState>,
+    lsp: State<'_, LspState>,
+) -> Result<Option<String>, String> {
+    let lane = {
+        let lanes = lanes.lanes.lock().unwrap();
+        lanes
+            .get(&lane_id)
+            .cloned()
+            .ok_or_else(|| format!("Lane not found: {}", lane_id))?
+    };
+    let options = formatting_options(tab_size, insert_spaces);
+    format_text(&lane, &lsp.manager, Path::new(&path), text, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lane_with(formatters: Vec<FormatterConfig>, format_on_save: bool) -> Lane {
+        let temp_dir = std::env::temp_dir();
+        let mut lane = Lane::new("test".to_string(), temp_dir.to_string_lossy().to_string());
+        lane.config.formatters = formatters;
+        lane.config.format_on_save = format_on_save;
+        lane
+    }
+
+    #[test]
+    fn test_formatter_handles_extensions() {
+        let formatter = FormatterConfig {
+            extensions: vec!["ts".to_string(), "tsx".to_string()],
+            command: "prettier".to_string(),
+            args: vec![],
+        };
+        assert!(formatter.handles(Path::new("/src/app.tsx")));
+        assert!(!formatter.handles(Path::new("/src/app.rs")));
+        assert!(!formatter.handles(Path::new("/src/Makefile")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_line_formatter() {
+        let formatter = FormatterConfig {
+            extensions: vec!["txt".to_string()],
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "tr a-z A-Z; echo \"# $0\"".to_string(),
+                "{path}".to_string(),
+            ],
+        };
+        let lane = lane_with(vec![formatter], true);
+        let manager = LspManager::new();
+
+        let formatted =
+            format_on_save(&lane, &manager, Path::new("/x/notes.txt"), "hi\n".into()).await;
+        assert_eq!(formatted.as_deref(), Some("HI\n# /x/notes.txt\n"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_format_on_save_keeps_contents_on_failure() {
+        let formatter = FormatterConfig {
+            extensions: vec!["txt".to_string()],
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "echo broken >&2; exit 1".to_string()],
+        };
+        let lane = lane_with(vec![formatter.clone()], true);
+        let manager = LspManager::new();
+        let path = Path::new("/x/notes.txt");
+
+        assert_eq!(
+            format_on_save(&lane, &manager, path, "hi".into()).await,
+            None
+        );
+        let err = run_formatter(&formatter, &std::env::temp_dir(), path, "hi")
+            .await
+            .unwrap_err();
+        assert_eq!(err, "sh failed: broken");
+    }
+
+    #[tokio::test]
+    async fn test_format_on_save_disabled_or_unformatted() {
+        let manager = LspManager::new();
+        let path = Path::new("/x/main.rs");
+
+        let lane = lane_with(vec![], false);
+        assert_eq!(
+            format_on_save(&lane, &manager, path, "fn main(){}".into()).await,
+            None
+        );
+        // No formatter configured and no language server running
+        let lane = lane_with(vec![], true);
+        assert_eq!(
+            format_on_save(&lane, &manager, path, "fn main(){}".into()).await,
+            None
+        );
+    }
+}
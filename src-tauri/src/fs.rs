@@ -4,13 +4,21 @@
 //! While Tauri provides built-in FS plugins, these commands offer
 //! additional functionality specific to Codelane's needs.
 
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
-use std::sync::mpsc;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, State};
+
+use crate::file_watch::{self, RootWatcher, WatchOptions};
+use crate::formatting;
+use crate::lane::LaneState;
+use crate::lsp::LspState;
+use crate::text_file::{self, LineEnding};
+
+/// Prefix of the error returned when a write is rejected because the file
+/// changed on disk since it was read
+pub const WRITE_CONFLICT_ERROR: &str = "Write conflict";
 
 /// File entry information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,12 +47,62 @@ pub struct FileStats {
     pub size: u64,
 }
 
+/// Version of a file on disk, used to detect changes made by others
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileVersion {
+    pub modified: Option<u64>, // Unix timestamp in milliseconds
+    pub size: u64,
+    pub hash: String, // SHA-256 of the raw contents
+}
+
+/// Text file contents, with what is needed to save the file back unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextFileContents {
+    pub contents: String,
+    pub encoding: String, // WHATWG name, e.g. "UTF-8", "UTF-16LE", "windows-1252"
+    pub has_bom: bool,
+    pub line_ending: Option<LineEnding>, // None if the file has no line breaks
+    pub version: FileVersion,
+}
+
+/// Result of `write_file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFile {
+    #[serde(flatten)]
+    pub version: FileVersion,
+    /// Contents as written, if format on save changed them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+}
+
+/// Options for `write_file`; unset fields keep the existing file's format
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteFileOptions {
+    /// Encoding label, e.g. "utf-8", "utf-16le", "shift_jis"
+    pub encoding: Option<String>,
+    /// Write a BOM (UTF-8 and UTF-16 only)
+    pub with_bom: Option<bool>,
+    /// Convert every line break to this style; by default unchanged lines
+    /// keep theirs and new lines follow the file's dominant style
+    pub line_ending: Option<LineEnding>,
+    /// Reject the write unless the file still has this hash
+    pub expected_hash: Option<String>,
+    /// Reject the write unless the file still has this modification time
+    /// (`FileVersion::modified`, in milliseconds)
+    pub expected_modified: Option<u64>,
+    /// Reject the write unless the file still has this size; pair it with
+    /// `expected_modified` to catch edits made within the same millisecond
+    pub expected_size: Option<u64>,
+}
+
 /// Watch handle identifier
 pub type WatchId = String;
 
 /// Managed state for file watchers
 pub struct FileWatchState {
-    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    /// Shared OS watchers by canonical root
+    watchers: Mutex<HashMap<PathBuf, RootWatcher>>,
 }
 
 impl FileWatchState {
@@ -55,43 +113,240 @@ impl FileWatchState {
     }
 }
 
-/// Read a file's contents
+fn modified_secs(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+fn modified_millis(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .and_then(|d| u64::try_from(d.as_millis()).ok())
+}
+
+fn file_version(path: &Path, bytes: &[u8]) -> FileVersion {
+    FileVersion {
+        modified: std::fs::metadata(path)
+            .ok()
+            .and_then(|m| modified_millis(&m)),
+        size: bytes.len() as u64,
+        hash: text_file::content_hash(bytes),
+    }
+}
+
+/// Read and decode a text file; without an explicit encoding, `reject_binary`
+/// refuses files that look binary
+fn read_text(
+    path: &Path,
+    encoding: Option<&str>,
+    reject_binary: bool,
+) -> Result<TextFileContents, String> {
+    let encoding = encoding.map(text_file::encoding_for_label).transpose()?;
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    if reject_binary && encoding.is_none() && text_file::is_binary(&bytes) {
+        return Err("Failed to read file: File appears to be binary".to_string());
+    }
+    let decoded =
+        text_file::decode(&bytes, encoding).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    Ok(TextFileContents {
+        line_ending: LineEnding::detect(&decoded.text),
+        encoding: decoded.encoding.name().to_string(),
+        has_bom: decoded.has_bom,
+        version: file_version(path, &bytes),
+        contents: decoded.text,
+    })
+}
+
+/// Reject the write if the file no longer matches what the caller read
+fn check_unchanged(
+    path: &Path,
+    existing: Option<&[u8]>,
+    options: &WriteFileOptions,
+) -> Result<(), String> {
+    let conflict = || {
+        format!(
+            "{}: {} changed on disk since it was read",
+            WRITE_CONFLICT_ERROR,
+            path.display()
+        )
+    };
+
+    if let Some(expected) = &options.expected_hash {
+        match existing {
+            Some(bytes) if text_file::content_hash(bytes) == *expected => {}
+            _ => return Err(conflict()),
+        }
+    }
+    if options.expected_modified.is_some() || options.expected_size.is_some() {
+        let metadata = std::fs::metadata(path).ok();
+        let modified = metadata.as_ref().and_then(modified_millis);
+        let size = metadata.as_ref().map(|m| m.len());
+        if options
+            .expected_modified
+            .is_some_and(|expected| modified != Some(expected))
+            || options
+                .expected_size
+                .is_some_and(|expected| size != Some(expected))
+        {
+            return Err(conflict());
+        }
+    }
+    Ok(())
+}
+
+fn write_text(
+    path: &Path,
+    contents: &str,
+    create_dirs: bool,
+    options: &WriteFileOptions,
+) -> Result<FileVersion, String> {
+    if create_dirs {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directories: {}", e))?;
+        }
+    }
+
+    let existing = match std::fs::read(path) {
+        Ok(bytes) => Some(bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read file: {}", e)),
+    };
+    check_unchanged(path, existing.as_deref(), options)?;
+
+    // Keep the format of the file being replaced unless told otherwise
+    let previous = existing
+        .as_deref()
+        .and_then(|bytes| text_file::decode(bytes, None).ok());
+    let encoding = match &options.encoding {
+        Some(label) => text_file::encoding_for_label(label)?,
+        None => previous.as_ref().map_or(encoding_rs::UTF_8, |p| p.encoding),
+    };
+    let with_bom = options.with_bom.unwrap_or_else(|| {
+        previous
+            .as_ref()
+            .is_some_and(|p| p.has_bom && p.encoding == encoding)
+    });
+
+    // An explicit style converts every line; otherwise unchanged lines keep
+    // their line breaks and new ones follow the file's dominant style
+    let contents = match (options.line_ending, &previous) {
+        (Some(line_ending), _) => std::borrow::Cow::Owned(line_ending.apply(contents)),
+        (None, Some(previous)) => match LineEnding::detect(&previous.text) {
+            Some(default) => std::borrow::Cow::Owned(text_file::restore_line_endings(
+                &previous.text,
+                contents,
+                default,
+            )),
+            None => std::borrow::Cow::Borrowed(contents),
+        },
+        (None, None) => std::borrow::Cow::Borrowed(contents),
+    };
+    let bytes = text_file::encode(&contents, encoding, with_bom)?;
+    text_file::write_atomic(path, &bytes)?;
+
+    Ok(file_version(path, &bytes))
+}
+
+/// Read a text file's contents
+///
+/// # Arguments
+/// * `path` - File to read
+/// * `encoding` - Encoding label to decode with; detected from the BOM or
+///   contents if not specified. A BOM always takes precedence.
 #[tauri::command]
-pub async fn read_file(
-    path: String,
-    _encoding: Option<String>,
-) -> Result<String, String> {
+pub async fn read_file(path: String, encoding: Option<String>) -> Result<String, String> {
     tracing::debug!("Reading file: {}", path);
 
-    let contents = tokio::fs::read_to_string(&path)
+    tokio::task::spawn_blocking(move || read_text(Path::new(&path), encoding.as_deref(), false))
         .await
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+        .map_err(|e| format!("Read task failed: {}", e))?
+        .map(|file| file.contents)
+}
+
+/// Read a text file along with its encoding, line endings and version
+///
+/// # Arguments
+/// * `path` - File to read
+/// * `encoding` - Encoding label to decode with; detected if not specified,
+///   in which case files that look binary are rejected
+///
+/// # Returns
+/// The decoded contents. Pass `version.hash` back to `write_file` as
+/// `expected_hash` to reject the save if someone else changed the file.
+#[tauri::command]
+pub async fn read_text_file(
+    path: String,
+    encoding: Option<String>,
+) -> Result<TextFileContents, String> {
+    tracing::debug!("Reading text file: {}", path);
 
-    Ok(contents)
+    tokio::task::spawn_blocking(move || read_text(Path::new(&path), encoding.as_deref(), true))
+        .await
+        .map_err(|e| format!("Read task failed: {}", e))?
 }
 
 /// Write contents to a file
+///
+/// The file is replaced atomically and keeps its permissions. Unless
+/// overridden in `options`, the existing file's encoding, BOM and line
+/// endings are preserved; new files are written as UTF-8.
+///
+/// If `lane_id` is given and the lane formats on save, the contents are
+/// formatted with the lane's formatter first.
+///
+/// # Arguments
+/// * `path` - File to write
+/// * `contents` - New text contents
+/// * `create_dirs` - Create missing parent directories
+/// * `options` - Encoding, line ending and conflict detection options
+/// * `lane_id` - Lane whose format-on-save settings apply
+///
+/// # Returns
+/// The version of the written file, and the formatted contents if formatting
+/// changed them. Fails with an error starting with `WRITE_CONFLICT_ERROR` if
+/// the file no longer matches the expected version.
 #[tauri::command]
 pub async fn write_file(
     path: String,
     contents: String,
     create_dirs: Option<bool>,
-) -> Result<(), String> {
+    options: Option<WriteFileOptions>,
+    lane_id: Option<String>,
+    lanes: State<'_, LaneState>,
+    lsp: State<'_, LspState>,
+) -> Result<SavedFile, String> {
     tracing::debug!("Writing file: {}", path);
 
-    if create_dirs.unwrap_or(false) {
-        if let Some(parent) = std::path::Path::new(&path).parent() {
-            tokio::fs::create_dir_all(parent)
+    let lane = lane_id.and_then(|id| lanes.lanes.lock().unwrap().get(&id).cloned());
+    let formatted = match lane {
+        Some(lane) => {
+            formatting::format_on_save(&lane, &lsp.manager, Path::new(&path), contents.clone())
                 .await
-                .map_err(|e| format!("Failed to create directories: {}", e))?;
+                .filter(|formatted| *formatted != contents)
         }
-    }
-
-    tokio::fs::write(&path, contents)
-        .await
-        .map_err(|e| format!("Failed to write file: {}", e))?;
+        None => None,
+    };
 
-    Ok(())
+    let contents = formatted.clone().unwrap_or(contents);
+    let options = options.unwrap_or_default();
+    let version = tokio::task::spawn_blocking(move || {
+        write_text(
+            Path::new(&path),
+            &contents,
+            create_dirs.unwrap_or(false),
+            &options,
+        )
+    })
+    .await
+    .map_err(|e| format!("Write task failed: {}", e))??;
+    Ok(SavedFile { version, formatted })
 }
 
 /// List contents of a directory
@@ -156,89 +411,59 @@ pub async fn get_file_stats(path: String) -> Result<FileStats, String> {
         .map_err(|e| format!("Failed to get file stats: {}", e))?;
 
     Ok(FileStats {
-        modified: metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs()),
+        modified: modified_secs(&metadata),
         size: metadata.len(),
     })
 }
 
+/// Canonicalize a watch event path that may no longer exist (e.g. a deleted file)
+pub(crate) fn canonical_event_path(path: &Path) -> Option<PathBuf> {
+    path.canonicalize().ok().or_else(|| {
+        let parent = path.parent()?.canonicalize().ok()?;
+        Some(parent.join(path.file_name()?))
+    })
+}
+
 /// Start watching a path for changes
+///
+/// Changes are debounced and delivered as `file-watch-batch` events (see
+/// [`FileWatchBatch`](crate::file_watch::FileWatchBatch)). Watches on the same
+/// path, or inside a recursively watched path, share one OS watcher. Event
+/// paths are reported under `path` as given, even if it goes through a symlink.
+///
+/// # Arguments
+/// * `path` - File or directory to watch
+/// * `recursive` - Watch subdirectories (default: true)
+/// * `options` - `.gitignore` handling and include/exclude globs
+///
+/// # Returns
+/// The watch ID to pass to `unwatch_path`
 #[tauri::command]
 pub async fn watch_path(
     app: AppHandle,
     state: tauri::State<'_, FileWatchState>,
     path: String,
     recursive: Option<bool>,
+    options: Option<WatchOptions>,
 ) -> Result<WatchId, String> {
     tracing::info!("Starting file watch on: {}", path);
 
     // Verify path exists
-    if !Path::new(&path).exists() {
-        return Err(format!("Path does not exist: {}", path));
-    }
+    let canonical = Path::new(&path)
+        .canonicalize()
+        .map_err(|_| format!("Path does not exist: {}", path))?;
 
     let watch_id = uuid::Uuid::new_v4().to_string();
-    let watch_id_for_thread = watch_id.clone();
-    let watch_id_for_storage = watch_id.clone();
-
-    let (tx, rx) = mpsc::channel();
-
-    let mut watcher = RecommendedWatcher::new(tx, Config::default())
-        .map_err(|e| format!("Failed to create watcher: {}", e))?;
-
-    let mode = if recursive.unwrap_or(true) {
-        RecursiveMode::Recursive
-    } else {
-        RecursiveMode::NonRecursive
-    };
-
-    watcher
-        .watch(Path::new(&path), mode)
-        .map_err(|e| format!("Failed to start watching: {}", e))?;
-
-    // Store watcher
-    {
-        let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
-        watchers.insert(watch_id_for_storage, watcher);
-    }
-
-    // Spawn thread to handle events (use Builder to handle spawn failure gracefully)
-    std::thread::Builder::new()
-        .name(format!("file-watch-{}", &watch_id[..8]))
-        .spawn(move || {
-            while let Ok(result) = rx.recv() {
-                match result {
-                    Ok(event) => {
-                        let kind = match &event.kind {
-                            notify::EventKind::Create(_) => "create",
-                            notify::EventKind::Modify(m) => {
-                                match m {
-                                    notify::event::ModifyKind::Name(_) => "rename",
-                                    _ => "modify",
-                                }
-                            }
-                            notify::EventKind::Remove(_) => "delete",
-                            _ => continue,
-                        };
-
-                        for path in event.paths {
-                            let watch_event = FileWatchEvent {
-                                watch_id: watch_id_for_thread.clone(),
-                                path: path.to_string_lossy().to_string(),
-                                kind: kind.to_string(),
-                            };
-
-                            let _ = app.emit("file-watch-event", &watch_event);
-                        }
-                    }
-                    Err(_) => {}
-                }
-            }
-        })
-        .map_err(|e| format!("Failed to spawn file watch thread: {}", e))?;
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    file_watch::subscribe(
+        &app,
+        &mut watchers,
+        &watch_id,
+        &canonical,
+        Path::new(&path),
+        recursive.unwrap_or(true),
+        &options.unwrap_or_default(),
+    )?;
 
     Ok(watch_id)
 }
@@ -252,7 +477,7 @@ pub async fn unwatch_path(
     tracing::info!("Stopping file watch: {}", watch_id);
 
     let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
-    watchers.remove(&watch_id);
+    file_watch::unsubscribe(&mut watchers, &watch_id);
 
     Ok(())
 }
@@ -260,6 +485,16 @@ pub async fn unwatch_path(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lane::Lane;
+    use tauri::Manager;
+
+    /// App managing the states `write_file` needs
+    fn mock_app() -> tauri::App<tauri::test::MockRuntime> {
+        let app = tauri::test::mock_app();
+        app.manage(LaneState::new());
+        app.manage(LspState::new());
+        app
+    }
 
     // ==================== FileEntry Tests ====================
 
@@ -671,6 +906,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_file_and_read() {
+        let app = mock_app();
         let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
         let file_path = temp_dir.path().join("test_write.txt");
         let content = "Hello, World!";
@@ -680,6 +916,10 @@ mod tests {
             file_path.to_string_lossy().to_string(),
             content.to_string(),
             None,
+            None,
+            None,
+            app.state(),
+            app.state(),
         )
         .await;
         assert!(write_result.is_ok());
@@ -692,6 +932,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_file_create_dirs() {
+        let app = mock_app();
         let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
         let nested_path = temp_dir.path().join("a").join("b").join("c").join("file.txt");
 
@@ -699,6 +940,10 @@ mod tests {
             nested_path.to_string_lossy().to_string(),
             "nested content".to_string(),
             Some(true),
+            None,
+            None,
+            app.state(),
+            app.state(),
         )
         .await;
 
@@ -706,6 +951,75 @@ mod tests {
         assert!(nested_path.exists());
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_file_formats_on_save() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("notes.txt");
+        let path = file_path.to_string_lossy().to_string();
+
+        let mut lane = Lane::new(
+            "Format".to_string(),
+            temp_dir.path().to_string_lossy().to_string(),
+        );
+        lane.config.format_on_save = true;
+        lane.config.formatters = vec![formatting::FormatterConfig {
+            extensions: vec!["txt".to_string()],
+            command: "tr".to_string(),
+            args: vec!["a-z".to_string(), "A-Z".to_string()],
+        }];
+        let lane_id = lane.id.clone();
+        app.state::<LaneState>()
+            .lanes
+            .lock()
+            .unwrap()
+            .insert(lane_id.clone(), lane);
+
+        let saved = write_file(
+            path.clone(),
+            "hello\n".to_string(),
+            None,
+            None,
+            Some(lane_id.clone()),
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(saved.formatted.as_deref(), Some("HELLO\n"));
+        assert_eq!(read_file(path.clone(), None).await.unwrap(), "HELLO\n");
+
+        // Contents the formatter leaves alone are not reported as formatted
+        let saved = write_file(
+            path.clone(),
+            "HELLO\n".to_string(),
+            None,
+            None,
+            Some(lane_id),
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(saved.formatted, None);
+
+        // Without a lane the contents are written as they are
+        let saved = write_file(
+            path.clone(),
+            "bye\n".to_string(),
+            None,
+            None,
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(saved.formatted, None);
+        assert_eq!(read_file(path, None).await.unwrap(), "bye\n");
+    }
+
     #[tokio::test]
     async fn test_get_file_stats_real() {
         let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
@@ -721,4 +1035,270 @@ mod tests {
         assert_eq!(stats.size, content.len() as u64);
         assert!(stats.modified.is_some());
     }
+
+    #[tokio::test]
+    async fn test_read_file_with_encoding() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("latin1.txt");
+        std::fs::write(&file_path, b"caf\xE9").unwrap();
+        let path = file_path.to_string_lossy().to_string();
+
+        // Not UTF-8, so detection falls back to Windows-1252
+        assert_eq!(read_file(path.clone(), None).await.unwrap(), "café");
+        let err = read_file(path, Some("utf-8".to_string()))
+            .await
+            .unwrap_err();
+        assert!(err.contains("not valid UTF-8"));
+    }
+
+    #[tokio::test]
+    async fn test_read_text_file_details() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("bom.txt");
+        std::fs::write(&file_path, b"\xEF\xBB\xBFa\r\nb\r\n").unwrap();
+
+        let file = read_text_file(file_path.to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(file.contents, "a\r\nb\r\n");
+        assert_eq!(file.encoding, "UTF-8");
+        assert!(file.has_bom);
+        assert_eq!(file.line_ending, Some(LineEnding::Crlf));
+        assert_eq!(file.version.size, 9);
+        assert!(file.version.modified.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_write_file_preserves_format() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("utf16.txt");
+        let original = text_file::encode("one\r\ntwo\r\n", encoding_rs::UTF_16LE, true).unwrap();
+        std::fs::write(&file_path, original).unwrap();
+        let path = file_path.to_string_lossy().to_string();
+
+        // The editor hands back LF line endings
+        write_file(
+            path.clone(),
+            "one\ntwo\nthree\n".to_string(),
+            None,
+            None,
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+
+        let file = read_text_file(path, None).await.unwrap();
+        assert_eq!(file.contents, "one\r\ntwo\r\nthree\r\n");
+        assert_eq!(file.encoding, "UTF-16LE");
+        assert!(file.has_bom);
+    }
+
+    #[tokio::test]
+    async fn test_write_file_keeps_mixed_line_endings() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("mixed.txt");
+        std::fs::write(&file_path, "one\r\ntwo\nthree\r\n").unwrap();
+        let path = file_path.to_string_lossy().to_string();
+
+        write_file(
+            path,
+            "one\ntwo\n3\nfour\n".to_string(),
+            None,
+            None,
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "one\r\ntwo\n3\r\nfour\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_binary_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("data.bin");
+        std::fs::write(&file_path, b"abc\0def").unwrap();
+        let path = file_path.to_string_lossy().to_string();
+
+        assert_eq!(read_file(path.clone(), None).await.unwrap(), "abc\0def");
+        let err = read_text_file(path, None).await.unwrap_err();
+        assert!(err.contains("binary"));
+    }
+
+    #[tokio::test]
+    async fn test_write_file_explicit_format() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("sjis.txt");
+        let options = WriteFileOptions {
+            encoding: Some("shift_jis".to_string()),
+            line_ending: Some(LineEnding::Crlf),
+            ..Default::default()
+        };
+
+        write_file(
+            file_path.to_string_lossy().to_string(),
+            "あ\n".to_string(),
+            None,
+            Some(options),
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"\x82\xA0\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_write_file_rejects_concurrent_change() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("shared.txt");
+        std::fs::write(&file_path, "original").unwrap();
+        let path = file_path.to_string_lossy().to_string();
+
+        let read = read_text_file(path.clone(), None).await.unwrap();
+        std::fs::write(&file_path, "changed by an agent").unwrap();
+
+        let options = WriteFileOptions {
+            expected_hash: Some(read.version.hash.clone()),
+            ..Default::default()
+        };
+        let err = write_file(
+            path.clone(),
+            "mine".to_string(),
+            None,
+            Some(options),
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.starts_with(WRITE_CONFLICT_ERROR));
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "changed by an agent"
+        );
+
+        // Writing against the current version succeeds and returns the new one
+        let current = read_text_file(path.clone(), None).await.unwrap();
+        let options = WriteFileOptions {
+            expected_hash: Some(current.version.hash),
+            ..Default::default()
+        };
+        let saved = write_file(
+            path.clone(),
+            "mine".to_string(),
+            None,
+            Some(options),
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            saved.version,
+            read_text_file(path, None).await.unwrap().version
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_file_rejects_same_second_change() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("shared.txt");
+        std::fs::write(&file_path, "original").unwrap();
+        let path = file_path.to_string_lossy().to_string();
+        let read = read_text_file(path.clone(), None).await.unwrap();
+
+        // Rewritten within the same second, with a different size
+        std::fs::write(&file_path, "changed").unwrap();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .unwrap();
+        let modified = std::time::UNIX_EPOCH
+            + std::time::Duration::from_millis(read.version.modified.unwrap() + 1);
+        file.set_modified(modified).unwrap();
+
+        for options in [
+            WriteFileOptions {
+                expected_modified: read.version.modified,
+                ..Default::default()
+            },
+            WriteFileOptions {
+                expected_size: Some(read.version.size),
+                ..Default::default()
+            },
+        ] {
+            let err = write_file(
+                path.clone(),
+                "mine".to_string(),
+                None,
+                Some(options),
+                None,
+                app.state(),
+                app.state(),
+            )
+            .await
+            .unwrap_err();
+            assert!(err.starts_with(WRITE_CONFLICT_ERROR));
+        }
+
+        let current = read_text_file(path.clone(), None).await.unwrap();
+        let options = WriteFileOptions {
+            expected_modified: current.version.modified,
+            expected_size: Some(current.version.size),
+            ..Default::default()
+        };
+        write_file(
+            path,
+            "mine".to_string(),
+            None,
+            Some(options),
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_file_conflict_when_deleted() {
+        let app = mock_app();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("gone.txt");
+        let options = WriteFileOptions {
+            expected_hash: Some(text_file::content_hash(b"old")),
+            ..Default::default()
+        };
+
+        let err = write_file(
+            file_path.to_string_lossy().to_string(),
+            "new".to_string(),
+            None,
+            Some(options),
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.starts_with(WRITE_CONFLICT_ERROR));
+        assert!(!file_path.exists());
+    }
 }
@@ -7,6 +7,10 @@ use std::path::Path;
 use std::process::Command;
 
 use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::lane::LaneState;
+use crate::plugins;
 
 // ============================================================================
 // Result Types
@@ -77,7 +81,7 @@ pub struct FileChangeStats {
 
 /// Validate that a path is inside a git repository or worktree
 /// Returns the path as-is if valid (for worktree support)
-fn validate_git_path(path: &str) -> Result<String, String> {
+pub(crate) fn validate_git_path(path: &str) -> Result<String, String> {
     let work_dir = Path::new(path);
 
     if !work_dir.exists() {
@@ -124,7 +128,7 @@ fn find_repo_root(path: &str) -> Result<String, String> {
 }
 
 /// Run a git command and return the output
-fn run_git(work_dir: &Path, args: &[&str]) -> Result<String, String> {
+pub(crate) fn run_git(work_dir: &Path, args: &[&str]) -> Result<String, String> {
     let output = Command::new("git")
         .current_dir(work_dir)
         .args(args)
@@ -371,17 +375,38 @@ pub async fn git_commit_file_diff(path: String, commit_hash: String, file: Strin
 const MAX_FILES_FOR_DEPENDENCY_ANALYSIS: usize = 50;
 
 /// Sort files according to the specified order
+///
+/// Smart orders use the file categories configured for the lane, if it is
+/// known, and the file categories of the active plugins.
 #[tauri::command]
 pub async fn git_sort_files(
     files: Vec<FileChangeStats>,
     sort_order: String,
     working_dir: Option<String>,
+    lane_id: Option<String>,
+    lanes: State<'_, LaneState>,
+    app: AppHandle,
 ) -> Result<Vec<FileChangeStats>, String> {
-    use crate::file_sorter;
+    use crate::file_sorter::{self, Categorizer};
     use std::collections::HashMap;
 
+    let file_sort = lane_id.and_then(|lane_id| {
+        let lanes = lanes.lanes.lock().unwrap();
+        lanes
+            .get(&lane_id)
+            .map(|lane| lane.config.file_sort.clone())
+    });
+    let mut categorizer = match file_sort {
+        Some(file_sort) => Categorizer::new(&file_sort),
+        None => Categorizer::default(),
+    };
+    // Sorting works without plugins, so an unavailable host is not an error
+    if let Ok(categories) = plugins::with_host(app, |host| Ok(host.file_categories())).await {
+        categorizer.add_plugin_categories(&categories);
+    }
+
     let sorted_files = match sort_order.as_str() {
-        "smart" => file_sorter::sort_files_smart(files),
+        "smart" => categorizer.sort_smart(files),
         "smart-dependencies" => {
             // Performance guard: Only do dependency analysis for reasonable file counts
             if files.len() > MAX_FILES_FOR_DEPENDENCY_ANALYSIS {
@@ -390,7 +415,7 @@ pub async fn git_sort_files(
                     "Too many files ({}) for dependency analysis, falling back to smart sort",
                     files.len()
                 );
-                file_sorter::sort_files_smart(files)
+                categorizer.sort_smart(files)
             } else {
                 // For dependency-aware sorting, we need file contents
                 let work_dir = working_dir.ok_or("working_dir required for smart-dependencies sorting")?;
@@ -405,7 +430,7 @@ pub async fn git_sort_files(
                     }
                 }
 
-                file_sorter::sort_files_smart_dependencies(files, file_contents)
+                categorizer.sort_smart_dependencies(files, &file_contents)
             }
         }
         "alphabetical" => file_sorter::sort_files_alphabetical(files),
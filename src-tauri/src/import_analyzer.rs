@@ -7,7 +7,7 @@ use std::path::Path;
 use tree_sitter::{Language, Parser, Query, QueryCursor};
 
 /// Supported languages for import analysis
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AnalysisLanguage {
     TypeScript,
     JavaScript,
@@ -17,6 +17,15 @@ pub enum AnalysisLanguage {
 }
 
 impl AnalysisLanguage {
+    /// Every supported language
+    pub const ALL: [AnalysisLanguage; 5] = [
+        Self::TypeScript,
+        Self::JavaScript,
+        Self::Python,
+        Self::Rust,
+        Self::Go,
+    ];
+
     /// Detect language from file extension
     pub fn from_path(path: &str) -> Option<Self> {
         let path_lower = path.to_lowercase();
@@ -37,7 +46,7 @@ impl AnalysisLanguage {
     }
 
     /// Get tree-sitter language parser
-    fn tree_sitter_language(&self) -> Language {
+    pub(crate) fn tree_sitter_language(&self) -> Language {
         match self {
             Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
             Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
@@ -3,7 +3,7 @@
 //! This module handles the creation, persistence, and management of lanes.
 //! A lane represents a project workspace with its own terminal and AI agents.
 
-use codelane_core::config::AgentConfig;
+use codelane_core::config::{AgentConfig, FileSortConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -12,6 +12,8 @@ use std::sync::Mutex;
 use tauri::State;
 use uuid::Uuid;
 
+use crate::formatting::FormatterConfig;
+
 /// Lane configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +29,19 @@ pub struct LaneConfig {
     /// LSP servers to enable
     #[serde(default)]
     pub lsp_servers: Vec<String>,
+
+    /// Format files with the lane's formatter when they are saved
+    #[serde(default)]
+    pub format_on_save: bool,
+
+    /// Command-line formatters, used instead of the language servers for the
+    /// extensions they list
+    #[serde(default)]
+    pub formatters: Vec<FormatterConfig>,
+
+    /// Extra file categories and category order for sorting files in review
+    #[serde(default)]
+    pub file_sort: FileSortConfig,
 }
 
 /// Represents a lane (project workspace)
@@ -213,7 +228,11 @@ pub fn lane_update(
 
 /// Deletes a lane
 #[tauri::command]
-pub fn lane_delete(lane_id: String, state: State<LaneState>) -> Result<(), String> {
+pub fn lane_delete(
+    lane_id: String,
+    state: State<LaneState>,
+    lsp: State<crate::lsp::LspState>,
+) -> Result<(), String> {
     let mut lanes = state.lanes.lock().unwrap();
 
     if lanes.remove(&lane_id).is_none() {
@@ -223,6 +242,9 @@ pub fn lane_delete(lane_id: String, state: State<LaneState>) -> Result<(), Strin
     // Delete from disk
     state.delete_lane_file(&lane_id)?;
 
+    // Shut down the lane's language servers
+    lsp.stop_lane_in_background(&lane_id);
+
     // Clean up hook events directory for this lane
     if let Ok(lane_events_dir) = codelane_core::paths::lane_hook_events_dir(&lane_id) {
         if lane_events_dir.exists() {
@@ -255,6 +277,7 @@ mod tests {
             agent_override: None,
             env: vec![("KEY".to_string(), "VALUE".to_string())],
             lsp_servers: vec!["rust-analyzer".to_string()],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).expect("Should serialize");
@@ -285,12 +308,55 @@ mod tests {
         assert!(config.lsp_servers.is_empty());
     }
 
+    #[test]
+    fn test_lane_config_formatting() {
+        let json = r#"{
+            "formatOnSave": true,
+            "formatters": [
+                {
+                    "extensions": ["ts"],
+                    "command": "prettier",
+                    "args": ["--stdin-filepath", "{path}"]
+                }
+            ]
+        }"#;
+
+        let config: LaneConfig = serde_json::from_str(json).expect("Should deserialize");
+        assert!(config.format_on_save);
+        assert_eq!(config.formatters[0].command, "prettier");
+        assert!(!LaneConfig::default().format_on_save);
+    }
+
+    #[test]
+    fn test_lane_config_file_sort() {
+        let json = r#"{
+            "fileSort": {
+                "categories": [
+                    {
+                        "label": "Migrations",
+                        "patterns": ["/migrations/\\d+_\\w+\\.py$"],
+                        "tier": "generated"
+                    }
+                ],
+                "order": ["Migrations", "Implementation"]
+            }
+        }"#;
+
+        let config: LaneConfig = serde_json::from_str(json).expect("Should deserialize");
+        let category = &config.file_sort.categories[0];
+        assert_eq!(category.label, "Migrations");
+        assert_eq!(category.tier, codelane_core::config::FileTier::Generated);
+        assert_eq!(config.file_sort.order.len(), 2);
+        assert!(LaneConfig::default().file_sort.categories.is_empty());
+    }
+
     #[test]
     fn test_lane_config_clone() {
         let config = LaneConfig {
             agent_override: None,
             env: vec![("A".to_string(), "B".to_string())],
             lsp_servers: vec!["lsp1".to_string(), "lsp2".to_string()],
+            ..Default::default()
         };
 
         let cloned = config.clone();
@@ -578,6 +644,7 @@ mod tests {
             }),
             env: vec![],
             lsp_servers: vec![],
+            ..Default::default()
         };
 
         assert!(config.agent_override.is_some());
@@ -600,6 +667,7 @@ mod tests {
             }),
             env: vec![("PATH".to_string(), "/usr/bin".to_string())],
             lsp_servers: vec!["rust-analyzer".to_string()],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).expect("Should serialize");
@@ -694,6 +762,7 @@ mod tests {
             agent_override: None,
             env: vec![],
             lsp_servers: vec!["test".to_string()],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).expect("Should serialize");
@@ -768,6 +837,7 @@ mod tests {
                 ("EDITOR".to_string(), "vim".to_string()),
             ],
             lsp_servers: vec![],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).expect("Should serialize");
@@ -786,6 +856,7 @@ mod tests {
                 "typescript-language-server".to_string(),
                 "pylsp".to_string(),
             ],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).expect("Should serialize");
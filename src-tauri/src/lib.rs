@@ -8,15 +8,34 @@ pub mod paths;
 pub mod settings;
 pub mod db;
 pub mod process;
+pub mod process_registry;
 pub mod terminal;
+pub mod terminal_automation;
+pub mod terminal_recording;
 pub mod search;
+pub mod search_index;
+pub mod search_replace;
+pub mod search_scope;
+pub mod search_structural;
+pub mod shell_integration;
 pub mod hooks;
 pub mod hook_monitor;
 mod git;
 mod fs;
+mod text_file;
+mod file_finder;
+mod file_ops;
+mod file_tree;
+mod file_watch;
 mod file_sorter;
 mod import_analyzer;
 mod dependency_graph;
+mod lsp;
+mod lsp_navigation;
+mod lsp_completion;
+mod formatting;
+mod diagnostics_delta;
+mod plugins;
 mod ai;
 
 use tauri::{Emitter, Manager};
@@ -48,10 +67,26 @@ pub fn run() {
         .manage(terminal::TerminalState::new())
         // Manage search state
         .manage(search::SearchState::new())
+        // Manage search index state
+        .manage(search_index::SearchIndexState::new())
+        // Manage search & replace state
+        .manage(search_replace::ReplaceState::new())
         // Manage file watch state
         .manage(fs::FileWatchState::new())
+        // Manage quick-open file finder state
+        .manage(file_finder::FileFinderState::new())
+        // Manage file operation journal
+        .manage(file_ops::FileOpsState::new())
+        // Manage language servers
+        .manage(lsp::LspState::new())
+        // Manage diagnostics baselines
+        .manage(diagnostics_delta::DiagnosticsBaselineState::new())
+        // Manage WASM plugins
+        .manage(plugins::PluginState::new())
         // Manage hook monitor state
         .manage(hook_monitor::HookMonitorState::new())
+        // Manage lane process registry
+        .manage(process_registry::ProcessRegistry::new())
         // Register commands
         .invoke_handler(tauri::generate_handler![
             // Database commands
@@ -72,6 +107,12 @@ pub fn run() {
             process::get_process_stats,
             process::find_process_by_lane,
             process::get_app_resource_usage,
+            process::get_process_tree,
+            process::kill_process_tree,
+            process_registry::list_lane_processes,
+            process_registry::get_lane_resource_usage,
+            process_registry::get_lanes_resource_usage,
+            process_registry::kill_lane_processes,
             // Git commands
             git::git_status,
             git::git_diff,
@@ -101,11 +142,26 @@ pub fn run() {
             ai::ai_get_available_tools,
             // Filesystem commands
             fs::read_file,
+            fs::read_text_file,
             fs::write_file,
             fs::list_directory,
             fs::watch_path,
             fs::unwatch_path,
             fs::get_file_stats,
+            file_tree::list_directory_tree,
+            file_ops::file_create,
+            file_ops::directory_create,
+            file_ops::file_rename,
+            file_ops::file_move,
+            file_ops::file_copy,
+            file_ops::file_delete,
+            file_ops::file_ops_undo,
+            file_ops::file_ops_history,
+            file_ops::trash_list,
+            file_ops::trash_restore,
+            file_ops::trash_empty,
+            file_finder::find_files,
+            file_finder::record_file_opened,
             // Terminal commands (using portable-pty)
             terminal::create_terminal,
             terminal::write_terminal,
@@ -115,13 +171,54 @@ pub fn run() {
             terminal::get_terminal_info,
             terminal::list_terminals,
             terminal::get_terminal_pid_by_lane,
+            terminal::terminal_start_recording,
+            terminal::terminal_stop_recording,
+            terminal::terminal_output_offset,
+            terminal::terminal_send_keys,
+            terminal::terminal_wait_for,
+            terminal::terminal_run_command,
+            terminal::terminal_get_commands,
+            terminal::terminal_get_cwd,
+            terminal::terminal_get_command_output,
+            terminal::terminal_get_process_tree,
+            terminal::terminal_shutdown,
+            terminal::terminal_shutdown_lane,
+            terminal_recording::terminal_replay_recording,
             // Search commands
             search::search_start,
             search::search_cancel,
+            search_index::search_index_build,
+            search_index::search_index_status,
+            search_index::search_index_delete,
+            search_replace::search_replace_preview,
+            search_replace::search_replace_apply,
+            search_replace::search_replace_undo,
             // Hook commands
             hooks::hooks_install,
             hooks::hooks_uninstall,
             hooks::hooks_check_status,
+            // Language server commands
+            lsp::lsp_start_lane,
+            lsp::lsp_stop_lane,
+            lsp::lsp_status,
+            lsp::lsp_diagnostics,
+            diagnostics_delta::lsp_diagnostics_delta,
+            lsp::lsp_open_document,
+            lsp::lsp_change_document,
+            lsp::lsp_close_document,
+            lsp_navigation::lsp_hover,
+            lsp_navigation::lsp_definition,
+            lsp_navigation::lsp_references,
+            lsp_navigation::lsp_document_symbols,
+            lsp_navigation::lsp_workspace_symbols,
+            lsp_completion::lsp_completion,
+            formatting::format_document,
+            // Plugin commands
+            plugins::plugin_commands,
+            plugins::plugin_run_command,
+            plugins::plugin_review,
+            plugins::plugin_set_developer_mode,
+            plugins::plugin_developer_mode,
         ])
         // Window setup
         .setup(|app| {
@@ -195,6 +292,17 @@ pub fn run() {
                 window.open_devtools();
             }
 
+            // Forward language server status to the frontend
+            app.state::<lsp::LspState>().forward_events(app.handle().clone());
+
+            // Load plugins without holding up startup, then reload changed
+            // plugins while developer mode is on
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                app_handle.state::<plugins::PluginState>().load_all();
+                plugins::reload_changed_plugins(app_handle);
+            });
+
             // Cleanup old hook events on startup (remove events older than 1 hour)
             if let Err(e) = hook_monitor::HookMonitorState::cleanup_old_events(1) {
                 tracing::warn!("Failed to cleanup old hook events on startup: {}", e);
@@ -0,0 +1,280 @@
+//! Language Servers
+//!
+//! Tauri commands around [`codelane_lsp::LspManager`]: starting a lane's
+//! language servers in its working directory, keeping the servers' view of
+//! open editor documents in sync, and reporting server status and each lane's
+//! problem list. Servers are shut down when their lane is deleted.
+
+use codelane_core::project::Project;
+use codelane_lsp::{
+    DiagnosticCounts, FileDiagnostics, LaneDiagnostics, LspEvent, LspManager, ServerStatus,
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::lane::LaneState;
+use crate::lsp_navigation::DiskDocuments;
+
+/// Event emitted whenever a language server changes state
+pub const LSP_STATUS_EVENT: &str = "lsp-status";
+
+/// Event emitted whenever a file's diagnostics change
+pub const LSP_DIAGNOSTICS_EVENT: &str = "lsp-diagnostics";
+
+/// Payload of [`LSP_DIAGNOSTICS_EVENT`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsChanged {
+    pub lane_id: String,
+    /// Current diagnostics of the file that changed
+    pub file: FileDiagnostics,
+    /// Totals for the whole lane
+    pub counts: DiagnosticCounts,
+}
+
+/// Language server manager shared by all lanes
+pub struct LspState {
+    pub(crate) manager: Arc<LspManager>,
+    pub(crate) disk_documents: DiskDocuments,
+}
+
+impl LspState {
+    pub fn new() -> Self {
+        Self {
+            manager: Arc::new(LspManager::new()),
+            disk_documents: DiskDocuments::default(),
+        }
+    }
+
+    /// Forward server status and diagnostics changes to the frontend
+    pub fn forward_events(&self, app: AppHandle) {
+        let mut events = self.manager.subscribe();
+        let manager = self.manager.clone();
+        tauri::async_runtime::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                match events.recv().await {
+                    Ok(LspEvent::Status(status)) => {
+                        let _ = app.emit(LSP_STATUS_EVENT, &status);
+                    }
+                    Ok(LspEvent::Diagnostics { lane_id, path }) => {
+                        let changed = DiagnosticsChanged {
+                            file: manager.file_diagnostics(&lane_id, &path),
+                            counts: manager.diagnostic_counts(&lane_id),
+                            lane_id,
+                        };
+                        let _ = app.emit(LSP_DIAGNOSTICS_EVENT, &changed);
+                    }
+                    Ok(LspEvent::Notification { .. }) => {}
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!("Dropped {} language server events", missed);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Shut down a lane's servers in the background
+    pub fn stop_lane_in_background(&self, lane_id: &str) {
+        self.disk_documents.forget_lane(lane_id);
+        let manager = self.manager.clone();
+        let lane_id = lane_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            manager.stop_lane(&lane_id).await;
+        });
+    }
+}
+
+impl Default for LspState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Servers to run for a lane: its configured servers, or the ones
+/// recommended for the projects found in its working directory
+pub(crate) fn lane_servers(configured: &[String], working_dir: &Path) -> Vec<String> {
+    if !configured.is_empty() {
+        return configured.to_vec();
+    }
+    Project::scan(working_dir).all_lsp_servers()
+}
+
+/// Start a lane's language servers
+///
+/// Servers already running for the lane are kept. Servers start in the
+/// background; progress is reported through `lsp-status` events.
+///
+/// # Arguments
+/// * `lane_id` - Lane whose working directory the servers run in
+///
+/// # Returns
+/// Status of each of the lane's servers
+#[tauri::command]
+pub async fn lsp_start_lane(
+    lane_id: String,
+    lanes: State<'_, LaneState>,
+    state: State<'_, LspState>,
+) -> Result<Vec<ServerStatus>, String> {
+    let lane = {
+        let lanes = lanes.lanes.lock().unwrap();
+        lanes
+            .get(&lane_id)
+            .cloned()
+            .ok_or_else(|| format!("Lane not found: {}", lane_id))?
+    };
+    let working_dir = Path::new(&lane.working_dir);
+    let servers = lane_servers(&lane.config.lsp_servers, working_dir);
+
+    state
+        .manager
+        .start_lane(&lane_id, working_dir, &servers)
+        .map_err(|e| format!("Failed to start language servers: {}", e))
+}
+
+/// Shut down a lane's language servers
+///
+/// # Returns
+/// Whether the lane had any servers
+#[tauri::command]
+pub async fn lsp_stop_lane(lane_id: String, state: State<'_, LspState>) -> Result<bool, String> {
+    state.disk_documents.forget_lane(&lane_id);
+    Ok(state.manager.stop_lane(&lane_id).await)
+}
+
+/// Get the status of a lane's language servers
+#[tauri::command]
+pub async fn lsp_status(
+    lane_id: String,
+    state: State<'_, LspState>,
+) -> Result<Vec<ServerStatus>, String> {
+    Ok(state.manager.status(&lane_id))
+}
+
+/// Get a lane's problem list
+///
+/// Diagnostics are pushed by the language servers as they analyze files, so
+/// this reflects edits (including an agent's) without running a build.
+///
+/// # Arguments
+/// * `lane_id` - Lane to report on
+/// * `path` - Only report diagnostics for this file
+///
+/// # Returns
+/// Diagnostics grouped by file, with counts by severity
+#[tauri::command]
+pub async fn lsp_diagnostics(
+    lane_id: String,
+    path: Option<String>,
+    state: State<'_, LspState>,
+) -> Result<LaneDiagnostics, String> {
+    let Some(path) = path else {
+        return Ok(state.manager.diagnostics(&lane_id));
+    };
+    let file = state
+        .manager
+        .file_diagnostics(&lane_id, &PathBuf::from(path));
+    Ok(LaneDiagnostics {
+        lane_id,
+        counts: file.counts,
+        files: if file.diagnostics.is_empty() {
+            Vec::new()
+        } else {
+            vec![file]
+        },
+    })
+}
+
+/// Tell a lane's servers that a document was opened in the editor
+///
+/// The editor takes over documents navigation opened from disk.
+///
+/// # Arguments
+/// * `lane_id` - Lane the document belongs to
+/// * `path` - Absolute path of the document
+/// * `text` - Current editor contents
+#[tauri::command]
+pub async fn lsp_open_document(
+    lane_id: String,
+    path: String,
+    text: String,
+    state: State<'_, LspState>,
+) -> Result<(), String> {
+    let path = Path::new(&path);
+    state.disk_documents.forget(&lane_id, path);
+    state
+        .manager
+        .open_document(&lane_id, path, text)
+        .map_err(|e| e.to_string())
+}
+
+/// Send the full new contents of an open document to a lane's servers
+///
+/// # Arguments
+/// * `lane_id` - Lane the document belongs to
+/// * `path` - Absolute path of the document
+/// * `text` - Current editor contents
+#[tauri::command]
+pub async fn lsp_change_document(
+    lane_id: String,
+    path: String,
+    text: String,
+    state: State<'_, LspState>,
+) -> Result<(), String> {
+    state
+        .manager
+        .change_document(&lane_id, Path::new(&path), text)
+        .map_err(|e| e.to_string())
+}
+
+/// Tell a lane's servers that a document was closed in the editor
+#[tauri::command]
+pub async fn lsp_close_document(
+    lane_id: String,
+    path: String,
+    state: State<'_, LspState>,
+) -> Result<(), String> {
+    let path = Path::new(&path);
+    state.disk_documents.forget(&lane_id, path);
+    state
+        .manager
+        .close_document(&lane_id, path)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_lane_servers_prefers_configuration() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]").unwrap();
+
+        let configured = vec!["gopls".to_string()];
+        assert_eq!(lane_servers(&configured, temp_dir.path()), configured);
+        assert_eq!(lane_servers(&[], temp_dir.path()), vec!["rust-analyzer"]);
+    }
+
+    #[test]
+    fn test_lane_servers_include_sub_projects() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::create_dir(temp_dir.path().join("web")).unwrap();
+        fs::write(temp_dir.path().join("web/package.json"), "{}").unwrap();
+
+        assert_eq!(
+            lane_servers(&[], temp_dir.path()),
+            vec!["rust-analyzer", "typescript-language-server"]
+        );
+    }
+
+    #[test]
+    fn test_lane_servers_unknown_project() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(lane_servers(&[], temp_dir.path()).is_empty());
+    }
+}
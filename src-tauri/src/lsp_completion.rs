@@ -0,0 +1,202 @@
+//! Code Completion
+//!
+//! Completion requests against a lane's language servers, with items
+//! converted for Monaco's completion provider. Ranges use Monaco's 1-based
+//! coordinates, as in [`crate::lsp_navigation`].
+
+use codelane_editor::monaco::{Position, Range};
+use codelane_lsp::lsp_types as lsp;
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+use crate::lsp::LspState;
+use crate::lsp_navigation::{ensure_open, from_lsp_range, to_lsp_position};
+
+/// Completions at a position
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Completions {
+    /// Further typing should request completions again instead of filtering
+    pub is_incomplete: bool,
+    pub items: Vec<CompletionItem>,
+}
+
+/// A single completion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: String,
+    pub detail: Option<String>,
+    /// Markdown documentation
+    pub documentation: Option<String>,
+    pub insert_text: String,
+    /// `insert_text` is a snippet with tab stops and placeholders
+    pub is_snippet: bool,
+    /// Range replaced by `insert_text`; the word at the cursor if unset
+    pub range: Option<Range>,
+    pub sort_text: Option<String>,
+    pub filter_text: Option<String>,
+    /// Edits elsewhere in the file, such as adding an import
+    pub additional_edits: Vec<TextEdit>,
+}
+
+/// A replacement of a range of a document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextEdit {
+    pub range: Range,
+    pub text: String,
+}
+
+fn completion_kind(kind: Option<lsp::CompletionItemKind>) -> String {
+    let name = match kind {
+        Some(lsp::CompletionItemKind::TEXT) => "text",
+        Some(lsp::CompletionItemKind::METHOD) => "method",
+        Some(lsp::CompletionItemKind::FUNCTION) => "function",
+        Some(lsp::CompletionItemKind::CONSTRUCTOR) => "constructor",
+        Some(lsp::CompletionItemKind::FIELD) => "field",
+        Some(lsp::CompletionItemKind::VARIABLE) => "variable",
+        Some(lsp::CompletionItemKind::CLASS) => "class",
+        Some(lsp::CompletionItemKind::INTERFACE) => "interface",
+        Some(lsp::CompletionItemKind::MODULE) => "module",
+        Some(lsp::CompletionItemKind::PROPERTY) => "property",
+        Some(lsp::CompletionItemKind::UNIT) => "unit",
+        Some(lsp::CompletionItemKind::VALUE) => "value",
+        Some(lsp::CompletionItemKind::ENUM) => "enum",
+        Some(lsp::CompletionItemKind::KEYWORD) => "keyword",
+        Some(lsp::CompletionItemKind::SNIPPET) => "snippet",
+        Some(lsp::CompletionItemKind::COLOR) => "color",
+        Some(lsp::CompletionItemKind::FILE) => "file",
+        Some(lsp::CompletionItemKind::REFERENCE) => "reference",
+        Some(lsp::CompletionItemKind::FOLDER) => "folder",
+        Some(lsp::CompletionItemKind::ENUM_MEMBER) => "enumMember",
+        Some(lsp::CompletionItemKind::CONSTANT) => "constant",
+        Some(lsp::CompletionItemKind::STRUCT) => "struct",
+        Some(lsp::CompletionItemKind::EVENT) => "event",
+        Some(lsp::CompletionItemKind::OPERATOR) => "operator",
+        Some(lsp::CompletionItemKind::TYPE_PARAMETER) => "typeParameter",
+        _ => "text",
+    };
+    name.to_string()
+}
+
+fn text_edit(edit: lsp::TextEdit) -> TextEdit {
+    TextEdit {
+        range: from_lsp_range(edit.range),
+        text: edit.new_text,
+    }
+}
+
+fn completion_item(item: lsp::CompletionItem) -> CompletionItem {
+    let (insert_text, range) = match item.text_edit {
+        Some(lsp::CompletionTextEdit::Edit(edit)) => {
+            (edit.new_text, Some(from_lsp_range(edit.range)))
+        }
+        Some(lsp::CompletionTextEdit::InsertAndReplace(edit)) => {
+            (edit.new_text, Some(from_lsp_range(edit.insert)))
+        }
+        None => (item.insert_text.unwrap_or_else(|| item.label.clone()), None),
+    };
+    let documentation = item.documentation.map(|docs| match docs {
+        lsp::Documentation::String(text) => text,
+        lsp::Documentation::MarkupContent(markup) => markup.value,
+    });
+
+    CompletionItem {
+        kind: completion_kind(item.kind),
+        detail: item.detail,
+        documentation,
+        insert_text,
+        is_snippet: item.insert_text_format == Some(lsp::InsertTextFormat::SNIPPET),
+        range,
+        sort_text: item.sort_text,
+        filter_text: item.filter_text,
+        additional_edits: item
+            .additional_text_edits
+            .unwrap_or_default()
+            .into_iter()
+            .map(text_edit)
+            .collect(),
+        label: item.label,
+    }
+}
+
+/// Get completions at a position
+///
+/// # Arguments
+/// * `lane_id` - Lane the file belongs to
+/// * `path` - Absolute path of the file
+/// * `position` - Cursor position in the editor
+/// * `trigger_character` - Character that triggered completion, if any
+///
+/// # Returns
+/// Completions, or `None` if no server completes the file
+#[tauri::command]
+pub async fn lsp_completion(
+    lane_id: String,
+    path: String,
+    position: Position,
+    trigger_character: Option<String>,
+    state: State<'_, LspState>,
+) -> Result<Option<Completions>, String> {
+    let path = Path::new(&path);
+    ensure_open(&state, &lane_id, path)?;
+    let list = state
+        .manager
+        .completion(&lane_id, path, to_lsp_position(position), trigger_character)
+        .await
+        .map_err(|e| format!("Completion failed: {}", e))?;
+
+    Ok(list.map(|list| Completions {
+        is_incomplete: list.is_incomplete,
+        items: list.items.into_iter().map(completion_item).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_item_with_text_edit() {
+        let item = lsp::CompletionItem {
+            label: "println!".to_string(),
+            kind: Some(lsp::CompletionItemKind::FUNCTION),
+            insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+            text_edit: Some(lsp::CompletionTextEdit::Edit(lsp::TextEdit::new(
+                lsp::Range::new(lsp::Position::new(2, 4), lsp::Position::new(2, 7)),
+                "println!(\"$1\")".to_string(),
+            ))),
+            additional_text_edits: Some(vec![lsp::TextEdit::new(
+                lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+                "use std::fmt;\n".to_string(),
+            )]),
+            ..Default::default()
+        };
+
+        let converted = completion_item(item);
+        assert_eq!(converted.kind, "function");
+        assert!(converted.is_snippet);
+        assert_eq!(converted.insert_text, "println!(\"$1\")");
+        assert_eq!(
+            converted.range.unwrap().start,
+            Position { line: 3, column: 5 }
+        );
+        assert_eq!(converted.additional_edits[0].text, "use std::fmt;\n");
+    }
+
+    #[test]
+    fn test_completion_item_defaults_to_label() {
+        let item = lsp::CompletionItem {
+            label: "len".to_string(),
+            documentation: Some(lsp::Documentation::String("Length".to_string())),
+            ..Default::default()
+        };
+
+        let converted = completion_item(item);
+        assert_eq!(converted.insert_text, "len");
+        assert_eq!(converted.kind, "text");
+        assert!(!converted.is_snippet);
+        assert!(converted.range.is_none());
+        assert_eq!(converted.documentation.as_deref(), Some("Length"));
+    }
+}
@@ -0,0 +1,728 @@
+//! Code Navigation
+//!
+//! Hover, go to definition, find references and symbol outlines backed by a
+//! lane's language servers. Positions cross the command boundary in Monaco's
+//! coordinates (1-based lines and columns, [`Position`]/[`Range`]) and are
+//! converted to and from the 0-based positions LSP uses. Both count columns in
+//! UTF-16 code units, so no document text is needed for the conversion.
+//!
+//! Files are opened in the servers from disk when the editor has not opened
+//! them yet, so navigation also works from the diff and search views; see
+//! [`DiskDocuments`].
+
+use codelane_editor::monaco::{Position, Range};
+use codelane_lsp::lsp_types as lsp;
+use codelane_lsp::lsp_types::request::{
+    DocumentSymbolRequest, GotoDefinition, HoverRequest, References, WorkspaceSymbolRequest,
+};
+use codelane_lsp::{LspClient, LspManager};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::lsp::LspState;
+
+/// Hover information for a position
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HoverInfo {
+    /// Markdown contents; plain text and marked strings are converted
+    pub contents: String,
+    /// Range the hover applies to
+    pub range: Option<Range>,
+}
+
+/// A range in a file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub path: String,
+    pub range: Range,
+}
+
+/// A symbol in a document's outline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub detail: Option<String>,
+    pub kind: String,
+    /// Full extent of the symbol, including its body
+    pub range: Range,
+    /// Range to select when navigating to the symbol, usually its name
+    pub selection_range: Range,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A symbol matching a workspace symbol search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: String,
+    pub container_name: Option<String>,
+    /// Where the symbol is defined; servers may only report the file
+    pub location: Option<Location>,
+}
+
+pub(crate) fn to_lsp_position(position: Position) -> lsp::Position {
+    lsp::Position {
+        line: position.line.saturating_sub(1),
+        character: position.column.saturating_sub(1),
+    }
+}
+
+fn from_lsp_position(position: lsp::Position) -> Position {
+    Position {
+        line: position.line + 1,
+        column: position.character + 1,
+    }
+}
+
+pub(crate) fn from_lsp_range(range: lsp::Range) -> Range {
+    Range {
+        start: from_lsp_position(range.start),
+        end: from_lsp_position(range.end),
+    }
+}
+
+/// Convert a location, skipping ones outside the file system
+fn from_lsp_location(uri: &lsp::Url, range: lsp::Range) -> Option<Location> {
+    let path = uri.to_file_path().ok()?;
+    Some(Location {
+        path: path.to_string_lossy().to_string(),
+        range: from_lsp_range(range),
+    })
+}
+
+fn symbol_kind(kind: lsp::SymbolKind) -> String {
+    let name = match kind {
+        lsp::SymbolKind::FILE => "file",
+        lsp::SymbolKind::MODULE => "module",
+        lsp::SymbolKind::NAMESPACE => "namespace",
+        lsp::SymbolKind::PACKAGE => "package",
+        lsp::SymbolKind::CLASS => "class",
+        lsp::SymbolKind::METHOD => "method",
+        lsp::SymbolKind::PROPERTY => "property",
+        lsp::SymbolKind::FIELD => "field",
+        lsp::SymbolKind::CONSTRUCTOR => "constructor",
+        lsp::SymbolKind::ENUM => "enum",
+        lsp::SymbolKind::INTERFACE => "interface",
+        lsp::SymbolKind::FUNCTION => "function",
+        lsp::SymbolKind::VARIABLE => "variable",
+        lsp::SymbolKind::CONSTANT => "constant",
+        lsp::SymbolKind::STRING => "string",
+        lsp::SymbolKind::NUMBER => "number",
+        lsp::SymbolKind::BOOLEAN => "boolean",
+        lsp::SymbolKind::ARRAY => "array",
+        lsp::SymbolKind::OBJECT => "object",
+        lsp::SymbolKind::KEY => "key",
+        lsp::SymbolKind::NULL => "null",
+        lsp::SymbolKind::ENUM_MEMBER => "enumMember",
+        lsp::SymbolKind::STRUCT => "struct",
+        lsp::SymbolKind::EVENT => "event",
+        lsp::SymbolKind::OPERATOR => "operator",
+        lsp::SymbolKind::TYPE_PARAMETER => "typeParameter",
+        _ => "unknown",
+    };
+    name.to_string()
+}
+
+fn marked_string(marked: lsp::MarkedString) -> String {
+    match marked {
+        lsp::MarkedString::String(text) => text,
+        lsp::MarkedString::LanguageString(code) => {
+            format!("```{}\n{}\n```", code.language, code.value)
+        }
+    }
+}
+
+fn hover_info(hover: lsp::Hover) -> HoverInfo {
+    let contents = match hover.contents {
+        lsp::HoverContents::Scalar(marked) => marked_string(marked),
+        lsp::HoverContents::Array(parts) => parts
+            .into_iter()
+            .map(marked_string)
+            .collect::<Vec<_>>()
+            .join("\n\n---\n\n"),
+        lsp::HoverContents::Markup(markup) => markup.value,
+    };
+    HoverInfo {
+        contents,
+        range: hover.range.map(from_lsp_range),
+    }
+}
+
+fn definition_locations(response: lsp::GotoDefinitionResponse) -> Vec<Location> {
+    match response {
+        lsp::GotoDefinitionResponse::Scalar(location) => {
+            from_lsp_location(&location.uri, location.range)
+                .into_iter()
+                .collect()
+        }
+        lsp::GotoDefinitionResponse::Array(locations) => locations
+            .iter()
+            .filter_map(|l| from_lsp_location(&l.uri, l.range))
+            .collect(),
+        lsp::GotoDefinitionResponse::Link(links) => links
+            .iter()
+            .filter_map(|l| from_lsp_location(&l.target_uri, l.target_selection_range))
+            .collect(),
+    }
+}
+
+fn document_symbol(symbol: lsp::DocumentSymbol) -> DocumentSymbol {
+    DocumentSymbol {
+        name: symbol.name,
+        detail: symbol.detail,
+        kind: symbol_kind(symbol.kind),
+        range: from_lsp_range(symbol.range),
+        selection_range: from_lsp_range(symbol.selection_range),
+        children: symbol
+            .children
+            .unwrap_or_default()
+            .into_iter()
+            .map(document_symbol)
+            .collect(),
+    }
+}
+
+fn document_symbols(response: lsp::DocumentSymbolResponse) -> Vec<DocumentSymbol> {
+    match response {
+        lsp::DocumentSymbolResponse::Nested(symbols) => {
+            symbols.into_iter().map(document_symbol).collect()
+        }
+        // Flat lists carry no hierarchy; each symbol becomes a top-level entry
+        lsp::DocumentSymbolResponse::Flat(symbols) => symbols
+            .into_iter()
+            .map(|symbol| {
+                let range = from_lsp_range(symbol.location.range);
+                DocumentSymbol {
+                    name: symbol.name,
+                    detail: symbol.container_name,
+                    kind: symbol_kind(symbol.kind),
+                    range,
+                    selection_range: range,
+                    children: Vec::new(),
+                }
+            })
+            .collect(),
+    }
+}
+
+fn workspace_symbols(response: lsp::WorkspaceSymbolResponse) -> Vec<WorkspaceSymbol> {
+    match response {
+        lsp::WorkspaceSymbolResponse::Flat(symbols) => symbols
+            .into_iter()
+            .map(|symbol| WorkspaceSymbol {
+                location: from_lsp_location(&symbol.location.uri, symbol.location.range),
+                name: symbol.name,
+                kind: symbol_kind(symbol.kind),
+                container_name: symbol.container_name,
+            })
+            .collect(),
+        lsp::WorkspaceSymbolResponse::Nested(symbols) => symbols
+            .into_iter()
+            .map(|symbol| WorkspaceSymbol {
+                location: match &symbol.location {
+                    lsp::OneOf::Left(location) => from_lsp_location(&location.uri, location.range),
+                    lsp::OneOf::Right(_) => None,
+                },
+                name: symbol.name,
+                kind: symbol_kind(symbol.kind),
+                container_name: symbol.container_name,
+            })
+            .collect(),
+    }
+}
+
+fn supports<T>(provider: &Option<lsp::OneOf<bool, T>>) -> bool {
+    !matches!(provider, None | Some(lsp::OneOf::Left(false)))
+}
+
+/// Most documents kept open from disk per lane before the least recently used
+/// is closed
+const MAX_DISK_DOCUMENTS: usize = 32;
+
+/// A document opened in the servers from disk rather than by the editor
+struct DiskDocument {
+    lane_id: String,
+    path: PathBuf,
+    /// Contents last sent to the servers
+    text: String,
+}
+
+/// Documents opened in a lane's servers from disk for navigation
+///
+/// Requests need the file open in the server, but the diff and search views
+/// have no editor model to keep it in sync. These documents are re-read from
+/// disk on every request and sent again when they changed, and the least
+/// recently used ones are closed. When the editor opens one of them it takes
+/// over, and the document is no longer tracked here.
+#[derive(Default)]
+pub struct DiskDocuments {
+    /// Least recently used first
+    documents: Mutex<Vec<DiskDocument>>,
+}
+
+impl DiskDocuments {
+    /// Open `path` in the lane's servers from disk unless the editor already
+    /// has, or bring a document opened from disk up to date
+    pub(crate) fn ensure_open(
+        &self,
+        manager: &LspManager,
+        lane_id: &str,
+        path: &Path,
+    ) -> Result<(), String> {
+        let mut documents = self.documents.lock().unwrap();
+        let mut tracked = documents
+            .iter()
+            .position(|d| d.lane_id == lane_id && d.path == path);
+        let open = manager.is_document_open(lane_id, path);
+        match tracked {
+            None if open => return Ok(()),
+            // Servers started or restarted without it; open it again
+            Some(index) if !open => {
+                documents.remove(index);
+                tracked = None;
+            }
+            _ => {}
+        }
+
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        match tracked {
+            Some(index) => {
+                let mut document = documents.remove(index);
+                if document.text != text {
+                    manager
+                        .change_document(lane_id, path, text.clone())
+                        .map_err(|e| e.to_string())?;
+                    document.text = text;
+                }
+                documents.push(document);
+            }
+            None => {
+                manager
+                    .open_document(lane_id, path, text.clone())
+                    .map_err(|e| e.to_string())?;
+                documents.push(DiskDocument {
+                    lane_id: lane_id.to_string(),
+                    path: path.to_path_buf(),
+                    text,
+                });
+            }
+        }
+
+        let lane_documents = documents.iter().filter(|d| d.lane_id == lane_id).count();
+        if lane_documents > MAX_DISK_DOCUMENTS {
+            if let Some(index) = documents.iter().position(|d| d.lane_id == lane_id) {
+                let oldest = documents.remove(index);
+                manager
+                    .close_document(lane_id, &oldest.path)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop tracking a document, e.g. because the editor opened or closed it
+    pub(crate) fn forget(&self, lane_id: &str, path: &Path) {
+        self.documents
+            .lock()
+            .unwrap()
+            .retain(|d| !(d.lane_id == lane_id && d.path == path));
+    }
+
+    /// Stop tracking a lane's documents once its servers are shut down
+    pub(crate) fn forget_lane(&self, lane_id: &str) {
+        self.documents
+            .lock()
+            .unwrap()
+            .retain(|d| d.lane_id != lane_id);
+    }
+
+    #[cfg(test)]
+    fn tracked(&self, lane_id: &str) -> Vec<PathBuf> {
+        self.documents
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|d| d.lane_id == lane_id)
+            .map(|d| d.path.clone())
+            .collect()
+    }
+}
+
+/// Open `path` in the lane's servers unless the editor already has
+pub(crate) fn ensure_open(state: &LspState, lane_id: &str, path: &Path) -> Result<(), String> {
+    state
+        .disk_documents
+        .ensure_open(&state.manager, lane_id, path)
+}
+
+/// Resolve the server for a request on `path` and open the file in it
+fn prepare(
+    state: &LspState,
+    lane_id: &str,
+    path: &Path,
+    supported: impl Fn(&lsp::ServerCapabilities) -> bool,
+) -> Result<Option<(Arc<LspClient>, lsp::TextDocumentIdentifier)>, String> {
+    let Some(client) = state.manager.client_supporting(lane_id, path, supported) else {
+        return Ok(None);
+    };
+    ensure_open(state, lane_id, path)?;
+    let uri = codelane_lsp::manager::file_uri(path).map_err(|e| e.to_string())?;
+    Ok(Some((client, lsp::TextDocumentIdentifier { uri })))
+}
+
+/// Get hover information at a position
+///
+/// # Arguments
+/// * `lane_id` - Lane the file belongs to
+/// * `path` - Absolute path of the file
+/// * `position` - Position in the editor
+///
+/// # Returns
+/// Hover contents as markdown, or `None` if no server has any
+#[tauri::command]
+pub async fn lsp_hover(
+    lane_id: String,
+    path: String,
+    position: Position,
+    state: State<'_, LspState>,
+) -> Result<Option<HoverInfo>, String> {
+    let path = Path::new(&path);
+    let Some((client, text_document)) = prepare(&state, &lane_id, path, |c| {
+        !matches!(
+            c.hover_provider,
+            None | Some(lsp::HoverProviderCapability::Simple(false))
+        )
+    })?
+    else {
+        return Ok(None);
+    };
+
+    let hover = client
+        .request::<HoverRequest>(lsp::HoverParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position: to_lsp_position(position),
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .map_err(|e| format!("Hover failed: {}", e))?;
+    Ok(hover.map(hover_info))
+}
+
+/// Find where the symbol at a position is defined
+///
+/// # Arguments
+/// * `lane_id` - Lane the file belongs to
+/// * `path` - Absolute path of the file
+/// * `position` - Position in the editor
+///
+/// # Returns
+/// Definition locations; empty if none were found
+#[tauri::command]
+pub async fn lsp_definition(
+    lane_id: String,
+    path: String,
+    position: Position,
+    state: State<'_, LspState>,
+) -> Result<Vec<Location>, String> {
+    let path = Path::new(&path);
+    let Some((client, text_document)) =
+        prepare(&state, &lane_id, path, |c| supports(&c.definition_provider))?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let response = client
+        .request::<GotoDefinition>(lsp::GotoDefinitionParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position: to_lsp_position(position),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .map_err(|e| format!("Go to definition failed: {}", e))?;
+    Ok(response.map(definition_locations).unwrap_or_default())
+}
+
+/// Find references to the symbol at a position
+///
+/// # Arguments
+/// * `lane_id` - Lane the file belongs to
+/// * `path` - Absolute path of the file
+/// * `position` - Position in the editor
+/// * `include_declaration` - Also return the declaration (default: true)
+///
+/// # Returns
+/// Reference locations; empty if none were found
+#[tauri::command]
+pub async fn lsp_references(
+    lane_id: String,
+    path: String,
+    position: Position,
+    include_declaration: Option<bool>,
+    state: State<'_, LspState>,
+) -> Result<Vec<Location>, String> {
+    let path = Path::new(&path);
+    let Some((client, text_document)) =
+        prepare(&state, &lane_id, path, |c| supports(&c.references_provider))?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let locations = client
+        .request::<References>(lsp::ReferenceParams {
+            text_document_position: lsp::TextDocumentPositionParams {
+                text_document,
+                position: to_lsp_position(position),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: lsp::ReferenceContext {
+                include_declaration: include_declaration.unwrap_or(true),
+            },
+        })
+        .await
+        .map_err(|e| format!("Find references failed: {}", e))?;
+    Ok(locations
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|l| from_lsp_location(&l.uri, l.range))
+        .collect())
+}
+
+/// Get the symbol outline of a file
+///
+/// # Arguments
+/// * `lane_id` - Lane the file belongs to
+/// * `path` - Absolute path of the file
+///
+/// # Returns
+/// Top-level symbols with their nested children
+#[tauri::command]
+pub async fn lsp_document_symbols(
+    lane_id: String,
+    path: String,
+    state: State<'_, LspState>,
+) -> Result<Vec<DocumentSymbol>, String> {
+    let path = Path::new(&path);
+    let Some((client, text_document)) = prepare(&state, &lane_id, path, |c| {
+        supports(&c.document_symbol_provider)
+    })?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let response = client
+        .request::<DocumentSymbolRequest>(lsp::DocumentSymbolParams {
+            text_document,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .map_err(|e| format!("Document symbols failed: {}", e))?;
+    Ok(response.map(document_symbols).unwrap_or_default())
+}
+
+/// Search symbols across a lane's workspace
+///
+/// Every running server of the lane is queried; a server failing does not
+/// hide the results of the others.
+///
+/// # Arguments
+/// * `lane_id` - Lane to search
+/// * `query` - Symbol name query, matched fuzzily by the servers
+///
+/// # Returns
+/// Matching symbols from all servers
+#[tauri::command]
+pub async fn lsp_workspace_symbols(
+    lane_id: String,
+    query: String,
+    state: State<'_, LspState>,
+) -> Result<Vec<WorkspaceSymbol>, String> {
+    let clients = state.manager.lane_clients(&lane_id);
+    let mut symbols = Vec::new();
+    for client in clients {
+        if !client
+            .capabilities()
+            .is_some_and(|c| supports(&c.workspace_symbol_provider))
+        {
+            continue;
+        }
+        let params = lsp::WorkspaceSymbolParams {
+            query: query.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        match client.request::<WorkspaceSymbolRequest>(params).await {
+            Ok(response) => symbols.extend(response.map(workspace_symbols).unwrap_or_default()),
+            Err(e) => tracing::warn!("Workspace symbol search failed: {}", e),
+        }
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lsp_range(start: (u32, u32), end: (u32, u32)) -> lsp::Range {
+        lsp::Range::new(
+            lsp::Position::new(start.0, start.1),
+            lsp::Position::new(end.0, end.1),
+        )
+    }
+
+    #[test]
+    fn test_disk_documents_are_bounded_and_forgotten() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = LspManager::new();
+        let documents = DiskDocuments::default();
+        let paths: Vec<PathBuf> = (0..=MAX_DISK_DOCUMENTS)
+            .map(|i| {
+                let path = dir.path().join(format!("file{}.rs", i));
+                std::fs::write(&path, "fn main() {}\n").unwrap();
+                path
+            })
+            .collect();
+
+        for path in &paths {
+            documents.ensure_open(&manager, "lane-1", path).unwrap();
+        }
+        documents
+            .ensure_open(&manager, "lane-2", &paths[0])
+            .unwrap();
+
+        // The least recently used document of the lane was closed
+        let tracked = documents.tracked("lane-1");
+        assert_eq!(tracked.len(), MAX_DISK_DOCUMENTS);
+        assert!(!tracked.contains(&paths[0]));
+        assert_eq!(tracked.last(), paths.last());
+        assert_eq!(documents.tracked("lane-2"), vec![paths[0].clone()]);
+
+        // Using a document again makes it the most recently used
+        documents
+            .ensure_open(&manager, "lane-1", &paths[1])
+            .unwrap();
+        assert_eq!(documents.tracked("lane-1").last(), Some(&paths[1]));
+
+        documents.forget("lane-1", &paths[1]);
+        assert!(!documents.tracked("lane-1").contains(&paths[1]));
+        documents.forget_lane("lane-1");
+        assert!(documents.tracked("lane-1").is_empty());
+        assert_eq!(documents.tracked("lane-2").len(), 1);
+
+        let missing = dir.path().join("missing.rs");
+        assert!(documents.ensure_open(&manager, "lane-2", &missing).is_err());
+    }
+
+    #[test]
+    fn test_position_conversion() {
+        let position = Position { line: 3, column: 7 };
+        assert_eq!(to_lsp_position(position), lsp::Position::new(2, 6));
+        assert_eq!(from_lsp_position(to_lsp_position(position)), position);
+
+        // Out-of-range editor positions clamp instead of underflowing
+        let origin = Position { line: 0, column: 0 };
+        assert_eq!(to_lsp_position(origin), lsp::Position::new(0, 0));
+    }
+
+    #[test]
+    fn test_hover_contents_to_markdown() {
+        let hover = lsp::Hover {
+            contents: lsp::HoverContents::Array(vec![
+                lsp::MarkedString::LanguageString(lsp::LanguageString {
+                    language: "rust".to_string(),
+                    value: "fn main()".to_string(),
+                }),
+                lsp::MarkedString::String("Entry point".to_string()),
+            ]),
+            range: Some(lsp_range((0, 3), (0, 7))),
+        };
+        let info = hover_info(hover);
+        assert_eq!(
+            info.contents,
+            "```rust\nfn main()\n```\n\n---\n\nEntry point"
+        );
+        assert_eq!(info.range.unwrap().start, Position { line: 1, column: 4 });
+    }
+
+    #[test]
+    fn test_definition_links_use_selection_range() {
+        let uri = lsp::Url::parse("file:///src/lib.rs").unwrap();
+        let response = lsp::GotoDefinitionResponse::Link(vec![lsp::LocationLink {
+            origin_selection_range: None,
+            target_uri: uri,
+            target_range: lsp_range((10, 0), (20, 1)),
+            target_selection_range: lsp_range((10, 7), (10, 11)),
+        }]);
+        let locations = definition_locations(response);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].path, "/src/lib.rs");
+        assert_eq!(
+            locations[0].range.start,
+            Position {
+                line: 11,
+                column: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_non_file_locations_are_skipped() {
+        let response = lsp::GotoDefinitionResponse::Array(vec![
+            lsp::Location::new(
+                lsp::Url::parse("jdt://contents/String.class").unwrap(),
+                lsp_range((0, 0), (0, 1)),
+            ),
+            lsp::Location::new(
+                lsp::Url::parse("file:///src/main.rs").unwrap(),
+                lsp_range((0, 0), (0, 1)),
+            ),
+        ]);
+        let locations = definition_locations(response);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].path, "/src/main.rs");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_nested_document_symbols() {
+        let field = lsp::DocumentSymbol {
+            name: "name".to_string(),
+            detail: Some("String".to_string()),
+            kind: lsp::SymbolKind::FIELD,
+            tags: None,
+            deprecated: None,
+            range: lsp_range((1, 4), (1, 16)),
+            selection_range: lsp_range((1, 4), (1, 8)),
+            children: None,
+        };
+        let response = lsp::DocumentSymbolResponse::Nested(vec![lsp::DocumentSymbol {
+            name: "User".to_string(),
+            detail: None,
+            kind: lsp::SymbolKind::STRUCT,
+            tags: None,
+            deprecated: None,
+            range: lsp_range((0, 0), (2, 1)),
+            selection_range: lsp_range((0, 7), (0, 11)),
+            children: Some(vec![field]),
+        }]);
+
+        let symbols = document_symbols(response);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].kind, "struct");
+        assert_eq!(
+            symbols[0].selection_range.start,
+            Position { line: 1, column: 8 }
+        );
+        assert_eq!(symbols[0].children[0].name, "name");
+        assert_eq!(symbols[0].children[0].kind, "field");
+    }
+}
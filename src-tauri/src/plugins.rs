@@ -0,0 +1,308 @@
+//! Plugins
+//!
+//! Tauri commands around [`codelane_plugin::host::PluginHost`]: plugin
+//! commands for the command palette, and plugin review rules run against a
+//! working tree's uncommitted changes. Plugins are loaded from the plugins
+//! directory in the background at startup.
+//!
+//! In developer mode the same background thread reloads plugins whose files
+//! changed and emits a [`PLUGIN_RELOAD_EVENT`] for each reload.
+//!
+//! Calls into a plugin block for up to the host's per-call timeout, so the
+//! commands use the host on the blocking thread pool.
+
+use codelane_git::diff::DiffResult;
+use codelane_plugin::host::{PluginCommand, PluginHost, PluginInfo, PluginReload};
+use codelane_plugin::review::ReviewReport;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::git::{run_git, validate_git_path};
+
+/// Event emitted when developer mode reloads a plugin
+pub const PLUGIN_RELOAD_EVENT: &str = "plugin-reload";
+
+/// How often developer mode checks for changed plugins
+const RELOAD_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A plugin reloaded in developer mode
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginReloadEvent {
+    /// The plugin's directory
+    pub path: PathBuf,
+    /// The reloaded plugin, unless it was removed or failed to load
+    pub plugin: Option<PluginInfo>,
+    /// Why the reload failed; the previous version keeps running
+    pub error: Option<String>,
+}
+
+impl From<PluginReload> for PluginReloadEvent {
+    fn from(reload: PluginReload) -> Self {
+        match reload {
+            PluginReload::Loaded(info) => Self {
+                path: info.path.clone(),
+                plugin: Some(*info),
+                error: None,
+            },
+            PluginReload::Removed(path) => Self {
+                path,
+                plugin: None,
+                error: None,
+            },
+            PluginReload::Failed(path, e) => Self {
+                path,
+                plugin: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Plugin host shared by all lanes
+pub struct PluginState {
+    /// The host, or why it could not be created
+    host: Result<Mutex<PluginHost>, String>,
+}
+
+impl PluginState {
+    /// Create the host for the plugins directory; plugins are loaded by
+    /// [`load_all`](Self::load_all)
+    pub fn new() -> Self {
+        let host = codelane_core::paths::plugins_dir()
+            .map_err(|e| e.to_string())
+            .and_then(|dir| PluginHost::new(dir).map_err(|e| e.to_string()))
+            .map(Mutex::new);
+        if let Err(e) = &host {
+            tracing::error!("Plugins are unavailable: {}", e);
+        }
+        Self { host }
+    }
+
+    pub(crate) fn host(&self) -> Result<MutexGuard<'_, PluginHost>, String> {
+        match &self.host {
+            Ok(host) => Ok(host.lock().unwrap()),
+            Err(e) => Err(format!("Plugins are unavailable: {}", e)),
+        }
+    }
+
+    /// Load the installed plugins
+    pub fn load_all(&self) {
+        let loaded = self
+            .host()
+            .and_then(|mut host| host.load_all().map_err(|e| e.to_string()));
+        match loaded {
+            Ok(plugins) => tracing::info!("Loaded {} plugins", plugins.len()),
+            Err(e) => tracing::warn!("Failed to load plugins: {}", e),
+        }
+    }
+}
+
+impl Default for PluginState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reload changed plugins while developer mode is on, emitting
+/// [`PLUGIN_RELOAD_EVENT`] for each reload. Runs until the host is
+/// unavailable, so call it on a dedicated thread.
+pub fn reload_changed_plugins(app: AppHandle) {
+    loop {
+        std::thread::sleep(RELOAD_INTERVAL);
+        let reloads = match app.state::<PluginState>().host() {
+            Ok(mut host) => host.reload_changed(),
+            Err(_) => return,
+        };
+        for reload in reloads {
+            let event = PluginReloadEvent::from(reload);
+            match &event.error {
+                Some(e) => tracing::warn!("Failed to reload {}: {}", event.path.display(), e),
+                None => tracing::info!("Reloaded {}", event.path.display()),
+            }
+            let _ = app.emit(PLUGIN_RELOAD_EVENT, &event);
+        }
+    }
+}
+
+/// Run `f` with the plugin host on the blocking thread pool, since the
+/// host stays locked while a plugin call runs
+pub(crate) async fn with_host<R: Send + 'static>(
+    app: AppHandle,
+    f: impl FnOnce(&mut PluginHost) -> Result<R, String> + Send + 'static,
+) -> Result<R, String> {
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<PluginState>();
+        let mut host = state.host()?;
+        f(&mut host)
+    })
+    .await
+    .map_err(|e| format!("Plugin task failed: {}", e))?
+}
+
+/// Uncommitted changes of the working tree at `working_dir`, with paths
+/// relative to it
+fn uncommitted_changes(working_dir: &Path) -> Result<DiffResult, String> {
+    let diff = run_git(
+        working_dir,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--relative",
+            "HEAD",
+            "--",
+        ],
+    )
+    .map_err(|e| format!("Failed to diff {}: {}", working_dir.display(), e))?;
+    Ok(DiffResult::parse(&diff))
+}
+
+/// Run every plugin review rule against the uncommitted changes in
+/// `working_dir`
+fn review_changes(host: &mut PluginHost, working_dir: &Path) -> Result<ReviewReport, String> {
+    let diff = uncommitted_changes(working_dir)?;
+    Ok(host.review(working_dir, &diff.files))
+}
+
+/// Get the commands of the active plugins, for the command palette
+#[tauri::command]
+pub async fn plugin_commands(app: AppHandle) -> Result<Vec<PluginCommand>, String> {
+    with_host(app, |host| Ok(host.commands())).await
+}
+
+/// Run a plugin command
+///
+/// # Arguments
+/// * `plugin` - Name of the plugin that registered the command
+/// * `id` - Command id
+/// * `working_dir` - Directory the plugin's file access is confined to,
+///   usually the active lane's working directory
+#[tauri::command]
+pub async fn plugin_run_command(
+    plugin: String,
+    id: String,
+    working_dir: Option<String>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let workspace = working_dir
+        .map(|dir| validate_git_path(&dir).map(PathBuf::from))
+        .transpose()?;
+    with_host(app, move |host| {
+        host.run_command(&plugin, &id, workspace.as_deref())
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Turn plugin developer mode on or off
+///
+/// In developer mode plugins are reloaded when their files change, and each
+/// reload emits a [`PLUGIN_RELOAD_EVENT`].
+#[tauri::command]
+pub async fn plugin_set_developer_mode(enabled: bool, app: AppHandle) -> Result<(), String> {
+    with_host(app, move |host| {
+        host.set_developer_mode(enabled)
+            .map_err(|e| format!("Failed to set developer mode: {}", e))
+    })
+    .await
+}
+
+/// Whether plugin developer mode is on
+#[tauri::command]
+pub async fn plugin_developer_mode(app: AppHandle) -> Result<bool, String> {
+    with_host(app, |host| Ok(host.developer_mode())).await
+}
+
+/// Run the plugins' review rules against a working tree's uncommitted changes
+///
+/// # Arguments
+/// * `working_dir` - Working tree to review, usually a lane's
+///
+/// # Returns
+/// Findings sorted by file and line, and the rules that failed to run
+#[tauri::command]
+pub async fn plugin_review(working_dir: String, app: AppHandle) -> Result<ReviewReport, String> {
+    let working_dir = PathBuf::from(validate_git_path(&working_dir)?);
+    with_host(app, move |host| review_changes(host, &working_dir)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_uncommitted_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo = temp_dir.path();
+        git(repo, &["init", "-q"]);
+        std::fs::write(repo.join("café.rs"), "fn a() {}\n").unwrap();
+        git(repo, &["add", "."]);
+        git(
+            repo,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-q",
+                "-m",
+                "init",
+            ],
+        );
+        std::fs::write(repo.join("café.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+
+        // Non-ASCII paths are quoted by git and come back unquoted
+        let diff = uncommitted_changes(repo).unwrap();
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, PathBuf::from("café.rs"));
+        assert_eq!(diff.additions, 1);
+
+        let mut host = PluginHost::new(repo.join("plugins")).unwrap();
+        let report = review_changes(&mut host, repo).unwrap();
+        assert!(report.findings.is_empty());
+        assert!(report.errors.is_empty());
+
+        assert!(uncommitted_changes(&repo.join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_reload_event() {
+        let dir = PathBuf::from("/plugins/linter");
+        let event = PluginReloadEvent::from(PluginReload::Failed(
+            dir.clone(),
+            codelane_plugin::Error::Other("missing entry".to_string()),
+        ));
+        assert_eq!(event.path, dir);
+        assert!(event.plugin.is_none());
+        assert!(event.error.unwrap().contains("missing entry"));
+
+        let event = PluginReloadEvent::from(PluginReload::Removed(dir.clone()));
+        assert_eq!(event.path, dir);
+        assert!(event.plugin.is_none() && event.error.is_none());
+    }
+
+    #[test]
+    fn test_unavailable_host() {
+        let state = PluginState {
+            host: Err("no engine".to_string()),
+        };
+        assert_eq!(
+            state.host().err(),
+            Some("Plugins are unavailable: no engine".to_string())
+        );
+    }
+}
@@ -1,8 +1,29 @@
 //! Process monitoring for terminal sessions and app resource usage
+//!
+//! Also provides process tree inspection and a graceful shutdown sequence
+//! (SIGINT, then SIGTERM, then SIGKILL) for everything running under a
+//! terminal, so stopping a lane stops the dev servers its agent started.
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
-use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System, MemoryRefreshKind};
+use std::time::{Duration, Instant};
+use sysinfo::{
+    MemoryRefreshKind, Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate,
+    RefreshKind, Signal, System,
+};
+use tauri::State;
+
+use crate::process_registry::ProcessRegistry;
+
+/// Default time to wait after SIGINT before escalating (milliseconds)
+pub const DEFAULT_INTERRUPT_TIMEOUT_MS: u64 = 2000;
+
+/// Default time to wait after SIGTERM before escalating to SIGKILL (milliseconds)
+pub const DEFAULT_TERMINATE_TIMEOUT_MS: u64 = 3000;
+
+/// How often to poll for exited processes during shutdown
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Cached system instance for efficient process monitoring
 static SYSTEM: Mutex<Option<System>> = Mutex::new(None);
@@ -42,59 +63,347 @@ pub struct AppResourceUsage {
     pub memory_percent: f32,   // Memory usage as percentage of total system memory
 }
 
-/// Find process PID by environment variable (lane ID)
-#[tauri::command]
-pub fn find_process_by_lane(lane_id: String) -> Result<Option<u32>, String> {
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-
-        // Use ps to find processes with CODELANE_LANE_ID env var
-        // macOS-specific: use ps with -E flag to show environment
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(format!(
-                "ps -eo pid,command | grep -v grep | grep 'CODELANE_LANE_ID={}' | awk '{{print $1}}' | head -1",
-                lane_id
-            ))
-            .output()
-            .map_err(|e| format!("Failed to execute ps command: {}", e))?;
-
-        if output.status.success() {
-            let pid_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !pid_str.is_empty() {
-                if let Ok(pid) = pid_str.parse::<u32>() {
-                    return Ok(Some(pid));
-                }
+/// A process and everything running under it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessTreeNode {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub cmdline: String,
+    pub cpu_usage: f32,      // Percentage
+    pub memory_usage: u64,   // Bytes
+    pub memory_usage_mb: f64, // Megabytes for display
+    pub children: Vec<ProcessTreeNode>,
+}
+
+/// Outcome of a graceful shutdown of a process tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownReport {
+    /// Every PID that was part of the tree
+    pub pids: Vec<u32>,
+    /// PIDs that were sent SIGINT and exited before SIGTERM
+    pub interrupted: Vec<u32>,
+    /// PIDs that exited after SIGTERM
+    pub terminated: Vec<u32>,
+    /// PIDs that had to be sent SIGKILL
+    pub killed: Vec<u32>,
+    /// PIDs still alive after SIGKILL (e.g. owned by another user)
+    pub remaining: Vec<u32>,
+}
+
+/// Map of parent PID to child PIDs for the current process table
+fn children_map(system: &System) -> HashMap<Pid, Vec<Pid>> {
+    let mut map: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        if let Some(parent) = process.parent() {
+            map.entry(parent).or_default().push(*pid);
+        }
+    }
+    map
+}
+
+/// `root` and all of its descendants, parents before children
+fn collect_descendants(system: &System, root: Pid) -> Vec<Pid> {
+    let children = children_map(system);
+    let mut result = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![root];
+
+    while let Some(pid) = stack.pop() {
+        if !seen.insert(pid) || system.process(pid).is_none() {
+            continue;
+        }
+        result.push(pid);
+        if let Some(kids) = children.get(&pid) {
+            stack.extend(kids.iter().copied());
+        }
+    }
+
+    result
+}
+
+fn build_tree_node(
+    system: &System,
+    children: &HashMap<Pid, Vec<Pid>>,
+    pid: Pid,
+    depth: usize,
+) -> Option<ProcessTreeNode> {
+    let process = system.process(pid)?;
+
+    // Guard against cycles from PID reuse while the table was being read
+    let mut kids: Vec<ProcessTreeNode> = if depth < 64 {
+        children
+            .get(&pid)
+            .map(|pids| {
+                pids.iter()
+                    .filter_map(|child| build_tree_node(system, children, *child, depth + 1))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    kids.sort_by_key(|node| node.pid);
+
+    let memory_bytes = process.memory();
+    Some(ProcessTreeNode {
+        pid: pid.as_u32(),
+        parent_pid: process.parent().map(|p| p.as_u32()),
+        name: process.name().to_string_lossy().to_string(),
+        cmdline: process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" "),
+        cpu_usage: process.cpu_usage(),
+        memory_usage: memory_bytes,
+        memory_usage_mb: memory_bytes as f64 / 1024.0 / 1024.0,
+        children: kids,
+    })
+}
+
+/// Build the process tree rooted at `pid`
+pub fn process_tree(pid: u32) -> Result<ProcessTreeNode, String> {
+    let mut system_guard = get_system();
+    let system = system_guard.as_mut().unwrap();
+
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::everything(),
+    );
+
+    let children = children_map(system);
+    build_tree_node(system, &children, Pid::from_u32(pid), 0)
+        .ok_or_else(|| format!("Process {} not found", pid))
+}
+
+/// Start time of a running process (seconds since the epoch)
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let mut system_guard = get_system();
+    let system = system_guard.as_mut().unwrap();
+    let sys_pid = Pid::from_u32(pid);
+
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[sys_pid]),
+        true,
+        ProcessRefreshKind::new(),
+    );
+    system.process(sys_pid).map(|p| p.start_time())
+}
+
+/// Resource usage of a set of process trees
+#[derive(Debug, Clone, Default)]
+pub struct TreeUsage {
+    /// Roots that are still running
+    pub live_roots: Vec<u32>,
+    /// Stats for every live root and descendant (each process once)
+    pub processes: Vec<ProcessStats>,
+}
+
+/// Collect stats for the given roots and all of their descendants
+///
+/// Each root is `(pid, start_time)`; a root whose PID now belongs to a
+/// different process (start time mismatch) is treated as exited. A start time
+/// of 0 matches any process. Uses a single process table refresh.
+pub fn tree_usage(roots: &[(u32, u64)]) -> TreeUsage {
+    let mut system_guard = get_system();
+    let system = system_guard.as_mut().unwrap();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::everything(),
+    );
+
+    let mut usage = TreeUsage::default();
+    let mut seen = HashSet::new();
+
+    for &(pid, start_time) in roots {
+        let sys_pid = Pid::from_u32(pid);
+        let alive = system.process(sys_pid).is_some_and(|p| {
+            (start_time == 0 || p.start_time() == start_time)
+                && !matches!(p.status(), ProcessStatus::Zombie | ProcessStatus::Dead)
+        });
+        if !alive {
+            continue;
+        }
+        usage.live_roots.push(pid);
+
+        for member in collect_descendants(system, sys_pid) {
+            if !seen.insert(member) {
+                continue;
+            }
+            if let Some(process) = system.process(member) {
+                let memory_bytes = process.memory();
+                usage.processes.push(ProcessStats {
+                    pid: member.as_u32(),
+                    cpu_usage: process.cpu_usage(),
+                    memory_usage: memory_bytes,
+                    memory_usage_mb: memory_bytes as f64 / 1024.0 / 1024.0,
+                });
             }
         }
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
+    usage
+}
 
-        // Linux: use /proc filesystem
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(format!(
-                "grep -l 'CODELANE_LANE_ID={}' /proc/*/environ 2>/dev/null | head -1 | cut -d/ -f3",
-                lane_id
-            ))
-            .output()
-            .map_err(|e| format!("Failed to execute grep command: {}", e))?;
-
-        if output.status.success() {
-            let pid_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !pid_str.is_empty() {
-                if let Ok(pid) = pid_str.parse::<u32>() {
-                    return Ok(Some(pid));
-                }
+/// A process identity that survives PID reuse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TrackedProcess {
+    pid: Pid,
+    start_time: u64,
+}
+
+fn is_alive(system: &System, tracked: &TrackedProcess) -> bool {
+    system.process(tracked.pid).is_some_and(|p| {
+        p.start_time() == tracked.start_time
+            && !matches!(p.status(), ProcessStatus::Zombie | ProcessStatus::Dead)
+    })
+}
+
+fn send_signal(process: &Process, signal: Signal) {
+    // Platforms without the signal (Windows) only support a hard kill
+    if process.kill_with(signal).is_none() {
+        process.kill();
+    }
+}
+
+/// Refresh the process table, add any new descendants of tracked processes,
+/// and return the tracked processes that are still alive
+fn refresh_tracked(tracked: &mut HashSet<TrackedProcess>, roots: &[Pid]) -> Vec<TrackedProcess> {
+    let mut system_guard = get_system();
+    let system = system_guard.as_mut().unwrap();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::new(),
+    );
+
+    // Children spawned while shutting down (e.g. a server restarting a worker)
+    let alive_roots: Vec<Pid> = tracked
+        .iter()
+        .filter(|t| is_alive(system, t))
+        .map(|t| t.pid)
+        .chain(roots.iter().copied())
+        .collect();
+    for root in alive_roots {
+        for pid in collect_descendants(system, root) {
+            if let Some(process) = system.process(pid) {
+                tracked.insert(TrackedProcess {
+                    pid,
+                    start_time: process.start_time(),
+                });
             }
         }
     }
 
-    Ok(None)
+    tracked.iter().filter(|t| is_alive(system, t)).copied().collect()
+}
+
+fn signal_all(targets: &[TrackedProcess], signal: Signal) {
+    let system_guard = get_system();
+    let system = system_guard.as_ref().unwrap();
+    for target in targets {
+        if let Some(process) = system.process(target.pid) {
+            if process.start_time() == target.start_time {
+                send_signal(process, signal);
+            }
+        }
+    }
+}
+
+/// Wait until all tracked processes have exited or the timeout expires,
+/// returning the ones still alive
+fn wait_for_exit(tracked: &mut HashSet<TrackedProcess>, timeout: Duration) -> Vec<TrackedProcess> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let alive = refresh_tracked(tracked, &[]);
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+        std::thread::sleep(SHUTDOWN_POLL_INTERVAL);
+    }
+}
+
+fn pids_of(processes: &[TrackedProcess]) -> Vec<u32> {
+    let mut pids: Vec<u32> = processes.iter().map(|t| t.pid.as_u32()).collect();
+    pids.sort_unstable();
+    pids
+}
+
+/// Gracefully stop a process and all of its descendants
+///
+/// Sends SIGINT to the whole tree and waits up to `interrupt_timeout`, then
+/// SIGTERM to whatever is left and waits up to `terminate_timeout`, then
+/// SIGKILL. Descendants are tracked from the start, so processes orphaned
+/// when their parent exits are still stopped. This blocks while waiting.
+pub fn shutdown_process_tree(
+    root_pid: u32,
+    interrupt_timeout: Duration,
+    terminate_timeout: Duration,
+) -> ShutdownReport {
+    let root = Pid::from_u32(root_pid);
+    let mut tracked = HashSet::new();
+    let mut report = ShutdownReport::default();
+
+    let initial = refresh_tracked(&mut tracked, &[root]);
+    if initial.is_empty() {
+        return report;
+    }
+
+    // SIGINT: lets dev servers and agents clean up like a Ctrl+C would
+    signal_all(&initial, Signal::Interrupt);
+    let after_interrupt = wait_for_exit(&mut tracked, interrupt_timeout);
+    let alive_after_interrupt: HashSet<TrackedProcess> = after_interrupt.iter().copied().collect();
+
+    let mut after_terminate = Vec::new();
+    if !after_interrupt.is_empty() {
+        signal_all(&after_interrupt, Signal::Term);
+        after_terminate = wait_for_exit(&mut tracked, terminate_timeout);
+    }
+    let terminated: HashSet<TrackedProcess> = after_terminate.iter().copied().collect();
+
+    if !after_terminate.is_empty() {
+        signal_all(&after_terminate, Signal::Kill);
+        report.remaining = pids_of(&wait_for_exit(&mut tracked, SHUTDOWN_POLL_INTERVAL * 5));
+    }
+
+    let all: Vec<TrackedProcess> = tracked.iter().copied().collect();
+    report.pids = pids_of(&all);
+    // Only the SIGINT targets count; descendants found later were never interrupted
+    report.interrupted = pids_of(
+        &initial
+            .iter()
+            .filter(|t| !alive_after_interrupt.contains(t))
+            .copied()
+            .collect::<Vec<_>>(),
+    );
+    report.terminated = pids_of(
+        &after_interrupt
+            .iter()
+            .filter(|t| !terminated.contains(t))
+            .copied()
+            .collect::<Vec<_>>(),
+    );
+    report.killed = pids_of(&after_terminate);
+
+    report
+}
+
+/// Find the main process of a lane
+///
+/// Resolved from the process registry (terminals first, then other processes
+/// Codelane spawned for the lane) instead of scanning every process's environment.
+#[tauri::command]
+pub fn find_process_by_lane(
+    registry: State<'_, ProcessRegistry>,
+    lane_id: String,
+) -> Result<Option<u32>, String> {
+    Ok(registry.primary_pid(&lane_id))
 }
 
 /// Get process statistics for a given PID
@@ -127,6 +436,35 @@ pub fn get_process_stats(pid: u32) -> Result<ProcessStats, String> {
     }
 }
 
+/// Get the process tree rooted at a PID
+///
+/// Returns name, command line, CPU and memory for the process and all of its
+/// descendants.
+#[tauri::command]
+pub fn get_process_tree(pid: u32) -> Result<ProcessTreeNode, String> {
+    process_tree(pid)
+}
+
+/// Gracefully stop a process and all of its descendants
+///
+/// SIGINT, then SIGTERM after `interrupt_timeout_ms`, then SIGKILL after
+/// `terminate_timeout_ms`.
+#[tauri::command]
+pub async fn kill_process_tree(
+    pid: u32,
+    interrupt_timeout_ms: Option<u64>,
+    terminate_timeout_ms: Option<u64>,
+) -> Result<ShutdownReport, String> {
+    let interrupt =
+        Duration::from_millis(interrupt_timeout_ms.unwrap_or(DEFAULT_INTERRUPT_TIMEOUT_MS));
+    let terminate =
+        Duration::from_millis(terminate_timeout_ms.unwrap_or(DEFAULT_TERMINATE_TIMEOUT_MS));
+
+    tokio::task::spawn_blocking(move || shutdown_process_tree(pid, interrupt, terminate))
+        .await
+        .map_err(|e| format!("Shutdown task failed: {}", e))
+}
+
 /// Get resource usage for the Codelane app (including all child processes like WebView)
 #[tauri::command]
 pub fn get_app_resource_usage() -> Result<AppResourceUsage, String> {
@@ -359,6 +697,132 @@ mod tests {
         }
     }
 
+    // =========================================================================
+    // Process tree tests
+    // =========================================================================
+
+    #[cfg(unix)]
+    fn spawn_sleeper_tree() -> std::process::Child {
+        // A shell with two sleeping children
+        std::process::Command::new("sh")
+            .arg("-c")
+            .arg("sleep 30 & sleep 30 & wait")
+            .spawn()
+            .expect("failed to spawn test processes")
+    }
+
+    #[cfg(unix)]
+    fn wait_for_children(pid: u32, count: usize) -> ProcessTreeNode {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let tree = process_tree(pid).unwrap();
+            if tree.children.len() >= count || Instant::now() >= deadline {
+                return tree;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn test_process_tree_not_found() {
+        let result = process_tree(4294967295);
+        assert!(result.unwrap_err().contains("not found"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_tree_includes_children() {
+        let mut child = spawn_sleeper_tree();
+        let pid = child.id();
+
+        let tree = wait_for_children(pid, 2);
+        assert_eq!(tree.pid, pid);
+        assert_eq!(tree.children.len(), 2);
+        assert!(tree.children.iter().all(|c| c.name.contains("sleep")));
+        assert!(tree.children.iter().all(|c| c.parent_pid == Some(pid)));
+        assert!(tree.cmdline.contains("sleep 30"));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shutdown_process_tree_stops_descendants() {
+        let mut child = spawn_sleeper_tree();
+        let pid = child.id();
+        let tree = wait_for_children(pid, 2);
+        let child_pids: Vec<u32> = tree.children.iter().map(|c| c.pid).collect();
+
+        // Reap the shell in the background so it does not linger as a zombie
+        let reaper = std::thread::spawn(move || child.wait());
+
+        let report =
+            shutdown_process_tree(pid, Duration::from_millis(500), Duration::from_millis(500));
+        assert!(report.pids.contains(&pid));
+        for child_pid in &child_pids {
+            assert!(report.pids.contains(child_pid));
+        }
+        assert!(report.remaining.is_empty());
+        let signalled: Vec<u32> = std::iter::once(pid)
+            .chain(child_pids.iter().copied())
+            .collect();
+        assert!(report.interrupted.iter().all(|p| signalled.contains(p)));
+
+        reaper.join().unwrap().unwrap();
+
+        // Orphaned children may linger as zombies until init reaps them
+        let mut system_guard = get_system();
+        let system = system_guard.as_mut().unwrap();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new(),
+        );
+        for child_pid in child_pids {
+            let status = system.process(Pid::from_u32(child_pid)).map(|p| p.status());
+            assert!(matches!(
+                status,
+                None | Some(ProcessStatus::Zombie) | Some(ProcessStatus::Dead)
+            ));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tree_usage_counts_descendants_once() {
+        let mut child = spawn_sleeper_tree();
+        let pid = child.id();
+        wait_for_children(pid, 2);
+
+        // The same root listed twice must not double count
+        let usage = tree_usage(&[(pid, 0), (pid, 0)]);
+        assert_eq!(usage.live_roots, vec![pid, pid]);
+        assert_eq!(usage.processes.len(), 3);
+
+        // A mismatched start time means the PID was reused by another process
+        let start = process_start_time(pid).unwrap();
+        assert!(tree_usage(&[(pid, start + 1)]).live_roots.is_empty());
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_shutdown_report_serialization() {
+        let report = ShutdownReport {
+            pids: vec![1, 2],
+            interrupted: vec![1],
+            terminated: vec![2],
+            killed: vec![],
+            remaining: vec![],
+        };
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"interrupted\":[1]"));
+        assert!(json.contains("\"terminated\":[2]"));
+    }
+
     // =========================================================================
     // Memory calculation tests
     // =========================================================================
@@ -0,0 +1,423 @@
+//! Registry of processes Codelane spawns on behalf of lanes
+//!
+//! Every terminal shell and AI tool invocation is registered with its lane
+//! when it starts and unregistered when it ends. Lane-level resource usage,
+//! "stop everything in this lane" and the status bar all read from here
+//! instead of scanning the environment of every process on the machine.
+//!
+//! Entries remember the process start time so a recycled PID is never
+//! mistaken for a lane process; entries whose process has exited are pruned
+//! lazily on lookup.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::State;
+
+use crate::process::{
+    self, ShutdownReport, DEFAULT_INTERRUPT_TIMEOUT_MS, DEFAULT_TERMINATE_TIMEOUT_MS,
+};
+
+/// What a registered process is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProcessKind {
+    /// Shell running in a lane terminal
+    Terminal,
+    /// AI tool invocation (review generation, commit messages, ...)
+    AiTool,
+    /// Anything else spawned for the lane
+    Other,
+}
+
+/// A process registered for a lane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredProcess {
+    pub pid: u32,
+    pub lane_id: String,
+    pub kind: ProcessKind,
+    /// Human-readable label (terminal ID, tool name, script path)
+    pub label: String,
+    /// Process start time (seconds since the epoch, 0 if unknown)
+    pub start_time: u64,
+    /// When the process was registered (unix timestamp)
+    pub registered_at: i64,
+}
+
+/// Resource usage of everything running for a lane
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaneResourceUsage {
+    pub lane_id: String,
+    /// Registered processes that are still running
+    pub processes: Vec<RegisteredProcess>,
+    /// Number of running processes, including descendants
+    pub process_count: usize,
+    /// Total CPU usage (percentage, may exceed 100 on multi-core systems)
+    pub cpu_percent: f32,
+    /// Total resident memory (bytes)
+    pub memory_usage: u64,
+    /// Total resident memory (MB)
+    pub memory_mb: f64,
+}
+
+/// Removes a registration when dropped
+///
+/// Returned by [`ProcessRegistry::track`] for processes whose lifetime is a
+/// single scope, such as an AI tool invocation.
+pub struct Registration<'a> {
+    registry: &'a ProcessRegistry,
+    pid: u32,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.registry.unregister(self.pid);
+    }
+}
+
+/// Lane process registry (managed Tauri state)
+#[derive(Default)]
+pub struct ProcessRegistry {
+    lanes: Mutex<HashMap<String, Vec<RegisteredProcess>>>,
+}
+
+impl ProcessRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a running process for a lane
+    pub fn register(&self, lane_id: &str, pid: u32, kind: ProcessKind, label: &str) {
+        if pid == 0 {
+            return;
+        }
+        let start_time = process::process_start_time(pid).unwrap_or(0);
+        self.insert(RegisteredProcess {
+            pid,
+            lane_id: lane_id.to_string(),
+            kind,
+            label: label.to_string(),
+            start_time,
+            registered_at: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    /// Register a process for the lifetime of the returned guard
+    pub fn track(
+        &self,
+        lane_id: &str,
+        pid: u32,
+        kind: ProcessKind,
+        label: &str,
+    ) -> Registration<'_> {
+        self.register(lane_id, pid, kind, label);
+        Registration {
+            registry: self,
+            pid,
+        }
+    }
+
+    fn insert(&self, entry: RegisteredProcess) {
+        let mut lanes = self.lanes.lock().unwrap();
+        // A PID can only belong to one lane at a time
+        for entries in lanes.values_mut() {
+            entries.retain(|e| e.pid != entry.pid);
+        }
+        lanes.entry(entry.lane_id.clone()).or_default().push(entry);
+    }
+
+    /// Remove a process from the registry
+    pub fn unregister(&self, pid: u32) {
+        let mut lanes = self.lanes.lock().unwrap();
+        for entries in lanes.values_mut() {
+            entries.retain(|e| e.pid != pid);
+        }
+        lanes.retain(|_, entries| !entries.is_empty());
+    }
+
+    /// Lanes with at least one registered process
+    pub fn lane_ids(&self) -> Vec<String> {
+        let lanes = self.lanes.lock().unwrap();
+        let mut ids: Vec<String> = lanes.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Registered processes of a lane, without checking whether they still run
+    pub fn entries(&self, lane_id: &str) -> Vec<RegisteredProcess> {
+        let lanes = self.lanes.lock().unwrap();
+        lanes.get(lane_id).cloned().unwrap_or_default()
+    }
+
+    /// Drop entries of a lane whose process is no longer one of `live_pids`
+    fn retain_live(&self, lane_id: &str, live_pids: &[u32]) {
+        let mut lanes = self.lanes.lock().unwrap();
+        if let Some(entries) = lanes.get_mut(lane_id) {
+            entries.retain(|e| live_pids.contains(&e.pid));
+            if entries.is_empty() {
+                lanes.remove(lane_id);
+            }
+        }
+    }
+
+    /// Resource usage of a lane's processes and all their descendants
+    ///
+    /// Registrations whose process has exited are pruned.
+    pub fn lane_usage(&self, lane_id: &str) -> LaneResourceUsage {
+        let entries = self.entries(lane_id);
+        let roots: Vec<(u32, u64)> = entries.iter().map(|e| (e.pid, e.start_time)).collect();
+        let usage = process::tree_usage(&roots);
+        self.retain_live(lane_id, &usage.live_roots);
+
+        let memory_usage: u64 = usage.processes.iter().map(|p| p.memory_usage).sum();
+        LaneResourceUsage {
+            lane_id: lane_id.to_string(),
+            processes: entries
+                .into_iter()
+                .filter(|e| usage.live_roots.contains(&e.pid))
+                .collect(),
+            process_count: usage.processes.len(),
+            cpu_percent: usage.processes.iter().map(|p| p.cpu_usage).sum(),
+            memory_usage,
+            memory_mb: memory_usage as f64 / 1024.0 / 1024.0,
+        }
+    }
+
+    /// Running registered processes of a lane
+    pub fn live_entries(&self, lane_id: &str) -> Vec<RegisteredProcess> {
+        self.lane_usage(lane_id).processes
+    }
+
+    /// The lane's main process: its first terminal, else its oldest process
+    pub fn primary_pid(&self, lane_id: &str) -> Option<u32> {
+        let live = self.live_entries(lane_id);
+        live.iter()
+            .find(|e| e.kind == ProcessKind::Terminal)
+            .or_else(|| live.first())
+            .map(|e| e.pid)
+    }
+}
+
+/// List the running processes registered for a lane
+///
+/// # Arguments
+/// * `lane_id` - Lane ID
+///
+/// # Returns
+/// Terminals, AI tools and other processes currently running for the lane
+#[tauri::command]
+pub fn list_lane_processes(
+    registry: State<'_, ProcessRegistry>,
+    lane_id: String,
+) -> Result<Vec<RegisteredProcess>, String> {
+    Ok(registry.live_entries(&lane_id))
+}
+
+/// Get the resource usage of a lane
+///
+/// # Arguments
+/// * `lane_id` - Lane ID
+///
+/// # Returns
+/// Aggregated CPU and memory of the lane's processes and their descendants
+#[tauri::command]
+pub fn get_lane_resource_usage(
+    registry: State<'_, ProcessRegistry>,
+    lane_id: String,
+) -> Result<LaneResourceUsage, String> {
+    Ok(registry.lane_usage(&lane_id))
+}
+
+/// Get the resource usage of every lane with running processes
+///
+/// # Returns
+/// One entry per lane, for the status bar
+#[tauri::command]
+pub fn get_lanes_resource_usage(
+    registry: State<'_, ProcessRegistry>,
+) -> Result<Vec<LaneResourceUsage>, String> {
+    Ok(registry
+        .lane_ids()
+        .iter()
+        .map(|lane_id| registry.lane_usage(lane_id))
+        .filter(|usage| !usage.processes.is_empty())
+        .collect())
+}
+
+/// Stop every process running for a lane
+///
+/// Each registered process tree is shut down concurrently with SIGINT, then
+/// SIGTERM, then SIGKILL.
+///
+/// # Arguments
+/// * `lane_id` - Lane ID
+/// * `interrupt_timeout_ms` - Grace period after SIGINT (default 2000)
+/// * `terminate_timeout_ms` - Grace period after SIGTERM (default 3000)
+///
+/// # Returns
+/// One shutdown report per registered process
+#[tauri::command]
+pub async fn kill_lane_processes(
+    registry: State<'_, ProcessRegistry>,
+    lane_id: String,
+    interrupt_timeout_ms: Option<u64>,
+    terminate_timeout_ms: Option<u64>,
+) -> Result<Vec<ShutdownReport>, String> {
+    let interrupt =
+        Duration::from_millis(interrupt_timeout_ms.unwrap_or(DEFAULT_INTERRUPT_TIMEOUT_MS));
+    let terminate =
+        Duration::from_millis(terminate_timeout_ms.unwrap_or(DEFAULT_TERMINATE_TIMEOUT_MS));
+
+    let entries = registry.live_entries(&lane_id);
+    let handles: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let pid = entry.pid;
+            tokio::task::spawn_blocking(move || {
+                process::shutdown_process_tree(pid, interrupt, terminate)
+            })
+        })
+        .collect();
+
+    let mut reports = Vec::with_capacity(handles.len());
+    for handle in handles {
+        reports.push(
+            handle
+                .await
+                .map_err(|e| format!("Shutdown task failed: {}", e))?,
+        );
+    }
+
+    for entry in &entries {
+        registry.unregister(entry.pid);
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_and_unregister() {
+        let registry = ProcessRegistry::new();
+        let pid = std::process::id();
+
+        registry.register("lane-a", pid, ProcessKind::Terminal, "term-1");
+        let entries = registry.entries("lane-a");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pid, pid);
+        assert!(entries[0].start_time > 0);
+        assert_eq!(registry.lane_ids(), vec!["lane-a".to_string()]);
+
+        registry.unregister(pid);
+        assert!(registry.entries("lane-a").is_empty());
+        assert!(registry.lane_ids().is_empty());
+    }
+
+    #[test]
+    fn test_register_ignores_zero_pid() {
+        let registry = ProcessRegistry::new();
+        registry.register("lane-a", 0, ProcessKind::Terminal, "term-1");
+        assert!(registry.lane_ids().is_empty());
+    }
+
+    #[test]
+    fn test_pid_moves_between_lanes() {
+        let registry = ProcessRegistry::new();
+        let pid = std::process::id();
+
+        registry.register("lane-a", pid, ProcessKind::Other, "x");
+        registry.register("lane-b", pid, ProcessKind::Other, "x");
+        assert!(registry.entries("lane-a").is_empty());
+        assert_eq!(registry.entries("lane-b").len(), 1);
+    }
+
+    #[test]
+    fn test_track_unregisters_on_drop() {
+        let registry = ProcessRegistry::new();
+        let pid = std::process::id();
+        {
+            let _registration = registry.track("lane-a", pid, ProcessKind::AiTool, "claude");
+            assert_eq!(registry.entries("lane-a").len(), 1);
+        }
+        assert!(registry.entries("lane-a").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lane_usage_prunes_exited_processes() {
+        let registry = ProcessRegistry::new();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let exited_pid = child.id();
+        registry.register("lane-a", exited_pid, ProcessKind::Other, "true");
+        child.wait().unwrap();
+
+        registry.register(
+            "lane-a",
+            std::process::id(),
+            ProcessKind::Terminal,
+            "term-1",
+        );
+
+        let usage = registry.lane_usage("lane-a");
+        assert_eq!(usage.processes.len(), 1);
+        assert_eq!(usage.processes[0].pid, std::process::id());
+        assert!(usage.process_count >= 1);
+        assert!(usage.memory_usage > 0);
+        assert_eq!(registry.entries("lane-a").len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_primary_pid_prefers_terminal() {
+        let registry = ProcessRegistry::new();
+        assert_eq!(registry.primary_pid("lane-a"), None);
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        registry.register("lane-a", child.id(), ProcessKind::AiTool, "claude");
+        registry.register(
+            "lane-a",
+            std::process::id(),
+            ProcessKind::Terminal,
+            "term-1",
+        );
+
+        assert_eq!(registry.primary_pid("lane-a"), Some(std::process::id()));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_lane_usage_serialization() {
+        let usage = LaneResourceUsage {
+            lane_id: "lane-a".to_string(),
+            processes: vec![RegisteredProcess {
+                pid: 42,
+                lane_id: "lane-a".to_string(),
+                kind: ProcessKind::AiTool,
+                label: "claude".to_string(),
+                start_time: 1,
+                registered_at: 2,
+            }],
+            process_count: 3,
+            cpu_percent: 1.5,
+            memory_usage: 1024,
+            memory_mb: 0.0,
+        };
+
+        let json = serde_json::to_string(&usage).unwrap();
+        assert!(json.contains("\"laneId\":\"lane-a\""));
+        assert!(json.contains("\"processCount\":3"));
+        assert!(json.contains("\"kind\":\"aiTool\""));
+        assert!(json.contains("\"registeredAt\":2"));
+    }
+}
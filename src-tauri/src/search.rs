@@ -1,13 +1,21 @@
 //! Project-Wide File Search
 //!
 //! This module provides fast, gitignore-aware project search with streaming results.
-//! Uses the `ignore` crate (same as ripgrep) for efficient file walking.
+//! Uses the `ignore` crate (same as ripgrep) for efficient file walking: files are
+//! walked and searched on a pool of worker threads, large files are memory-mapped,
+//! and files containing NUL bytes are treated as binary.
 //!
 //! # Architecture
 //!
 //! - `SearchState`: Manages active searches with cancel flags
-//! - `search_start`: Spawns async search task, returns search ID immediately
+//! - `search_start`: Spawns a blocking search task, returns search ID immediately
+//! - `search_parallel`: Parallel walker; workers send per-file matches to the
+//!   search task, which batches them into events (completion order, not path order)
 //! - `search_cancel`: Sets cancel flag for graceful termination
+//! - [`crate::search_scope`]: Restricts a search to lane changes, added lines or
+//!   a git revision's tree
+//! - [`crate::search_index`]: Optional trigram index that lets workers skip files
+//!   which cannot match
 //!
 //! # Events
 //!
@@ -18,18 +26,25 @@
 //! - `search-error`: Search error
 //!   - Payload: `{ search_id, message }`
 
-use ignore::WalkBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use memmap2::Mmap;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use tauri::{AppHandle, Emitter, State};
 
+use crate::search_index::{IndexCheck, IndexedSearch, SearchIndexState, TrigramQuery};
+use crate::search_scope::{self, ResolvedScope, SearchScope};
+use crate::search_structural::StructuralQuery;
+
 /// Maximum file size to search (1MB)
-const MAX_FILE_SIZE: u64 = 1024 * 1024;
+pub(crate) const MAX_FILE_SIZE: u64 = 1024 * 1024;
 
 /// Number of matches to batch before emitting event
 const BATCH_SIZE: usize = 50;
@@ -40,6 +55,12 @@ const CONTEXT_LINES: usize = 2;
 /// Default maximum matches to return (0 = unlimited)
 const DEFAULT_MAX_MATCHES: u32 = 1000;
 
+/// Upper bound on walker threads per search
+const MAX_SEARCH_THREADS: usize = 12;
+
+/// Files at least this large are memory-mapped instead of read into memory
+const MMAP_THRESHOLD: u64 = 64 * 1024;
+
 /// State for managing active searches
 pub struct SearchState {
     searches: Mutex<HashMap<String, SearchInstance>>,
@@ -81,6 +102,90 @@ pub struct SearchMatch {
     pub context_before: Vec<String>,
     /// Context lines after the match
     pub context_after: Vec<String>,
+    /// 1-indexed line number where the match ends (multiline/structural matches span lines)
+    #[serde(default)]
+    pub end_line_number: u32,
+    /// 0-indexed column where the match ends (exclusive)
+    #[serde(default)]
+    pub end_column: u32,
+}
+
+/// How a query is matched against file contents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Regex/literal matched within each line
+    #[default]
+    Line,
+    /// Regex matched against the whole file, so matches can span lines
+    /// (`^`/`$` match at line boundaries, `\n` and `\s` cross them)
+    Multiline,
+    /// Tree-sitter query matched against the syntax tree of supported languages
+    Structural,
+}
+
+/// A compiled search query
+pub(crate) enum Matcher {
+    Line(Regex),
+    Multiline(Regex),
+    Structural(StructuralQuery),
+}
+
+impl Matcher {
+    /// Compile a query for the given mode
+    ///
+    /// `is_regex`, `case_sensitive` and `match_word` do not apply to structural queries.
+    pub(crate) fn new(
+        mode: SearchMode,
+        query: &str,
+        is_regex: bool,
+        case_sensitive: bool,
+        match_word: bool,
+    ) -> Result<Self, String> {
+        match mode {
+            SearchMode::Line => Ok(Matcher::Line(build_pattern(
+                query,
+                is_regex,
+                case_sensitive,
+                match_word,
+            )?)),
+            SearchMode::Multiline => {
+                let pattern = build_pattern(query, is_regex, case_sensitive, match_word)?;
+                Regex::new(&format!("(?mR){}", pattern.as_str()))
+                    .map(Matcher::Multiline)
+                    .map_err(|e| format!("Invalid search pattern: {}", e))
+            }
+            SearchMode::Structural => StructuralQuery::new(query).map(Matcher::Structural),
+        }
+    }
+
+    /// Whether a file can match at all (structural queries only apply to known languages)
+    pub(crate) fn accepts(&self, path: &Path) -> bool {
+        match self {
+            Matcher::Structural(query) => query.supports(path),
+            _ => true,
+        }
+    }
+
+    /// Search a single file
+    fn search_file(&self, path: &Path) -> Option<Vec<SearchMatch>> {
+        let text = read_text(path)?;
+        let matches = self.search_text(&path.to_string_lossy(), &text);
+        if matches.is_empty() {
+            None
+        } else {
+            Some(matches)
+        }
+    }
+
+    /// Search file contents that are already in memory (e.g. a blob from a git revision)
+    pub(crate) fn search_text(&self, file_path: &str, text: &str) -> Vec<SearchMatch> {
+        match self {
+            Matcher::Line(pattern) => search_text_lines(file_path, text, pattern),
+            Matcher::Multiline(pattern) => search_text_multiline(file_path, text, pattern),
+            Matcher::Structural(query) => query.search_text(file_path, text),
+        }
+    }
 }
 
 /// Payload for search result events
@@ -124,6 +229,11 @@ pub struct SearchErrorPayload {
 /// * `exclude_pattern` - Optional glob pattern to exclude files
 /// * `max_matches` - Maximum number of matches to return (default: 1000, 0 = unlimited)
 /// * `file_paths` - Optional list of specific files or directories to search (absolute paths)
+/// * `mode` - `line` (default), `multiline` (regex across lines) or `structural`
+///   (tree-sitter query, e.g. `((call_expression) @match (#inside? @match "impl_item"))`)
+/// * `scope` - `{ type: "workingTree" }` (default), `{ type: "changedFiles" }` (files in
+///   `git_changes_with_stats`), `{ type: "addedLines" }` (lines added in the working tree
+///   diff) or `{ type: "revision", revision }` (a commit's tree, without checking it out)
 ///
 /// # Returns
 /// The search ID (UUID) on success
@@ -131,6 +241,7 @@ pub struct SearchErrorPayload {
 pub async fn search_start(
     app: AppHandle,
     state: State<'_, SearchState>,
+    index_state: State<'_, SearchIndexState>,
     root_path: String,
     query: String,
     is_regex: Option<bool>,
@@ -140,17 +251,21 @@ pub async fn search_start(
     exclude_pattern: Option<String>,
     max_matches: Option<u32>,
     file_paths: Option<Vec<String>>,
+    mode: Option<SearchMode>,
+    scope: Option<SearchScope>,
 ) -> Result<String, String> {
     let search_id = uuid::Uuid::new_v4().to_string();
+    let mode = mode.unwrap_or_default();
     let is_regex = is_regex.unwrap_or(false);
     let case_sensitive = case_sensitive.unwrap_or(false);
     let match_word = match_word.unwrap_or(false);
     let max_matches = max_matches.unwrap_or(DEFAULT_MAX_MATCHES);
 
     tracing::info!(
-        "Starting search '{}' in {} (regex={}, case_sensitive={}, match_word={}, max_matches={}, file_paths={:?})",
+        "Starting search '{}' in {} (mode={:?}, regex={}, case_sensitive={}, match_word={}, max_matches={}, file_paths={:?})",
         query,
         root_path,
+        mode,
         is_regex,
         case_sensitive,
         match_word,
@@ -164,6 +279,17 @@ pub async fn search_start(
         return Err(format!("Search path does not exist: {}", root_path));
     }
 
+    // Compile the query and resolve the scope before registering the search
+    let matcher = Matcher::new(mode, &query, is_regex, case_sensitive, match_word)?;
+    let scope = search_scope::resolve(scope.unwrap_or_default(), &root_path).await?;
+
+    // Narrow the candidate files with a trigram index, if one covers the root
+    let index = match scope {
+        ResolvedScope::Revision { .. } => None,
+        _ => TrigramQuery::new(mode, &query, is_regex, case_sensitive, match_word)
+            .and_then(|trigrams| index_state.search(&root_path, trigrams)),
+    };
+
     // Create cancel flag
     let cancel_flag = Arc::new(AtomicBool::new(false));
 
@@ -181,60 +307,26 @@ pub async fn search_start(
         );
     }
 
-    // Build regex pattern
-    let pattern = if is_regex {
-        let base_pattern = &query;
-        let pattern_with_word = if match_word {
-            format!(r"\b{}\b", base_pattern)
-        } else {
-            base_pattern.to_string()
-        };
-
-        if case_sensitive {
-            Regex::new(&pattern_with_word)
-        } else {
-            Regex::new(&format!("(?i){}", pattern_with_word))
-        }
-    } else {
-        // Escape special regex characters for literal search
-        let escaped = regex::escape(&query);
-        let pattern_with_word = if match_word {
-            format!(r"\b{}\b", escaped)
-        } else {
-            escaped
-        };
-
-        if case_sensitive {
-            Regex::new(&pattern_with_word)
-        } else {
-            Regex::new(&format!("(?i){}", pattern_with_word))
-        }
+    let options = SearchOptions {
+        include_pattern,
+        exclude_pattern,
+        max_matches,
+        file_paths,
+        scope,
+        index,
     };
 
-    let pattern = pattern.map_err(|e| format!("Invalid search pattern: {}", e))?;
-
-    // Clone values for the spawned task
+    // Spawn search task (blocking: it drives the parallel walker)
     let search_id_clone = search_id.clone();
-    let root_path_clone = root_path.clone();
-    let app_clone = app.clone();
-    let include = include_pattern.clone();
-    let exclude = exclude_pattern.clone();
-    let paths = file_paths.clone();
-
-    // Spawn search task
-    tokio::spawn(async move {
+    tokio::task::spawn_blocking(move || {
         run_search(
-            app_clone,
+            app,
             search_id_clone,
-            root_path_clone,
-            pattern,
+            root_path,
+            matcher,
             cancel_flag,
-            include,
-            exclude,
-            max_matches,
-            paths,
-        )
-        .await;
+            options,
+        );
     });
 
     Ok(search_id)
@@ -266,181 +358,141 @@ pub async fn search_cancel(
     }
 }
 
-/// Run the actual search (called in spawned task)
-async fn run_search(
-    app: AppHandle,
-    search_id: String,
-    root_path: String,
-    pattern: Regex,
-    cancel_flag: Arc<AtomicBool>,
-    include_pattern: Option<String>,
-    exclude_pattern: Option<String>,
-    max_matches: u32,
-    file_paths: Option<Vec<String>>,
-) {
-    let mut matches_batch: Vec<SearchMatch> = Vec::with_capacity(BATCH_SIZE);
-    let mut total_matches: u32 = 0;
-    let mut files_searched: u32 = 0;
-    let mut truncated = false;
-
-    // Convert file_paths to a HashSet for fast lookup (canonicalize for reliable comparison)
-    let restricted_paths: Option<std::collections::HashSet<std::path::PathBuf>> =
-        file_paths.map(|paths| {
-            paths
-                .into_iter()
-                .filter_map(|p| {
-                    let path = std::path::PathBuf::from(&p);
-                    // Try to canonicalize, fall back to original path
-                    path.canonicalize().ok().or(Some(path))
-                })
-                .collect()
-        });
-
-    if let Some(ref restricted) = restricted_paths {
-        tracing::info!("Search restricted to {} paths: {:?}", restricted.len(), restricted);
-    }
+/// Options that control which files a search visits and when it stops
+pub(crate) struct SearchOptions {
+    pub(crate) include_pattern: Option<String>,
+    pub(crate) exclude_pattern: Option<String>,
+    /// Maximum number of matches (0 = unlimited)
+    pub(crate) max_matches: u32,
+    pub(crate) file_paths: Option<Vec<String>>,
+    /// Lane changes or git revision to search instead of the whole working tree
+    pub(crate) scope: ResolvedScope,
+    /// Trigram index used to skip files that cannot match
+    pub(crate) index: Option<IndexedSearch>,
+}
 
-    // Build the file walker with gitignore support
-    let mut walker = WalkBuilder::new(&root_path);
-    walker
-        .hidden(false) // Don't skip hidden files by default
-        .ignore(true) // Respect .gitignore
-        .git_ignore(true) // Respect .gitignore
-        .git_global(true) // Respect global .gitignore
-        .git_exclude(true) // Respect .git/info/exclude
-        .follow_links(false) // Don't follow symlinks
-        .parents(true); // Check parent directories for .gitignore
+/// Totals reported when a search finishes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SearchSummary {
+    pub(crate) total_matches: u32,
+    pub(crate) files_searched: u32,
+    pub(crate) truncated: bool,
+}
 
-    // Add custom include/exclude patterns
-    if let Some(ref include) = include_pattern {
-        // For include patterns, we need to add a custom glob
-        let mut override_builder = ignore::overrides::OverrideBuilder::new(&root_path);
-        if let Err(e) = override_builder.add(include) {
-            tracing::warn!("Invalid include pattern '{}': {}", include, e);
-        }
-        if let Ok(overrides) = override_builder.build() {
-            walker.overrides(overrides);
-        }
-    }
+/// Batches per-file matches into `on_batch(matches, files_searched)` calls of up
+/// to `BATCH_SIZE` matches and enforces the match limit
+pub(crate) struct MatchBatcher<F: FnMut(Vec<SearchMatch>, u32)> {
+    batch: Vec<SearchMatch>,
+    summary: SearchSummary,
+    max_matches: u32,
+    on_batch: F,
+}
 
-    if let Some(ref exclude) = exclude_pattern {
-        // For exclude patterns, prefix with ! to negate
-        let mut override_builder = ignore::overrides::OverrideBuilder::new(&root_path);
-        if let Err(e) = override_builder.add(&format!("!{}", exclude)) {
-            tracing::warn!("Invalid exclude pattern '{}': {}", exclude, e);
-        }
-        if let Ok(overrides) = override_builder.build() {
-            walker.overrides(overrides);
+impl<F: FnMut(Vec<SearchMatch>, u32)> MatchBatcher<F> {
+    pub(crate) fn new(max_matches: u32, on_batch: F) -> Self {
+        Self {
+            batch: Vec::with_capacity(BATCH_SIZE),
+            summary: SearchSummary::default(),
+            max_matches,
+            on_batch,
         }
     }
 
-    let walker = walker.build();
+    /// Add the matches of one searched file; returns false once the limit is reached
+    pub(crate) fn add_file(&mut self, file_matches: Vec<SearchMatch>) -> bool {
+        self.summary.files_searched += 1;
 
-    'outer: for entry in walker {
-        // Check cancel flag
-        if cancel_flag.load(Ordering::SeqCst) {
-            tracing::info!("Search {} cancelled by user", search_id);
-            break;
-        }
-
-        // Check max matches limit (0 = unlimited)
-        if max_matches > 0 && total_matches >= max_matches {
-            truncated = true;
-            tracing::info!(
-                "Search {} reached max matches limit ({})",
-                search_id,
-                max_matches
-            );
-            break;
-        }
+        for m in file_matches {
+            self.batch.push(m);
+            self.summary.total_matches += 1;
 
-        let entry = match entry {
-            Ok(e) => e,
-            Err(e) => {
-                tracing::debug!("Error walking entry: {}", e);
-                continue;
+            // Emit batch when full
+            if self.batch.len() >= BATCH_SIZE {
+                (self.on_batch)(std::mem::take(&mut self.batch), self.summary.files_searched);
             }
-        };
-
-        // Skip directories
-        let file_type = match entry.file_type() {
-            Some(ft) => ft,
-            None => continue,
-        };
-
-        if !file_type.is_file() {
-            continue;
-        }
 
-        let path = entry.path();
-
-        // Check if file is in restricted paths (if specified)
-        if let Some(ref restricted) = restricted_paths {
-            // Canonicalize the entry path for reliable comparison
-            let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
-            let path_matches = restricted.contains(&canonical_path)
-                || restricted.iter().any(|restricted_path| {
-                    // Check if restricted_path is a parent directory of path
-                    canonical_path.starts_with(restricted_path)
-                });
-            if !path_matches {
-                continue;
+            // Check max matches limit after each match (0 = unlimited)
+            if self.max_matches > 0 && self.summary.total_matches >= self.max_matches {
+                self.summary.truncated = true;
+                return false;
             }
         }
 
-        // Check file size
-        if let Ok(metadata) = path.metadata() {
-            if metadata.len() > MAX_FILE_SIZE {
-                tracing::debug!("Skipping large file: {:?}", path);
-                continue;
-            }
-        }
+        true
+    }
 
-        // Search the file
-        if let Some(file_matches) = search_file(path, &pattern) {
-            for m in file_matches {
-                matches_batch.push(m);
-                total_matches += 1;
-
-                // Emit batch when full
-                if matches_batch.len() >= BATCH_SIZE {
-                    let _ = app.emit(
-                        "search-result",
-                        SearchResultPayload {
-                            search_id: search_id.clone(),
-                            matches: matches_batch.clone(),
-                            files_searched,
-                        },
-                    );
-                    matches_batch.clear();
-                }
-
-                // Check max matches limit after each match
-                if max_matches > 0 && total_matches >= max_matches {
-                    truncated = true;
-                    tracing::info!(
-                        "Search {} reached max matches limit ({})",
-                        search_id,
-                        max_matches
-                    );
-                    break 'outer;
-                }
-            }
+    /// Emit remaining matches and return the totals
+    pub(crate) fn finish(mut self) -> SearchSummary {
+        if !self.batch.is_empty() {
+            (self.on_batch)(self.batch, self.summary.files_searched);
         }
-
-        files_searched += 1;
+        self.summary
     }
+}
 
-    // Emit remaining matches
-    if !matches_batch.is_empty() {
+/// Run the actual search (called on a blocking task) and stream results as events
+fn run_search(
+    app: AppHandle,
+    search_id: String,
+    root_path: String,
+    matcher: Matcher,
+    cancel_flag: Arc<AtomicBool>,
+    options: SearchOptions,
+) {
+    let on_batch = |matches, files_searched| {
         let _ = app.emit(
             "search-result",
             SearchResultPayload {
                 search_id: search_id.clone(),
-                matches: matches_batch,
+                matches,
                 files_searched,
             },
         );
+    };
+
+    let result = match options.scope {
+        ResolvedScope::Revision { ref revision } => search_scope::search_revision(
+            &root_path,
+            revision,
+            &matcher,
+            &cancel_flag,
+            &options,
+            on_batch,
+        ),
+        _ => Ok(search_parallel(
+            &root_path,
+            &matcher,
+            &cancel_flag,
+            &options,
+            on_batch,
+        )),
+    };
+
+    let summary = match result {
+        Ok(summary) => summary,
+        Err(message) => {
+            tracing::error!("Search {} failed: {}", search_id, message);
+            let _ = app.emit(
+                "search-error",
+                SearchErrorPayload {
+                    search_id: search_id.clone(),
+                    message,
+                },
+            );
+            SearchSummary::default()
+        }
+    };
+
+    if let Some(ref index) = options.index {
+        index.finish();
+    }
+
+    if summary.truncated {
+        tracing::info!(
+            "Search {} reached max matches limit ({})",
+            search_id,
+            options.max_matches
+        );
     }
 
     // Emit completion event
@@ -449,71 +501,444 @@ async fn run_search(
         "search-complete",
         SearchCompletePayload {
             search_id: search_id.clone(),
-            total_matches,
-            total_files: files_searched,
+            total_matches: summary.total_matches,
+            total_files: summary.files_searched,
             cancelled,
-            truncated,
+            truncated: summary.truncated,
         },
     );
 
     tracing::info!(
         "Search {} complete: {} matches in {} files (cancelled={}, truncated={})",
         search_id,
-        total_matches,
-        files_searched,
+        summary.total_matches,
+        summary.files_searched,
         cancelled,
-        truncated
+        summary.truncated
     );
 }
 
-/// Search a single file for matches
-fn search_file(path: &std::path::Path, pattern: &Regex) -> Option<Vec<SearchMatch>> {
-    let file = File::open(path).ok()?;
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader.lines().filter_map(|l| l.ok()).collect();
+/// Number of walker threads used for a search
+fn search_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(MAX_SEARCH_THREADS)
+}
+
+/// Walk `root_path` on a pool of worker threads and search every file
+///
+/// Workers search files independently and send their matches to the calling
+/// thread, which batches them into `on_batch(matches, files_searched)` calls
+/// of up to `BATCH_SIZE` matches. Results arrive in completion order, not
+/// path order. Setting `cancel_flag` or reaching `max_matches` stops the
+/// workers after the file each is currently searching.
+fn search_parallel(
+    root_path: &str,
+    matcher: &Matcher,
+    cancel_flag: &AtomicBool,
+    options: &SearchOptions,
+    on_batch: impl FnMut(Vec<SearchMatch>, u32),
+) -> SearchSummary {
+    let restricted_paths = restricted_paths(options.file_paths.clone());
+    if let Some(ref restricted) = restricted_paths {
+        tracing::info!(
+            "Search restricted to {} paths: {:?}",
+            restricted.len(),
+            restricted
+        );
+    }
+
+    let walker = build_walker(
+        root_path,
+        options.include_pattern.as_deref(),
+        options.exclude_pattern.as_deref(),
+    )
+    .threads(search_threads())
+    .build_parallel();
+
+    // Set once the match limit is reached so workers stop early
+    let limit_reached = AtomicBool::new(false);
+    let (tx, rx) = mpsc::channel::<Vec<SearchMatch>>();
+    let mut batcher = MatchBatcher::new(options.max_matches, on_batch);
+
+    let index = options.index.as_ref().map(|index| (index, index.reader()));
+    if index.is_some() {
+        tracing::info!("Search narrowed by trigram index");
+    }
+
+    std::thread::scope(|scope| {
+        let restricted_paths = &restricted_paths;
+        let limit_reached = &limit_reached;
+        let search_scope = &options.scope;
+        let index = &index;
+
+        scope.spawn(move || {
+            walker.run(|| {
+                let tx = tx.clone();
+                Box::new(move |entry| {
+                    if cancel_flag.load(Ordering::SeqCst) || limit_reached.load(Ordering::SeqCst) {
+                        return WalkState::Quit;
+                    }
+
+                    let entry = match entry {
+                        Ok(e) => e,
+                        Err(e) => {
+                            tracing::debug!("Error walking entry: {}", e);
+                            return WalkState::Continue;
+                        }
+                    };
+
+                    // Skip directories
+                    if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                        return WalkState::Continue;
+                    }
+
+                    let path = entry.path();
+
+                    // Check if file is in restricted paths (if specified)
+                    if !is_path_allowed(restricted_paths.as_ref(), path)
+                        || !search_scope.allows_file(path)
+                        || !matcher.accepts(path)
+                    {
+                        return WalkState::Continue;
+                    }
+
+                    // Check file size
+                    let metadata = entry.metadata().ok();
+                    if let Some(ref metadata) = metadata {
+                        if metadata.len() > MAX_FILE_SIZE {
+                            tracing::debug!("Skipping large file: {:?}", path);
+                            return WalkState::Continue;
+                        }
+                    }
+
+                    let check = match (index, &metadata) {
+                        (Some((_, reader)), Some(metadata)) => reader.check(path, metadata),
+                        _ => IndexCheck::Search,
+                    };
+                    let mut file_matches = match check {
+                        IndexCheck::Skip => Vec::new(),
+                        IndexCheck::Search => matcher.search_file(path).unwrap_or_default(),
+                        IndexCheck::Stale(relative) => {
+                            // Index the contents while they are in memory
+                            let Some(text) = read_text(path) else {
+                                return WalkState::Continue;
+                            };
+                            if let (Some((search, _)), Some(metadata)) = (index, &metadata) {
+                                search.record(relative, &text, metadata);
+                            }
+                            matcher.search_text(&path.to_string_lossy(), &text)
+                        }
+                    };
+                    search_scope.filter_matches(path, &mut file_matches);
+                    if tx.send(file_matches).is_err() {
+                        return WalkState::Quit;
+                    }
+                    WalkState::Continue
+                })
+            });
+        });
+
+        for file_matches in rx {
+            if cancel_flag.load(Ordering::SeqCst) {
+                break;
+            }
+            if !batcher.add_file(file_matches) {
+                limit_reached.store(true, Ordering::SeqCst);
+                break;
+            }
+        }
+    });
+
+    batcher.finish()
+}
+
+/// Build the regex for a search query
+///
+/// Literal queries are escaped; `match_word` adds word boundaries and
+/// case-insensitive searches use the `(?i)` flag.
+pub(crate) fn build_pattern(
+    query: &str,
+    is_regex: bool,
+    case_sensitive: bool,
+    match_word: bool,
+) -> Result<Regex, String> {
+    let base_pattern = if is_regex {
+        query.to_string()
+    } else {
+        // Escape special regex characters for literal search
+        regex::escape(query)
+    };
 
-    if lines.is_empty() {
+    let pattern_with_word = if match_word {
+        format!(r"\b{}\b", base_pattern)
+    } else {
+        base_pattern
+    };
+
+    let pattern = if case_sensitive {
+        Regex::new(&pattern_with_word)
+    } else {
+        Regex::new(&format!("(?i){}", pattern_with_word))
+    };
+
+    pattern.map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// Build a gitignore-aware file walker with optional include/exclude globs
+pub(crate) fn build_walker(
+    root_path: &str,
+    include_pattern: Option<&str>,
+    exclude_pattern: Option<&str>,
+) -> WalkBuilder {
+    let mut walker = WalkBuilder::new(root_path);
+    walker
+        .hidden(false) // Don't skip hidden files by default
+        .ignore(true) // Respect .gitignore
+        .git_ignore(true) // Respect .gitignore
+        .git_global(true) // Respect global .gitignore
+        .git_exclude(true) // Respect .git/info/exclude
+        .follow_links(false) // Don't follow symlinks
+        .parents(true); // Check parent directories for .gitignore
+
+    if let Some(overrides) = build_overrides(root_path, include_pattern, exclude_pattern) {
+        walker.overrides(overrides);
+    }
+
+    walker
+}
+
+/// Build the include/exclude glob overrides for a search rooted at `root_path`
+///
+/// Include and exclude globs share one override set so both apply.
+pub(crate) fn build_overrides(
+    root_path: &str,
+    include_pattern: Option<&str>,
+    exclude_pattern: Option<&str>,
+) -> Option<Override> {
+    if include_pattern.is_none() && exclude_pattern.is_none() {
         return None;
     }
 
+    let mut override_builder = OverrideBuilder::new(root_path);
+    if let Some(include) = include_pattern {
+        if let Err(e) = override_builder.add(include) {
+            tracing::warn!("Invalid include pattern '{}': {}", include, e);
+        }
+    }
+    if let Some(exclude) = exclude_pattern {
+        // For exclude patterns, prefix with ! to negate
+        if let Err(e) = override_builder.add(&format!("!{}", exclude)) {
+            tracing::warn!("Invalid exclude pattern '{}': {}", exclude, e);
+        }
+    }
+    override_builder.build().ok()
+}
+
+/// Canonicalize an optional list of files/directories to restrict a search to
+pub(crate) fn restricted_paths(file_paths: Option<Vec<String>>) -> Option<HashSet<PathBuf>> {
+    file_paths.map(|paths| {
+        paths
+            .into_iter()
+            .map(|p| {
+                let path = PathBuf::from(&p);
+                // Try to canonicalize, fall back to original path
+                path.canonicalize().unwrap_or(path)
+            })
+            .collect()
+    })
+}
+
+/// Whether `path` is one of (or inside one of) the restricted paths
+pub(crate) fn is_path_allowed(restricted: Option<&HashSet<PathBuf>>, path: &Path) -> bool {
+    let Some(restricted) = restricted else {
+        return true;
+    };
+    // Canonicalize the entry path for reliable comparison
+    let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    restricted.contains(&canonical_path)
+        || restricted.iter().any(|restricted_path| {
+            // Check if restricted_path is a parent directory of path
+            canonical_path.starts_with(restricted_path)
+        })
+}
+
+/// File contents, memory-mapped for large files
+enum FileContents {
+    Mapped(Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl std::ops::Deref for FileContents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileContents::Mapped(map) => map,
+            FileContents::Buffered(buf) => buf,
+        }
+    }
+}
+
+/// Read a file, memory-mapping it when it is at least `MMAP_THRESHOLD` bytes
+fn read_contents(path: &Path) -> Option<FileContents> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+
+    if len >= MMAP_THRESHOLD {
+        // SAFETY: the map is read-only and only lives for one file search. A
+        // concurrent truncation by another process can fault, the same trade-off
+        // ripgrep makes for its mmap searches.
+        if let Ok(map) = unsafe { Mmap::map(&file) } {
+            return Some(FileContents::Mapped(map));
+        }
+    }
+
+    let mut buf = Vec::with_capacity(len as usize);
+    file.read_to_end(&mut buf).ok()?;
+    Some(FileContents::Buffered(buf))
+}
+
+/// Length of the searchable text in `bytes`
+///
+/// Like grep-searcher's binary detection, a NUL byte marks the file as binary
+/// and searching stops before the line that contains it, so a file that starts
+/// with binary data yields no matches.
+fn searchable_len(bytes: &[u8]) -> usize {
+    match bytes.iter().position(|&b| b == 0) {
+        Some(nul) => bytes[..nul]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |newline| newline + 1),
+        None => bytes.len(),
+    }
+}
+
+/// Read the searchable text of a file (see [`searchable_len`])
+pub(crate) fn read_text(path: &Path) -> Option<String> {
+    let contents = read_contents(path)?;
+    Some(decode_text(&contents))
+}
+
+/// Decode the searchable part of `bytes` as (lossy) UTF-8
+pub(crate) fn decode_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(&bytes[..searchable_len(bytes)]).into_owned()
+}
+
+/// Maps byte offsets in a file to line/column positions
+pub(crate) struct LineIndex<'a> {
+    text: &'a str,
+    /// Byte offset of the start of each line
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    /// 0-indexed line and byte column of an offset
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        (line, offset - self.starts[line])
+    }
+
+    /// Content of a line without its line ending
+    fn line(&self, line: usize) -> &'a str {
+        let start = self.starts[line];
+        let end = self
+            .starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.text.len());
+        self.text[start..end]
+            .trim_end_matches('\n')
+            .trim_end_matches('\r')
+    }
+
+    /// Number of lines (a trailing newline does not start a new line)
+    fn line_count(&self) -> usize {
+        if self.text.ends_with('\n') {
+            self.starts.len() - 1
+        } else {
+            self.starts.len()
+        }
+    }
+
+    /// Build a match for the byte range `start..end`
+    pub(crate) fn search_match(&self, file_path: &str, start: usize, end: usize) -> SearchMatch {
+        let (start_line, start_column) = self.position(start);
+        let (end_line, end_column) = self.position(end);
+        let line_count = self.line_count();
+
+        SearchMatch {
+            file_path: file_path.to_string(),
+            line_number: (start_line + 1) as u32,
+            column: start_column as u32,
+            line_content: self.line(start_line).to_string(),
+            match_text: self.text[start..end].to_string(),
+            context_before: (start_line.saturating_sub(CONTEXT_LINES)..start_line)
+                .map(|l| self.line(l).to_string())
+                .collect(),
+            context_after: (end_line + 1..(end_line + 1 + CONTEXT_LINES).min(line_count))
+                .map(|l| self.line(l).to_string())
+                .collect(),
+            end_line_number: (end_line + 1) as u32,
+            end_column: end_column as u32,
+        }
+    }
+}
+
+/// Search text with a multiline regex
+fn search_text_multiline(file_path: &str, text: &str, pattern: &Regex) -> Vec<SearchMatch> {
+    let index = LineIndex::new(text);
+    pattern
+        .find_iter(text)
+        .map(|mat| index.search_match(file_path, mat.start(), mat.end()))
+        .collect()
+}
+
+/// Search text line by line
+fn search_text_lines(path_str: &str, text: &str, pattern: &Regex) -> Vec<SearchMatch> {
+    let lines: Vec<&str> = text.lines().collect();
     let mut matches = Vec::new();
-    let path_str = path.to_string_lossy().to_string();
 
     for (line_idx, line) in lines.iter().enumerate() {
         // Find all matches in the line
         for mat in pattern.find_iter(line) {
             // Get context lines
             let context_before: Vec<String> = lines
+                [line_idx.saturating_sub(CONTEXT_LINES)..line_idx]
                 .iter()
-                .skip(line_idx.saturating_sub(CONTEXT_LINES))
-                .take(line_idx.saturating_sub(line_idx.saturating_sub(CONTEXT_LINES)))
-                .cloned()
+                .map(|l| l.to_string())
                 .collect();
 
             let context_after: Vec<String> = lines
                 .iter()
                 .skip(line_idx + 1)
                 .take(CONTEXT_LINES)
-                .cloned()
+                .map(|l| l.to_string())
                 .collect();
 
             matches.push(SearchMatch {
-                file_path: path_str.clone(),
+                file_path: path_str.to_string(),
                 line_number: (line_idx + 1) as u32,
                 column: mat.start() as u32,
-                line_content: line.clone(),
+                line_content: line.to_string(),
                 match_text: mat.as_str().to_string(),
                 context_before,
                 context_after,
+                end_line_number: (line_idx + 1) as u32,
+                end_column: mat.end() as u32,
             });
         }
     }
 
-    if matches.is_empty() {
-        None
-    } else {
-        Some(matches)
-    }
+    matches
 }
 
 #[cfg(test)]
@@ -522,6 +947,11 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    /// Search a single file line by line
+    fn search_file(path: &Path, pattern: &Regex) -> Option<Vec<SearchMatch>> {
+        Matcher::Line(pattern.clone()).search_file(path)
+    }
+
     // =========================================================================
     // SearchState tests
     // =========================================================================
@@ -574,6 +1004,8 @@ mod tests {
             match_text: "foo".to_string(),
             context_before: vec!["// comment".to_string()],
             context_after: vec!["let y = bar;".to_string()],
+            end_line_number: 42,
+            end_column: 13,
         };
 
         let json = serde_json::to_string(&m).unwrap();
@@ -612,6 +1044,8 @@ mod tests {
             match_text: "first".to_string(),
             context_before: vec![],
             context_after: vec![],
+            end_line_number: 1,
+            end_column: 5,
         };
 
         let json = serde_json::to_string(&m).unwrap();
@@ -651,6 +1085,8 @@ mod tests {
                     match_text: "hello".to_string(),
                     context_before: vec![],
                     context_after: vec![],
+                    end_line_number: 1,
+                    end_column: 5,
                 },
             ],
             files_searched: 1,
@@ -911,6 +1347,8 @@ mod tests {
             match_text: "test".to_string(),
             context_before: vec!["before".to_string()],
             context_after: vec!["after".to_string()],
+            end_line_number: 1,
+            end_column: 4,
         };
 
         let cloned = original.clone();
@@ -1245,6 +1683,8 @@ mod tests {
             match_text: "test_function".to_string(),
             context_before: vec!["// A test".to_string(), "impl Test {".to_string()],
             context_after: vec!["        // body".to_string(), "    }".to_string()],
+            end_line_number: 100,
+            end_column: 38,
         };
 
         assert_eq!(m.file_path, "/path/to/file.rs");
@@ -1264,6 +1704,8 @@ mod tests {
             match_text: "test".to_string(),
             context_before: vec![],
             context_after: vec![],
+            end_line_number: 1,
+            end_column: 4,
         };
 
         // Test Debug trait
@@ -1349,4 +1791,293 @@ mod tests {
 
         assert!(matches.is_none());
     }
+
+    // =========================================================================
+    // Binary detection and memory-mapped reads
+    // =========================================================================
+
+    #[test]
+    fn test_searchable_len() {
+        assert_eq!(searchable_len(b"abc\ndef\n"), 8);
+        assert_eq!(searchable_len(b"\0abc\n"), 0);
+        assert_eq!(searchable_len(b"abc\nde\0f\nghi\n"), 4);
+        assert_eq!(searchable_len(b""), 0);
+    }
+
+    #[test]
+    fn test_search_file_stops_at_binary_content() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("mixed.bin");
+        fs::write(&file_path, b"needle one\nneedle\0two\nneedle three\n").unwrap();
+
+        let pattern = Regex::new("needle").unwrap();
+        let matches = search_file(&file_path, &pattern).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 1);
+    }
+
+    #[test]
+    fn test_search_file_memory_mapped() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("large.txt");
+        let mut content = "filler line\n".repeat((MMAP_THRESHOLD as usize / 12) + 1);
+        content.push_str("needle at the end\n");
+        fs::write(&file_path, &content).unwrap();
+        assert!(fs::metadata(&file_path).unwrap().len() >= MMAP_THRESHOLD);
+
+        let pattern = Regex::new("needle").unwrap();
+        let matches = search_file(&file_path, &pattern).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number as usize, content.lines().count());
+        assert_eq!(
+            matches[0].context_before,
+            vec!["filler line", "filler line"]
+        );
+    }
+
+    #[test]
+    fn test_search_file_invalid_utf8_keeps_line_numbers() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("latin1.txt");
+        fs::write(&file_path, b"caf\xe9\nneedle\n").unwrap();
+
+        let pattern = Regex::new("needle").unwrap();
+        let matches = search_file(&file_path, &pattern).unwrap();
+
+        assert_eq!(matches[0].line_number, 2);
+    }
+
+    // =========================================================================
+    // Parallel walker tests
+    // =========================================================================
+
+    fn search_options(max_matches: u32) -> SearchOptions {
+        SearchOptions {
+            include_pattern: None,
+            exclude_pattern: None,
+            max_matches,
+            file_paths: None,
+            scope: ResolvedScope::WorkingTree,
+            index: None,
+        }
+    }
+
+    fn create_tree(files: usize) -> TempDir {
+        let temp = TempDir::new().unwrap();
+        for i in 0..files {
+            let dir = temp.path().join(format!("dir{}", i % 4));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(format!("file{}.txt", i)),
+                "needle\nother\nneedle\n",
+            )
+            .unwrap();
+        }
+        temp
+    }
+
+    #[test]
+    fn test_search_parallel_finds_all_matches() {
+        let temp = create_tree(40);
+        let matcher = Matcher::Line(Regex::new("needle").unwrap());
+        let cancel = AtomicBool::new(false);
+
+        let mut batches = Vec::new();
+        let summary = search_parallel(
+            &temp.path().to_string_lossy(),
+            &matcher,
+            &cancel,
+            &search_options(0),
+            |matches, files_searched| batches.push((matches.len(), files_searched)),
+        );
+
+        assert_eq!(summary.total_matches, 80);
+        assert_eq!(summary.files_searched, 40);
+        assert!(!summary.truncated);
+        assert_eq!(batches.iter().map(|(n, _)| n).sum::<usize>(), 80);
+        assert!(batches.iter().all(|(n, _)| *n <= BATCH_SIZE));
+    }
+
+    #[test]
+    fn test_search_parallel_stops_at_max_matches() {
+        let temp = create_tree(40);
+        let matcher = Matcher::Line(Regex::new("needle").unwrap());
+        let cancel = AtomicBool::new(false);
+
+        let mut received = 0;
+        let summary = search_parallel(
+            &temp.path().to_string_lossy(),
+            &matcher,
+            &cancel,
+            &search_options(5),
+            |matches, _| received += matches.len(),
+        );
+
+        assert_eq!(summary.total_matches, 5);
+        assert_eq!(received, 5);
+        assert!(summary.truncated);
+    }
+
+    #[test]
+    fn test_search_parallel_cancelled() {
+        let temp = create_tree(10);
+        let matcher = Matcher::Line(Regex::new("needle").unwrap());
+        let cancel = AtomicBool::new(true);
+
+        let summary = search_parallel(
+            &temp.path().to_string_lossy(),
+            &matcher,
+            &cancel,
+            &search_options(0),
+            |_, _| {},
+        );
+
+        assert_eq!(summary.total_matches, 0);
+    }
+
+    #[test]
+    fn test_search_parallel_respects_scope() {
+        let temp = create_tree(8);
+        let matcher = Matcher::Line(Regex::new("needle").unwrap());
+        let cancel = AtomicBool::new(false);
+        let changed = temp.path().join("dir1/file1.txt").canonicalize().unwrap();
+
+        let mut options = search_options(0);
+        options.scope = ResolvedScope::AddedLines(HashMap::from([(changed.clone(), vec![3..=3])]));
+
+        let mut found = Vec::new();
+        let summary = search_parallel(
+            &temp.path().to_string_lossy(),
+            &matcher,
+            &cancel,
+            &options,
+            |matches, _| found.extend(matches),
+        );
+
+        assert_eq!(summary.files_searched, 1);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line_number, 3);
+        assert_eq!(
+            PathBuf::from(&found[0].file_path).canonicalize().unwrap(),
+            changed
+        );
+    }
+
+    #[test]
+    fn test_search_parallel_include_and_exclude() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.rs"), "needle\n").unwrap();
+        fs::write(temp.path().join("b.rs"), "needle\n").unwrap();
+        fs::write(temp.path().join("c.ts"), "needle\n").unwrap();
+        let matcher = Matcher::Line(Regex::new("needle").unwrap());
+        let cancel = AtomicBool::new(false);
+
+        let mut options = search_options(0);
+        options.include_pattern = Some("*.rs".to_string());
+        options.exclude_pattern = Some("b.rs".to_string());
+
+        let mut files = Vec::new();
+        search_parallel(
+            &temp.path().to_string_lossy(),
+            &matcher,
+            &cancel,
+            &options,
+            |matches, _| files.extend(matches.into_iter().map(|m| m.file_path)),
+        );
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("a.rs"));
+    }
+
+    // =========================================================================
+    // Multiline mode tests
+    // =========================================================================
+
+    #[test]
+    fn test_search_mode_deserialization() {
+        let mode: SearchMode = serde_json::from_str("\"multiline\"").unwrap();
+        assert_eq!(mode, SearchMode::Multiline);
+        let mode: SearchMode = serde_json::from_str("\"structural\"").unwrap();
+        assert_eq!(mode, SearchMode::Structural);
+        assert_eq!(SearchMode::default(), SearchMode::Line);
+    }
+
+    #[test]
+    fn test_search_match_end_position_defaults() {
+        let json = r#"{
+            "file_path": "/a.rs",
+            "line_number": 3,
+            "column": 1,
+            "line_content": "x",
+            "match_text": "x",
+            "context_before": [],
+            "context_after": []
+        }"#;
+        let m: SearchMatch = serde_json::from_str(json).unwrap();
+        assert_eq!(m.end_line_number, 0);
+        assert_eq!(m.end_column, 0);
+    }
+
+    #[test]
+    fn test_line_index_positions() {
+        let text = "ab\r\ncd\nef";
+        let index = LineIndex::new(text);
+
+        assert_eq!(index.position(0), (0, 0));
+        assert_eq!(index.position(4), (1, 0));
+        assert_eq!(index.position(8), (2, 1));
+        assert_eq!(index.line(0), "ab");
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(LineIndex::new("a\nb\n").line_count(), 2);
+    }
+
+    #[test]
+    fn test_search_file_multiline_spans_lines() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("test.rs");
+        fs::write(
+            &file_path,
+            "fn main() {\n    let x = foo(\n        1,\n    );\n}\nafter\n",
+        )
+        .unwrap();
+
+        let matcher =
+            Matcher::new(SearchMode::Multiline, r"foo\(\s*1,\s*\)", true, true, false).unwrap();
+        let matches = matcher.search_file(&file_path).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        let m = &matches[0];
+        assert_eq!((m.line_number, m.column), (2, 12));
+        assert_eq!((m.end_line_number, m.end_column), (4, 5));
+        assert_eq!(m.line_content, "    let x = foo(");
+        assert_eq!(m.match_text, "foo(\n        1,\n    )");
+        assert_eq!(m.context_before, vec!["fn main() {"]);
+        assert_eq!(m.context_after, vec!["}", "after"]);
+    }
+
+    #[test]
+    fn test_search_file_multiline_anchors_match_lines() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("test.txt");
+        fs::write(&file_path, "foo\r\nbar foo\r\nfoo\r\n").unwrap();
+
+        let matcher = Matcher::new(SearchMode::Multiline, "^foo$", true, true, false).unwrap();
+        let matches = matcher.search_file(&file_path).unwrap();
+
+        let lines: Vec<u32> = matches.iter().map(|m| m.line_number).collect();
+        assert_eq!(lines, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_line_mode_matches_have_end_positions() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("test.txt");
+        fs::write(&file_path, "a needle b\n").unwrap();
+
+        let matcher = Matcher::new(SearchMode::Line, "needle", false, true, false).unwrap();
+        let matches = matcher.search_file(&file_path).unwrap();
+        assert_eq!((matches[0].end_line_number, matches[0].end_column), (1, 8));
+    }
 }
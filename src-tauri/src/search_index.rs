@@ -0,0 +1,1085 @@
+//! Persistent Trigram Index
+//!
+//! An optional per-directory index of the trigrams (3-byte sequences) each file
+//! contains, used by `search_start` to skip files that cannot match before
+//! running the regex. Trigrams are taken from ASCII-lowercased contents so one
+//! index serves case-sensitive and case-insensitive searches.
+//!
+//! # Freshness
+//!
+//! - Each entry records the file's size and mtime; a search only trusts entries
+//!   that still match the file on disk and re-indexes the others as it reads them
+//! - `watch_path` watchers feed change events to [`SearchIndexState::apply_changes`],
+//!   which re-indexes or drops files that are already indexed. Updates that
+//!   arrive while a search is reading the index are applied when it finishes,
+//!   so the watcher never waits for a search
+//! - New files are indexed by the next search that visits them
+//!
+//! Indexes are saved under `~/.codelane/<env>/search-index/` and loaded on the
+//! first search of a directory inside an indexed root.
+
+use ignore::WalkState;
+use regex_syntax::hir::{Hir, HirKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, Metadata};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::State;
+
+use crate::fs::canonical_event_path;
+use crate::search::{build_pattern, build_walker, read_text, SearchMode, MAX_FILE_SIZE};
+
+/// A trigram packed into the low 24 bits
+type Trigram = u32;
+
+/// Header of index files
+const INDEX_MAGIC: &[u8; 8] = b"CLTRIDX1";
+
+/// Minimum time between saves triggered by searches and watch events
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Upper bound on the alternatives a query is expanded into
+const MAX_ALTERNATIVES: usize = 64;
+
+/// Indexed trigrams of one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IndexedFile {
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u64,
+    size: u64,
+    /// Sorted, deduplicated trigrams
+    trigrams: Vec<Trigram>,
+}
+
+impl IndexedFile {
+    fn new(text: &str, metadata: &Metadata) -> Self {
+        Self {
+            modified: modified_nanos(metadata),
+            size: metadata.len(),
+            trigrams: trigrams(text.as_bytes()),
+        }
+    }
+
+    /// Whether the entry still describes the file on disk
+    fn is_fresh(&self, metadata: &Metadata) -> bool {
+        self.size == metadata.len() && self.modified == modified_nanos(metadata)
+    }
+}
+
+fn modified_nanos(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Trigrams of ASCII-lowercased `bytes`
+fn trigrams(bytes: &[u8]) -> Vec<Trigram> {
+    let mut trigrams: Vec<Trigram> = bytes.windows(3).map(|w| pack(w[0], w[1], w[2])).collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+fn pack(a: u8, b: u8, c: u8) -> Trigram {
+    let lower = |b: u8| b.to_ascii_lowercase() as u32;
+    (lower(a) << 16) | (lower(b) << 8) | lower(c)
+}
+
+/// Trigram index of the files below a root directory
+#[derive(Debug)]
+pub(crate) struct TrigramIndex {
+    /// Canonical root directory
+    root: PathBuf,
+    /// Entries keyed by path relative to `root`
+    files: HashMap<PathBuf, IndexedFile>,
+    /// Unix timestamp (seconds) of the last change
+    updated_at: u64,
+    /// Changed since the last save
+    dirty: bool,
+    last_saved: Option<Instant>,
+}
+
+impl TrigramIndex {
+    fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            files: HashMap::new(),
+            updated_at: now_secs(),
+            dirty: true,
+            last_saved: None,
+        }
+    }
+
+    /// Index every searchable file below `root` (gitignore-aware, like search)
+    fn build(root: PathBuf) -> Self {
+        let walker = build_walker(&root.to_string_lossy(), None, None)
+            .threads(
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(4),
+            )
+            .build_parallel();
+        let (tx, rx) = mpsc::channel::<(PathBuf, IndexedFile)>();
+
+        walker.run(|| {
+            let tx = tx.clone();
+            let root = &root;
+            Box::new(move |entry| {
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                    return WalkState::Continue;
+                }
+                let Ok(relative) = entry.path().strip_prefix(root) else {
+                    return WalkState::Continue;
+                };
+                if let Some(indexed) = index_path(entry.path()) {
+                    if tx.send((relative.to_path_buf(), indexed)).is_err() {
+                        return WalkState::Quit;
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+        drop(tx);
+
+        let mut index = Self::new(root);
+        index.files = rx.into_iter().collect();
+        index
+    }
+
+    fn relative<'p>(&self, path: &'p Path) -> Option<&'p Path> {
+        path.strip_prefix(&self.root).ok()
+    }
+
+    fn update(&mut self, relative: PathBuf, indexed: Option<IndexedFile>) {
+        match indexed {
+            Some(indexed) => {
+                self.files.insert(relative, indexed);
+            }
+            None => {
+                self.files.remove(&relative);
+            }
+        }
+        self.updated_at = now_secs();
+        self.dirty = true;
+    }
+
+    fn trigram_count(&self) -> usize {
+        self.files.values().map(|f| f.trigrams.len()).sum()
+    }
+
+    /// Approximate heap usage of the loaded index
+    fn memory_bytes(&self) -> u64 {
+        self.files
+            .iter()
+            .map(|(path, f)| {
+                path.as_os_str().len() + std::mem::size_of::<IndexedFile>() + f.trigrams.len() * 4
+            })
+            .sum::<usize>() as u64
+    }
+
+    /// Write the index to `path` (temp file + rename)
+    fn save(&mut self, path: &Path) -> Result<(), String> {
+        let tmp = path.with_extension("tmp");
+        let file = File::create(&tmp).map_err(|e| format!("Failed to create index: {}", e))?;
+        let mut out = BufWriter::new(file);
+        self.encode(&mut out)
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("Failed to write index: {}", e))?;
+        drop(out);
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to save index: {}", e))?;
+
+        self.dirty = false;
+        self.last_saved = Some(Instant::now());
+        Ok(())
+    }
+
+    fn encode(&self, out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(INDEX_MAGIC)?;
+        write_bytes(out, self.root.to_string_lossy().as_bytes())?;
+        out.write_all(&self.updated_at.to_le_bytes())?;
+        out.write_all(&(self.files.len() as u32).to_le_bytes())?;
+
+        for (path, file) in &self.files {
+            write_bytes(out, path.to_string_lossy().as_bytes())?;
+            out.write_all(&file.modified.to_le_bytes())?;
+            out.write_all(&file.size.to_le_bytes())?;
+            out.write_all(&(file.trigrams.len() as u32).to_le_bytes())?;
+            // Trigrams are sorted, so store varint-encoded deltas
+            let mut previous = 0;
+            for &trigram in &file.trigrams {
+                write_varint(out, trigram - previous)?;
+                previous = trigram;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read an index written by [`TrigramIndex::save`]
+    fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open index: {}", e))?;
+        let mut input = BufReader::new(file);
+        Self::decode(&mut input).map_err(|e| format!("Corrupt index {:?}: {}", path, e))
+    }
+
+    fn decode(input: &mut impl Read) -> std::io::Result<Self> {
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC {
+            return Err(invalid_data("unknown format"));
+        }
+
+        let root = PathBuf::from(read_string(input)?);
+        let updated_at = read_u64(input)?;
+        let count = read_u32(input)?;
+
+        let mut files = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let path = PathBuf::from(read_string(input)?);
+            let modified = read_u64(input)?;
+            let size = read_u64(input)?;
+            let len = read_u32(input)?;
+            let mut trigrams = Vec::with_capacity(len as usize);
+            let mut previous: Trigram = 0;
+            for _ in 0..len {
+                previous = previous
+                    .checked_add(read_varint(input)?)
+                    .ok_or_else(|| invalid_data("trigram overflow"))?;
+                trigrams.push(previous);
+            }
+            files.insert(
+                path,
+                IndexedFile {
+                    modified,
+                    size,
+                    trigrams,
+                },
+            );
+        }
+
+        Ok(Self {
+            root,
+            files,
+            updated_at,
+            dirty: false,
+            last_saved: Some(Instant::now()),
+        })
+    }
+}
+
+/// Read and index a file; `None` if it is too large or unreadable
+fn index_path(path: &Path) -> Option<IndexedFile> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_FILE_SIZE {
+        return None;
+    }
+    let text = read_text(path)?;
+    Some(IndexedFile::new(&text, &metadata))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn write_bytes(out: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+    out.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    out.write_all(bytes)
+}
+
+fn write_varint(out: &mut impl Write, mut value: u32) -> std::io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_u32(input: &mut impl Read) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(input: &mut impl Read) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_string(input: &mut impl Read) -> std::io::Result<String> {
+    let len = read_u32(input)? as usize;
+    let mut buf = vec![0u8; len];
+    input.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|_| invalid_data("invalid path"))
+}
+
+fn read_varint(input: &mut impl Read) -> std::io::Result<u32> {
+    let mut value = 0u32;
+    for shift in (0..32).step_by(7) {
+        let mut byte = [0u8; 1];
+        input.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u32) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+/// Trigrams a file must contain to possibly match a search
+///
+/// A file is a candidate if it contains every trigram of at least one
+/// alternative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TrigramQuery {
+    alternatives: Vec<Vec<Trigram>>,
+}
+
+impl TrigramQuery {
+    /// Derive the query for a search; `None` if the index cannot narrow it
+    pub(crate) fn new(
+        mode: SearchMode,
+        query: &str,
+        is_regex: bool,
+        case_sensitive: bool,
+        match_word: bool,
+    ) -> Option<Self> {
+        if mode == SearchMode::Structural {
+            return None;
+        }
+        // Literals are extracted from the case-sensitive pattern; lowercasing
+        // them below makes them match case-insensitive searches too
+        let pattern = build_pattern(query, is_regex, true, match_word).ok()?;
+        let hir = regex_syntax::Parser::new().parse(pattern.as_str()).ok()?;
+
+        let mut alternatives = Vec::new();
+        for literals in required_literals(&hir) {
+            let mut trigrams: Vec<Trigram> = literals
+                .iter()
+                .flat_map(|literal| literal_trigrams(literal, case_sensitive))
+                .collect();
+            // An alternative without trigrams matches every file
+            if trigrams.is_empty() {
+                return None;
+            }
+            trigrams.sort_unstable();
+            trigrams.dedup();
+            alternatives.push(trigrams);
+        }
+
+        if alternatives.is_empty() {
+            None
+        } else {
+            Some(Self { alternatives })
+        }
+    }
+
+    /// Whether a file with these (sorted) trigrams can contain a match
+    fn matches(&self, trigrams: &[Trigram]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|required| required.iter().all(|t| trigrams.binary_search(t).is_ok()))
+    }
+}
+
+/// Trigrams of a literal that every match must contain
+fn literal_trigrams(literal: &[u8], case_sensitive: bool) -> Vec<Trigram> {
+    // U+FFFD can stand in for invalid UTF-8 in lossily decoded files
+    if literal.windows(3).any(|w| w == "\u{FFFD}".as_bytes()) {
+        return Vec::new();
+    }
+    literal
+        .windows(3)
+        .filter(|w| {
+            // Case-insensitive matching uses Unicode case folding: non-ASCII
+            // letters fold to other bytes, and `k`/`s` also match the Kelvin
+            // sign and long s
+            case_sensitive
+                || w.iter()
+                    .all(|b| b.is_ascii() && !matches!(b.to_ascii_lowercase(), b'k' | b's'))
+        })
+        .map(|w| pack(w[0], w[1], w[2]))
+        .collect()
+}
+
+/// Literal strings a match must contain, as alternatives (OR) of sets (AND)
+///
+/// Sub-expressions that can match without a literal (classes, optional
+/// repetitions, ...) contribute nothing, so the result may be weaker than the
+/// pattern but never rejects a matching file.
+fn required_literals(hir: &Hir) -> Vec<Vec<Vec<u8>>> {
+    match hir.kind() {
+        HirKind::Literal(literal) => vec![vec![literal.0.to_vec()]],
+        HirKind::Capture(capture) => required_literals(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min > 0 => required_literals(&repetition.sub),
+        HirKind::Concat(subs) => subs.iter().fold(vec![Vec::new()], |all, sub| {
+            let next = required_literals(sub);
+            if all.len() * next.len() > MAX_ALTERNATIVES {
+                // Dropping a constraint only widens the candidate set
+                return all;
+            }
+            all.iter()
+                .flat_map(|left| {
+                    next.iter()
+                        .map(move |right| left.iter().chain(right).cloned().collect())
+                })
+                .collect()
+        }),
+        HirKind::Alternation(subs) => {
+            let alternatives: Vec<_> = subs.iter().flat_map(required_literals).collect();
+            if alternatives.len() > MAX_ALTERNATIVES {
+                vec![Vec::new()]
+            } else {
+                alternatives
+            }
+        }
+        _ => vec![Vec::new()],
+    }
+}
+
+/// How the index classifies a file during a search
+pub(crate) enum IndexCheck {
+    /// Up to date and cannot match
+    Skip,
+    /// Up to date and may match
+    Search,
+    /// Missing or outdated; the contents should be indexed while searching
+    Stale(PathBuf),
+}
+
+/// A loaded index shared by searches and file watchers
+struct SharedIndex {
+    index: RwLock<TrigramIndex>,
+    /// Watcher updates that arrived while the index was locked, applied by
+    /// the next writer. Until then the affected entries fail the freshness
+    /// check, so searches read those files directly.
+    pending: Mutex<Vec<(PathBuf, Option<IndexedFile>)>>,
+}
+
+impl SharedIndex {
+    fn new(index: TrigramIndex) -> Arc<Self> {
+        Arc::new(Self {
+            index: RwLock::new(index),
+            pending: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, TrigramIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lock the index for writing, applying deferred watcher updates
+    fn write(&self) -> RwLockWriteGuard<'_, TrigramIndex> {
+        let mut index = self.index.write().unwrap_or_else(|e| e.into_inner());
+        self.apply_pending(&mut index);
+        index
+    }
+
+    /// Apply watcher updates now, or defer them if a search holds the index
+    fn update_or_defer(&self, updates: Vec<(PathBuf, Option<IndexedFile>)>) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.extend(updates);
+        }
+        let mut index = match self.index.try_write() {
+            Ok(index) => index,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+        self.apply_pending(&mut index);
+        save_if_due(&mut index);
+    }
+
+    fn apply_pending(&self, index: &mut TrigramIndex) {
+        let pending = match self.pending.lock() {
+            Ok(mut pending) => std::mem::take(&mut *pending),
+            Err(_) => return,
+        };
+        for (relative, indexed) in pending {
+            if index.files.contains_key(&relative) {
+                index.update(relative, indexed);
+            }
+        }
+    }
+}
+
+/// A loaded index paired with the query of one search
+pub(crate) struct IndexedSearch {
+    index: Arc<SharedIndex>,
+    query: TrigramQuery,
+    /// Updates collected from stale files during the search
+    updates: Mutex<Vec<(PathBuf, IndexedFile)>>,
+}
+
+impl IndexedSearch {
+    /// Lock the index for the duration of a search
+    pub(crate) fn reader(&self) -> IndexReader<'_> {
+        IndexReader {
+            index: self.index.read(),
+            query: &self.query,
+        }
+    }
+
+    /// Index contents read from a stale file
+    pub(crate) fn record(&self, relative: PathBuf, text: &str, metadata: &Metadata) {
+        if let Ok(mut updates) = self.updates.lock() {
+            updates.push((relative, IndexedFile::new(text, metadata)));
+        }
+    }
+
+    /// Apply the recorded updates and save the index if a save is due
+    pub(crate) fn finish(&self) {
+        let updates = match self.updates.lock() {
+            Ok(mut updates) => std::mem::take(&mut *updates),
+            Err(_) => return,
+        };
+        let mut index = self.index.write();
+        if !updates.is_empty() {
+            tracing::debug!("Search refreshed {} index entries", updates.len());
+        }
+        for (relative, indexed) in updates {
+            index.update(relative, Some(indexed));
+        }
+        save_if_due(&mut index);
+    }
+}
+
+/// Read access to an index during a search
+pub(crate) struct IndexReader<'a> {
+    index: RwLockReadGuard<'a, TrigramIndex>,
+    query: &'a TrigramQuery,
+}
+
+impl IndexReader<'_> {
+    /// Classify a file found by the search walker
+    pub(crate) fn check(&self, path: &Path, metadata: &Metadata) -> IndexCheck {
+        let Some(relative) = self.index.relative(path) else {
+            return IndexCheck::Search;
+        };
+        match self.index.files.get(relative) {
+            Some(indexed) if indexed.is_fresh(metadata) => {
+                if self.query.matches(&indexed.trigrams) {
+                    IndexCheck::Search
+                } else {
+                    IndexCheck::Skip
+                }
+            }
+            _ => IndexCheck::Stale(relative.to_path_buf()),
+        }
+    }
+}
+
+fn save_if_due(index: &mut TrigramIndex) {
+    let due = index
+        .last_saved
+        .is_none_or(|t| t.elapsed() >= SAVE_INTERVAL);
+    if !index.dirty || !due {
+        return;
+    }
+    match index_file_path(&index.root) {
+        Ok(path) => {
+            if let Err(e) = index.save(&path) {
+                tracing::warn!("Failed to save search index for {:?}: {}", index.root, e);
+            }
+        }
+        Err(e) => tracing::warn!("{}", e),
+    }
+}
+
+/// Location of the saved index for a canonical root
+fn index_file_path(root: &Path) -> Result<PathBuf, String> {
+    let dir = codelane_core::paths::search_index_dir()
+        .map_err(|e| format!("Failed to get search index directory: {}", e))?;
+    Ok(dir.join(format!(
+        "{:016x}.idx",
+        fnv1a(root.to_string_lossy().as_bytes())
+    )))
+}
+
+/// FNV-1a hash, stable across builds so index file names stay valid
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Index status reported to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndexStatus {
+    /// Canonical indexed root, if an index covers the requested path
+    pub root_path: Option<String>,
+    /// "none", "building" or "ready"
+    pub state: String,
+    pub file_count: u32,
+    pub trigram_count: u64,
+    /// Size of the saved index file in bytes
+    pub disk_bytes: u64,
+    /// Approximate memory used by the loaded index in bytes
+    pub memory_bytes: u64,
+    /// Unix timestamp (seconds) of the last change
+    pub updated_at: Option<u64>,
+}
+
+/// State for loaded trigram indexes
+pub struct SearchIndexState {
+    /// Loaded indexes keyed by canonical root
+    indexes: Mutex<HashMap<PathBuf, Arc<SharedIndex>>>,
+    /// Roots with a build in progress
+    building: Mutex<HashSet<PathBuf>>,
+}
+
+impl SearchIndexState {
+    pub fn new() -> Self {
+        Self {
+            indexes: Mutex::new(HashMap::new()),
+            building: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The index covering `path`, loading a saved one from disk if needed
+    fn find(&self, path: &Path) -> Option<Arc<SharedIndex>> {
+        let mut indexes = self.indexes.lock().ok()?;
+        if let Some(index) = indexes
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.as_os_str().len())
+            .map(|(_, index)| index.clone())
+        {
+            return Some(index);
+        }
+
+        for root in path.ancestors() {
+            let Ok(file) = index_file_path(root) else {
+                return None;
+            };
+            if !file.exists() {
+                continue;
+            }
+            match TrigramIndex::load(&file) {
+                Ok(index) if index.root == root => {
+                    tracing::info!(
+                        "Loaded search index for {:?} ({} files)",
+                        root,
+                        index.files.len()
+                    );
+                    let index = SharedIndex::new(index);
+                    indexes.insert(root.to_path_buf(), index.clone());
+                    return Some(index);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+        None
+    }
+
+    /// Prepare an indexed search of `root_path`, if an index covers it
+    pub(crate) fn search(&self, root_path: &str, query: TrigramQuery) -> Option<IndexedSearch> {
+        let root = Path::new(root_path).canonicalize().ok()?;
+        Some(IndexedSearch {
+            index: self.find(&root)?,
+            query,
+            updates: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Re-index or drop indexed files reported by a file watcher
+    pub fn apply_changes(&self, paths: &[PathBuf]) {
+        // Release the map before touching any index so new searches can start
+        let indexes: Vec<Arc<SharedIndex>> = match self.indexes.lock() {
+            Ok(indexes) => indexes.values().cloned().collect(),
+            Err(_) => return,
+        };
+        if indexes.is_empty() {
+            return;
+        }
+
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|p| canonical_event_path(p))
+            .collect();
+        for shared in indexes {
+            let changed: Vec<(&PathBuf, PathBuf)> = {
+                let index = shared.read();
+                paths
+                    .iter()
+                    .filter_map(|path| {
+                        let relative = index.relative(path)?;
+                        index
+                            .files
+                            .contains_key(relative)
+                            .then(|| (path, relative.to_path_buf()))
+                    })
+                    .collect()
+            };
+            // Read the files without holding the index
+            let updates = changed
+                .into_iter()
+                .map(|(path, relative)| (relative, index_path(path)))
+                .collect();
+            shared.update_or_defer(updates);
+        }
+    }
+
+    fn status(&self, path: &Path) -> SearchIndexStatus {
+        let building = self.building.lock().is_ok_and(|b| b.contains(path));
+        let mut status = SearchIndexStatus {
+            root_path: None,
+            state: if building { "building" } else { "none" }.to_string(),
+            file_count: 0,
+            trigram_count: 0,
+            disk_bytes: 0,
+            memory_bytes: 0,
+            updated_at: None,
+        };
+
+        if let Some(index) = self.find(path) {
+            let index = index.read();
+            if !building {
+                status.state = "ready".to_string();
+            }
+            status.root_path = Some(index.root.to_string_lossy().to_string());
+            status.file_count = index.files.len() as u32;
+            status.trigram_count = index.trigram_count() as u64;
+            status.memory_bytes = index.memory_bytes();
+            status.updated_at = Some(index.updated_at);
+            status.disk_bytes = index_file_path(&index.root)
+                .ok()
+                .and_then(|file| fs::metadata(file).ok())
+                .map_or(0, |m| m.len());
+        }
+        status
+    }
+}
+
+impl Default for SearchIndexState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn canonical_dir(root_path: &str) -> Result<PathBuf, String> {
+    let root = Path::new(root_path)
+        .canonicalize()
+        .map_err(|e| format!("Invalid path {}: {}", root_path, e))?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root_path));
+    }
+    Ok(root)
+}
+
+/// Build (or rebuild) the trigram index for a directory
+///
+/// Indexes every file a search of `root_path` would visit and saves the index
+/// to disk. Later searches inside `root_path` use it automatically.
+///
+/// # Arguments
+/// * `root_path` - Directory to index, typically a lane's working directory
+///
+/// # Returns
+/// The status of the new index
+#[tauri::command]
+pub async fn search_index_build(
+    state: State<'_, SearchIndexState>,
+    root_path: String,
+) -> Result<SearchIndexStatus, String> {
+    let root = canonical_dir(&root_path)?;
+    {
+        let mut building = state
+            .building
+            .lock()
+            .map_err(|e| format!("Failed to lock search index state: {}", e))?;
+        if !building.insert(root.clone()) {
+            return Err(format!("Index for {} is already being built", root_path));
+        }
+    }
+
+    tracing::info!("Building search index for {:?}", root);
+    let started = Instant::now();
+    let build_root = root.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut index = TrigramIndex::build(build_root);
+        index.save(&index_file_path(&index.root)?)?;
+        Ok::<_, String>(index)
+    })
+    .await
+    .map_err(|e| format!("Index build failed: {}", e))
+    .and_then(|result| result);
+
+    if let Ok(mut building) = state.building.lock() {
+        building.remove(&root);
+    }
+    let index = result?;
+    tracing::info!(
+        "Indexed {} files in {:?} in {:?}",
+        index.files.len(),
+        root,
+        started.elapsed()
+    );
+
+    state
+        .indexes
+        .lock()
+        .map_err(|e| format!("Failed to lock search index state: {}", e))?
+        .insert(root.clone(), SharedIndex::new(index));
+
+    Ok(state.status(&root))
+}
+
+/// Get the status of the trigram index covering a directory
+///
+/// # Arguments
+/// * `root_path` - Directory to check
+///
+/// # Returns
+/// Index state, file and trigram counts and size on disk / in memory
+#[tauri::command]
+pub async fn search_index_status(
+    state: State<'_, SearchIndexState>,
+    root_path: String,
+) -> Result<SearchIndexStatus, String> {
+    let root = canonical_dir(&root_path)?;
+    Ok(state.status(&root))
+}
+
+/// Delete the trigram index for a directory
+///
+/// # Arguments
+/// * `root_path` - Indexed directory
+#[tauri::command]
+pub async fn search_index_delete(
+    state: State<'_, SearchIndexState>,
+    root_path: String,
+) -> Result<(), String> {
+    let root = canonical_dir(&root_path)?;
+    state
+        .indexes
+        .lock()
+        .map_err(|e| format!("Failed to lock search index state: {}", e))?
+        .remove(&root);
+
+    let file = index_file_path(&root)?;
+    if file.exists() {
+        fs::remove_file(&file).map_err(|e| format!("Failed to delete index: {}", e))?;
+    }
+    tracing::info!("Deleted search index for {:?}", root);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn query(text: &str, is_regex: bool, case_sensitive: bool) -> Option<TrigramQuery> {
+        TrigramQuery::new(SearchMode::Line, text, is_regex, case_sensitive, false)
+    }
+
+    fn file_trigrams(text: &str) -> Vec<Trigram> {
+        trigrams(text.as_bytes())
+    }
+
+    #[test]
+    fn test_literal_query_matches_case_insensitively() {
+        let q = query("Config", false, true).unwrap();
+        assert!(q.matches(&file_trigrams("let config = load();")));
+        assert!(q.matches(&file_trigrams("struct Config;")));
+        assert!(!q.matches(&file_trigrams("struct Settings;")));
+    }
+
+    #[test]
+    fn test_short_queries_cannot_be_narrowed() {
+        assert!(query("ab", false, true).is_none());
+        assert!(query("a.c", true, true).is_none());
+        assert!(query("\\w+", true, true).is_none());
+    }
+
+    #[test]
+    fn test_regex_concat_and_alternation() {
+        let q = query("fn (load|save)_config", true, true).unwrap();
+        assert!(q.matches(&file_trigrams("fn load_config()")));
+        assert!(q.matches(&file_trigrams("fn save_config()")));
+        assert!(!q.matches(&file_trigrams("fn read_config()")));
+
+        // An alternative without literals matches everything
+        assert!(query("(foo|.)", true, true).is_none());
+        // Optional parts are not required
+        let q = query("unwrap(_or_default)?", true, true).unwrap();
+        assert!(q.matches(&file_trigrams("x.unwrap()")));
+    }
+
+    #[test]
+    fn test_case_insensitive_skips_folding_bytes() {
+        // `s` and `k` fold to non-ASCII characters, so "sea" is not required
+        let q = query("search", false, false).unwrap();
+        assert_eq!(
+            q.alternatives,
+            vec![vec![
+                pack(b'a', b'r', b'c'),
+                pack(b'e', b'a', b'r'),
+                pack(b'r', b'c', b'h')
+            ]]
+        );
+        assert!(query("caf\u{e9}", false, false).is_some());
+        assert!(query("\u{e9}\u{e9}", false, false).is_none());
+    }
+
+    #[test]
+    fn test_structural_mode_not_indexed() {
+        assert!(
+            TrigramQuery::new(SearchMode::Structural, "(call) @m", false, true, false).is_none()
+        );
+    }
+
+    #[test]
+    fn test_index_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.rs"), "fn main() {}\n").unwrap();
+        fs::create_dir(temp.path().join("src")).unwrap();
+        fs::write(temp.path().join("src/b.rs"), "struct Config;\n").unwrap();
+
+        let root = temp.path().canonicalize().unwrap();
+        let index = TrigramIndex::build(root.clone());
+        assert_eq!(index.files.len(), 2);
+
+        let mut bytes = Vec::new();
+        index.encode(&mut bytes).unwrap();
+        let decoded = TrigramIndex::decode(&mut bytes.as_slice()).unwrap();
+        assert_eq!(decoded.root, root);
+        assert_eq!(decoded.files, index.files);
+
+        assert!(TrigramIndex::decode(&mut &b"garbage!"[..]).is_err());
+        assert!(TrigramIndex::decode(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_reader_classifies_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let config = root.join("config.rs");
+        let other = root.join("other.rs");
+        fs::write(&config, "struct Config;\n").unwrap();
+        fs::write(&other, "struct Other;\n").unwrap();
+
+        let mut index = TrigramIndex::build(root.clone());
+        // Pretend the index was just saved so the test does not write to disk
+        index.last_saved = Some(Instant::now());
+        let search = IndexedSearch {
+            index: SharedIndex::new(index),
+            query: query("Config", false, true).unwrap(),
+            updates: Mutex::new(Vec::new()),
+        };
+
+        {
+            let reader = search.reader();
+            let check = |path: &Path| reader.check(path, &fs::metadata(path).unwrap());
+            assert!(matches!(check(&config), IndexCheck::Search));
+            assert!(matches!(check(&other), IndexCheck::Skip));
+        }
+
+        // A changed file is no longer trusted until it is re-indexed
+        fs::write(&other, "struct OtherConfig;\n").unwrap();
+        let metadata = fs::metadata(&other).unwrap();
+        let relative = match search.reader().check(&other, &metadata) {
+            IndexCheck::Stale(relative) => relative,
+            _ => panic!("changed file should be stale"),
+        };
+        search.record(relative, "struct OtherConfig;\n", &metadata);
+        search.finish();
+        assert!(matches!(
+            search.reader().check(&other, &metadata),
+            IndexCheck::Search
+        ));
+    }
+
+    #[test]
+    fn test_apply_changes_updates_indexed_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let file = root.join("a.rs");
+        fs::write(&file, "struct Config;\n").unwrap();
+
+        let state = SearchIndexState::new();
+        let index = SharedIndex::new(TrigramIndex::build(root.clone()));
+        // Pretend the index was just saved so the test does not write to disk
+        index.write().last_saved = Some(Instant::now());
+        state
+            .indexes
+            .lock()
+            .unwrap()
+            .insert(root.clone(), index.clone());
+
+        fs::write(&file, "struct Settings;\n").unwrap();
+        state.apply_changes(std::slice::from_ref(&file));
+        let updated = index.read().files[Path::new("a.rs")].clone();
+        assert_eq!(updated.trigrams, file_trigrams("struct Settings;\n"));
+
+        fs::remove_file(&file).unwrap();
+        state.apply_changes(&[file]);
+        assert!(index.read().files.is_empty());
+    }
+
+    #[test]
+    fn test_apply_changes_does_not_wait_for_searches() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let file = root.join("a.rs");
+        fs::write(&file, "struct Config;\n").unwrap();
+
+        let state = SearchIndexState::new();
+        let mut index = TrigramIndex::build(root.clone());
+        index.last_saved = Some(Instant::now());
+        let search = IndexedSearch {
+            index: SharedIndex::new(index),
+            query: query("Config", false, true).unwrap(),
+            updates: Mutex::new(Vec::new()),
+        };
+        state
+            .indexes
+            .lock()
+            .unwrap()
+            .insert(root.clone(), search.index.clone());
+
+        // A search is reading the index while the file changes
+        let reader = search.reader();
+        fs::write(&file, "struct Settings;\n").unwrap();
+        state.apply_changes(std::slice::from_ref(&file));
+        assert!(state
+            .search(
+                &root.to_string_lossy(),
+                query("Settings", false, true).unwrap()
+            )
+            .is_some());
+        drop(reader);
+
+        // The deferred update is applied when the search finishes
+        search.finish();
+        let updated = search.index.read().files[Path::new("a.rs")].clone();
+        assert_eq!(updated.trigrams, file_trigrams("struct Settings;\n"));
+    }
+
+    #[test]
+    fn test_index_file_path_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        let a = index_file_path(Path::new("/repo/a")).unwrap();
+        let b = index_file_path(Path::new("/repo/b")).unwrap();
+        assert_ne!(a, b);
+        assert!(a.to_string_lossy().ends_with(".idx"));
+    }
+}
@@ -0,0 +1,874 @@
+//! Project-Wide Search and Replace
+//!
+//! Builds on the search walker in [`crate::search`] to replace matches across
+//! files in two steps:
+//!
+//! - `search_replace_preview`: finds every match and returns the before/after
+//!   text per match (regex queries expand `$1` / `${name}` capture groups).
+//!   The planned edits are kept server-side under a preview ID.
+//! - `search_replace_apply`: rewrites the selected matches of a preview. Every
+//!   file must be unchanged since the preview (agents edit concurrently); if
+//!   any file changed, nothing is written. Files are written to temp files
+//!   first and renamed into place, rolling back on failure. Returns an undo
+//!   token.
+//! - `search_replace_undo`: restores the files of an apply, provided they have
+//!   not been modified since.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::search::{
+    build_pattern, build_walker, is_path_allowed, restricted_paths, MAX_FILE_SIZE,
+};
+
+/// Default maximum matches in a preview (0 = unlimited)
+const DEFAULT_MAX_PREVIEW_MATCHES: u32 = 10_000;
+
+/// Number of previews kept for applying
+const MAX_PREVIEWS: usize = 8;
+
+/// Number of applied replacements that can be undone
+const MAX_UNDO_ENTRIES: usize = 20;
+
+/// Bytes inspected for NUL when detecting binary files
+const BINARY_CHECK_LEN: usize = 8000;
+
+/// A single planned replacement (byte range in the file)
+#[derive(Debug, Clone)]
+struct PlannedEdit {
+    id: u32,
+    start: usize,
+    end: usize,
+    replacement: String,
+}
+
+/// Planned edits for one file, with the fingerprint of the previewed content
+#[derive(Debug, Clone)]
+struct PlannedFile {
+    fingerprint: String,
+    edits: Vec<PlannedEdit>,
+}
+
+/// Original content of a rewritten file, for undo
+#[derive(Debug, Clone)]
+struct UndoEntry {
+    path: PathBuf,
+    original: Vec<u8>,
+    /// Fingerprint of the content written by the replacement
+    replaced_fingerprint: String,
+}
+
+/// State for pending previews and undoable replacements
+pub struct ReplaceState {
+    previews: Mutex<VecDeque<(String, HashMap<String, PlannedFile>)>>,
+    undo: Mutex<VecDeque<(String, Vec<UndoEntry>)>>,
+}
+
+impl ReplaceState {
+    pub fn new() -> Self {
+        Self {
+            previews: Mutex::new(VecDeque::new()),
+            undo: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl Default for ReplaceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single match with its replacement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceMatch {
+    /// Match ID within the file (used to select matches when applying)
+    pub id: u32,
+    /// 1-indexed line number
+    pub line_number: u32,
+    /// 0-indexed column (byte offset in the line) where the match starts
+    pub column: u32,
+    /// The matched text
+    pub match_text: String,
+    /// The text the match is replaced with (capture groups expanded)
+    pub replacement_text: String,
+    /// The line before replacement
+    pub line_before: String,
+    /// The line with only this match replaced
+    pub line_after: String,
+}
+
+/// All matches in one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReplacePreview {
+    /// Absolute file path
+    pub file_path: String,
+    pub matches: Vec<ReplaceMatch>,
+}
+
+/// Result of a replace preview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplacePreview {
+    /// ID to pass to `search_replace_apply`
+    pub preview_id: String,
+    pub files: Vec<FileReplacePreview>,
+    pub total_matches: u32,
+    pub total_files: u32,
+    /// Whether the preview stopped at `max_matches`
+    pub truncated: bool,
+}
+
+/// Matches of one file to apply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceSelection {
+    /// Absolute file path (as returned in the preview)
+    pub file_path: String,
+    /// Match IDs to replace (all matches in the file if omitted)
+    pub match_ids: Option<Vec<u32>>,
+}
+
+/// Result of applying a replacement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceApplyResult {
+    /// Token for `search_replace_undo`
+    pub undo_token: String,
+    /// Files that were rewritten
+    pub files: Vec<String>,
+    /// Number of matches replaced
+    pub replacements: u32,
+}
+
+/// Preview a project-wide replacement
+///
+/// # Arguments
+/// * `root_path` - Directory to search in
+/// * `query` - Search query (plain text or regex)
+/// * `replacement` - Replacement text (`$1`, `${name}` expand capture groups in regex mode)
+/// * `is_regex` - Whether to treat query as regex
+/// * `case_sensitive` - Whether search is case sensitive
+/// * `match_word` - Whether to match whole words only
+/// * `include_pattern` - Optional glob pattern to include files
+/// * `exclude_pattern` - Optional glob pattern to exclude files
+/// * `max_matches` - Maximum number of matches (default: 10000, 0 = unlimited)
+/// * `file_paths` - Optional list of specific files or directories to search
+///
+/// # Returns
+/// Per-match before/after text and a preview ID for `search_replace_apply`
+#[tauri::command]
+pub async fn search_replace_preview(
+    state: State<'_, ReplaceState>,
+    root_path: String,
+    query: String,
+    replacement: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    match_word: Option<bool>,
+    include_pattern: Option<String>,
+    exclude_pattern: Option<String>,
+    max_matches: Option<u32>,
+    file_paths: Option<Vec<String>>,
+) -> Result<ReplacePreview, String> {
+    let is_regex = is_regex.unwrap_or(false);
+    let pattern = build_pattern(
+        &query,
+        is_regex,
+        case_sensitive.unwrap_or(false),
+        match_word.unwrap_or(false),
+    )?;
+
+    if !Path::new(&root_path).exists() {
+        return Err(format!("Search path does not exist: {}", root_path));
+    }
+
+    let options = PreviewOptions {
+        replacement,
+        expand_captures: is_regex,
+        include_pattern,
+        exclude_pattern,
+        max_matches: max_matches.unwrap_or(DEFAULT_MAX_PREVIEW_MATCHES),
+        file_paths,
+    };
+
+    let (mut preview, plan) =
+        tokio::task::spawn_blocking(move || preview_replace(&root_path, &pattern, &options))
+            .await
+            .map_err(|e| format!("Replace preview task failed: {}", e))?;
+
+    preview.preview_id = uuid::Uuid::new_v4().to_string();
+
+    let mut previews = state
+        .previews
+        .lock()
+        .map_err(|e| format!("Failed to lock replace state: {}", e))?;
+    previews.push_back((preview.preview_id.clone(), plan));
+    while previews.len() > MAX_PREVIEWS {
+        previews.pop_front();
+    }
+
+    tracing::info!(
+        "Replace preview {}: {} matches in {} files (truncated={})",
+        preview.preview_id,
+        preview.total_matches,
+        preview.total_files,
+        preview.truncated
+    );
+
+    Ok(preview)
+}
+
+/// Apply the selected matches of a replace preview
+///
+/// Fails without writing anything if any selected file changed since the preview.
+///
+/// # Arguments
+/// * `preview_id` - ID returned by `search_replace_preview`
+/// * `selections` - Files and match IDs to replace (every previewed match if omitted)
+///
+/// # Returns
+/// The rewritten files and an undo token
+#[tauri::command]
+pub async fn search_replace_apply(
+    state: State<'_, ReplaceState>,
+    preview_id: String,
+    selections: Option<Vec<ReplaceSelection>>,
+) -> Result<ReplaceApplyResult, String> {
+    let plan = {
+        let mut previews = state
+            .previews
+            .lock()
+            .map_err(|e| format!("Failed to lock replace state: {}", e))?;
+        let index = previews
+            .iter()
+            .position(|(id, _)| id == &preview_id)
+            .ok_or_else(|| format!("Replace preview not found: {}", preview_id))?;
+        previews
+            .remove(index)
+            .map(|(_, plan)| plan)
+            .unwrap_or_default()
+    };
+
+    let (entries, replacements) =
+        tokio::task::spawn_blocking(move || apply_plan(&plan, selections.as_deref()))
+            .await
+            .map_err(|e| format!("Replace task failed: {}", e))??;
+
+    let undo_token = uuid::Uuid::new_v4().to_string();
+    let files = entries
+        .iter()
+        .map(|e| e.path.to_string_lossy().to_string())
+        .collect();
+
+    let mut undo = state
+        .undo
+        .lock()
+        .map_err(|e| format!("Failed to lock replace state: {}", e))?;
+    undo.push_back((undo_token.clone(), entries));
+    while undo.len() > MAX_UNDO_ENTRIES {
+        undo.pop_front();
+    }
+
+    tracing::info!(
+        "Applied replace preview {} ({} replacements)",
+        preview_id,
+        replacements
+    );
+
+    Ok(ReplaceApplyResult {
+        undo_token,
+        files,
+        replacements,
+    })
+}
+
+/// Undo an applied replacement
+///
+/// Fails without restoring anything if any file changed since the replacement;
+/// the token stays valid so the undo can be retried once the files are fixed.
+///
+/// # Arguments
+/// * `undo_token` - Token returned by `search_replace_apply`
+///
+/// # Returns
+/// The restored files
+#[tauri::command]
+pub async fn search_replace_undo(
+    state: State<'_, ReplaceState>,
+    undo_token: String,
+) -> Result<Vec<String>, String> {
+    let lock_undo = || {
+        state
+            .undo
+            .lock()
+            .map_err(|e| format!("Failed to lock replace state: {}", e))
+    };
+    let entries = lock_undo()?
+        .iter()
+        .find(|(token, _)| token == &undo_token)
+        .map(|(_, entries)| entries.clone())
+        .ok_or_else(|| format!("Undo token not found: {}", undo_token))?;
+
+    let restored = tokio::task::spawn_blocking(move || undo_replace(&entries))
+        .await
+        .map_err(|e| format!("Undo task failed: {}", e))??;
+
+    lock_undo()?.retain(|(token, _)| token != &undo_token);
+    Ok(restored)
+}
+
+/// Options for building a replace preview
+struct PreviewOptions {
+    replacement: String,
+    expand_captures: bool,
+    include_pattern: Option<String>,
+    exclude_pattern: Option<String>,
+    max_matches: u32,
+    file_paths: Option<Vec<String>>,
+}
+
+/// Walk the tree and plan the replacement of every match
+fn preview_replace(
+    root_path: &str,
+    pattern: &Regex,
+    options: &PreviewOptions,
+) -> (ReplacePreview, HashMap<String, PlannedFile>) {
+    let restricted = restricted_paths(options.file_paths.clone());
+    let walker = build_walker(
+        root_path,
+        options.include_pattern.as_deref(),
+        options.exclude_pattern.as_deref(),
+    )
+    .build();
+
+    let mut preview = ReplacePreview {
+        preview_id: String::new(),
+        files: Vec::new(),
+        total_matches: 0,
+        total_files: 0,
+        truncated: false,
+    };
+    let mut plan = HashMap::new();
+
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        if !is_path_allowed(restricted.as_ref(), path) {
+            continue;
+        }
+        if path
+            .metadata()
+            .map(|m| m.len() > MAX_FILE_SIZE)
+            .unwrap_or(true)
+        {
+            continue;
+        }
+        let Some(content) = read_text(path) else {
+            continue;
+        };
+
+        let mut planned = plan_file(
+            &content,
+            pattern,
+            &options.replacement,
+            options.expand_captures,
+        );
+        if planned.is_empty() {
+            continue;
+        }
+
+        if options.max_matches > 0 {
+            planned.truncate((options.max_matches - preview.total_matches) as usize);
+        }
+
+        let file_path = path.to_string_lossy().to_string();
+        preview.total_matches += planned.len() as u32;
+        preview.total_files += 1;
+
+        let (edits, matches): (Vec<_>, Vec<_>) = planned.into_iter().unzip();
+        plan.insert(
+            file_path.clone(),
+            PlannedFile {
+                fingerprint: fingerprint(content.as_bytes()),
+                edits,
+            },
+        );
+        preview
+            .files
+            .push(FileReplacePreview { file_path, matches });
+
+        if options.max_matches > 0 && preview.total_matches >= options.max_matches {
+            preview.truncated = true;
+            break;
+        }
+    }
+
+    (preview, plan)
+}
+
+/// Read a file as UTF-8 text, skipping binary files
+fn read_text(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    if bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Content fingerprint used to detect concurrent modification
+fn fingerprint(bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    format!("{:016x}-{}", hasher.finish(), bytes.len())
+}
+
+/// Find every match in `content` (line by line, like `search_start`) and plan its replacement
+fn plan_file(
+    content: &str,
+    pattern: &Regex,
+    replacement: &str,
+    expand_captures: bool,
+) -> Vec<(PlannedEdit, ReplaceMatch)> {
+    let mut planned = Vec::new();
+    let mut line_offset = 0;
+
+    for (line_idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line = raw_line.trim_end_matches('\n').trim_end_matches('\r');
+
+        for caps in pattern.captures_iter(line) {
+            let mat = caps.get(0).expect("capture group 0 always exists");
+            let replacement_text = if expand_captures {
+                let mut expanded = String::new();
+                caps.expand(replacement, &mut expanded);
+                expanded
+            } else {
+                replacement.to_string()
+            };
+
+            let line_after = format!(
+                "{}{}{}",
+                &line[..mat.start()],
+                replacement_text,
+                &line[mat.end()..]
+            );
+            let id = planned.len() as u32;
+
+            planned.push((
+                PlannedEdit {
+                    id,
+                    start: line_offset + mat.start(),
+                    end: line_offset + mat.end(),
+                    replacement: replacement_text.clone(),
+                },
+                ReplaceMatch {
+                    id,
+                    line_number: (line_idx + 1) as u32,
+                    column: mat.start() as u32,
+                    match_text: mat.as_str().to_string(),
+                    replacement_text,
+                    line_before: line.to_string(),
+                    line_after,
+                },
+            ));
+        }
+
+        line_offset += raw_line.len();
+    }
+
+    planned
+}
+
+/// Apply non-overlapping edits (sorted by start offset) to `content`
+fn apply_edits(content: &str, edits: &[&PlannedEdit]) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for edit in edits {
+        result.push_str(&content[last..edit.start]);
+        result.push_str(&edit.replacement);
+        last = edit.end;
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+/// Rewrite the selected files of a plan, all or nothing
+fn apply_plan(
+    plan: &HashMap<String, PlannedFile>,
+    selections: Option<&[ReplaceSelection]>,
+) -> Result<(Vec<UndoEntry>, u32), String> {
+    // Resolve selections to (file, edits)
+    let mut selected: Vec<(&str, &PlannedFile, Vec<&PlannedEdit>)> = Vec::new();
+    match selections {
+        Some(selections) => {
+            for selection in selections {
+                let planned = plan.get(&selection.file_path).ok_or_else(|| {
+                    format!("File is not part of the preview: {}", selection.file_path)
+                })?;
+                let edits: Vec<&PlannedEdit> = planned
+                    .edits
+                    .iter()
+                    .filter(|e| {
+                        selection
+                            .match_ids
+                            .as_ref()
+                            .is_none_or(|ids| ids.contains(&e.id))
+                    })
+                    .collect();
+                if !edits.is_empty() {
+                    selected.push((&selection.file_path, planned, edits));
+                }
+            }
+        }
+        None => {
+            for (path, planned) in plan {
+                selected.push((path, planned, planned.edits.iter().collect()));
+            }
+        }
+    }
+    selected.sort_by(|a, b| a.0.cmp(b.0));
+
+    // Verify nothing changed since the preview before touching any file
+    let mut originals = Vec::with_capacity(selected.len());
+    let mut stale = Vec::new();
+    for (path, planned, _) in &selected {
+        match fs::read(path) {
+            Ok(bytes) if fingerprint(&bytes) == planned.fingerprint => originals.push(bytes),
+            _ => stale.push(path.to_string()),
+        }
+    }
+    if !stale.is_empty() {
+        return Err(format!(
+            "Files changed since the preview, re-run the preview: {}",
+            stale.join(", ")
+        ));
+    }
+
+    let mut writes = Vec::with_capacity(selected.len());
+    let mut replacements = 0;
+    for ((path, _, edits), original) in selected.iter().zip(originals) {
+        // Fingerprint matched the previewed UTF-8 content
+        let content = String::from_utf8(original).map_err(|e| e.to_string())?;
+        let updated = apply_edits(&content, edits);
+        replacements += edits.len() as u32;
+        writes.push((
+            PathBuf::from(path),
+            content.into_bytes(),
+            updated.into_bytes(),
+        ));
+    }
+
+    write_all_or_nothing(&writes)?;
+
+    let entries = writes
+        .into_iter()
+        .map(|(path, original, updated)| UndoEntry {
+            path,
+            original,
+            replaced_fingerprint: fingerprint(&updated),
+        })
+        .collect();
+
+    Ok((entries, replacements))
+}
+
+/// Restore the original content of an applied replacement
+fn undo_replace(entries: &[UndoEntry]) -> Result<Vec<String>, String> {
+    let stale: Vec<String> = entries
+        .iter()
+        .filter(|e| {
+            fs::read(&e.path)
+                .map(|bytes| fingerprint(&bytes) != e.replaced_fingerprint)
+                .unwrap_or(true)
+        })
+        .map(|e| e.path.to_string_lossy().to_string())
+        .collect();
+    if !stale.is_empty() {
+        return Err(format!(
+            "Files changed since the replacement, cannot undo: {}",
+            stale.join(", ")
+        ));
+    }
+
+    let writes: Vec<(PathBuf, Vec<u8>, Vec<u8>)> = entries
+        .iter()
+        .map(|e| {
+            let current = fs::read(&e.path).unwrap_or_default();
+            (e.path.clone(), current, e.original.clone())
+        })
+        .collect();
+    write_all_or_nothing(&writes)?;
+
+    Ok(entries
+        .iter()
+        .map(|e| e.path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Temp file next to `path`, so the final rename stays on one filesystem
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.codelane-replace.tmp", name))
+}
+
+/// Write `(path, current, new)` triples atomically as a group
+///
+/// New contents go to temp files (keeping permissions) and are renamed into
+/// place only once every temp file is written. If a rename fails, files
+/// already replaced are restored to `current`.
+fn write_all_or_nothing(writes: &[(PathBuf, Vec<u8>, Vec<u8>)]) -> Result<(), String> {
+    let mut temps = Vec::with_capacity(writes.len());
+    for (path, _, new) in writes {
+        let temp = temp_path(path);
+        let written = fs::write(&temp, new).and_then(|_| {
+            let permissions = fs::metadata(path)?.permissions();
+            fs::set_permissions(&temp, permissions)
+        });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp);
+            for temp in &temps {
+                let _ = fs::remove_file(temp);
+            }
+            return Err(format!("Failed to write {}: {}", path.display(), e));
+        }
+        temps.push(temp);
+    }
+
+    for (index, ((path, _, _), temp)) in writes.iter().zip(&temps).enumerate() {
+        if let Err(e) = fs::rename(temp, path) {
+            for (restored, _, current) in &writes[..index] {
+                if let Err(e) = fs::write(restored, current) {
+                    tracing::error!("Failed to restore {}: {}", restored.display(), e);
+                }
+            }
+            for temp in &temps[index..] {
+                let _ = fs::remove_file(temp);
+            }
+            return Err(format!("Failed to replace {}: {}", path.display(), e));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options(replacement: &str, expand_captures: bool) -> PreviewOptions {
+        PreviewOptions {
+            replacement: replacement.to_string(),
+            expand_captures,
+            include_pattern: None,
+            exclude_pattern: None,
+            max_matches: 0,
+            file_paths: None,
+        }
+    }
+
+    fn root(dir: &TempDir) -> String {
+        dir.path().to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_plan_file_literal_replacement() {
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let planned = plan_file("foo bar\nbaz foo foo\n", &pattern, "$1qux", false);
+
+        assert_eq!(planned.len(), 3);
+        let (_, first) = &planned[0];
+        assert_eq!(first.line_number, 1);
+        assert_eq!(first.replacement_text, "$1qux");
+        assert_eq!(first.line_after, "$1qux bar");
+
+        let (edit, third) = &planned[2];
+        assert_eq!(third.line_number, 2);
+        assert_eq!(third.column, 8);
+        assert_eq!(third.line_after, "baz foo $1qux");
+        assert_eq!((edit.start, edit.end), (16, 19));
+    }
+
+    #[test]
+    fn test_plan_file_expands_capture_groups() {
+        let pattern = build_pattern(r"(\w+)\.unwrap\(\)", true, true, false).unwrap();
+        let planned = plan_file("let x = y.unwrap();\r\n", &pattern, "$1?", true);
+
+        assert_eq!(planned.len(), 1);
+        let (_, m) = &planned[0];
+        assert_eq!(m.match_text, "y.unwrap()");
+        assert_eq!(m.replacement_text, "y?");
+        assert_eq!(m.line_before, "let x = y.unwrap();");
+        assert_eq!(m.line_after, "let x = y?;");
+    }
+
+    #[test]
+    fn test_plan_file_named_groups() {
+        let pattern = build_pattern(r"(?P<k>\w+)=(?P<v>\w+)", true, true, false).unwrap();
+        let planned = plan_file("a=b", &pattern, "${v}=${k}", true);
+        assert_eq!(planned[0].1.line_after, "b=a");
+    }
+
+    #[test]
+    fn test_apply_edits_preserves_line_endings() {
+        let content = "foo\r\nbar foo\r\n";
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let planned = plan_file(content, &pattern, "x", false);
+        let edits: Vec<&PlannedEdit> = planned.iter().map(|(e, _)| e).collect();
+        assert_eq!(apply_edits(content, &edits), "x\r\nbar x\r\n");
+    }
+
+    #[test]
+    fn test_preview_and_apply_selected_matches() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "one foo\ntwo foo\n").unwrap();
+        fs::write(&b, "foo\n").unwrap();
+        fs::write(dir.path().join("c.txt"), "nothing here\n").unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let (preview, plan) = preview_replace(&root(&dir), &pattern, &options("bar", false));
+        assert_eq!(preview.total_matches, 3);
+        assert_eq!(preview.total_files, 2);
+        assert!(!preview.truncated);
+
+        let selections = vec![ReplaceSelection {
+            file_path: a.to_string_lossy().to_string(),
+            match_ids: Some(vec![1]),
+        }];
+        let (entries, replacements) = apply_plan(&plan, Some(&selections)).unwrap();
+        assert_eq!(replacements, 1);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "one foo\ntwo bar\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "foo\n");
+        assert!(!temp_path(&a).exists());
+
+        undo_replace(&entries).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "one foo\ntwo foo\n");
+    }
+
+    #[test]
+    fn test_apply_rejects_files_changed_since_preview() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "foo\n").unwrap();
+        fs::write(&b, "foo\n").unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let (_, plan) = preview_replace(&root(&dir), &pattern, &options("bar", false));
+
+        fs::write(&b, "foo edited\n").unwrap();
+
+        let err = apply_plan(&plan, None).unwrap_err();
+        assert!(err.contains("b.txt"));
+        // Nothing was written
+        assert_eq!(fs::read_to_string(&a).unwrap(), "foo\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "foo edited\n");
+    }
+
+    #[test]
+    fn test_undo_rejects_files_changed_since_apply() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.txt");
+        fs::write(&a, "foo\n").unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let (_, plan) = preview_replace(&root(&dir), &pattern, &options("bar", false));
+        let (entries, _) = apply_plan(&plan, None).unwrap();
+
+        fs::write(&a, "bar edited\n").unwrap();
+        assert!(undo_replace(&entries).is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "bar edited\n");
+    }
+
+    #[tokio::test]
+    async fn test_stale_undo_can_be_retried() {
+        use tauri::Manager;
+
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.txt");
+        fs::write(&a, "foo\n").unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let (_, plan) = preview_replace(&root(&dir), &pattern, &options("bar", false));
+        let (entries, _) = apply_plan(&plan, None).unwrap();
+        let app = tauri::test::mock_app();
+        app.manage(ReplaceState::new());
+        app.state::<ReplaceState>()
+            .undo
+            .lock()
+            .unwrap()
+            .push_back(("token".to_string(), entries));
+
+        fs::write(&a, "bar edited\n").unwrap();
+        assert!(search_replace_undo(app.state(), "token".to_string())
+            .await
+            .is_err());
+
+        // Once the file is back to the replaced content, the undo goes through
+        fs::write(&a, "bar\n").unwrap();
+        let restored = search_replace_undo(app.state(), "token".to_string())
+            .await
+            .unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "foo\n");
+        assert!(search_replace_undo(app.state(), "token".to_string())
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_preview_truncates_at_max_matches() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "foo foo foo\n").unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let mut opts = options("bar", false);
+        opts.max_matches = 2;
+        let (preview, plan) = preview_replace(&root(&dir), &pattern, &opts);
+
+        assert_eq!(preview.total_matches, 2);
+        assert!(preview.truncated);
+        assert_eq!(plan.values().next().unwrap().edits.len(), 2);
+    }
+
+    #[test]
+    fn test_preview_skips_binary_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("bin.dat"), b"foo\0foo").unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let (preview, _) = preview_replace(&root(&dir), &pattern, &options("bar", false));
+        assert_eq!(preview.total_matches, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "echo foo\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let pattern = build_pattern("foo", false, true, false).unwrap();
+        let (_, plan) = preview_replace(&root(&dir), &pattern, &options("bar", false));
+        apply_plan(&plan, None).unwrap();
+
+        let mode = fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(fs::read_to_string(&script).unwrap(), "echo bar\n");
+    }
+}
@@ -0,0 +1,585 @@
+//! Search scopes backed by git
+//!
+//! A search can cover the working tree (default), only the files the lane
+//! changed (the files reported by `git_changes_with_stats`), only the lines
+//! added in the working tree diff against `HEAD`, or the tree of a git
+//! revision. Revision searches read blobs through a single
+//! `git cat-file --batch` process, so nothing is checked out; matches are
+//! reported with working-tree paths so the UI can open the current file.
+
+use codelane_git::diff::unquote_path;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::git::{self, run_git};
+use crate::search::{
+    build_overrides, decode_text, is_path_allowed, restricted_paths, MatchBatcher, Matcher,
+    SearchMatch, SearchOptions, SearchSummary, MAX_FILE_SIZE,
+};
+
+/// Which version of the code a search covers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SearchScope {
+    /// Every file in the working tree
+    #[default]
+    WorkingTree,
+    /// Files changed in the working tree (staged, unstaged and untracked)
+    ChangedFiles,
+    /// Only lines added relative to `HEAD`; untracked files count as all added
+    AddedLines,
+    /// The tree of a git revision (branch, tag or commit)
+    Revision { revision: String },
+}
+
+/// A scope resolved against the repository when the search starts
+#[derive(Debug)]
+pub(crate) enum ResolvedScope {
+    WorkingTree,
+    /// Canonical paths of the files to search
+    Files(HashSet<PathBuf>),
+    /// Added line ranges (1-based) keyed by canonical path
+    AddedLines(HashMap<PathBuf, Vec<RangeInclusive<u32>>>),
+    Revision {
+        revision: String,
+    },
+}
+
+impl ResolvedScope {
+    /// Whether a working-tree file is part of the scope
+    pub(crate) fn allows_file(&self, path: &Path) -> bool {
+        match self {
+            ResolvedScope::WorkingTree | ResolvedScope::Revision { .. } => true,
+            ResolvedScope::Files(files) => files.contains(&canonical(path)),
+            ResolvedScope::AddedLines(lines) => lines.contains_key(&canonical(path)),
+        }
+    }
+
+    /// Drop matches that start outside the scope's added lines
+    pub(crate) fn filter_matches(&self, path: &Path, matches: &mut Vec<SearchMatch>) {
+        let ResolvedScope::AddedLines(lines) = self else {
+            return;
+        };
+        if matches.is_empty() {
+            return;
+        }
+        match lines.get(&canonical(path)) {
+            Some(ranges) => matches.retain(|m| ranges.iter().any(|r| r.contains(&m.line_number))),
+            None => matches.clear(),
+        }
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Resolve a scope for a search rooted at `root_path`
+pub(crate) async fn resolve(scope: SearchScope, root_path: &str) -> Result<ResolvedScope, String> {
+    match scope {
+        SearchScope::WorkingTree => Ok(ResolvedScope::WorkingTree),
+        SearchScope::ChangedFiles => {
+            let toplevel = repo_toplevel(root_path)?;
+            let changes = git::git_changes_with_stats(root_path.to_string()).await?;
+            let files: HashSet<PathBuf> = changes
+                .iter()
+                .filter(|change| change.status != "deleted")
+                .filter_map(|change| toplevel.join(status_path(&change.path)).canonicalize().ok())
+                .collect();
+            tracing::info!("Search scoped to {} changed files", files.len());
+            Ok(ResolvedScope::Files(files))
+        }
+        SearchScope::AddedLines => {
+            let toplevel = repo_toplevel(root_path)?;
+            // Explicit prefixes, since `diff.noprefix` and `diff.mnemonicPrefix`
+            // change the `+++ b/` lines this parses
+            let diff = run_git(
+                &toplevel,
+                &[
+                    "diff",
+                    "HEAD",
+                    "--unified=0",
+                    "--no-color",
+                    "--no-ext-diff",
+                    "--src-prefix=a/",
+                    "--dst-prefix=b/",
+                ],
+            )
+            .map_err(|e| format!("Failed to diff working tree: {}", e))?;
+            let untracked = run_git(
+                &toplevel,
+                &["ls-files", "--others", "--exclude-standard", "-z"],
+            )?;
+
+            let mut lines = HashMap::new();
+            for (path, ranges) in parse_added_lines(&diff) {
+                if let Ok(path) = toplevel.join(path).canonicalize() {
+                    lines.insert(path, ranges);
+                }
+            }
+            for path in untracked.split('\0').filter(|p| !p.is_empty()) {
+                if let Ok(path) = toplevel.join(path).canonicalize() {
+                    lines.insert(path, vec![1..=u32::MAX]);
+                }
+            }
+            tracing::info!("Search scoped to added lines in {} files", lines.len());
+            Ok(ResolvedScope::AddedLines(lines))
+        }
+        SearchScope::Revision { revision } => {
+            let revision = revision.trim().to_string();
+            if revision.is_empty() || revision.starts_with('-') {
+                return Err(format!("Invalid revision: '{}'", revision));
+            }
+            let work_dir = git::validate_git_path(root_path)?;
+            run_git(
+                Path::new(&work_dir),
+                &[
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    &format!("{}^{{tree}}", revision),
+                ],
+            )
+            .map_err(|_| format!("Unknown revision: {}", revision))?;
+            Ok(ResolvedScope::Revision { revision })
+        }
+    }
+}
+
+/// Top-level directory of the work tree containing `root_path`
+fn repo_toplevel(root_path: &str) -> Result<PathBuf, String> {
+    let work_dir = git::validate_git_path(root_path)?;
+    let toplevel = run_git(Path::new(&work_dir), &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(toplevel.trim()))
+}
+
+/// The current path of a `git status --porcelain` entry
+///
+/// Renames are reported as `old -> new` and paths with special or non-ASCII
+/// characters are quoted.
+fn status_path(path: &str) -> String {
+    unquote_path(path.rsplit(" -> ").next().unwrap_or(path))
+}
+
+/// Parse the added line ranges of each file from a `--unified=0` diff
+fn parse_added_lines(diff: &str) -> HashMap<String, Vec<RangeInclusive<u32>>> {
+    let mut files: HashMap<String, Vec<RangeInclusive<u32>>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            // Deleted files have no new side
+            current = unquote_path(path).strip_prefix("b/").map(str::to_string);
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            let Some(path) = &current else {
+                continue;
+            };
+            if let Some(range) = parse_hunk_new_range(hunk) {
+                files.entry(path.clone()).or_default().push(range);
+            }
+        }
+    }
+
+    files
+}
+
+/// Added line range of a hunk header (`-a,b +c,d @@ ...`); `None` for pure deletions
+fn parse_hunk_new_range(hunk: &str) -> Option<RangeInclusive<u32>> {
+    let new_side = hunk
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix('+'))?;
+    let (start, count) = match new_side.split_once(',') {
+        Some((start, count)) => (start.parse::<u32>().ok()?, count.parse::<u32>().ok()?),
+        None => (new_side.parse::<u32>().ok()?, 1),
+    };
+    if count == 0 {
+        return None;
+    }
+    Some(start..=start + count - 1)
+}
+
+/// A blob listed by `git ls-tree --long`
+struct TreeEntry {
+    object: String,
+    size: u64,
+    /// Path relative to the search root
+    path: String,
+}
+
+/// List the regular files of `revision` below `root` (relative to `root`)
+fn list_tree(root: &Path, revision: &str) -> Result<Vec<TreeEntry>, String> {
+    let output = run_git(
+        root,
+        &["ls-tree", "-r", "-z", "--long", revision, "--", "."],
+    )
+    .map_err(|e| format!("Failed to list {}: {}", revision, e))?;
+
+    // Format: <mode> SP <type> SP <object> SP+ <size> TAB <path> NUL
+    Ok(output
+        .split('\0')
+        .filter_map(|record| {
+            let (meta, path) = record.split_once('\t')?;
+            let mut fields = meta.split_whitespace();
+            let mode = fields.next()?;
+            let kind = fields.next()?;
+            let object = fields.next()?;
+            let size = fields.next()?.parse().ok()?;
+            // Skip symlinks and submodules
+            if kind != "blob" || mode == "120000" {
+                return None;
+            }
+            Some(TreeEntry {
+                object: object.to_string(),
+                size,
+                path: path.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// A `git cat-file --batch` process that reads blobs by object id
+struct BlobReader {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    fn spawn(work_dir: &Path) -> Result<Self, String> {
+        let mut child = Command::new("git")
+            .current_dir(work_dir)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        let stdin = child.stdin.take().ok_or("Failed to open git stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open git stdout")?;
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    fn read(&mut self, object: &str) -> Result<Vec<u8>, String> {
+        writeln!(self.stdin, "{}", object)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Failed to request {}: {}", object, e))?;
+
+        // Header: <object> SP <type> SP <size> LF, or <object> SP missing LF
+        let mut header = String::new();
+        self.stdout
+            .read_line(&mut header)
+            .map_err(|e| format!("Failed to read {}: {}", object, e))?;
+        let size = header
+            .split_whitespace()
+            .nth(2)
+            .and_then(|size| size.parse::<usize>().ok())
+            .ok_or_else(|| format!("Unexpected cat-file output: {}", header.trim()))?;
+
+        // Contents are followed by a LF
+        let mut contents = vec![0; size + 1];
+        self.stdout
+            .read_exact(&mut contents)
+            .map_err(|e| format!("Failed to read {}: {}", object, e))?;
+        contents.truncate(size);
+        Ok(contents)
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Search the files of `revision` below `root_path` without checking it out
+///
+/// Include/exclude globs and `file_paths` apply to the working-tree location of
+/// each file, and matches are reported with those paths.
+pub(crate) fn search_revision(
+    root_path: &str,
+    revision: &str,
+    matcher: &Matcher,
+    cancel_flag: &AtomicBool,
+    options: &SearchOptions,
+    on_batch: impl FnMut(Vec<SearchMatch>, u32),
+) -> Result<SearchSummary, String> {
+    let root = Path::new(root_path);
+    let entries = list_tree(root, revision)?;
+    tracing::info!("Searching {} files of {}", entries.len(), revision);
+
+    let overrides = build_overrides(
+        root_path,
+        options.include_pattern.as_deref(),
+        options.exclude_pattern.as_deref(),
+    );
+    let restricted_paths = restricted_paths(options.file_paths.clone());
+    let canonical_root = canonical(root);
+
+    let mut reader = BlobReader::spawn(root)?;
+    let mut batcher = MatchBatcher::new(options.max_matches, on_batch);
+
+    for entry in entries {
+        if cancel_flag.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let path = root.join(&entry.path);
+        if overrides
+            .as_ref()
+            .is_some_and(|o| o.matched(&entry.path, false).is_ignore())
+            || !is_path_allowed(restricted_paths.as_ref(), &canonical_root.join(&entry.path))
+            || !matcher.accepts(&path)
+        {
+            continue;
+        }
+        if entry.size > MAX_FILE_SIZE {
+            tracing::debug!("Skipping large file: {:?}", path);
+            continue;
+        }
+
+        let contents = reader.read(&entry.object)?;
+        let text = decode_text(&contents);
+        let file_matches = matcher.search_text(&path.to_string_lossy(), &text);
+        if !batcher.add_file(file_matches) {
+            break;
+        }
+    }
+
+    Ok(batcher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchMode;
+    use std::fs;
+
+    #[test]
+    fn test_parse_added_lines() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3,0 +4,2 @@ fn main() {
++    let a = 1;
++    let b = 2;
+@@ -10 +12 @@ fn other() {
+-    old();
++    new();
+@@ -20,3 +21,0 @@ fn gone() {
+diff --git \"a/caf\\303\\251.rs\" \"b/caf\\303\\251.rs\"
+--- \"a/caf\\303\\251.rs\"
++++ \"b/caf\\303\\251.rs\"
+@@ -1 +1 @@
+-old
++new
+diff --git a/removed.txt b/removed.txt
+deleted file mode 100644
+--- a/removed.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let files = parse_added_lines(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files["src/lib.rs"], vec![4..=5, 12..=12]);
+        assert_eq!(files["café.rs"], vec![1..=1]);
+    }
+
+    #[test]
+    fn test_status_path() {
+        assert_eq!(status_path("src/lib.rs"), "src/lib.rs");
+        assert_eq!(status_path("old.rs -> new.rs"), "new.rs");
+        assert_eq!(status_path("\"with space.rs\""), "with space.rs");
+        assert_eq!(status_path("\"caf\\303\\251.rs\""), "café.rs");
+    }
+
+    fn search_match(line_number: u32) -> SearchMatch {
+        SearchMatch {
+            file_path: "/repo/a.rs".to_string(),
+            line_number,
+            column: 1,
+            end_line_number: line_number,
+            end_column: 2,
+            line_content: String::new(),
+            match_text: "x".to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_added_lines_filter() {
+        let path = PathBuf::from("/repo/a.rs");
+        let scope = ResolvedScope::AddedLines(HashMap::from([(path.clone(), vec![2..=3])]));
+
+        assert!(scope.allows_file(&path));
+        assert!(!scope.allows_file(Path::new("/repo/b.rs")));
+
+        let mut matches = vec![search_match(1), search_match(2), search_match(3)];
+        scope.filter_matches(&path, &mut matches);
+        let lines: Vec<u32> = matches.iter().map(|m| m.line_number).collect();
+        assert_eq!(lines, vec![2, 3]);
+
+        let mut matches = vec![search_match(1)];
+        scope.filter_matches(Path::new("/repo/b.rs"), &mut matches);
+        assert!(matches.is_empty());
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        run_git(dir, args).unwrap();
+    }
+
+    /// A repo whose committed `lib.rs` differs from the working tree
+    fn test_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        git(root, &["config", "user.name", "Test"]);
+        git(root, &["config", "user.email", "test@example.com"]);
+
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn a() { todo() }\nfn b() {}\n").unwrap();
+        fs::write(root.join("README.md"), "todo: docs\n").unwrap();
+        fs::write(root.join("café.rs"), "fn c() {}\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "initial"]);
+
+        fs::write(
+            root.join("src/lib.rs"),
+            "fn a() { todo() }\nfn b() { todo() }\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/new.rs"), "// todo\n").unwrap();
+        // git quotes non-ASCII paths by default
+        fs::write(root.join("café.rs"), "fn c() {}\nfn d() {}\n").unwrap();
+        dir
+    }
+
+    fn options(scope: ResolvedScope) -> SearchOptions {
+        SearchOptions {
+            include_pattern: None,
+            exclude_pattern: None,
+            max_matches: 0,
+            file_paths: None,
+            scope,
+            index: None,
+        }
+    }
+
+    fn matcher() -> Matcher {
+        Matcher::new(SearchMode::Line, "todo", false, true, false).unwrap()
+    }
+
+    fn run_revision(root: &Path, options: &SearchOptions) -> Vec<SearchMatch> {
+        let mut found = Vec::new();
+        let summary = search_revision(
+            &root.to_string_lossy(),
+            "main",
+            &matcher(),
+            &AtomicBool::new(false),
+            options,
+            |matches, _| found.extend(matches),
+        )
+        .unwrap();
+        assert_eq!(summary.total_matches as usize, found.len());
+        found.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        found
+    }
+
+    #[test]
+    fn test_search_revision_reads_committed_tree() {
+        let repo = test_repo();
+        let root = repo.path();
+
+        let found = run_revision(
+            root,
+            &options(ResolvedScope::Revision {
+                revision: "main".to_string(),
+            }),
+        );
+        let hits: Vec<(String, u32)> = found
+            .iter()
+            .map(|m| (m.file_path.clone(), m.line_number))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![
+                (root.join("README.md").to_string_lossy().into_owned(), 1),
+                (root.join("src/lib.rs").to_string_lossy().into_owned(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_revision_applies_globs() {
+        let repo = test_repo();
+        let mut options = options(ResolvedScope::Revision {
+            revision: "main".to_string(),
+        });
+        options.include_pattern = Some("*.rs".to_string());
+
+        let found = run_revision(repo.path(), &options);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].file_path.ends_with("lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_scopes() {
+        let repo = test_repo();
+        let root = repo.path().to_string_lossy().into_owned();
+        let lib = repo.path().join("src/lib.rs").canonicalize().unwrap();
+        let new = repo.path().join("src/new.rs").canonicalize().unwrap();
+        let readme = repo.path().join("README.md");
+        let cafe = repo.path().join("café.rs").canonicalize().unwrap();
+
+        let scope = resolve(SearchScope::ChangedFiles, &root).await.unwrap();
+        assert!(scope.allows_file(&lib));
+        assert!(scope.allows_file(&new));
+        assert!(scope.allows_file(&cafe));
+        assert!(!scope.allows_file(&readme));
+
+        let scope = resolve(SearchScope::AddedLines, &root).await.unwrap();
+        let ResolvedScope::AddedLines(ref lines) = scope else {
+            panic!("expected added lines scope");
+        };
+        assert_eq!(lines[&lib], vec![2..=2]);
+        assert_eq!(lines[&new], vec![1..=u32::MAX]);
+        assert_eq!(lines[&cafe], vec![2..=2]);
+
+        // Diff prefix settings must not change which lines are found
+        for setting in ["diff.noprefix", "diff.mnemonicPrefix"] {
+            git(repo.path(), &["config", setting, "true"]);
+            let scope = resolve(SearchScope::AddedLines, &root).await.unwrap();
+            let ResolvedScope::AddedLines(lines) = scope else {
+                panic!("expected added lines scope");
+            };
+            assert_eq!(lines[&lib], vec![2..=2], "with {}", setting);
+            git(repo.path(), &["config", "--unset", setting]);
+        }
+
+        let revision = SearchScope::Revision {
+            revision: "main".to_string(),
+        };
+        assert!(resolve(revision, &root).await.is_ok());
+        let missing = SearchScope::Revision {
+            revision: "no-such-branch".to_string(),
+        };
+        assert!(resolve(missing, &root).await.is_err());
+        let option = SearchScope::Revision {
+            revision: "--output=x".to_string(),
+        };
+        assert!(resolve(option, &root).await.is_err());
+    }
+}
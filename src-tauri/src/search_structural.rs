@@ -0,0 +1,313 @@
+//! Structural search with tree-sitter queries
+//!
+//! A structural query is a tree-sitter query (S-expression) compiled against
+//! every grammar in [`crate::import_analyzer`]; languages whose grammar does not
+//! know the query's node types are skipped. The node captured as `@match` (or
+//! the first capture of a pattern) is reported as the match range.
+//!
+//! Besides the built-in text predicates (`#eq?`, `#match?`, ...), queries can
+//! use `#inside?` / `#not-inside?` to require a capture to have (or not have) an
+//! ancestor of one of the given node kinds, since query patterns themselves
+//! only describe direct children:
+//!
+//! ```text
+//! ((call_expression
+//!    function: (field_expression field: (field_identifier) @method)) @match
+//!  (#eq? @method "unwrap")
+//!  (#inside? @match "impl_item"))
+//! ```
+
+use std::collections::HashSet;
+use std::path::Path;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Parser, Query, QueryCursor, QueryMatch, QueryPredicateArg};
+
+use crate::import_analyzer::AnalysisLanguage;
+use crate::search::{LineIndex, SearchMatch};
+
+/// Name of the capture reported as the match range
+const MATCH_CAPTURE: &str = "match";
+
+/// Predicates evaluated here rather than by tree-sitter
+const ANCESTOR_PREDICATES: [&str; 2] = ["inside?", "not-inside?"];
+
+/// A query compiled for one language
+struct LanguageQuery {
+    language: AnalysisLanguage,
+    query: Query,
+    /// Index of the `@match` capture, if the query has one
+    match_capture: Option<u32>,
+}
+
+/// A tree-sitter query compiled for every language that accepts it
+pub struct StructuralQuery {
+    queries: Vec<LanguageQuery>,
+}
+
+impl StructuralQuery {
+    /// Compile `source` for every supported language
+    ///
+    /// Fails if no language accepts the query, the query has no captures, or
+    /// it uses an unknown predicate.
+    pub fn new(source: &str) -> Result<Self, String> {
+        let mut queries = Vec::new();
+        let mut first_error = None;
+
+        for language in AnalysisLanguage::ALL {
+            match Query::new(&language.tree_sitter_language(), source) {
+                Ok(query) => {
+                    validate_query(&query)?;
+                    queries.push(LanguageQuery {
+                        language,
+                        match_capture: query.capture_index_for_name(MATCH_CAPTURE),
+                        query,
+                    });
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        if queries.is_empty() {
+            return Err(match first_error {
+                Some(e) => format!("Invalid structural query: {}", e),
+                None => "Invalid structural query".to_string(),
+            });
+        }
+
+        Ok(Self { queries })
+    }
+
+    fn query_for(&self, path: &Path) -> Option<&LanguageQuery> {
+        let language = AnalysisLanguage::from_path(&path.to_string_lossy())?;
+        self.queries.iter().find(|q| q.language == language)
+    }
+
+    /// Whether the query applies to a file's language
+    pub fn supports(&self, path: &Path) -> bool {
+        self.query_for(path).is_some()
+    }
+
+    /// Search file contents; the language is detected from `file_path`
+    pub fn search_text(&self, file_path: &str, text: &str) -> Vec<SearchMatch> {
+        let Some(compiled) = self.query_for(Path::new(file_path)) else {
+            return Vec::new();
+        };
+
+        let mut parser = Parser::new();
+        if parser
+            .set_language(&compiled.language.tree_sitter_language())
+            .is_err()
+        {
+            return Vec::new();
+        }
+        let Some(tree) = parser.parse(text, None) else {
+            return Vec::new();
+        };
+
+        let index = LineIndex::new(text);
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&compiled.query, tree.root_node(), text.as_bytes());
+        while let Some(m) = matches.next() {
+            if !ancestor_predicates_hold(&compiled.query, m) {
+                continue;
+            }
+            let Some(node) = match_node(m, compiled.match_capture) else {
+                continue;
+            };
+            // Several patterns can report the same node
+            if seen.insert((node.start_byte(), node.end_byte())) {
+                results.push(index.search_match(file_path, node.start_byte(), node.end_byte()));
+            }
+        }
+
+        results
+    }
+}
+
+/// Reject queries without captures or with predicates we cannot evaluate
+fn validate_query(query: &Query) -> Result<(), String> {
+    if query.capture_names().is_empty() {
+        return Err(
+            "Structural query needs a capture, e.g. `(call_expression) @match`".to_string(),
+        );
+    }
+
+    for pattern in 0..query.pattern_count() {
+        for predicate in query.general_predicates(pattern) {
+            if !ANCESTOR_PREDICATES.contains(&&*predicate.operator) {
+                return Err(format!("Unsupported predicate: #{}", predicate.operator));
+            }
+            let valid = matches!(
+                &predicate.args[..],
+                [QueryPredicateArg::Capture(_), kinds @ ..]
+                    if !kinds.is_empty()
+                        && kinds.iter().all(|k| matches!(k, QueryPredicateArg::String(_)))
+            );
+            if !valid {
+                return Err(format!(
+                    "#{} expects a capture followed by node kinds",
+                    predicate.operator
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Evaluate `#inside?` / `#not-inside?` for a match
+fn ancestor_predicates_hold(query: &Query, m: &QueryMatch) -> bool {
+    query
+        .general_predicates(m.pattern_index)
+        .iter()
+        .all(|predicate| {
+            let [QueryPredicateArg::Capture(capture), kinds @ ..] = &predicate.args[..] else {
+                return true;
+            };
+            let kinds: Vec<&str> = kinds
+                .iter()
+                .filter_map(|k| match k {
+                    QueryPredicateArg::String(kind) => Some(&**kind),
+                    QueryPredicateArg::Capture(_) => None,
+                })
+                .collect();
+
+            let inside = m
+                .captures
+                .iter()
+                .filter(|c| c.index == *capture)
+                .any(|c| has_ancestor(c.node, &kinds));
+
+            inside == (&*predicate.operator == "inside?")
+        })
+}
+
+fn has_ancestor(node: Node, kinds: &[&str]) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if kinds.contains(&parent.kind()) {
+            return true;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// The node reported for a match: its `@match` capture, else its first capture
+fn match_node<'tree>(m: &QueryMatch<'_, 'tree>, match_capture: Option<u32>) -> Option<Node<'tree>> {
+    match match_capture {
+        Some(index) => m.captures.iter().find(|c| c.index == index).map(|c| c.node),
+        None => m.captures.first().map(|c| c.node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST_SOURCE: &str = r#"fn helper() -> u32 {
+    "1".parse().unwrap()
+}
+
+impl Config {
+    fn load(&self) -> u32 {
+        let value = self.read().unwrap();
+        value.expect("set")
+    }
+}
+"#;
+
+    const UNWRAP_IN_IMPL: &str = r#"
+        ((call_expression
+           function: (field_expression field: (field_identifier) @method)) @match
+         (#eq? @method "unwrap")
+         (#inside? @match "impl_item"))
+    "#;
+
+    #[test]
+    fn test_inside_predicate_limits_to_impl_blocks() {
+        let query = StructuralQuery::new(UNWRAP_IN_IMPL).unwrap();
+        let matches = query.search_text("/src/lib.rs", RUST_SOURCE);
+
+        assert_eq!(matches.len(), 1);
+        let m = &matches[0];
+        assert_eq!(m.match_text, "self.read().unwrap()");
+        assert_eq!((m.line_number, m.column), (7, 20));
+        assert_eq!((m.end_line_number, m.end_column), (7, 40));
+        assert_eq!(m.line_content, "        let value = self.read().unwrap();");
+    }
+
+    #[test]
+    fn test_not_inside_predicate() {
+        let query = StructuralQuery::new(
+            r#"((call_expression
+                  function: (field_expression field: (field_identifier) @method)) @match
+                (#eq? @method "unwrap")
+                (#not-inside? @match "impl_item"))"#,
+        )
+        .unwrap();
+        let matches = query.search_text("/src/lib.rs", RUST_SOURCE);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_first_capture_used_without_match_capture() {
+        let query = StructuralQuery::new("(impl_item type: (type_identifier) @name)").unwrap();
+        let matches = query.search_text("/src/lib.rs", RUST_SOURCE);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].match_text, "Config");
+    }
+
+    #[test]
+    fn test_multiline_match_range() {
+        let query = StructuralQuery::new("(impl_item) @match").unwrap();
+        let matches = query.search_text("/src/lib.rs", RUST_SOURCE);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            (matches[0].line_number, matches[0].end_line_number),
+            (5, 10)
+        );
+        assert!(matches[0].match_text.starts_with("impl Config {"));
+    }
+
+    #[test]
+    fn test_query_only_applies_to_accepting_languages() {
+        let query = StructuralQuery::new(UNWRAP_IN_IMPL).unwrap();
+        assert!(query.supports(Path::new("/src/lib.rs")));
+        assert!(!query.supports(Path::new("/src/app.py")));
+        assert!(!query.supports(Path::new("/README.md")));
+
+        // `call_expression` exists in several grammars
+        let query = StructuralQuery::new("(call_expression) @match").unwrap();
+        assert!(query.supports(Path::new("/src/app.ts")));
+        assert!(query.supports(Path::new("/src/main.go")));
+    }
+
+    fn query_error(source: &str) -> String {
+        match StructuralQuery::new(source) {
+            Ok(_) => panic!("query should be rejected: {}", source),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!(query_error("(not_a_real_node) @match").contains("Invalid structural query"));
+        assert!(query_error("(call_expression)").contains("needs a capture"));
+        assert!(
+            query_error(r#"((call_expression) @match (#frobnicate? @match "x"))"#)
+                .contains("Unsupported predicate")
+        );
+        assert!(query_error("((call_expression) @match (#inside? @match))")
+            .contains("expects a capture"));
+    }
+}
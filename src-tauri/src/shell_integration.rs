@@ -0,0 +1,651 @@
+//! Shell integration (OSC 133 / OSC 633 / OSC 7)
+//!
+//! When Codelane spawns an interactive bash, zsh or fish shell it injects a
+//! small integration script (see `src-tauri/shell-integration/`) that reports:
+//!
+//! - `OSC 133;A` / `OSC 133;B`: prompt start / prompt end (command input starts)
+//! - `OSC 633;E;<cmd>`: the command line about to run (`\\` and `\xNN` escaped)
+//! - `OSC 133;C`: command output starts
+//! - `OSC 133;D;<exit>`: command finished with exit code
+//! - `OSC 7;file://<host><path>`: current working directory
+//!
+//! `ShellIntegration` parses these marks out of the raw PTY stream and turns
+//! them into per-command records with exit code, duration and the byte range
+//! of the command's output (absolute stream offsets, matching
+//! [`crate::terminal_automation::OutputLog`]).
+
+use portable_pty::CommandBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const BASH_SCRIPT: &str = include_str!("../shell-integration/codelane.bash");
+const ZSH_SCRIPT: &str = include_str!("../shell-integration/codelane.zsh");
+const FISH_SCRIPT: &str = include_str!("../shell-integration/codelane.fish");
+
+/// Startup files zsh reads from `$ZDOTDIR`, in load order
+const ZSH_STARTUP_FILES: [&str; 4] = [".zshenv", ".zprofile", ".zshrc", ".zlogin"];
+
+/// Maximum OSC payload we buffer; longer sequences are dropped
+const MAX_OSC_LEN: usize = 8192;
+
+/// Maximum number of command records kept per terminal
+const MAX_COMMAND_RECORDS: usize = 500;
+
+/// Env var marking integrated shells; passing `0` in `create_terminal`'s env
+/// disables injection for that terminal
+pub const ENV_VAR: &str = "CODELANE_SHELL_INTEGRATION";
+
+/// Shells with integration support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ShellKind {
+    /// Detect the shell from its executable path (`/bin/zsh`, `bash`, ...)
+    pub fn detect(shell: &str) -> Option<Self> {
+        let name = Path::new(shell).file_name()?.to_str()?;
+        match name.trim_start_matches('-') {
+            "bash" => Some(Self::Bash),
+            "zsh" => Some(Self::Zsh),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Configure an interactive login shell to load Codelane's integration script
+///
+/// Replaces the default `-l -i` arguments `create_terminal` would pass.
+/// Scripts are written to `<data dir>/shell-integration/` on demand.
+pub fn configure_shell(cmd: &mut CommandBuilder, kind: ShellKind) -> Result<(), String> {
+    let dir = codelane_core::paths::data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?
+        .join("shell-integration");
+    configure_shell_in(cmd, kind, &dir)
+}
+
+fn configure_shell_in(cmd: &mut CommandBuilder, kind: ShellKind, dir: &Path) -> Result<(), String> {
+    match kind {
+        ShellKind::Bash => {
+            let script = write_if_changed(&dir.join("codelane.bash"), BASH_SCRIPT)?;
+            // --init-file is ignored by login shells, so the script sources the
+            // profile files itself
+            cmd.arg("--init-file");
+            cmd.arg(script);
+            cmd.arg("-i");
+        }
+        ShellKind::Zsh => {
+            let zdotdir = dir.join("zsh");
+            let script = write_if_changed(&dir.join("codelane.zsh"), ZSH_SCRIPT)?;
+            for name in ZSH_STARTUP_FILES {
+                write_if_changed(&zdotdir.join(name), &zsh_startup_file(name, &script))?;
+            }
+
+            let user_zdotdir = std::env::var("ZDOTDIR")
+                .ok()
+                .or_else(|| std::env::var("HOME").ok())
+                .unwrap_or_default();
+            cmd.env("CODELANE_USER_ZDOTDIR", user_zdotdir);
+            cmd.env("CODELANE_ZDOTDIR", &zdotdir);
+            cmd.env("ZDOTDIR", &zdotdir);
+            cmd.arg("-l");
+            cmd.arg("-i");
+        }
+        ShellKind::Fish => {
+            let script = write_if_changed(&dir.join("codelane.fish"), FISH_SCRIPT)?;
+            cmd.arg("-l");
+            cmd.arg("-i");
+            cmd.arg("--init-command");
+            cmd.arg(format!("source '{}'", script.display()));
+        }
+    }
+
+    cmd.env(ENV_VAR, "1");
+    Ok(())
+}
+
+/// Wrapper for one of zsh's startup files that sources the user's original
+/// from their real `$ZDOTDIR`, keeping our directory in place until the last
+/// startup file has run.
+fn zsh_startup_file(name: &str, script: &Path) -> String {
+    let mut body = format!(
+        r#"# Generated by Codelane - sources your own {name}
+__codelane_user_zdotdir="${{CODELANE_USER_ZDOTDIR:-$HOME}}"
+if [[ -f "$__codelane_user_zdotdir/{name}" ]]; then
+    ZDOTDIR="$__codelane_user_zdotdir"
+    . "$__codelane_user_zdotdir/{name}"
+    CODELANE_USER_ZDOTDIR="$ZDOTDIR"
+fi
+ZDOTDIR="$CODELANE_ZDOTDIR"
+"#
+    );
+
+    match name {
+        ".zshrc" => {
+            body.push_str(&format!(". '{}'\n", script.display()));
+            body.push_str("[[ -o login ]] || ZDOTDIR=\"$CODELANE_USER_ZDOTDIR\"\n");
+        }
+        ".zlogin" => body.push_str("ZDOTDIR=\"$CODELANE_USER_ZDOTDIR\"\n"),
+        _ => {}
+    }
+    body
+}
+
+/// Write a file only when its contents differ, returning its path
+fn write_if_changed(path: &Path, contents: &str) -> Result<PathBuf, String> {
+    if std::fs::read_to_string(path).ok().as_deref() != Some(contents) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(path.to_path_buf())
+}
+
+/// A shell integration mark found in the output stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellMark {
+    PromptStart,
+    CommandStart,
+    CommandLine(String),
+    CommandExecuted,
+    CommandFinished(Option<i32>),
+    Cwd(String),
+}
+
+/// A mark plus the absolute stream offsets of its escape sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkEvent {
+    pub mark: ShellMark,
+    /// Offset of the ESC that started the sequence
+    pub start: u64,
+    /// Offset just past the sequence terminator
+    pub end: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseState {
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Incremental OSC parser that survives sequences split across reads
+pub struct OscParser {
+    state: ParseState,
+    offset: u64,
+    seq_start: u64,
+    payload: Vec<u8>,
+}
+
+impl OscParser {
+    pub fn new() -> Self {
+        Self {
+            state: ParseState::Ground,
+            offset: 0,
+            seq_start: 0,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Feed a chunk of output, returning the marks it completed
+    pub fn feed(&mut self, data: &[u8]) -> Vec<MarkEvent> {
+        let mut events = Vec::new();
+
+        for &byte in data {
+            let pos = self.offset;
+            self.offset += 1;
+
+            match self.state {
+                ParseState::Ground => {
+                    if byte == 0x1b {
+                        self.state = ParseState::Escape;
+                        self.seq_start = pos;
+                    }
+                }
+                ParseState::Escape => {
+                    if byte == b']' {
+                        self.state = ParseState::Osc;
+                        self.payload.clear();
+                    } else if byte == 0x1b {
+                        self.seq_start = pos;
+                    } else {
+                        self.state = ParseState::Ground;
+                    }
+                }
+                ParseState::Osc => match byte {
+                    0x07 => self.finish(&mut events),
+                    0x1b => self.state = ParseState::OscEscape,
+                    _ => {
+                        if self.payload.len() < MAX_OSC_LEN {
+                            self.payload.push(byte);
+                        } else {
+                            self.state = ParseState::Ground;
+                        }
+                    }
+                },
+                ParseState::OscEscape => {
+                    if byte == b'\\' {
+                        self.finish(&mut events);
+                    } else {
+                        // Unterminated OSC followed by a new escape sequence
+                        self.seq_start = pos - 1;
+                        self.state = if byte == b']' {
+                            self.payload.clear();
+                            ParseState::Osc
+                        } else {
+                            ParseState::Ground
+                        };
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    fn finish(&mut self, events: &mut Vec<MarkEvent>) {
+        self.state = ParseState::Ground;
+        let payload = String::from_utf8_lossy(&self.payload).into_owned();
+        if let Some(mark) = parse_osc_payload(&payload) {
+            events.push(MarkEvent {
+                mark,
+                start: self.seq_start,
+                end: self.offset,
+            });
+        }
+    }
+}
+
+impl Default for OscParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interpret the payload of an OSC sequence (without `ESC ]` and terminator)
+fn parse_osc_payload(payload: &str) -> Option<ShellMark> {
+    let (code, rest) = payload.split_once(';').unwrap_or((payload, ""));
+    match code {
+        "133" => {
+            let mut parts = rest.split(';');
+            match parts.next()? {
+                "A" => Some(ShellMark::PromptStart),
+                "B" => Some(ShellMark::CommandStart),
+                "C" => Some(ShellMark::CommandExecuted),
+                "D" => Some(ShellMark::CommandFinished(
+                    parts.next().and_then(|c| c.trim().parse().ok()),
+                )),
+                _ => None,
+            }
+        }
+        "633" => {
+            let (kind, value) = rest.split_once(';').unwrap_or((rest, ""));
+            match kind {
+                "E" => Some(ShellMark::CommandLine(unescape_command(value))),
+                "P" => value
+                    .strip_prefix("Cwd=")
+                    .map(|cwd| ShellMark::Cwd(cwd.to_string())),
+                _ => None,
+            }
+        }
+        "7" => parse_file_url(rest).map(ShellMark::Cwd),
+        _ => None,
+    }
+}
+
+/// Undo the `\\` / `\xNN` escaping applied by the integration scripts
+fn unescape_command(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if bytes.get(i + 1) == Some(&b'\\') {
+                out.push(b'\\');
+                i += 2;
+                continue;
+            }
+            if bytes.get(i + 1) == Some(&b'x') {
+                if let Some(byte) = value
+                    .get(i + 2..i + 4)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    out.push(byte);
+                    i += 4;
+                    continue;
+                }
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Extract the (percent-decoded) path from an OSC 7 `file://host/path` URL
+fn parse_file_url(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    Some(percent_decode(path))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A command run in a terminal, as reported by shell integration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandRecord {
+    /// Sequence number within the terminal (starts at 1)
+    pub id: u64,
+    /// The command line as typed
+    pub command: String,
+    /// Working directory the command ran in (if known)
+    pub cwd: Option<String>,
+    /// Exit code (None while running or if the shell did not report one)
+    pub exit_code: Option<i32>,
+    /// Unix timestamp (milliseconds) when the command started
+    pub started_at: i64,
+    /// Unix timestamp (milliseconds) when the command finished
+    pub finished_at: Option<i64>,
+    /// How long the command ran, in milliseconds
+    pub duration_ms: Option<u64>,
+    /// Absolute output offset where the command's output starts
+    pub output_start: u64,
+    /// Absolute output offset where the command's output ends
+    pub output_end: Option<u64>,
+}
+
+/// Something the frontend should hear about after feeding output
+#[derive(Debug, Clone)]
+pub enum ShellUpdate {
+    CommandStarted(CommandRecord),
+    CommandFinished(CommandRecord),
+    CwdChanged(String),
+}
+
+/// Per-terminal shell integration state
+pub struct ShellIntegration {
+    parser: OscParser,
+    /// Whether any integration mark has been seen
+    active: bool,
+    cwd: Option<String>,
+    /// Command line announced by OSC 633;E, waiting for OSC 133;C
+    pending_command: Option<String>,
+    running: Option<(CommandRecord, Instant)>,
+    commands: VecDeque<CommandRecord>,
+    next_id: u64,
+}
+
+impl ShellIntegration {
+    pub fn new() -> Self {
+        Self {
+            parser: OscParser::new(),
+            active: false,
+            cwd: None,
+            pending_command: None,
+            running: None,
+            commands: VecDeque::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Whether the shell has reported at least one integration mark
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Last working directory reported by the shell
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+
+    /// Finished commands (oldest first) followed by the running command, if any
+    pub fn commands(&self) -> Vec<CommandRecord> {
+        let mut all: Vec<CommandRecord> = self.commands.iter().cloned().collect();
+        if let Some((running, _)) = &self.running {
+            all.push(running.clone());
+        }
+        all
+    }
+
+    /// Most recent finished command whose output started at or after `offset`
+    pub fn finished_since(&self, offset: u64) -> Option<&CommandRecord> {
+        self.commands
+            .iter()
+            .rev()
+            .find(|record| record.output_start >= offset)
+    }
+
+    /// Feed raw PTY output
+    pub fn feed(&mut self, data: &[u8]) -> Vec<ShellUpdate> {
+        let mut updates = Vec::new();
+
+        for event in self.parser.feed(data) {
+            self.active = true;
+            match event.mark {
+                ShellMark::PromptStart | ShellMark::CommandStart => {}
+                ShellMark::CommandLine(command) => self.pending_command = Some(command),
+                ShellMark::CommandExecuted => {
+                    let record = CommandRecord {
+                        id: self.next_id,
+                        command: self.pending_command.take().unwrap_or_default(),
+                        cwd: self.cwd.clone(),
+                        exit_code: None,
+                        started_at: chrono::Utc::now().timestamp_millis(),
+                        finished_at: None,
+                        duration_ms: None,
+                        output_start: event.end,
+                        output_end: None,
+                    };
+                    self.next_id += 1;
+                    updates.push(ShellUpdate::CommandStarted(record.clone()));
+                    self.running = Some((record, Instant::now()));
+                }
+                ShellMark::CommandFinished(exit_code) => {
+                    if let Some((mut record, started)) = self.running.take() {
+                        record.exit_code = exit_code;
+                        record.finished_at = Some(chrono::Utc::now().timestamp_millis());
+                        record.duration_ms = Some(started.elapsed().as_millis() as u64);
+                        record.output_end = Some(event.start);
+
+                        if self.commands.len() >= MAX_COMMAND_RECORDS {
+                            self.commands.pop_front();
+                        }
+                        self.commands.push_back(record.clone());
+                        updates.push(ShellUpdate::CommandFinished(record));
+                    }
+                }
+                ShellMark::Cwd(cwd) => {
+                    if self.cwd.as_deref() != Some(cwd.as_str()) {
+                        self.cwd = Some(cwd.clone());
+                        updates.push(ShellUpdate::CwdChanged(cwd));
+                    }
+                }
+            }
+        }
+
+        updates
+    }
+}
+
+impl Default for ShellIntegration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn marks(events: Vec<MarkEvent>) -> Vec<ShellMark> {
+        events.into_iter().map(|e| e.mark).collect()
+    }
+
+    #[test]
+    fn test_shell_kind_detect() {
+        assert_eq!(ShellKind::detect("/bin/bash"), Some(ShellKind::Bash));
+        assert_eq!(
+            ShellKind::detect("/usr/local/bin/zsh"),
+            Some(ShellKind::Zsh)
+        );
+        assert_eq!(ShellKind::detect("fish"), Some(ShellKind::Fish));
+        assert_eq!(ShellKind::detect("-zsh"), Some(ShellKind::Zsh));
+        assert_eq!(ShellKind::detect("/usr/bin/nu"), None);
+        assert_eq!(ShellKind::detect("/opt/zsh-wrapper/claude"), None);
+    }
+
+    #[test]
+    fn test_parser_recognizes_marks() {
+        let mut parser = OscParser::new();
+        let data = b"\x1b]133;A\x07$ \x1b]133;B\x07\x1b]633;E;ls -la\x07\x1b]133;C\x07out\x1b]133;D;2\x1b\\";
+
+        assert_eq!(
+            marks(parser.feed(data)),
+            vec![
+                ShellMark::PromptStart,
+                ShellMark::CommandStart,
+                ShellMark::CommandLine("ls -la".to_string()),
+                ShellMark::CommandExecuted,
+                ShellMark::CommandFinished(Some(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_handles_split_sequences_and_offsets() {
+        let mut parser = OscParser::new();
+        assert!(parser.feed(b"ab\x1b]13").is_empty());
+        let events = parser.feed(b"3;C\x07");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].mark, ShellMark::CommandExecuted);
+        assert_eq!(events[0].start, 2);
+        assert_eq!(events[0].end, 10);
+    }
+
+    #[test]
+    fn test_parser_ignores_other_sequences() {
+        let mut parser = OscParser::new();
+        let data = b"\x1b[31mred\x1b[0m\x1b]0;window title\x07\x1b]8;;http://x\x1b\\";
+        assert!(parser.feed(data).is_empty());
+    }
+
+    #[test]
+    fn test_parse_osc7_cwd() {
+        assert_eq!(
+            parse_osc_payload("7;file://my-host/Users/me/My%20Project"),
+            Some(ShellMark::Cwd("/Users/me/My Project".to_string()))
+        );
+        assert_eq!(parse_osc_payload("7;not-a-url"), None);
+    }
+
+    #[test]
+    fn test_unescape_command() {
+        assert_eq!(unescape_command(r"echo a\x3bb"), "echo a;b");
+        assert_eq!(unescape_command(r"printf 'x\\n'"), r"printf 'x\n'");
+        assert_eq!(unescape_command(r"line1\x0aline2"), "line1\nline2");
+        assert_eq!(unescape_command(r"bad\xZZ"), r"bad\xZZ");
+    }
+
+    #[test]
+    fn test_integration_builds_command_records() {
+        let mut shell = ShellIntegration::new();
+        assert!(!shell.is_active());
+
+        let updates = shell.feed(b"\x1b]7;file://host/tmp\x07\x1b]133;A\x07$ ");
+        assert!(shell.is_active());
+        assert!(matches!(&updates[..], [ShellUpdate::CwdChanged(cwd)] if cwd == "/tmp"));
+
+        shell.feed(b"\x1b]633;E;make test\x07\x1b]133;C\x07");
+        let running = shell.commands();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].command, "make test");
+        assert!(running[0].exit_code.is_none());
+
+        let output_start = running[0].output_start;
+        let updates = shell.feed(b"ok\r\n\x1b]133;D;0\x07");
+        let finished = match &updates[..] {
+            [ShellUpdate::CommandFinished(record)] => record.clone(),
+            other => panic!("unexpected updates: {:?}", other),
+        };
+
+        assert_eq!(finished.id, 1);
+        assert_eq!(finished.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(finished.exit_code, Some(0));
+        assert_eq!(finished.output_end, Some(output_start + 4));
+        assert!(finished.duration_ms.is_some());
+        assert_eq!(shell.finished_since(output_start).map(|r| r.id), Some(1));
+        assert!(shell.finished_since(output_start + 1).is_none());
+    }
+
+    #[test]
+    fn test_integration_ignores_finish_without_start() {
+        let mut shell = ShellIntegration::new();
+        assert!(shell.feed(b"\x1b]133;D;1\x07").is_empty());
+        assert!(shell.commands().is_empty());
+    }
+
+    #[test]
+    fn test_configure_shell_writes_scripts() {
+        let dir = TempDir::new().unwrap();
+
+        let mut cmd = CommandBuilder::new("/bin/zsh");
+        configure_shell_in(&mut cmd, ShellKind::Zsh, dir.path()).unwrap();
+        for name in ZSH_STARTUP_FILES {
+            assert!(dir.path().join("zsh").join(name).exists());
+        }
+        let zshrc = std::fs::read_to_string(dir.path().join("zsh/.zshrc")).unwrap();
+        assert!(zshrc.contains("codelane.zsh"));
+        assert_eq!(
+            cmd.get_env("ZDOTDIR").map(|v| v.to_os_string()),
+            Some(dir.path().join("zsh").into_os_string())
+        );
+
+        let mut cmd = CommandBuilder::new("/bin/bash");
+        configure_shell_in(&mut cmd, ShellKind::Bash, dir.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("codelane.bash")).unwrap(),
+            BASH_SCRIPT
+        );
+        let argv: Vec<String> = cmd
+            .get_argv()
+            .iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(argv[1], "--init-file");
+    }
+}
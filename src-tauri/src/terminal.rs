@@ -15,16 +15,54 @@
 //!   - Payload: `{ id: String, data: String }`
 //! - `terminal-exit`: Emitted when a terminal process exits
 //!   - Payload: `{ id: String, code: Option<i32> }`
+//!
+//! # Recording
+//!
+//! Any terminal can be recorded to an asciicast v2 file with
+//! `terminal_start_recording` / `terminal_stop_recording`. See
+//! [`crate::terminal_recording`] for the file format and replay.
+//!
+//! # Automation
+//!
+//! `terminal_send_keys`, `terminal_wait_for` and `terminal_run_command` let
+//! callers script terminals (e.g. start an agent, wait for its prompt, paste a
+//! task). Output positions are absolute byte offsets; see
+//! [`crate::terminal_automation`].
+//!
+//! # Shell Integration
+//!
+//! Interactive bash/zsh/fish shells get an integration script injected (see
+//! [`crate::shell_integration`]) so each terminal tracks its working directory
+//! and a record per command. Additional events:
+//!
+//! - `terminal-command-started` / `terminal-command-finished`
+//!   - Payload: `{ id: String, command: CommandRecord }`
+//! - `terminal-cwd-changed`: Payload `{ id: String, cwd: String }`
+//!
+//! # Shutdown
+//!
+//! `close_terminal` only drops the PTY. `terminal_shutdown` and
+//! `terminal_shutdown_lane` stop the whole process tree under a terminal
+//! (SIGINT, SIGTERM, then SIGKILL) before closing it.
 
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::process::{self, ProcessTreeNode, ShutdownReport};
+use crate::process_registry::{ProcessKind, ProcessRegistry};
+use crate::shell_integration::{self, CommandRecord, ShellIntegration, ShellKind, ShellUpdate};
+use crate::terminal_automation::{self, CommandCapture, OutputLog, OutputMatch};
+use crate::terminal_recording::{self, AsciicastRecorder, RecordingSummary};
+
+/// Active recording shared between the terminal instance and its reader thread
+type SharedRecorder = Arc<Mutex<Option<AsciicastRecorder>>>;
+
 /// State for managing active terminal instances
 ///
 /// This struct is managed by Tauri and provides thread-safe access
@@ -68,6 +106,48 @@ struct TerminalInstance {
     /// Output buffer for polling-based reads (optional fallback)
     #[allow(dead_code)]
     output_buffer: Vec<u8>,
+    /// Active asciicast recording (if any)
+    recorder: SharedRecorder,
+    /// Recent output, shared with the reader thread for automation waits
+    output: Arc<OutputLog>,
+    /// Command boundaries and cwd reported by shell integration
+    shell: Arc<Mutex<ShellIntegration>>,
+}
+
+impl TerminalInstance {
+    /// Write input to the PTY, taking the writer on first use
+    fn write_input(&mut self, data: &str) -> Result<(), String> {
+        // Take writer on first use and keep it for subsequent writes
+        if self.writer.is_none() {
+            let writer = self
+                .master
+                .take_writer()
+                .map_err(|e| format!("Failed to take PTY writer: {}", e))?;
+            self.writer = Some(writer);
+        }
+
+        // Write using the stored writer
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or("Writer not available")?;
+
+        writer
+            .write_all(data.as_bytes())
+            .map_err(|e| format!("Failed to write to PTY: {}", e))?;
+
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush PTY: {}", e))?;
+
+        if let Ok(mut guard) = self.recorder.lock() {
+            if let Some(rec) = guard.as_mut() {
+                rec.record_input(data);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Payload for terminal output events emitted to the frontend
@@ -88,6 +168,24 @@ pub struct TerminalExitPayload {
     pub code: Option<i32>,
 }
 
+/// Payload for command started/finished events emitted to the frontend
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TerminalCommandPayload {
+    /// Terminal ID
+    pub id: String,
+    /// The command record
+    pub command: CommandRecord,
+}
+
+/// Payload for working directory change events emitted to the frontend
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TerminalCwdPayload {
+    /// Terminal ID
+    pub id: String,
+    /// New working directory
+    pub cwd: String,
+}
+
 /// Terminal information returned by get_terminal_info
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TerminalInfo {
@@ -171,6 +269,10 @@ pub async fn create_terminal(
     // Determine the user's login shell for wrapping commands
     let login_shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
     let is_shell_command = shell_cmd.contains("zsh") || shell_cmd.contains("bash") || shell_cmd.contains("fish");
+    let integration_disabled = env
+        .as_ref()
+        .and_then(|e| e.get(shell_integration::ENV_VAR))
+        .is_some_and(|v| v == "0");
 
     // Build the command
     // For non-shell commands, wrap in a login shell to ensure .zshrc/.bashrc is sourced
@@ -205,9 +307,23 @@ pub async fn create_terminal(
                 wrapper.arg(arg);
             }
         } else {
-            // For zsh/bash without explicit args, use login shell mode
-            wrapper.arg("-l");  // Login shell
-            wrapper.arg("-i");  // Interactive
+            // For zsh/bash/fish without explicit args, start an interactive login
+            // shell with shell integration, falling back to plain `-l -i`
+            let integrated = match ShellKind::detect(&shell_cmd).filter(|_| !integration_disabled) {
+                Some(kind) => match shell_integration::configure_shell(&mut wrapper, kind) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        tracing::warn!("Shell integration unavailable for {}: {}", shell_cmd, e);
+                        false
+                    }
+                },
+                None => false,
+            };
+
+            if !integrated {
+                wrapper.arg("-l");  // Login shell
+                wrapper.arg("-i");  // Interactive
+            }
         }
         wrapper
     };
@@ -270,12 +386,18 @@ pub async fn create_terminal(
 
     let id_clone = terminal_id.clone();
     let app_clone = app.clone();
+    let recorder: SharedRecorder = Arc::new(Mutex::new(None));
+    let recorder_clone = recorder.clone();
+    let output = Arc::new(OutputLog::new());
+    let output_clone = output.clone();
+    let shell = Arc::new(Mutex::new(ShellIntegration::new()));
+    let shell_clone = shell.clone();
 
     // Spawn a background thread to read PTY output and emit events
     thread::Builder::new()
         .name(format!("pty-read-{}", &terminal_id[..8]))
         .spawn(move || {
-            read_pty_output(reader, id_clone, app_clone);
+            read_pty_output(reader, id_clone, app_clone, recorder_clone, output_clone, shell_clone);
         })
         .map_err(|e| format!("Failed to spawn PTY reader thread: {}", e))?;
 
@@ -289,6 +411,9 @@ pub async fn create_terminal(
         cols: 80,
         rows: 24,
         output_buffer: Vec::new(),
+        recorder,
+        output,
+        shell,
     };
 
     // Store the terminal instance
@@ -307,6 +432,9 @@ pub async fn create_terminal(
 
     // Start hook monitoring if lane_id is available
     if let Some(ref lid) = lane_id_for_hooks {
+        let registry = app.state::<ProcessRegistry>();
+        registry.register(lid, pid, ProcessKind::Terminal, &terminal_id);
+
         let hook_monitor = app.state::<crate::hook_monitor::HookMonitorState>();
         if let Err(e) = hook_monitor.start_monitoring(lid.clone(), app.clone()) {
             tracing::warn!("Failed to start hook monitoring for lane {}: {}", lid, e);
@@ -317,7 +445,14 @@ pub async fn create_terminal(
 }
 
 /// Read PTY output in a background thread and emit events to the frontend
-fn read_pty_output(mut reader: Box<dyn Read + Send>, terminal_id: String, app: AppHandle) {
+fn read_pty_output(
+    mut reader: Box<dyn Read + Send>,
+    terminal_id: String,
+    app: AppHandle,
+    recorder: SharedRecorder,
+    output: Arc<OutputLog>,
+    shell: Arc<Mutex<ShellIntegration>>,
+) {
     let mut buf = [0u8; 4096];
 
     loop {
@@ -338,6 +473,20 @@ fn read_pty_output(mut reader: Box<dyn Read + Send>, terminal_id: String, app: A
                 // Send raw bytes to preserve escape sequences
                 let data = buf[..n].to_vec();
 
+                if let Ok(mut guard) = recorder.lock() {
+                    if let Some(rec) = guard.as_mut() {
+                        rec.record_output(&data);
+                    }
+                }
+
+                // Track command boundaries before publishing the output, so
+                // automation waiters woken by the append see the finished command
+                let updates = shell
+                    .lock()
+                    .map(|mut s| s.feed(&data))
+                    .unwrap_or_default();
+                output.append(&data);
+
                 // Emit the terminal output event
                 if let Err(e) = app.emit(
                     "terminal-output",
@@ -348,6 +497,10 @@ fn read_pty_output(mut reader: Box<dyn Read + Send>, terminal_id: String, app: A
                 ) {
                     tracing::warn!("Failed to emit terminal-output event: {}", e);
                 }
+
+                for update in updates {
+                    emit_shell_update(&app, &terminal_id, update);
+                }
             }
             Err(e) => {
                 // Check if it's a would-block error (non-blocking I/O)
@@ -377,6 +530,61 @@ fn read_pty_output(mut reader: Box<dyn Read + Send>, terminal_id: String, app: A
             }
         }
     }
+
+    // The shell is gone: wake automation waiters and close out any recording
+    output.close();
+    finish_recording(&recorder, &terminal_id);
+}
+
+/// Emit a shell integration update to the frontend
+fn emit_shell_update(app: &AppHandle, terminal_id: &str, update: ShellUpdate) {
+    let result = match update {
+        ShellUpdate::CommandStarted(command) => app.emit(
+            "terminal-command-started",
+            TerminalCommandPayload {
+                id: terminal_id.to_string(),
+                command,
+            },
+        ),
+        ShellUpdate::CommandFinished(command) => app.emit(
+            "terminal-command-finished",
+            TerminalCommandPayload {
+                id: terminal_id.to_string(),
+                command,
+            },
+        ),
+        ShellUpdate::CwdChanged(cwd) => app.emit(
+            "terminal-cwd-changed",
+            TerminalCwdPayload {
+                id: terminal_id.to_string(),
+                cwd,
+            },
+        ),
+    };
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to emit shell integration event: {}", e);
+    }
+}
+
+/// Finish the active recording (if any), logging instead of failing
+fn finish_recording(recorder: &SharedRecorder, terminal_id: &str) -> Option<RecordingSummary> {
+    let active = recorder.lock().ok()?.take()?;
+    match active.finish() {
+        Ok(summary) => {
+            tracing::info!(
+                "Finished recording terminal {} to {} ({} events)",
+                terminal_id,
+                summary.path,
+                summary.event_count
+            );
+            Some(summary)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to finish recording for terminal {}: {}", terminal_id, e);
+            None
+        }
+    }
 }
 
 /// Write data to a terminal
@@ -404,28 +612,7 @@ pub async fn write_terminal(
         .get_mut(&id)
         .ok_or_else(|| format!("Terminal not found: {}", id))?;
 
-    // Take writer on first use and keep it for subsequent writes
-    if instance.writer.is_none() {
-        let writer = instance
-            .master
-            .take_writer()
-            .map_err(|e| format!("Failed to take PTY writer: {}", e))?;
-        instance.writer = Some(writer);
-    }
-
-    // Write using the stored writer
-    let writer = instance
-        .writer
-        .as_mut()
-        .ok_or("Writer not available")?;
-
-    writer
-        .write_all(data.as_bytes())
-        .map_err(|e| format!("Failed to write to PTY: {}", e))?;
-
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush PTY: {}", e))?;
+    instance.write_input(&data)?;
 
     tracing::trace!("Wrote {} bytes to terminal {}", data.len(), id);
 
@@ -512,6 +699,12 @@ pub async fn resize_terminal(
     instance.cols = cols;
     instance.rows = rows;
 
+    if let Ok(mut guard) = instance.recorder.lock() {
+        if let Some(rec) = guard.as_mut() {
+            rec.record_resize(cols, rows);
+        }
+    }
+
     tracing::debug!("Resized terminal {} to {}x{}", id, cols, rows);
 
     Ok(())
@@ -533,13 +726,18 @@ pub async fn close_terminal(
     state: State<'_, TerminalState>,
     id: String,
 ) -> Result<(), String> {
+    remove_terminal(&app, &state, &id)
+}
+
+/// Remove a terminal instance, stopping hook monitoring and any recording
+fn remove_terminal(app: &AppHandle, state: &TerminalState, id: &str) -> Result<(), String> {
     // Get lane_id before removing terminal
     let lane_id = {
         let terminals = state
             .terminals
             .lock()
             .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
-        terminals.get(&id).and_then(|t| t.lane_id.clone())
+        terminals.get(id).and_then(|t| t.lane_id.clone())
     };
 
     // Stop hook monitoring if lane_id is available
@@ -555,15 +753,156 @@ pub async fn close_terminal(
 
     // Remove and drop the terminal instance
     // Dropping the master PTY will close the terminal
-    terminals
-        .remove(&id)
+    let instance = terminals
+        .remove(id)
         .ok_or_else(|| format!("Terminal not found: {}", id))?;
 
+    finish_recording(&instance.recorder, id);
+
+    if lane_id.is_some() {
+        let registry = app.state::<ProcessRegistry>();
+        registry.unregister(instance.pid);
+    }
+
     tracing::info!("Closed terminal {}", id);
 
     Ok(())
 }
 
+/// Get the process tree running under a terminal
+///
+/// # Arguments
+/// * `id` - Terminal ID
+///
+/// # Returns
+/// The terminal's shell process with all descendants (name, cmdline, CPU, memory)
+#[tauri::command]
+pub async fn terminal_get_process_tree(
+    state: State<'_, TerminalState>,
+    id: String,
+) -> Result<ProcessTreeNode, String> {
+    let pid = terminal_pid(&state, &id)?;
+    process::process_tree(pid)
+}
+
+/// Look up the shell PID of a terminal
+fn terminal_pid(state: &TerminalState, id: &str) -> Result<u32, String> {
+    let terminals = state
+        .terminals
+        .lock()
+        .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+    let instance = terminals
+        .get(id)
+        .ok_or_else(|| format!("Terminal not found: {}", id))?;
+
+    if instance.pid == 0 {
+        return Err(format!("Terminal {} has no process ID", id));
+    }
+    Ok(instance.pid)
+}
+
+/// Gracefully stop a terminal's process tree and close the terminal
+///
+/// Sends SIGINT to the shell and everything it started, then SIGTERM, then
+/// SIGKILL, waiting between steps. The terminal is closed afterwards.
+///
+/// # Arguments
+/// * `id` - Terminal ID
+/// * `interrupt_timeout_ms` - Wait after SIGINT (defaults to 2 seconds)
+/// * `terminate_timeout_ms` - Wait after SIGTERM (defaults to 3 seconds)
+///
+/// # Returns
+/// Which processes exited at each step
+#[tauri::command]
+pub async fn terminal_shutdown(
+    app: AppHandle,
+    state: State<'_, TerminalState>,
+    id: String,
+    interrupt_timeout_ms: Option<u64>,
+    terminate_timeout_ms: Option<u64>,
+) -> Result<ShutdownReport, String> {
+    let pid = terminal_pid(&state, &id)?;
+    let report = process::kill_process_tree(pid, interrupt_timeout_ms, terminate_timeout_ms).await?;
+
+    remove_terminal(&app, &state, &id)?;
+
+    tracing::info!(
+        "Shut down terminal {} ({} processes, {} killed)",
+        id,
+        report.pids.len(),
+        report.killed.len()
+    );
+
+    Ok(report)
+}
+
+/// Gracefully stop every terminal belonging to a lane
+///
+/// Terminals are shut down concurrently; see `terminal_shutdown`.
+///
+/// # Arguments
+/// * `lane_id` - The lane whose terminals should be stopped
+/// * `interrupt_timeout_ms` - Wait after SIGINT (defaults to 2 seconds)
+/// * `terminate_timeout_ms` - Wait after SIGTERM (defaults to 3 seconds)
+///
+/// # Returns
+/// One report per terminal that was stopped
+#[tauri::command]
+pub async fn terminal_shutdown_lane(
+    app: AppHandle,
+    state: State<'_, TerminalState>,
+    lane_id: String,
+    interrupt_timeout_ms: Option<u64>,
+    terminate_timeout_ms: Option<u64>,
+) -> Result<Vec<ShutdownReport>, String> {
+    let targets: Vec<(String, u32)> = {
+        let terminals = state
+            .terminals
+            .lock()
+            .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+        terminals
+            .iter()
+            .filter(|(_, t)| t.lane_id.as_deref() == Some(lane_id.as_str()))
+            .map(|(id, t)| (id.clone(), t.pid))
+            .collect()
+    };
+
+    let interrupt = std::time::Duration::from_millis(
+        interrupt_timeout_ms.unwrap_or(process::DEFAULT_INTERRUPT_TIMEOUT_MS),
+    );
+    let terminate = std::time::Duration::from_millis(
+        terminate_timeout_ms.unwrap_or(process::DEFAULT_TERMINATE_TIMEOUT_MS),
+    );
+
+    // Start all shutdowns before awaiting any, so they run concurrently
+    let tasks: Vec<_> = targets
+        .iter()
+        .map(|(_, pid)| {
+            let pid = *pid;
+            tokio::task::spawn_blocking(move || {
+                process::shutdown_process_tree(pid, interrupt, terminate)
+            })
+        })
+        .collect();
+
+    let mut reports = Vec::new();
+    for ((id, _), task) in targets.iter().zip(tasks) {
+        match task.await {
+            Ok(report) => reports.push(report),
+            Err(e) => tracing::warn!("Failed to shut down terminal {}: {}", id, e),
+        }
+        if let Err(e) = remove_terminal(&app, &state, id) {
+            tracing::warn!("Failed to close terminal {}: {}", id, e);
+        }
+    }
+
+    tracing::info!("Shut down {} terminals for lane {}", targets.len(), lane_id);
+
+    Ok(reports)
+}
+
 /// Get information about a terminal
 ///
 /// # Arguments
@@ -635,6 +974,403 @@ pub async fn get_terminal_pid_by_lane(
     Ok(None)
 }
 
+/// Start recording a terminal to an asciicast v2 file
+///
+/// Output and resize events are always recorded; input (keystrokes sent via
+/// `write_terminal`) is only recorded when `record_input` is true, since it
+/// may contain secrets typed at password prompts.
+///
+/// # Arguments
+/// * `id` - Terminal ID
+/// * `path` - Optional output path, which must not exist yet (defaults to
+///   `<data dir>/recordings/<timestamp>-<id>.cast`)
+/// * `record_input` - Whether to record input events (defaults to false)
+/// * `title` - Optional title stored in the recording header
+///
+/// # Returns
+/// The path of the recording file, or an error message
+#[tauri::command]
+pub async fn terminal_start_recording(
+    state: State<'_, TerminalState>,
+    id: String,
+    path: Option<String>,
+    record_input: Option<bool>,
+    title: Option<String>,
+) -> Result<String, String> {
+    let terminals = state
+        .terminals
+        .lock()
+        .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+    let instance = terminals
+        .get(&id)
+        .ok_or_else(|| format!("Terminal not found: {}", id))?;
+
+    let mut recorder = instance
+        .recorder
+        .lock()
+        .map_err(|e| format!("Failed to lock recorder: {}", e))?;
+
+    if let Some(active) = recorder.as_ref() {
+        return Err(format!(
+            "Terminal {} is already being recorded to {}",
+            id,
+            active.path().display()
+        ));
+    }
+
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => terminal_recording::default_recording_path(&id)?,
+    };
+
+    let new_recorder = AsciicastRecorder::create(
+        &path,
+        instance.cols,
+        instance.rows,
+        title,
+        record_input.unwrap_or(false),
+    )?;
+    *recorder = Some(new_recorder);
+
+    tracing::info!("Started recording terminal {} to {}", id, path.display());
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Stop recording a terminal
+///
+/// # Arguments
+/// * `id` - Terminal ID
+///
+/// # Returns
+/// A summary of the finished recording, or an error if none was active
+#[tauri::command]
+pub async fn terminal_stop_recording(
+    state: State<'_, TerminalState>,
+    id: String,
+) -> Result<RecordingSummary, String> {
+    let recorder = {
+        let terminals = state
+            .terminals
+            .lock()
+            .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+        let instance = terminals
+            .get(&id)
+            .ok_or_else(|| format!("Terminal not found: {}", id))?;
+
+        let active = instance
+            .recorder
+            .lock()
+            .map_err(|e| format!("Failed to lock recorder: {}", e))?
+            .take();
+        active
+    };
+
+    let recorder = recorder.ok_or_else(|| format!("Terminal {} is not being recorded", id))?;
+    let summary = recorder.finish()?;
+
+    tracing::info!("Stopped recording terminal {} ({})", id, summary.path);
+
+    Ok(summary)
+}
+
+/// Look up a terminal's output log
+fn output_log(state: &TerminalState, id: &str) -> Result<Arc<OutputLog>, String> {
+    let terminals = state
+        .terminals
+        .lock()
+        .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+    terminals
+        .get(id)
+        .map(|instance| instance.output.clone())
+        .ok_or_else(|| format!("Terminal not found: {}", id))
+}
+
+/// Write input to a terminal and return the output offset from before the write
+fn write_and_mark(
+    state: &TerminalState,
+    id: &str,
+    data: &str,
+) -> Result<(Arc<OutputLog>, u64), String> {
+    let mut terminals = state
+        .terminals
+        .lock()
+        .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+    let instance = terminals
+        .get_mut(id)
+        .ok_or_else(|| format!("Terminal not found: {}", id))?;
+
+    let offset = instance.output.end_offset();
+    instance.write_input(data)?;
+
+    Ok((instance.output.clone(), offset))
+}
+
+/// Get the current output offset of a terminal
+///
+/// Pass the result as `from_offset` to `terminal_wait_for` to only match
+/// output that arrives after this point.
+///
+/// # Arguments
+/// * `id` - Terminal ID
+#[tauri::command]
+pub async fn terminal_output_offset(
+    state: State<'_, TerminalState>,
+    id: String,
+) -> Result<u64, String> {
+    Ok(output_log(&state, &id)?.end_offset())
+}
+
+/// Send named keys to a terminal
+///
+/// Each entry is a key name such as `enter`, `tab`, `up`, `ctrl+c`, `alt+b`
+/// or `f5` (see [`terminal_automation::key_sequence`]).
+///
+/// # Arguments
+/// * `id` - Terminal ID
+/// * `keys` - Key names, sent in order
+///
+/// # Returns
+/// The output offset from before the keys were sent, or an error for unknown keys
+#[tauri::command]
+pub async fn terminal_send_keys(
+    state: State<'_, TerminalState>,
+    id: String,
+    keys: Vec<String>,
+) -> Result<u64, String> {
+    let mut data = String::new();
+    for key in &keys {
+        let seq = terminal_automation::key_sequence(key)
+            .ok_or_else(|| format!("Unknown key: {}", key))?;
+        data.push_str(&seq);
+    }
+
+    let (_, offset) = write_and_mark(&state, &id, &data)?;
+
+    tracing::trace!("Sent {} keys to terminal {}", keys.len(), id);
+
+    Ok(offset)
+}
+
+/// Wait until a regex matches the terminal's output
+///
+/// Escape sequences are stripped before matching, so patterns match the text
+/// as displayed.
+///
+/// # Arguments
+/// * `id` - Terminal ID
+/// * `pattern` - Regular expression to wait for
+/// * `from_offset` - Only match output after this offset (defaults to the current end)
+/// * `timeout_ms` - Maximum time to wait (defaults to 10 seconds)
+///
+/// # Returns
+/// The match and the output offset after it, or an error on timeout / terminal exit
+#[tauri::command]
+pub async fn terminal_wait_for(
+    state: State<'_, TerminalState>,
+    id: String,
+    pattern: String,
+    from_offset: Option<u64>,
+    timeout_ms: Option<u64>,
+) -> Result<OutputMatch, String> {
+    let regex = regex::Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+    let log = output_log(&state, &id)?;
+    let from = from_offset.unwrap_or_else(|| log.end_offset());
+    let timeout =
+        std::time::Duration::from_millis(timeout_ms.unwrap_or(terminal_automation::DEFAULT_TIMEOUT_MS));
+
+    tokio::task::spawn_blocking(move || log.wait_for_pattern(&regex, from, timeout))
+        .await
+        .map_err(|e| format!("Wait task failed: {}", e))?
+}
+
+/// Run a command and capture its output until the shell prompt returns
+///
+/// The command is typed into the terminal followed by Enter. When the shell
+/// has shell integration and no `prompt_pattern` is given, capture ends when
+/// the shell reports the command finished (and its exit code is returned).
+/// Otherwise capture ends when the last output line matches `prompt_pattern`.
+/// The echoed command line and the prompt are not included in the output.
+///
+/// # Arguments
+/// * `id` - Terminal ID
+/// * `command` - Command line to run
+/// * `prompt_pattern` - Regex matching the shell prompt line (defaults to `DEFAULT_PROMPT_PATTERN`)
+/// * `timeout_ms` - Maximum time to wait (defaults to 10 seconds)
+///
+/// # Returns
+/// The captured output, or an error on timeout / terminal exit
+#[tauri::command]
+pub async fn terminal_run_command(
+    state: State<'_, TerminalState>,
+    id: String,
+    command: String,
+    prompt_pattern: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<CommandCapture, String> {
+    let prompt = regex::Regex::new(
+        prompt_pattern
+            .as_deref()
+            .unwrap_or(terminal_automation::DEFAULT_PROMPT_PATTERN),
+    )
+    .map_err(|e| format!("Invalid prompt pattern: {}", e))?;
+    let timeout =
+        std::time::Duration::from_millis(timeout_ms.unwrap_or(terminal_automation::DEFAULT_TIMEOUT_MS));
+
+    let shell = terminal_shell(&state, &id)?;
+    let integrated = prompt_pattern.is_none() && shell.lock().map(|s| s.is_active()).unwrap_or(false);
+
+    let (log, from) = write_and_mark(&state, &id, &format!("{}\r", command))?;
+
+    if integrated {
+        let finished = tokio::task::spawn_blocking(move || {
+            let mut record = None;
+            let found = log.wait_until(from, timeout, |text| {
+                record = shell.lock().ok()?.finished_since(from).cloned();
+                record.as_ref().map(|_| (text.len(), text.len()))
+            })?;
+            let record = record.ok_or("Command record missing")?;
+            let output = record
+                .output_end
+                .and_then(|end| log.text_range(record.output_start, end))
+                .unwrap_or_default();
+            Ok::<_, String>((record, output, found.offset))
+        })
+        .await
+        .map_err(|e| format!("Wait task failed: {}", e))??;
+
+        let (record, output, offset) = finished;
+        return Ok(CommandCapture {
+            command,
+            output: terminal_automation::strip_ansi(&output)
+                .replace("\r\n", "\n")
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+            prompt: String::new(),
+            exit_code: record.exit_code,
+            offset,
+        });
+    }
+
+    let found = tokio::task::spawn_blocking(move || {
+        log.wait_until(from, timeout, |text| {
+            terminal_automation::find_trailing_prompt(text, &prompt)
+        })
+    })
+    .await
+    .map_err(|e| format!("Wait task failed: {}", e))??;
+
+    let (output, prompt) = terminal_automation::split_command_output(&found.output);
+
+    Ok(CommandCapture {
+        command,
+        output,
+        prompt,
+        exit_code: None,
+        offset: found.offset,
+    })
+}
+
+/// Look up a terminal's shell integration state
+fn terminal_shell(state: &TerminalState, id: &str) -> Result<Arc<Mutex<ShellIntegration>>, String> {
+    let terminals = state
+        .terminals
+        .lock()
+        .map_err(|e| format!("Failed to lock terminal state: {}", e))?;
+
+    terminals
+        .get(id)
+        .map(|instance| instance.shell.clone())
+        .ok_or_else(|| format!("Terminal not found: {}", id))
+}
+
+/// List the commands run in a terminal
+///
+/// Requires shell integration; returns an empty list for shells without it.
+///
+/// # Arguments
+/// * `id` - Terminal ID
+///
+/// # Returns
+/// Command records, oldest first (a still-running command comes last)
+#[tauri::command]
+pub async fn terminal_get_commands(
+    state: State<'_, TerminalState>,
+    id: String,
+) -> Result<Vec<CommandRecord>, String> {
+    let shell = terminal_shell(&state, &id)?;
+    let commands = shell
+        .lock()
+        .map_err(|e| format!("Failed to lock shell integration state: {}", e))?
+        .commands();
+    Ok(commands)
+}
+
+/// Get the current working directory of a terminal's shell
+///
+/// # Arguments
+/// * `id` - Terminal ID
+///
+/// # Returns
+/// The last directory reported by shell integration, or None if unknown
+#[tauri::command]
+pub async fn terminal_get_cwd(
+    state: State<'_, TerminalState>,
+    id: String,
+) -> Result<Option<String>, String> {
+    let shell = terminal_shell(&state, &id)?;
+    let cwd = shell
+        .lock()
+        .map_err(|e| format!("Failed to lock shell integration state: {}", e))?
+        .cwd()
+        .map(str::to_string);
+    Ok(cwd)
+}
+
+/// Get the output of a command recorded by shell integration
+///
+/// # Arguments
+/// * `id` - Terminal ID
+/// * `command_id` - Command record ID
+/// * `strip_ansi` - Remove escape sequences (defaults to true)
+///
+/// # Returns
+/// The command's output, or an error if the command is unknown or its output
+/// has been trimmed from the terminal's buffer
+#[tauri::command]
+pub async fn terminal_get_command_output(
+    state: State<'_, TerminalState>,
+    id: String,
+    command_id: u64,
+    strip_ansi: Option<bool>,
+) -> Result<String, String> {
+    let shell = terminal_shell(&state, &id)?;
+    let log = output_log(&state, &id)?;
+
+    let record = shell
+        .lock()
+        .map_err(|e| format!("Failed to lock shell integration state: {}", e))?
+        .commands()
+        .into_iter()
+        .find(|record| record.id == command_id)
+        .ok_or_else(|| format!("Command {} not found in terminal {}", command_id, id))?;
+
+    let end = record.output_end.unwrap_or_else(|| log.end_offset());
+    let output = log
+        .text_range(record.output_start, end)
+        .ok_or_else(|| format!("Output of command {} is no longer available", command_id))?;
+
+    if strip_ansi.unwrap_or(true) {
+        Ok(terminal_automation::strip_ansi(&output))
+    } else {
+        Ok(output)
+    }
+}
+
 /// Initialize the terminal module and return the command handlers
 ///
 /// This function returns a handler that can be used with Tauri's invoke_handler.
@@ -660,6 +1396,18 @@ pub fn init() -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
         close_terminal,
         get_terminal_info,
         list_terminals,
+        terminal_start_recording,
+        terminal_stop_recording,
+        terminal_output_offset,
+        terminal_send_keys,
+        terminal_wait_for,
+        terminal_run_command,
+        terminal_get_commands,
+        terminal_get_cwd,
+        terminal_get_command_output,
+        terminal_get_process_tree,
+        terminal_shutdown,
+        terminal_shutdown_lane,
     ]
 }
 
@@ -0,0 +1,518 @@
+//! Scriptable terminal automation
+//!
+//! Building blocks for driving lane terminals programmatically, e.g.
+//! "start agent, wait for its prompt, paste the task":
+//!
+//! - `OutputLog`: a bounded, offset-addressed log of PTY output that waiters
+//!   can block on until new output arrives
+//! - `key_sequence`: maps named keys (`enter`, `ctrl+c`, `up`, `f5`, ...) to
+//!   the bytes a terminal sends for them
+//! - `strip_ansi`: removes escape sequences so patterns match visible text
+//!
+//! Offsets are absolute byte positions in the terminal's output stream since
+//! it was created, so they stay valid after old output is trimmed.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Maximum number of output bytes retained per terminal
+const MAX_RETAINED_BYTES: usize = 1024 * 1024;
+
+/// Default timeout for wait operations (milliseconds)
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Default prompt pattern used when capturing command output
+///
+/// Matches a whole line that looks like a prompt: a bare `$`, `#`, `%` or `❯`,
+/// or one of those (or `>`) after words containing `@`, `~`, `/`, `:` or `]`,
+/// such as `user@host:~/src$ ` or `~/src ❯ `. Output that merely ends in one
+/// of these characters (`50%`, `<div>`, a heredoc's `> `) does not match.
+pub const DEFAULT_PROMPT_PATTERN: &str =
+    r"^(?:\S*[@~/:\]]\S*\s)*(?:[$#%❯]|\S*[@~/:\]]\S*[$#%>❯])\s?$";
+
+/// Bounded log of terminal output with blocking waits
+pub struct OutputLog {
+    inner: Mutex<OutputLogInner>,
+    changed: Condvar,
+}
+
+struct OutputLogInner {
+    /// Retained output bytes
+    data: Vec<u8>,
+    /// Absolute offset of `data[0]` in the output stream
+    start_offset: u64,
+    /// Set once the PTY reader has hit EOF
+    closed: bool,
+}
+
+impl OutputLogInner {
+    fn end_offset(&self) -> u64 {
+        self.start_offset + self.data.len() as u64
+    }
+
+    /// Retained bytes from `offset` onwards (clamped to what is still retained)
+    fn since(&self, offset: u64) -> &[u8] {
+        let skip = offset.saturating_sub(self.start_offset) as usize;
+        &self.data[skip.min(self.data.len())..]
+    }
+}
+
+/// A successful pattern match in terminal output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMatch {
+    /// The matched text (ANSI escape sequences stripped)
+    pub matched: String,
+    /// Output between the search start and the end of the match (ANSI stripped)
+    pub output: String,
+    /// Absolute output offset at which the search stopped; pass this as
+    /// `from_offset` to continue waiting after this match
+    pub offset: u64,
+}
+
+impl OutputLog {
+    /// Create an empty output log
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(OutputLogInner {
+                data: Vec::new(),
+                start_offset: 0,
+                closed: false,
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Append output, trimming the oldest bytes beyond the retention limit
+    pub fn append(&self, bytes: &[u8]) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.data.extend_from_slice(bytes);
+            if inner.data.len() > MAX_RETAINED_BYTES {
+                let excess = inner.data.len() - MAX_RETAINED_BYTES;
+                inner.data.drain(..excess);
+                inner.start_offset += excess as u64;
+            }
+        }
+        self.changed.notify_all();
+    }
+
+    /// Mark the log as closed (the terminal exited) and wake all waiters
+    pub fn close(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.closed = true;
+        }
+        self.changed.notify_all();
+    }
+
+    /// Absolute offset of the end of the output received so far
+    pub fn end_offset(&self) -> u64 {
+        self.inner
+            .lock()
+            .map(|inner| inner.end_offset())
+            .unwrap_or(0)
+    }
+
+    /// Output received since `offset` as lossy UTF-8 (escape sequences kept)
+    pub fn text_since(&self, offset: u64) -> String {
+        self.inner
+            .lock()
+            .map(|inner| String::from_utf8_lossy(inner.since(offset)).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Output in the absolute range `start..end` as lossy UTF-8 (escape sequences kept)
+    ///
+    /// Returns None if the start of the range has already been trimmed.
+    pub fn text_range(&self, start: u64, end: u64) -> Option<String> {
+        let inner = self.inner.lock().ok()?;
+        if start < inner.start_offset || end < start {
+            return None;
+        }
+        let from = (start - inner.start_offset) as usize;
+        let to = ((end - inner.start_offset) as usize).min(inner.data.len());
+        Some(String::from_utf8_lossy(&inner.data[from.min(to)..to]).into_owned())
+    }
+
+    /// Block until `check` accepts the visible output since `from_offset`
+    ///
+    /// `check` receives the ANSI-stripped text and returns the byte range of
+    /// the match within it. Returns an error on timeout or if the terminal
+    /// exits before a match is found.
+    pub fn wait_until<F>(
+        &self,
+        from_offset: u64,
+        timeout: Duration,
+        mut check: F,
+    ) -> Result<OutputMatch, String>
+    where
+        F: FnMut(&str) -> Option<(usize, usize)>,
+    {
+        let deadline = Instant::now() + timeout;
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|e| format!("Failed to lock output log: {}", e))?;
+
+        loop {
+            let text = strip_ansi(&String::from_utf8_lossy(inner.since(from_offset)));
+            if let Some((start, end)) = check(&text) {
+                return Ok(OutputMatch {
+                    matched: text[start..end].to_string(),
+                    output: text[..end].to_string(),
+                    offset: inner.end_offset(),
+                });
+            }
+
+            if inner.closed {
+                return Err("Terminal exited before the expected output appeared".to_string());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(format!(
+                    "Timed out after {}ms waiting for terminal output",
+                    timeout.as_millis()
+                ));
+            }
+
+            inner = self
+                .changed
+                .wait_timeout(inner, deadline - now)
+                .map_err(|e| format!("Failed to wait for output: {}", e))?
+                .0;
+        }
+    }
+
+    /// Block until `pattern` matches the visible output since `from_offset`
+    pub fn wait_for_pattern(
+        &self,
+        pattern: &Regex,
+        from_offset: u64,
+        timeout: Duration,
+    ) -> Result<OutputMatch, String> {
+        self.wait_until(from_offset, timeout, |text| {
+            pattern.find(text).map(|m| (m.start(), m.end()))
+        })
+    }
+}
+
+impl Default for OutputLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result of running a command and capturing its output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandCapture {
+    /// The command that was sent
+    pub command: String,
+    /// Output printed by the command (echoed command line and prompt removed)
+    pub output: String,
+    /// The prompt line that ended the capture (empty when shell integration
+    /// reported the command boundaries)
+    pub prompt: String,
+    /// Exit code reported by shell integration (None without integration)
+    pub exit_code: Option<i32>,
+    /// Absolute output offset after the prompt
+    pub offset: u64,
+}
+
+/// Find the prompt that ends a command's output
+///
+/// The first line is the shell echoing the command itself, so the prompt
+/// must appear on a later, final line. Returns the range of that final line.
+pub fn find_trailing_prompt(text: &str, prompt: &Regex) -> Option<(usize, usize)> {
+    // Everything up to the first newline is the echoed command line
+    text.find('\n')?;
+    let last_line_start = text.rfind('\n')? + 1;
+
+    // A carriage return redraws the line (e.g. zsh's partial-line marker)
+    let last_line = text[last_line_start..]
+        .rsplit('\r')
+        .next()
+        .unwrap_or_default();
+    if last_line.trim().is_empty() || !prompt.is_match(last_line) {
+        return None;
+    }
+    Some((last_line_start, text.len()))
+}
+
+/// Split captured text into (command output, prompt line)
+pub fn split_command_output(text: &str) -> (String, String) {
+    let body_start = text.find('\n').map(|i| i + 1).unwrap_or(0);
+    let prompt_start = text.rfind('\n').map(|i| i + 1).unwrap_or(text.len());
+    let body = if prompt_start > body_start {
+        &text[body_start..prompt_start]
+    } else {
+        ""
+    };
+    let output = body
+        .replace("\r\n", "\n")
+        .trim_end_matches(['\r', '\n'])
+        .to_string();
+    (output, text[prompt_start..].trim_end().to_string())
+}
+
+/// Translate a named key into the bytes a terminal sends for it
+///
+/// Names are case-insensitive. Supported:
+/// - `enter`/`return`, `tab`, `backspace`, `escape`/`esc`, `space`, `delete`, `insert`
+/// - `up`, `down`, `left`, `right`, `home`, `end`, `pageup`, `pagedown`
+/// - `f1`..`f12`
+/// - `ctrl+<letter>` (plus `ctrl+[`, `ctrl+\`, `ctrl+]`, `ctrl+@`)
+/// - `alt+<key>` (escape-prefixed)
+pub fn key_sequence(name: &str) -> Option<String> {
+    let lower = name.trim().to_ascii_lowercase();
+
+    if let Some(rest) = lower.strip_prefix("alt+") {
+        let inner = key_sequence(rest).or_else(|| single_char(rest))?;
+        return Some(format!("\x1b{}", inner));
+    }
+
+    if let Some(rest) = lower.strip_prefix("ctrl+") {
+        let mut chars = rest.chars();
+        let c = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        let code = match c {
+            'a'..='z' => c as u8 - b'a' + 1,
+            '@' | ' ' => 0,
+            '[' => 0x1b,
+            '\\' => 0x1c,
+            ']' => 0x1d,
+            _ => return None,
+        };
+        return Some((code as char).to_string());
+    }
+
+    let seq = match lower.as_str() {
+        "enter" | "return" => "\r",
+        "tab" => "\t",
+        "backspace" => "\x7f",
+        "escape" | "esc" => "\x1b",
+        "space" => " ",
+        "up" => "\x1b[A",
+        "down" => "\x1b[B",
+        "right" => "\x1b[C",
+        "left" => "\x1b[D",
+        "home" => "\x1b[H",
+        "end" => "\x1b[F",
+        "insert" => "\x1b[2~",
+        "delete" => "\x1b[3~",
+        "pageup" => "\x1b[5~",
+        "pagedown" => "\x1b[6~",
+        "f1" => "\x1bOP",
+        "f2" => "\x1bOQ",
+        "f3" => "\x1bOR",
+        "f4" => "\x1bOS",
+        "f5" => "\x1b[15~",
+        "f6" => "\x1b[17~",
+        "f7" => "\x1b[18~",
+        "f8" => "\x1b[19~",
+        "f9" => "\x1b[20~",
+        "f10" => "\x1b[21~",
+        "f11" => "\x1b[23~",
+        "f12" => "\x1b[24~",
+        _ => return None,
+    };
+    Some(seq.to_string())
+}
+
+/// A single printable character key (used for `alt+<char>`)
+fn single_char(name: &str) -> Option<String> {
+    let mut chars = name.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(c.to_string())
+}
+
+/// Remove ANSI escape sequences (CSI, OSC, and two-byte escapes)
+pub fn strip_ansi(text: &str) -> String {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    let re = ANSI.get_or_init(|| {
+        Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+            .expect("valid ANSI regex")
+    });
+    re.replace_all(text, "").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_key_sequence_named_keys() {
+        assert_eq!(key_sequence("enter").as_deref(), Some("\r"));
+        assert_eq!(key_sequence("Up").as_deref(), Some("\x1b[A"));
+        assert_eq!(key_sequence("F5").as_deref(), Some("\x1b[15~"));
+        assert_eq!(key_sequence("nope"), None);
+    }
+
+    #[test]
+    fn test_key_sequence_modifiers() {
+        assert_eq!(key_sequence("ctrl+c").as_deref(), Some("\x03"));
+        assert_eq!(key_sequence("Ctrl+D").as_deref(), Some("\x04"));
+        assert_eq!(key_sequence("ctrl+[").as_deref(), Some("\x1b"));
+        assert_eq!(key_sequence("alt+b").as_deref(), Some("\x1bb"));
+        assert_eq!(key_sequence("alt+left").as_deref(), Some("\x1b\x1b[D"));
+        assert_eq!(key_sequence("ctrl+cc"), None);
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        let text = "\x1b[1;31mred\x1b[0m \x1b]0;title\x07plain\x1b]7;file:///tmp\x1b\\";
+        assert_eq!(strip_ansi(text), "red plain");
+    }
+
+    #[test]
+    fn test_output_log_offsets_and_trimming() {
+        let log = OutputLog::new();
+        log.append(b"hello ");
+        assert_eq!(log.end_offset(), 6);
+        log.append(b"world");
+        assert_eq!(log.text_since(6), "world");
+
+        let big = vec![b'x'; MAX_RETAINED_BYTES];
+        log.append(&big);
+        assert_eq!(log.end_offset(), (11 + MAX_RETAINED_BYTES) as u64);
+        // Trimmed output is no longer available, but offsets stay absolute
+        assert_eq!(log.text_since(0).len(), MAX_RETAINED_BYTES);
+    }
+
+    #[test]
+    fn test_output_log_text_range() {
+        let log = OutputLog::new();
+        log.append(b"prompt$ ls\r\nfile.txt\r\n");
+        assert_eq!(log.text_range(12, 22).as_deref(), Some("file.txt\r\n"));
+        // End past the received output is clamped
+        assert_eq!(log.text_range(12, 100).as_deref(), Some("file.txt\r\n"));
+        assert!(log.text_range(5, 2).is_none());
+
+        log.append(&vec![b'x'; MAX_RETAINED_BYTES]);
+        assert!(log.text_range(0, 10).is_none());
+    }
+
+    #[test]
+    fn test_wait_for_pattern_already_present() {
+        let log = OutputLog::new();
+        log.append(b"\x1b[32mready>\x1b[0m ");
+
+        let re = Regex::new("ready>").unwrap();
+        let found = log
+            .wait_for_pattern(&re, 0, Duration::from_millis(10))
+            .unwrap();
+        assert_eq!(found.matched, "ready>");
+        assert_eq!(found.offset, log.end_offset());
+    }
+
+    #[test]
+    fn test_wait_for_pattern_wakes_on_append() {
+        let log = Arc::new(OutputLog::new());
+        let writer = log.clone();
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            writer.append(b"loading...\r\n");
+            writer.append(b"Server listening on 3000\r\n");
+        });
+
+        let re = Regex::new(r"listening on (\d+)").unwrap();
+        let found = log
+            .wait_for_pattern(&re, 0, Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(found.matched, "listening on 3000");
+        assert!(found.output.starts_with("loading..."));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_for_pattern_ignores_output_before_offset() {
+        let log = OutputLog::new();
+        log.append(b"$ ");
+        let offset = log.end_offset();
+
+        let re = Regex::new(r"\$").unwrap();
+        assert!(log
+            .wait_for_pattern(&re, offset, Duration::from_millis(20))
+            .is_err());
+    }
+
+    #[test]
+    fn test_wait_for_pattern_fails_when_closed() {
+        let log = OutputLog::new();
+        log.close();
+
+        let re = Regex::new("never").unwrap();
+        let err = log
+            .wait_for_pattern(&re, 0, Duration::from_secs(5))
+            .unwrap_err();
+        assert!(err.contains("exited"));
+    }
+
+    #[test]
+    fn test_find_trailing_prompt() {
+        let prompt = Regex::new(DEFAULT_PROMPT_PATTERN).unwrap();
+
+        // Only the echoed command so far
+        assert!(find_trailing_prompt("ls\r\n", &prompt).is_none());
+        // Output but no prompt yet
+        assert!(find_trailing_prompt("ls\r\nfile.txt\r\n", &prompt).is_none());
+
+        let text = "ls\r\nfile.txt\r\nuser@host:~$ ";
+        let (start, end) = find_trailing_prompt(text, &prompt).unwrap();
+        assert_eq!(&text[start..end], "user@host:~$ ");
+
+        // zsh marks a missing final newline, then returns to redraw the line
+        let text = "ls\r\nfile.txt\r\n%     \r \ruser@host ~ % ";
+        assert!(find_trailing_prompt(text, &prompt).is_some());
+        assert!(find_trailing_prompt("make\r\n[====>  ] 50%", &prompt).is_none());
+    }
+
+    #[test]
+    fn test_default_prompt_pattern() {
+        let prompt = Regex::new(DEFAULT_PROMPT_PATTERN).unwrap();
+
+        for line in [
+            "$ ",
+            "# ",
+            "% ",
+            "❯ ",
+            "user@host:~/src/app$ ",
+            "[user@host app]$ ",
+            "user@MacBook ~ % ",
+            "~/src ❯ ",
+            "user@host ~> ",
+        ] {
+            assert!(prompt.is_match(line), "{:?} should be a prompt", line);
+        }
+        for line in [
+            "Downloading 50%",
+            "[=====>    ] 3/10 50%",
+            "> ",
+            "<div>",
+            "cat <<EOF >",
+        ] {
+            assert!(!prompt.is_match(line), "{:?} should not be a prompt", line);
+        }
+    }
+
+    #[test]
+    fn test_split_command_output() {
+        let (output, prompt) = split_command_output("ls\r\na.txt\r\nb.txt\r\n~ % ");
+        assert_eq!(output, "a.txt\nb.txt");
+        assert_eq!(prompt, "~ %");
+
+        let (output, prompt) = split_command_output("true\r\n$ ");
+        assert_eq!(output, "");
+        assert_eq!(prompt, "$");
+    }
+}
@@ -0,0 +1,634 @@
+//! Terminal session recording in asciicast v2 format
+//!
+//! Lane terminals can be recorded to `.cast` files so agent sessions can be
+//! attached to bug reports or audited later. Recordings are plain asciicast v2
+//! (newline-delimited JSON), so they also play back in `asciinema play`.
+//!
+//! # Format
+//!
+//! - Line 1: header object `{ "version": 2, "width": .., "height": .., ... }`
+//! - Following lines: events `[time, code, data]` where `code` is
+//!   `"o"` (output), `"i"` (input), `"r"` (resize, data `"COLSxROWS"`) or `"m"` (marker)
+//!
+//! # Events
+//!
+//! Replaying a recording emits the regular terminal events for the target view:
+//!
+//! - `terminal-output`: Payload `{ id: String, data: Vec<u8> }`
+//! - `terminal-replay-resize`: Payload `{ id: String, cols: u16, rows: u16 }`
+//! - `terminal-replay-finished`: Payload `{ id: String, path: String }`
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::terminal::TerminalOutputPayload;
+
+/// Default cap on idle time between replayed events (seconds)
+const DEFAULT_MAX_IDLE_SECS: f64 = 2.0;
+
+/// Range that replay speed multipliers are clamped to
+const MIN_REPLAY_SPEED: f64 = 0.1;
+const MAX_REPLAY_SPEED: f64 = 100.0;
+
+/// Header line of an asciicast v2 file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsciicastHeader {
+    /// Format version (always 2)
+    pub version: u8,
+    /// Terminal width in columns at the start of the recording
+    pub width: u16,
+    /// Terminal height in rows at the start of the recording
+    pub height: u16,
+    /// Unix timestamp of the recording start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Optional title shown by players
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Captured environment (`SHELL`, `TERM`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+/// Kind of a recorded event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastEventKind {
+    Output,
+    Input,
+    Resize,
+    Marker,
+}
+
+impl CastEventKind {
+    /// The event code used in the file format
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Output => "o",
+            Self::Input => "i",
+            Self::Resize => "r",
+            Self::Marker => "m",
+        }
+    }
+
+    /// Parse an event code, returning None for unknown codes
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "o" => Some(Self::Output),
+            "i" => Some(Self::Input),
+            "r" => Some(Self::Resize),
+            "m" => Some(Self::Marker),
+            _ => None,
+        }
+    }
+}
+
+/// A single recorded event
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastEvent {
+    /// Seconds since the start of the recording
+    pub time: f64,
+    pub kind: CastEventKind,
+    pub data: String,
+}
+
+impl CastEvent {
+    /// Serialize as an asciicast event line (`[time, code, data]`)
+    fn to_line(&self) -> String {
+        serde_json::json!([self.time, self.kind.code(), self.data]).to_string()
+    }
+
+    /// Parse an asciicast event line
+    fn from_line(line: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(line).map_err(|e| format!("Invalid event line: {}", e))?;
+        let parts = value.as_array().ok_or("Event is not an array")?;
+        if parts.len() != 3 {
+            return Err(format!("Event has {} fields, expected 3", parts.len()));
+        }
+
+        let time = parts[0].as_f64().ok_or("Event time is not a number")?;
+        let code = parts[1].as_str().ok_or("Event code is not a string")?;
+        let kind = CastEventKind::from_code(code)
+            .ok_or_else(|| format!("Unknown event code: {}", code))?;
+        let data = parts[2].as_str().ok_or("Event data is not a string")?;
+
+        Ok(Self {
+            time,
+            kind,
+            data: data.to_string(),
+        })
+    }
+}
+
+/// A fully loaded recording
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub header: AsciicastHeader,
+    pub events: Vec<CastEvent>,
+}
+
+/// Summary returned when a recording is stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSummary {
+    /// Path of the `.cast` file
+    pub path: String,
+    /// Length of the recording in seconds
+    pub duration_secs: f64,
+    /// Number of events written (excluding the header)
+    pub event_count: usize,
+}
+
+/// Writes asciicast v2 events for a live terminal
+///
+/// Output arrives from the PTY in arbitrary chunks, so multi-byte UTF-8
+/// sequences split across reads are carried over to the next chunk instead of
+/// being written as replacement characters.
+pub struct AsciicastRecorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    started: Instant,
+    record_input: bool,
+    event_count: usize,
+    output_carry: Vec<u8>,
+}
+
+impl AsciicastRecorder {
+    /// Create a new recording file and write its header
+    ///
+    /// Fails if the file already exists rather than overwriting it.
+    pub fn create(
+        path: &Path,
+        cols: u16,
+        rows: u16,
+        title: Option<String>,
+        record_input: bool,
+    ) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create recording directory: {}", e))?;
+        }
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| format!("Failed to create recording file {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+
+        let mut env = HashMap::new();
+        env.insert("TERM".to_string(), "xterm-256color".to_string());
+        if let Ok(shell) = std::env::var("SHELL") {
+            env.insert("SHELL".to_string(), shell);
+        }
+
+        let header = AsciicastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: Some(chrono::Utc::now().timestamp()),
+            title,
+            env,
+        };
+        let header_line = serde_json::to_string(&header)
+            .map_err(|e| format!("Failed to serialize recording header: {}", e))?;
+        writeln!(writer, "{}", header_line)
+            .map_err(|e| format!("Failed to write recording header: {}", e))?;
+
+        Ok(Self {
+            writer,
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            record_input,
+            event_count: 0,
+            output_carry: Vec::new(),
+        })
+    }
+
+    /// Path of the file being written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a chunk of PTY output
+    pub fn record_output(&mut self, data: &[u8]) {
+        let text = decode_utf8_chunk(&mut self.output_carry, data);
+        if !text.is_empty() {
+            self.write_event(CastEventKind::Output, text);
+        }
+    }
+
+    /// Record input sent to the terminal (ignored unless input recording is enabled)
+    pub fn record_input(&mut self, data: &str) {
+        if self.record_input && !data.is_empty() {
+            self.write_event(CastEventKind::Input, data.to_string());
+        }
+    }
+
+    /// Record a terminal resize
+    pub fn record_resize(&mut self, cols: u16, rows: u16) {
+        self.write_event(CastEventKind::Resize, format!("{}x{}", cols, rows));
+    }
+
+    /// Record a named marker (shown as a chapter by asciinema players)
+    pub fn record_marker(&mut self, label: &str) {
+        self.write_event(CastEventKind::Marker, label.to_string());
+    }
+
+    /// Flush and close the recording
+    pub fn finish(mut self) -> Result<RecordingSummary, String> {
+        if !self.output_carry.is_empty() {
+            let rest = String::from_utf8_lossy(&self.output_carry).into_owned();
+            self.output_carry.clear();
+            self.write_event(CastEventKind::Output, rest);
+        }
+
+        self.writer
+            .flush()
+            .map_err(|e| format!("Failed to flush recording: {}", e))?;
+
+        Ok(RecordingSummary {
+            path: self.path.to_string_lossy().to_string(),
+            duration_secs: self.started.elapsed().as_secs_f64(),
+            event_count: self.event_count,
+        })
+    }
+
+    fn write_event(&mut self, kind: CastEventKind, data: String) {
+        let event = CastEvent {
+            time: self.started.elapsed().as_secs_f64(),
+            kind,
+            data,
+        };
+
+        // Recording is best-effort: a full disk must never break the terminal itself
+        if let Err(e) = writeln!(self.writer, "{}", event.to_line()) {
+            tracing::warn!("Failed to write recording event to {:?}: {}", self.path, e);
+            return;
+        }
+        self.event_count += 1;
+    }
+}
+
+/// Decode a chunk of bytes as UTF-8, carrying an incomplete trailing sequence
+/// over to the next call. Invalid bytes are replaced with U+FFFD.
+fn decode_utf8_chunk(carry: &mut Vec<u8>, data: &[u8]) -> String {
+    carry.extend_from_slice(data);
+
+    // Find how many trailing bytes form an incomplete (but so far valid) sequence
+    let keep = match std::str::from_utf8(carry) {
+        Ok(_) => 0,
+        Err(e) if e.error_len().is_none() => carry.len() - e.valid_up_to(),
+        Err(_) => incomplete_tail_len(carry),
+    };
+
+    let split = carry.len() - keep;
+    let text = String::from_utf8_lossy(&carry[..split]).into_owned();
+    carry.drain(..split);
+    text
+}
+
+/// Length of an incomplete UTF-8 sequence at the end of `bytes` (0..=3)
+fn incomplete_tail_len(bytes: &[u8]) -> usize {
+    for back in 1..=3.min(bytes.len()) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 == 0x80 {
+            // Continuation byte, keep looking for the lead byte
+            continue;
+        }
+        let needed = match byte {
+            b if b & 0xE0 == 0xC0 => 2,
+            b if b & 0xF0 == 0xE0 => 3,
+            b if b & 0xF8 == 0xF0 => 4,
+            _ => return 0,
+        };
+        return if needed > back { back } else { 0 };
+    }
+    0
+}
+
+/// Load an asciicast v2 file
+pub fn read_recording(path: &Path) -> Result<Recording, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open recording {}: {}", path.display(), e))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header_line = lines
+        .next()
+        .ok_or("Recording is empty")?
+        .map_err(|e| format!("Failed to read recording header: {}", e))?;
+    let header: AsciicastHeader = serde_json::from_str(&header_line)
+        .map_err(|e| format!("Invalid recording header: {}", e))?;
+    if header.version != 2 {
+        return Err(format!(
+            "Unsupported asciicast version: {} (expected 2)",
+            header.version
+        ));
+    }
+
+    let mut events = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line.map_err(|e| format!("Failed to read recording: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event =
+            CastEvent::from_line(&line).map_err(|e| format!("Line {}: {}", index + 2, e))?;
+        events.push(event);
+    }
+
+    Ok(Recording { header, events })
+}
+
+/// Default path for a new recording of the given terminal
+pub fn default_recording_path(terminal_id: &str) -> Result<PathBuf, String> {
+    let dir = codelane_core::paths::recordings_dir()
+        .map_err(|e| format!("Failed to get recordings directory: {}", e))?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let short_id = &terminal_id[..8.min(terminal_id.len())];
+    Ok(dir.join(format!("{}-{}.cast", stamp, short_id)))
+}
+
+/// Payload for resize events emitted during replay
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayResizePayload {
+    pub id: String,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Payload emitted when a replay has finished
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayFinishedPayload {
+    pub id: String,
+    pub path: String,
+}
+
+/// Compute the delay before each event, honouring playback speed and idle cap
+fn replay_delays(
+    events: &[CastEvent],
+    speed: f64,
+    max_idle_secs: f64,
+) -> Result<Vec<Duration>, String> {
+    let mut previous = 0.0;
+    events
+        .iter()
+        .map(|event| {
+            let gap = (event.time - previous).max(0.0).min(max_idle_secs);
+            previous = event.time;
+            Duration::try_from_secs_f64(gap / speed)
+                .map_err(|_| format!("Invalid pause of {} seconds at {}", gap, event.time))
+        })
+        .collect()
+}
+
+/// Replay a recording into a terminal view
+///
+/// Output events are emitted as `terminal-output` events addressed to
+/// `target_id`, so any terminal view listening for that ID renders the
+/// recording exactly like a live session. Input events are not replayed.
+///
+/// # Arguments
+/// * `path` - Path of the `.cast` file
+/// * `target_id` - ID of the terminal view that should render the replay
+/// * `speed` - Playback speed multiplier (defaults to 1.0, clamped to 0.1-100)
+/// * `max_idle_secs` - Cap on pauses between events (defaults to 2 seconds)
+///
+/// # Returns
+/// The recording header (initial size, title) so the view can size itself
+#[tauri::command]
+pub async fn terminal_replay_recording(
+    app: AppHandle,
+    path: String,
+    target_id: String,
+    speed: Option<f64>,
+    max_idle_secs: Option<f64>,
+) -> Result<AsciicastHeader, String> {
+    let speed = speed.unwrap_or(1.0);
+    if speed.is_nan() || speed <= 0.0 {
+        return Err("Replay speed must be greater than 0".to_string());
+    }
+    let speed = speed.clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+    let max_idle_secs = max_idle_secs.unwrap_or(DEFAULT_MAX_IDLE_SECS).max(0.0);
+
+    let recording = read_recording(Path::new(&path))?;
+    let header = recording.header.clone();
+    let delays = replay_delays(&recording.events, speed, max_idle_secs)?;
+
+    thread::Builder::new()
+        .name(format!(
+            "cast-replay-{}",
+            &target_id[..8.min(target_id.len())]
+        ))
+        .spawn(move || {
+            for (event, delay) in recording.events.into_iter().zip(delays) {
+                thread::sleep(delay);
+                match event.kind {
+                    CastEventKind::Output => {
+                        let _ = app.emit(
+                            "terminal-output",
+                            TerminalOutputPayload {
+                                id: target_id.clone(),
+                                data: event.data.into_bytes(),
+                            },
+                        );
+                    }
+                    CastEventKind::Resize => {
+                        if let Some((cols, rows)) = parse_resize(&event.data) {
+                            let _ = app.emit(
+                                "terminal-replay-resize",
+                                ReplayResizePayload {
+                                    id: target_id.clone(),
+                                    cols,
+                                    rows,
+                                },
+                            );
+                        }
+                    }
+                    CastEventKind::Input | CastEventKind::Marker => {}
+                }
+            }
+
+            let _ = app.emit(
+                "terminal-replay-finished",
+                ReplayFinishedPayload {
+                    id: target_id.clone(),
+                    path,
+                },
+            );
+        })
+        .map_err(|e| format!("Failed to spawn replay thread: {}", e))?;
+
+    Ok(header)
+}
+
+/// Parse resize event data (`"COLSxROWS"`)
+fn parse_resize(data: &str) -> Option<(u16, u16)> {
+    let (cols, rows) = data.split_once('x')?;
+    Some((cols.trim().parse().ok()?, rows.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_event_line_roundtrip() {
+        let event = CastEvent {
+            time: 1.25,
+            kind: CastEventKind::Output,
+            data: "hello\r\n\u{1b}[31mred".to_string(),
+        };
+
+        let line = event.to_line();
+        assert!(line.starts_with("[1.25,\"o\","));
+        assert_eq!(CastEvent::from_line(&line).unwrap(), event);
+    }
+
+    #[test]
+    fn test_event_from_line_rejects_unknown_code() {
+        assert!(CastEvent::from_line(r#"[0.5,"x","data"]"#).is_err());
+        assert!(CastEvent::from_line(r#"[0.5,"o"]"#).is_err());
+        assert!(CastEvent::from_line("not json").is_err());
+    }
+
+    #[test]
+    fn test_decode_utf8_chunk_carries_split_sequence() {
+        let mut carry = Vec::new();
+        let bytes = "héllo".as_bytes();
+        // Split in the middle of the two-byte 'é'
+        let first = decode_utf8_chunk(&mut carry, &bytes[..2]);
+        assert_eq!(first, "h");
+        assert_eq!(carry.len(), 1);
+
+        let second = decode_utf8_chunk(&mut carry, &bytes[2..]);
+        assert_eq!(second, "éllo");
+        assert!(carry.is_empty());
+    }
+
+    #[test]
+    fn test_decode_utf8_chunk_replaces_invalid_bytes() {
+        let mut carry = Vec::new();
+        let text = decode_utf8_chunk(&mut carry, &[b'a', 0xff, b'b']);
+        assert_eq!(text, "a\u{fffd}b");
+        assert!(carry.is_empty());
+    }
+
+    #[test]
+    fn test_recorder_writes_readable_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("session.cast");
+
+        let mut recorder =
+            AsciicastRecorder::create(&path, 120, 40, Some("demo".to_string()), false).unwrap();
+        recorder.record_output(b"$ ls\r\n");
+        recorder.record_input("ls\r");
+        recorder.record_resize(100, 30);
+        recorder.record_marker("done");
+        let summary = recorder.finish().unwrap();
+
+        // Input is not recorded unless enabled
+        assert_eq!(summary.event_count, 3);
+
+        let recording = read_recording(&path).unwrap();
+        assert_eq!(recording.header.version, 2);
+        assert_eq!(recording.header.width, 120);
+        assert_eq!(recording.header.height, 40);
+        assert_eq!(recording.header.title.as_deref(), Some("demo"));
+        assert_eq!(recording.events.len(), 3);
+        assert_eq!(recording.events[0].kind, CastEventKind::Output);
+        assert_eq!(recording.events[0].data, "$ ls\r\n");
+        assert_eq!(recording.events[1].kind, CastEventKind::Resize);
+        assert_eq!(recording.events[1].data, "100x30");
+        assert_eq!(recording.events[2].kind, CastEventKind::Marker);
+    }
+
+    #[test]
+    fn test_recorder_does_not_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "keep me").unwrap();
+
+        assert!(AsciicastRecorder::create(&path, 80, 24, None, false).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn test_recorder_records_input_when_enabled() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("input.cast");
+
+        let mut recorder = AsciicastRecorder::create(&path, 80, 24, None, true).unwrap();
+        recorder.record_input("echo hi\r");
+        recorder.finish().unwrap();
+
+        let recording = read_recording(&path).unwrap();
+        assert_eq!(recording.events.len(), 1);
+        assert_eq!(recording.events[0].kind, CastEventKind::Input);
+        assert_eq!(recording.events[0].data, "echo hi\r");
+    }
+
+    #[test]
+    fn test_read_recording_rejects_other_versions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("v1.cast");
+        std::fs::write(&path, "{\"version\":1,\"width\":80,\"height\":24}\n").unwrap();
+
+        let err = read_recording(&path).unwrap_err();
+        assert!(err.contains("Unsupported asciicast version"));
+    }
+
+    #[test]
+    fn test_replay_delays_apply_speed_and_idle_cap() {
+        let events = vec![
+            CastEvent {
+                time: 1.0,
+                kind: CastEventKind::Output,
+                data: "a".into(),
+            },
+            CastEvent {
+                time: 11.0,
+                kind: CastEventKind::Output,
+                data: "b".into(),
+            },
+            CastEvent {
+                time: 12.0,
+                kind: CastEventKind::Output,
+                data: "c".into(),
+            },
+        ];
+
+        let delays = replay_delays(&events, 2.0, 2.0).unwrap();
+        assert_eq!(delays[0], Duration::from_millis(500));
+        // 10 second gap capped at 2 seconds, then halved
+        assert_eq!(delays[1], Duration::from_secs(1));
+        assert_eq!(delays[2], Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_replay_delays_reject_overflowing_pauses() {
+        let events = vec![CastEvent {
+            time: 1e300,
+            kind: CastEventKind::Output,
+            data: "a".into(),
+        }];
+
+        assert!(replay_delays(&events, MIN_REPLAY_SPEED, f64::INFINITY).is_err());
+        assert!(replay_delays(&events, MIN_REPLAY_SPEED, 2.0).is_ok());
+    }
+
+    #[test]
+    fn test_parse_resize() {
+        assert_eq!(parse_resize("120x40"), Some((120, 40)));
+        assert_eq!(parse_resize("bad"), None);
+        assert_eq!(parse_resize("12xab"), None);
+    }
+}
@@ -0,0 +1,417 @@
+//! Text File Encoding
+//!
+//! Helpers for reading and saving text files from the editor: BOM and charset
+//! detection, line ending detection, content hashes used to notice concurrent
+//! edits, and atomic writes that keep the original file's permissions.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Bytes inspected for NUL when deciding whether a file is binary
+const BINARY_CHECK_LEN: usize = 8 * 1024;
+
+/// Line ending style of a text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    /// Detect the dominant line ending, or `None` if there are no line breaks
+    pub fn detect(text: &str) -> Option<Self> {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        match (crlf, lf) {
+            (0, 0) => None,
+            (crlf, lf) if crlf > lf => Some(LineEnding::Crlf),
+            _ => Some(LineEnding::Lf),
+        }
+    }
+
+    /// Convert every line break in `text` to this style
+    pub fn apply(self, text: &str) -> String {
+        let normalized = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => normalized,
+            LineEnding::Crlf => normalized.replace('\n', "\r\n"),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+/// Split text into lines and their line breaks ("" for a last unterminated line)
+fn split_lines(text: &str) -> Vec<(&str, &str)> {
+    text.split_inclusive('\n')
+        .map(|line| {
+            if let Some(content) = line.strip_suffix("\r\n") {
+                (content, "\r\n")
+            } else if let Some(content) = line.strip_suffix('\n') {
+                (content, "\n")
+            } else {
+                (line, "")
+            }
+        })
+        .collect()
+}
+
+/// Give edited text the line breaks of the text it replaces.
+///
+/// Lines of `text` that are unchanged from `previous` keep the line break they
+/// had there, so saving a file with mixed line endings only touches the lines
+/// that were edited. New and edited lines get `default`.
+pub fn restore_line_endings(previous: &str, text: &str, default: LineEnding) -> String {
+    let old = split_lines(previous);
+    let new = split_lines(text);
+
+    // Unchanged lines at the start and end line up directly
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a.0 == b.0).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.0 == b.0)
+        .count();
+
+    // In between, match lines to the first unused identical old line, in order
+    let middle = prefix..old.len() - suffix;
+    let mut positions: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for i in middle.clone() {
+        positions.entry(old[i].0).or_default().push_back(i);
+    }
+    let mut next = middle.start;
+
+    let mut result = String::with_capacity(text.len() + text.len() / 32);
+    for (i, (content, ending)) in new.iter().enumerate() {
+        result.push_str(content);
+        if ending.is_empty() {
+            continue;
+        }
+        let original = if i < prefix {
+            Some(old[i].1)
+        } else if i >= new.len() - suffix {
+            Some(old[i + old.len() - new.len()].1)
+        } else {
+            positions.get_mut(content).and_then(|indices| {
+                while indices.front().is_some_and(|&j| j < next) {
+                    indices.pop_front();
+                }
+                let j = indices.pop_front()?;
+                next = j + 1;
+                Some(old[j].1)
+            })
+        };
+        result.push_str(
+            original
+                .filter(|e| !e.is_empty())
+                .unwrap_or(default.as_str()),
+        );
+    }
+    result
+}
+
+/// Text decoded from a file, with what is needed to save it back unchanged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+}
+
+/// Look up an encoding by its WHATWG label ("utf-8", "latin1", "shift_jis", ...)
+pub fn encoding_for_label(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("Unknown encoding: {}", label))
+}
+
+/// Decode file contents.
+///
+/// A BOM always wins. Otherwise `encoding` is used when given, then UTF-8 if
+/// the bytes are valid, then Windows-1252, which decodes any byte sequence.
+pub fn decode(bytes: &[u8], encoding: Option<&'static Encoding>) -> Result<DecodedText, String> {
+    if let Some((bom_encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = bom_encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Ok(DecodedText {
+            text: text.into_owned(),
+            encoding: bom_encoding,
+            has_bom: true,
+        });
+    }
+
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => WINDOWS_1252,
+    };
+
+    let text = encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .ok_or_else(|| format!("File is not valid {}", encoding.name()))?;
+    Ok(DecodedText {
+        text: text.into_owned(),
+        encoding,
+        has_bom: false,
+    })
+}
+
+/// Whether file contents look binary: no BOM and a NUL byte near the start
+pub fn is_binary(bytes: &[u8]) -> bool {
+    Encoding::for_bom(bytes).is_none() && bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0)
+}
+
+/// Encode text for saving.
+///
+/// A BOM is only written for UTF-8 and UTF-16. Characters the encoding cannot
+/// represent are an error rather than being silently replaced.
+pub fn encode(text: &str, encoding: &'static Encoding, with_bom: bool) -> Result<Vec<u8>, String> {
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let to_bytes = if encoding == UTF_16LE {
+            u16::to_le_bytes
+        } else {
+            u16::to_be_bytes
+        };
+        let mut bytes = Vec::with_capacity(text.len() * 2 + 2);
+        if with_bom {
+            bytes.extend_from_slice(&to_bytes(0xFEFF));
+        }
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&to_bytes(unit));
+        }
+        return Ok(bytes);
+    }
+
+    let encoding = encoding.output_encoding();
+    if encoding == UTF_8 {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if with_bom {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        bytes.extend_from_slice(text.as_bytes());
+        return Ok(bytes);
+    }
+
+    let (bytes, _, had_unmappable) = encoding.encode(text);
+    if had_unmappable {
+        return Err(format!(
+            "Text contains characters that cannot be saved as {}",
+            encoding.name()
+        ));
+    }
+    Ok(bytes.into_owned())
+}
+
+/// SHA-256 of raw file contents, hex encoded
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Replace `path` with `bytes` atomically.
+///
+/// The data goes to a temporary file in the same directory, which is synced
+/// and renamed over the target, so readers never see a partially written file.
+/// The original file's permissions are kept, and writing through a symlink
+/// replaces the link target rather than the link itself.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let (Some(dir), Some(name)) = (target.parent(), target.file_name()) else {
+        return Err(format!("Invalid file path: {}", path.display()));
+    };
+    let permissions = fs::metadata(&target).ok().map(|m| m.permissions());
+    let temp = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)?;
+        file.write_all(bytes)?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, &target)
+    })();
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to write file: {}", e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::SHIFT_JIS;
+
+    #[test]
+    fn test_line_ending_detect() {
+        assert_eq!(LineEnding::detect("no breaks"), None);
+        assert_eq!(LineEnding::detect("a\nb\n"), Some(LineEnding::Lf));
+        assert_eq!(LineEnding::detect("a\r\nb\r\n"), Some(LineEnding::Crlf));
+        assert_eq!(LineEnding::detect("a\r\nb\nc\r\n"), Some(LineEnding::Crlf));
+        assert_eq!(LineEnding::detect("a\r\nb\nc\n"), Some(LineEnding::Lf));
+    }
+
+    #[test]
+    fn test_line_ending_apply() {
+        assert_eq!(LineEnding::Crlf.apply("a\nb\r\nc"), "a\r\nb\r\nc");
+        assert_eq!(LineEnding::Lf.apply("a\r\nb\nc"), "a\nb\nc");
+    }
+
+    #[test]
+    fn test_restore_line_endings() {
+        // Only the edited line takes the default
+        assert_eq!(
+            restore_line_endings("a\r\nb\nc\r\n", "a\nB\nc\n", LineEnding::Crlf),
+            "a\r\nB\r\nc\r\n"
+        );
+        assert_eq!(
+            restore_line_endings("a\nb\r\nc\nd\n", "a\nb\nx\nc\nd\n", LineEnding::Lf),
+            "a\nb\r\nx\nc\nd\n"
+        );
+        // Lines between two edits keep their breaks too
+        assert_eq!(
+            restore_line_endings(
+                "1\n2\r\n3\r\n4\n5\n",
+                "one\n2\n3\nfour\n5\n",
+                LineEnding::Lf
+            ),
+            "one\n2\r\n3\r\nfour\n5\n"
+        );
+        // The break added after a previously unterminated last line
+        assert_eq!(
+            restore_line_endings("a\r\nb", "a\nb\nc", LineEnding::Crlf),
+            "a\r\nb\r\nc"
+        );
+        assert_eq!(restore_line_endings("", "a\nb\n", LineEnding::Lf), "a\nb\n");
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        let decoded = decode("héllo".as_bytes(), None).unwrap();
+        assert_eq!(decoded.text, "héllo");
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(!decoded.has_bom);
+    }
+
+    #[test]
+    fn test_decode_boms() {
+        let decoded = decode(b"\xEF\xBB\xBFhi", None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding), ("hi", UTF_8));
+        assert!(decoded.has_bom);
+
+        let decoded = decode(b"\xFF\xFEh\0i\0", None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding), ("hi", UTF_16LE));
+
+        // The BOM overrides an explicit encoding
+        let decoded = decode(b"\xFE\xFF\0h\0i", Some(WINDOWS_1252)).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding), ("hi", UTF_16BE));
+    }
+
+    #[test]
+    fn test_decode_falls_back_to_windows_1252() {
+        let decoded = decode(b"caf\xE9", None).unwrap();
+        assert_eq!(decoded.text, "café");
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn test_decode_explicit_encoding() {
+        let decoded = decode(b"\x82\xA0", Some(SHIFT_JIS)).unwrap();
+        assert_eq!(decoded.text, "あ");
+        assert!(decode(b"\xFF\xFF", Some(UTF_8)).is_err());
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(is_binary(b"abc\0def"));
+        assert!(!is_binary(b"abcdef"));
+        assert!(!is_binary(b"\xFF\xFEh\0i\0"));
+        // Decoding still works; rejecting binary files is up to the caller
+        assert_eq!(decode(b"abc\0def", None).unwrap().text, "abc\0def");
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        for (encoding, with_bom) in [
+            (UTF_8, false),
+            (UTF_8, true),
+            (UTF_16LE, true),
+            (UTF_16BE, true),
+            (SHIFT_JIS, false),
+        ] {
+            let bytes = encode("あいう\n", encoding, with_bom).unwrap();
+            let decoded = decode(&bytes, Some(encoding)).unwrap();
+            assert_eq!(decoded.text, "あいう\n");
+            assert_eq!(decoded.encoding, encoding);
+            assert_eq!(decoded.has_bom, with_bom);
+        }
+    }
+
+    #[test]
+    fn test_encode_rejects_unmappable() {
+        let err = encode("日本 €", encoding_for_label("latin1").unwrap(), false).unwrap_err();
+        assert!(err.contains("cannot be saved"));
+        assert!(encoding_for_label("no-such-encoding").is_err());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        // No temporary files left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_preserves_permissions_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("script.sh");
+        fs::write(&path, "echo old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        let link = temp_dir.path().join("link.sh");
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+
+        write_atomic(&link, b"echo new").unwrap();
+
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(fs::read(&path).unwrap(), b"echo new");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+}